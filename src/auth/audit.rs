@@ -0,0 +1,296 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// A record of every change made to API keys, webhooks, and organizations: who made it,
+// when, and what was changed before and after
+
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio_rusqlite::Connection;
+
+use super::models::{AuditLogEntry, AuditLogQuery};
+use super::service::get_api_key;
+
+// The server itself, for changes made at startup and by background jobs
+pub const SYSTEM_ACTOR: &str = "system";
+// Whoever holds the static ADMIN_API_KEY
+pub const ADMIN_KEY_ACTOR: &str = "admin-key";
+// Anyone signing up for a trial key through POST /signup/trial
+pub const SIGNUP_ACTOR: &str = "signup";
+
+// An API key acting as admin, or on itself through /api/me
+pub fn key_actor(id: &str) -> String {
+    format!("key:{}", id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    KeyCreated,
+    KeyUpdated,
+    KeyRevoked,
+    // Deleted along with its usage, by an admin or by cleanup after the retention period
+    KeyPurged,
+    // Deactivated by cleanup for passing its expires_at
+    KeysExpired,
+    KeyRotated,
+    RateLimitSet,
+    QuotaSet,
+    UsageReset,
+    RoutesSet,
+    IpsSet,
+    OriginsSet,
+    SigningEnabled,
+    SigningDisabled,
+    WebhookCreated,
+    WebhookDeleted,
+    OrgCreated,
+    OrgQuotaSet,
+    OrgDeleted,
+    // A trial key deactivated by its last allowed request
+    TrialExhausted,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::KeyCreated => "key_created",
+            AuditAction::KeyUpdated => "key_updated",
+            AuditAction::KeyRevoked => "key_revoked",
+            AuditAction::KeyPurged => "key_purged",
+            AuditAction::KeysExpired => "keys_expired",
+            AuditAction::KeyRotated => "key_rotated",
+            AuditAction::RateLimitSet => "rate_limit_set",
+            AuditAction::QuotaSet => "quota_set",
+            AuditAction::UsageReset => "usage_reset",
+            AuditAction::RoutesSet => "routes_set",
+            AuditAction::IpsSet => "ips_set",
+            AuditAction::OriginsSet => "origins_set",
+            AuditAction::SigningEnabled => "signing_enabled",
+            AuditAction::SigningDisabled => "signing_disabled",
+            AuditAction::WebhookCreated => "webhook_created",
+            AuditAction::WebhookDeleted => "webhook_deleted",
+            AuditAction::OrgCreated => "org_created",
+            AuditAction::OrgQuotaSet => "org_quota_set",
+            AuditAction::OrgDeleted => "org_deleted",
+            AuditAction::TrialExhausted => "trial_exhausted",
+        }
+    }
+}
+
+// One audited change. Snapshots hold the key or webhook as admins list it, never a secret.
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub actor: String,
+    pub action: AuditAction,
+    pub target_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(actor: impl Into<String>, action: AuditAction, target_id: Option<&str>) -> Self {
+        Self {
+            actor: actor.into(),
+            action,
+            target_id: target_id.map(str::to_string),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn snapshots(mut self, before: Option<Value>, after: Option<Value>) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+}
+
+// Write events in one transaction
+pub async fn record(db: &Connection, events: Vec<AuditEvent>) -> Result<(), String> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO audit_log (actor, action, target_id, before, after)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for event in events {
+                stmt.execute(rusqlite::params![
+                    event.actor,
+                    event.action.as_str(),
+                    event.target_id,
+                    event.before.map(|value| value.to_string()),
+                    event.after.map(|value| value.to_string()),
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to write audit log: {}", e))
+}
+
+// A key as admins see it, for an event's before or after snapshot; None once it is gone
+pub async fn snapshot(db: &Connection, id: &str) -> Option<Value> {
+    let key = get_api_key(db, id.to_string()).await.ok()??;
+    serde_json::to_value(key).ok()
+}
+
+// A bound of an audit log search in the stored "YYYY-MM-DD HH:MM:SS" UTC form. A bare day
+// covers all of it: from its first second when `end` is false, to its last when true.
+pub fn range_bound(value: &str, end: bool) -> Result<String, String> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.to_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    let day = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        format!("Invalid time '{}' (expected RFC 3339 or YYYY-MM-DD)", value)
+    })?;
+    let time = if end { "23:59:59" } else { "00:00:00" };
+    Ok(format!("{} {}", day.format("%Y-%m-%d"), time))
+}
+
+// Events matching `query`, newest first, with the total number that match. The query's
+// from and to must already be bounds from range_bound.
+pub async fn list_events(
+    db: &Connection,
+    query: &AuditLogQuery,
+    limit: u32,
+) -> Result<(Vec<AuditLogEntry>, u64), String> {
+    let filter = "WHERE (?1 IS NULL OR created_at >= ?1)
+           AND (?2 IS NULL OR created_at <= ?2)
+           AND (?3 IS NULL OR actor = ?3)
+           AND (?4 IS NULL OR action = ?4)
+           AND (?5 IS NULL OR target_id = ?5)";
+    let (from, to, actor, target_id, offset) = (
+        query.from.clone(),
+        query.to.clone(),
+        query.actor.clone(),
+        query.target_id.clone(),
+        query.offset,
+    );
+    let action = query.action.map(AuditAction::as_str);
+
+    db.call(move |conn| {
+        let total: u64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM audit_log {}", filter),
+            rusqlite::params![from, to, actor, action, target_id],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, created_at, actor, action, target_id, before, after
+             FROM audit_log {} ORDER BY id DESC LIMIT ?6 OFFSET ?7",
+            filter
+        ))?;
+        let params = rusqlite::params![from, to, actor, action, target_id, limit, offset];
+        let snapshot =
+            |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+        let events = stmt
+            .query_map(params, |row| {
+                Ok(AuditLogEntry {
+                    id: row.get(0)?,
+                    created_at: row.get(1)?,
+                    actor: row.get(2)?,
+                    action: row.get(3)?,
+                    target_id: row.get(4)?,
+                    before: snapshot(row.get(5)?),
+                    after: snapshot(row.get(6)?),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((events, total))
+    })
+    .await
+    .map_err(|e| format!("Failed to read audit log: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::service::create_api_key;
+    use crate::db::init_db;
+
+    #[tokio::test]
+    async fn test_record() {
+        let db = init_db(":memory:").await;
+        let key = create_api_key(&db, "audited".to_string(), None).await.unwrap();
+        let after = snapshot(&db, &key.id).await;
+        assert_eq!(after.as_ref().unwrap()["name"], "audited");
+        assert!(snapshot(&db, "missing").await.is_none());
+
+        let event = AuditEvent::new(ADMIN_KEY_ACTOR, AuditAction::KeyCreated, Some(&key.id))
+            .snapshots(None, after);
+        record(&db, vec![event]).await.unwrap();
+        let (actor, action, before, after): (String, String, Option<String>, String) = db
+            .call(|conn| {
+                Ok(conn.query_row(
+                    "SELECT actor, action, before, after FROM audit_log",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )?)
+            })
+            .await
+            .unwrap();
+        assert_eq!((actor.as_str(), action.as_str()), ("admin-key", "key_created"));
+        assert_eq!(before, None);
+        assert!(!after.contains(&key.api_key));
+    }
+
+    #[test]
+    fn test_range_bound() {
+        assert_eq!(range_bound("2026-03-01", false).unwrap(), "2026-03-01 00:00:00");
+        assert_eq!(range_bound("2026-03-01", true).unwrap(), "2026-03-01 23:59:59");
+        let at = range_bound("2026-03-01T10:30:00+02:00", true).unwrap();
+        assert_eq!(at, "2026-03-01 08:30:00");
+        assert!(range_bound("March 1st", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_events() {
+        let db = init_db(":memory:").await;
+        let events = vec![
+            AuditEvent::new(ADMIN_KEY_ACTOR, AuditAction::KeyCreated, Some("a")),
+            AuditEvent::new(SYSTEM_ACTOR, AuditAction::KeyPurged, Some("b")),
+            AuditEvent::new(ADMIN_KEY_ACTOR, AuditAction::KeyRevoked, Some("a")),
+        ];
+        record(&db, events).await.unwrap();
+
+        let all = AuditLogQuery::default();
+        let (found, total) = list_events(&db, &all, 2).await.unwrap();
+        assert_eq!(total, 3);
+        let actions: Vec<&str> = found.iter().map(|event| event.action.as_str()).collect();
+        assert_eq!(actions, vec!["key_revoked", "key_purged"]);
+
+        let query = AuditLogQuery {
+            actor: Some(ADMIN_KEY_ACTOR.to_string()),
+            action: Some(AuditAction::KeyCreated),
+            ..Default::default()
+        };
+        let (found, total) = list_events(&db, &query, 10).await.unwrap();
+        assert_eq!((total, found[0].target_id.as_deref()), (1, Some("a")));
+
+        let query = AuditLogQuery {
+            to: Some("2000-01-01 00:00:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list_events(&db, &query, 10).await.unwrap().1, 0);
+    }
+}
@@ -0,0 +1,144 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio_rusqlite::Connection;
+
+use super::audit::{self, AuditAction, AuditEvent, SYSTEM_ACTOR};
+use super::service::delete_key_rows;
+use super::usage::UsageMeter;
+
+// How often expired keys are deactivated and old revoked keys purged
+pub const CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CleanupReport {
+    // Keys that had passed their expiry and were deactivated
+    pub expired: usize,
+    // Ids of keys deleted for being inactive longer than the retention period
+    pub purged: Vec<String>,
+}
+
+// Deactivate keys past their expiry, then delete keys that have been inactive for more
+// than `retention_days` (0 keeps them forever). An expired key counts as inactive from its
+// expiry time.
+pub async fn cleanup_keys(db: &Connection, retention_days: u32) -> Result<CleanupReport, String> {
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        let expired = tx.execute(
+            "UPDATE api_keys SET is_active = 0, revoked_at = expires_at
+             WHERE is_active = 1 AND expires_at IS NOT NULL AND expires_at <= datetime('now')",
+            [],
+        )?;
+        // Keys revoked before revoked_at existed start their retention period now
+        tx.execute(
+            "UPDATE api_keys SET revoked_at = datetime('now')
+             WHERE is_active = 0 AND revoked_at IS NULL",
+            [],
+        )?;
+        let mut purged = Vec::new();
+        if retention_days > 0 {
+            purged = tx
+                .prepare(
+                    "SELECT id FROM api_keys
+                     WHERE is_active = 0 AND revoked_at <= datetime('now', ?1)
+                     ORDER BY revoked_at, id",
+                )?
+                .query_map([format!("-{} days", retention_days)], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            for id in &purged {
+                delete_key_rows(&tx, id)?;
+            }
+        }
+        tx.commit()?;
+        Ok(CleanupReport { expired, purged })
+    })
+    .await
+    .map_err(|e| format!("Failed to clean up API keys: {}", e))
+}
+
+// Run cleanup_keys every CLEANUP_INTERVAL for as long as the server runs, dropping any
+// buffered usage for the keys it purges
+pub fn spawn_cleanup(db: Connection, meter: Arc<UsageMeter>, retention_days: u32) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match cleanup_keys(&db, retention_days).await {
+                Ok(report) => {
+                    meter.forget(&report.purged);
+                    if let Err(e) = record_cleanup(&db, &report).await {
+                        tracing::error!("{}", e);
+                    }
+                    if report.expired > 0 || !report.purged.is_empty() {
+                        tracing::info!(
+                            "Deactivated {} expired API keys, purged {}",
+                            report.expired,
+                            report.purged.len()
+                        );
+                    }
+                }
+                Err(e) => tracing::error!("{}", e),
+            }
+        }
+    });
+}
+
+// Add what a cleanup run did to the audit log
+async fn record_cleanup(db: &Connection, report: &CleanupReport) -> Result<(), String> {
+    let mut events: Vec<AuditEvent> = report
+        .purged
+        .iter()
+        .map(|id| AuditEvent::new(SYSTEM_ACTOR, AuditAction::KeyPurged, Some(id)))
+        .collect();
+    if report.expired > 0 {
+        let summary = serde_json::json!({ "expired": report.expired });
+        let event = AuditEvent::new(SYSTEM_ACTOR, AuditAction::KeysExpired, None);
+        events.push(event.snapshots(None, Some(summary)));
+    }
+    audit::record(db, events).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::service::{create_api_key, get_api_key, revoke_api_key};
+    use crate::db::init_db;
+
+    #[tokio::test]
+    async fn test_cleanup_keys() {
+        let db = init_db(":memory:").await;
+        let active = create_api_key(&db, "active".to_string(), None).await.unwrap();
+        let expired = create_api_key(&db, "expired".to_string(), Some("2000-01-01 00:00:00".into()))
+            .await
+            .unwrap();
+        let revoked = create_api_key(&db, "revoked".to_string(), None).await.unwrap();
+        revoke_api_key(&db, revoked.id.clone()).await.unwrap();
+
+        let report = cleanup_keys(&db, 30).await.unwrap();
+        assert_eq!(report.expired, 1);
+        // Expired long ago, so already past retention; the revocation is recent
+        assert_eq!(report.purged, vec![expired.id.clone()]);
+        assert!(get_api_key(&db, active.id.clone()).await.unwrap().unwrap().is_active);
+        assert!(get_api_key(&db, revoked.id.clone()).await.unwrap().is_some());
+
+        let report = cleanup_keys(&db, 0).await.unwrap();
+        assert_eq!(report, CleanupReport::default());
+    }
+}
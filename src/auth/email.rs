@@ -0,0 +1,318 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Emails to key owners, at their owner_email, when a key is created, is about to expire,
+// or is revoked. Sent through the SMTP relay in the configuration.
+
+use std::sync::Arc;
+
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tokio_rusqlite::Connection;
+
+use crate::config::{SmtpConfig, SmtpTls};
+
+use super::webhooks::{ExpiringKey, EXPIRY_CHECK_INTERVAL};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    Created,
+    Expiring { days_remaining: u32 },
+    Revoked,
+}
+
+// What an email tells the owner about their key
+#[derive(Debug, Clone)]
+pub struct KeyNotice {
+    pub event: KeyEvent,
+    pub id: String,
+    pub name: String,
+    pub expires_at: Option<String>,
+}
+
+// Subject and plain-text body of an email about a key
+pub fn compose(notice: &KeyNotice) -> (String, String) {
+    let KeyNotice { id, name, .. } = notice;
+    let expiry = match &notice.expires_at {
+        Some(at) => format!("It expires at {} UTC.", at),
+        None => "It does not expire.".to_string(),
+    };
+    match notice.event {
+        KeyEvent::Created => (
+            format!("Epoch Zone API key \"{}\" created", name),
+            format!(
+                "An Epoch Zone API key named \"{}\" (id {}) was created for you. {}\n\n\
+                 The key itself is only shown once, to whoever created it.\n",
+                name, id, expiry
+            ),
+        ),
+        KeyEvent::Expiring { days_remaining } => {
+            let days = if days_remaining == 1 {
+                "1 day".to_string()
+            } else {
+                format!("{} days", days_remaining)
+            };
+            (
+                format!("Epoch Zone API key \"{}\" expires in {}", name, days),
+                format!(
+                    "Your Epoch Zone API key \"{}\" (id {}) expires in {}. {}\n\n\
+                     Ask your administrator for a new key before then to avoid an outage.\n",
+                    name, id, days, expiry
+                ),
+            )
+        }
+        KeyEvent::Revoked => (
+            format!("Epoch Zone API key \"{}\" revoked", name),
+            format!(
+                "Your Epoch Zone API key \"{}\" (id {}) has been revoked and no longer works.\n",
+                name, id
+            ),
+        ),
+    }
+}
+
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn from_config(config: &SmtpConfig) -> Result<Self, String> {
+        let builder = match config.tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host),
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )),
+        }
+        .map_err(|e| format!("Invalid SMTP_HOST '{}': {}", config.host, e))?;
+        let mut builder = builder.port(config.port);
+        if let Some(username) = &config.username {
+            let password = config.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+        let from = config
+            .from
+            .parse()
+            .map_err(|e| format!("Invalid SMTP_FROM '{}': {}", config.from, e))?;
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+
+    pub async fn send(&self, to: &str, notice: &KeyNotice) -> Result<(), String> {
+        let recipient: Mailbox = to
+            .parse()
+            .map_err(|e| format!("Invalid owner email '{}': {}", to, e))?;
+        let (subject, body) = compose(notice);
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(recipient)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| format!("Failed to build email: {}", e))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| format!("Failed to email {}: {}", to, e))?;
+        Ok(())
+    }
+}
+
+// Active keys with an owner that expire within `days` and whose owner hasn't been told
+// about this expiry yet
+async fn pending_expiry_emails(
+    db: &Connection,
+    days: u32,
+) -> Result<Vec<(String, ExpiringKey)>, String> {
+    db.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT owner_email, id, name, expires_at, tags,
+                    julianday(expires_at) - julianday('now')
+             FROM api_keys k
+             WHERE is_active = 1
+               AND owner_email IS NOT NULL
+               AND expires_at IS NOT NULL
+               AND expires_at > datetime('now')
+               AND expires_at <= datetime('now', '+' || ?1 || ' days')
+               AND NOT EXISTS (
+                   SELECT 1 FROM expiry_emails e
+                   WHERE e.key_id = k.id AND e.expires_at = k.expires_at
+               )
+             ORDER BY expires_at, id",
+        )?;
+        let pending = stmt
+            .query_map([days], |row| {
+                let owner_email: String = row.get(0)?;
+                let days_left: f64 = row.get(5)?;
+                let key = ExpiringKey {
+                    id: row.get(1)?,
+                    name: row.get(2)?,
+                    expires_at: row.get(3)?,
+                    owner_email: Some(owner_email.clone()),
+                    tags: row
+                        .get::<_, Option<String>>(4)?
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                    days_remaining: days_left.ceil().max(0.0) as u32,
+                };
+                Ok((owner_email, key))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pending)
+    })
+    .await
+    .map_err(|e| format!("Failed to find keys to email about: {}", e))
+}
+
+async fn mark_emailed(db: &Connection, key: &ExpiringKey) -> Result<(), String> {
+    let (key_id, expires_at) = (key.id.clone(), key.expires_at.clone());
+    db.call(move |conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO expiry_emails (key_id, expires_at) VALUES (?1, ?2)",
+            rusqlite::params![key_id, expires_at],
+        )?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to record expiry email: {}", e))
+}
+
+// Email the owner of every key expiring within `days`, returning how many were sent.
+// Failed sends are retried on the next run.
+pub async fn email_expiring_keys(
+    db: &Connection,
+    mailer: &Mailer,
+    days: u32,
+) -> Result<usize, String> {
+    let mut sent = 0;
+    for (to, key) in pending_expiry_emails(db, days).await? {
+        let notice = KeyNotice {
+            event: KeyEvent::Expiring {
+                days_remaining: key.days_remaining,
+            },
+            id: key.id.clone(),
+            name: key.name.clone(),
+            expires_at: Some(key.expires_at.clone()),
+        };
+        match mailer.send(&to, &notice).await {
+            Ok(()) => {
+                mark_emailed(db, &key).await?;
+                sent += 1;
+            }
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    Ok(sent)
+}
+
+// Run email_expiring_keys every EXPIRY_CHECK_INTERVAL for as long as the server runs
+pub fn spawn_expiry_emails(db: Connection, mailer: Arc<Mailer>, days: u32) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match email_expiring_keys(&db, &mailer, days).await {
+                Ok(0) => {}
+                Ok(sent) => tracing::info!("Emailed {} key owners about coming expiry", sent),
+                Err(e) => tracing::error!("{}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::models::CreateApiKeyRequest;
+    use crate::auth::service::create_api_key_from;
+    use crate::db::init_db;
+
+    #[test]
+    fn test_compose() {
+        let notice = KeyNotice {
+            event: KeyEvent::Expiring { days_remaining: 1 },
+            id: "k1".to_string(),
+            name: "partner".to_string(),
+            expires_at: Some("2026-03-07 00:00:00".to_string()),
+        };
+        let (subject, body) = compose(&notice);
+        assert_eq!(subject, "Epoch Zone API key \"partner\" expires in 1 day");
+        assert!(body.contains("(id k1)") && body.contains("2026-03-07 00:00:00 UTC"));
+
+        let created = KeyNotice {
+            event: KeyEvent::Created,
+            expires_at: None,
+            ..notice.clone()
+        };
+        assert!(compose(&created).1.contains("It does not expire."));
+        let revoked = KeyNotice {
+            event: KeyEvent::Revoked,
+            ..notice
+        };
+        assert_eq!(compose(&revoked).0, "Epoch Zone API key \"partner\" revoked");
+    }
+
+    #[tokio::test]
+    async fn test_pending_expiry_emails() {
+        let db = init_db(":memory:").await;
+        let soon = (chrono::Utc::now() + chrono::Duration::days(2))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let owned = CreateApiKeyRequest {
+            name: "owned".to_string(),
+            expires_at: Some(soon.clone()),
+            owner_email: Some("ops@example.com".to_string()),
+            ..Default::default()
+        };
+        let key = create_api_key_from(&db, owned, None).await.unwrap();
+        let unowned = CreateApiKeyRequest {
+            name: "unowned".to_string(),
+            expires_at: Some(soon),
+            ..Default::default()
+        };
+        create_api_key_from(&db, unowned, None).await.unwrap();
+
+        assert!(pending_expiry_emails(&db, 1).await.unwrap().is_empty());
+        let pending = pending_expiry_emails(&db, 7).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "ops@example.com");
+        assert_eq!((pending[0].1.id.as_str(), pending[0].1.days_remaining), (key.id.as_str(), 2));
+
+        mark_emailed(&db, &pending[0].1).await.unwrap();
+        assert!(pending_expiry_emails(&db, 7).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mailer_from_config() {
+        let mut config = SmtpConfig {
+            host: "smtp.example.com".to_string(),
+            port: 587,
+            tls: SmtpTls::StartTls,
+            username: Some("keys".to_string()),
+            password: Some("secret".to_string()),
+            from: "Epoch Zone <keys@epoch.zone>".to_string(),
+        };
+        assert!(Mailer::from_config(&config).is_ok());
+        config.tls = SmtpTls::None;
+        assert!(Mailer::from_config(&config).is_ok());
+        config.from = "not an address".to_string();
+        assert!(Mailer::from_config(&config).is_err());
+    }
+}
@@ -15,43 +15,268 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
+    extract::{rejection::QueryRejection, Extension, FromRequest, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::config::AppConfig;
 use crate::models::ErrorResponse;
 use crate::AppState;
 
-use super::models::{ApiKeyListItem, CreateApiKeyRequest, CreateApiKeyResponse};
-use super::service;
+use super::models::{
+    AggregateUsageQuery, AggregateUsageResponse, ApiKeyListItem, AuditLogQuery, AuthFailureStats,
+    AuthenticatedKey, CreateApiKeyRequest, CreateApiKeyResponse, CreateOrganizationRequest,
+    CreateWebhookRequest, CreateWebhookResponse, ExportFormat, KeyIntrospection, KeyRole,
+    KeyUsageResponse, ListApiKeysQuery, Organization, OrganizationDetails, PurgeApiKeysResponse,
+    RateLimitSource, RateLimitStatus, RevokeApiKeyQuery, RotateApiKeyRequest, RotateApiKeyResponse,
+    SetAllowedIpsRequest, SetAllowedOriginsRequest, SetAllowedRoutesRequest, SetQuotaRequest,
+    SetRateLimitRequest, SigningSecretResponse, TrialSignupRequest, UpdateApiKeyRequest,
+    UsageExportQuery, UsageQuery, UsageStatus, Webhook,
+};
+use super::audit::{self, AuditAction, AuditEvent};
+use super::email::{KeyEvent, KeyNotice};
+use super::throttle::INVALID_CREDENTIALS;
+use super::{networks, orgs, origins, patterns, service, signing, trial, usage, webhooks};
+
+// Who is calling an admin endpoint
+struct Admin {
+    // For the audit log
+    actor: String,
+    // The organization an organization's admin key is confined to; None for admins of the
+    // whole service
+    org_id: Option<String>,
+}
 
-fn verify_admin_key(headers: &HeaderMap, admin_key: &str) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
-    let provided = headers
-        .get("X-API-Key")
-        .and_then(|v| v.to_str().ok());
+// Admin endpoints accept the configured admin key, if there is one, and any active key
+// with the admin role
+async fn verify_admin_key(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Admin, (StatusCode, Json<ErrorResponse>)> {
+    let Some(provided) = headers.get("X-API-Key").and_then(|v| v.to_str().ok()) else {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse::new("Missing X-API-Key header")),
+        ));
+    };
 
-    match provided {
-        Some(key) if key == admin_key => Ok(()),
+    if let Some(admin_key_hash) = &state.config.admin_api_key_hash {
+        if service::hashes_equal(&service::hash_api_key(provided, None), admin_key_hash) {
+            return Ok(Admin {
+                actor: audit::ADMIN_KEY_ACTOR.to_string(),
+                org_id: None,
+            });
+        }
+    }
+    let pepper = state.config.api_key_pepper.as_deref();
+    match service::authenticate_api_key(&state.db, provided, pepper).await {
+        Some(key) if key.role == KeyRole::Admin => Ok(Admin {
+            actor: audit::key_actor(&key.id),
+            org_id: key.org_id,
+        }),
+        // A working key without the admin role is not a guess
         Some(_) => Err((
             StatusCode::FORBIDDEN,
             Json(ErrorResponse::new("Invalid admin API key")),
         )),
         None => Err((
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse::new("Missing X-API-Key header")),
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::with_code(INVALID_CREDENTIALS, "Invalid admin API key")),
+        )),
+    }
+}
+
+// Endpoints that reach beyond one organization: webhooks, the audit log, and managing
+// organizations. Returns the admin's audit log actor.
+async fn verify_service_admin(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(state, headers).await?;
+    if admin.org_id.is_some() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::new("Organization admin keys cannot use this endpoint")),
+        ));
+    }
+    Ok(admin.actor)
+}
+
+// Endpoints acting on one key. An organization's admin keys see keys of other
+// organizations as missing.
+async fn verify_key_admin(
+    state: &AppState,
+    headers: &HeaderMap,
+    id: &str,
+) -> Result<Admin, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(state, headers).await?;
+    let Some(org_id) = &admin.org_id else {
+        return Ok(admin);
+    };
+    let key = service::get_api_key(&state.db, id.to_string()).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+    if key.is_some_and(|key| key.org_id.as_ref() != Some(org_id)) {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ));
+    }
+    Ok(admin)
+}
+
+// The organization a request may put keys in: an organization's admin keys only their own,
+// other admins any that exists
+async fn resolve_org(
+    state: &AppState,
+    admin: &Admin,
+    requested: Option<String>,
+) -> Result<Option<String>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(own) = &admin.org_id {
+        if requested.as_ref().is_some_and(|org_id| org_id != own) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::new(
+                    "Organization admin keys can only manage their own organization",
+                )),
+            ));
+        }
+        return Ok(Some(own.clone()));
+    }
+    let Some(org_id) = requested else {
+        return Ok(None);
+    };
+    let org = orgs::get_organization(&state.db, org_id.clone()).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+    match org {
+        Some(_) => Ok(Some(org_id)),
+        None => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!("Unknown organization '{}'", org_id))),
         )),
     }
 }
 
+// Record an admin action. The action has already happened, so a failure to write the
+// audit log is reported in the server log rather than to the caller.
+async fn audit(state: &AppState, events: Vec<AuditEvent>) {
+    // Every change to a key is audited, so this is where other instances' cached copies of
+    // changed keys are dropped
+    #[cfg(feature = "redis")]
+    if let Some(shared) = &state.shared {
+        for target_id in events.iter().filter_map(|event| event.target_id.as_deref()) {
+            if let Err(e) = shared.forget_key(target_id).await {
+                tracing::error!("{}", e);
+            }
+        }
+    }
+    if let Err(e) = audit::record(&state.db, events).await {
+        tracing::error!("{}", e);
+    }
+}
+
+// Requests `key_id` made in the minute containing `now`, across instances when Redis is
+// configured
+async fn used_this_minute(state: &AppState, key_id: &str, now: DateTime<Utc>) -> u32 {
+    #[cfg(feature = "redis")]
+    if let Some(shared) = &state.shared {
+        match shared.used(key_id, now).await {
+            Ok(used) => return used,
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    state.rate_limiter.used(key_id, now)
+}
+
+// Email a key's owner in the background, when SMTP is configured and the key has an owner
+fn email_owner(state: &AppState, owner_email: Option<&str>, notice: KeyNotice) {
+    let (Some(mailer), Some(to)) = (state.mailer.clone(), owner_email.map(str::to_string)) else {
+        return;
+    };
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(&to, &notice).await {
+            tracing::warn!("{}", e);
+        }
+    });
+}
+
+fn created_notice(key: &CreateApiKeyResponse) -> KeyNotice {
+    KeyNotice {
+        event: KeyEvent::Created,
+        id: key.id.clone(),
+        name: key.name.clone(),
+        expires_at: key.expires_at.clone(),
+    }
+}
+
 pub async fn create_api_key(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(payload): Json<CreateApiKeyRequest>,
 ) -> Result<(StatusCode, Json<CreateApiKeyResponse>), (StatusCode, Json<ErrorResponse>)> {
-    verify_admin_key(&headers, &state.config.admin_api_key)?;
+    let admin = verify_admin_key(&state, &headers).await?;
+
+    let mut payload = validate_create_request(payload, &state.config)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+    payload.org_id = resolve_org(&state, &admin, payload.org_id).await?;
+
+    let pepper = state.config.api_key_pepper.as_deref();
+    let response = service::create_api_key_from(&state.db, payload, pepper)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?;
+
+    let after = audit::snapshot(&state.db, &response.id).await;
+    let event = AuditEvent::new(admin.actor, AuditAction::KeyCreated, Some(&response.id));
+    audit(&state, vec![event.snapshots(None, after)]).await;
+    email_owner(&state, response.owner_email.as_deref(), created_notice(&response));
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+// Most keys one bulk request may create
+const MAX_BULK_KEYS: usize = 500;
+
+// Create several keys in one request; if any entry is invalid, none are created
+pub async fn create_api_keys_bulk(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<Vec<CreateApiKeyRequest>>,
+) -> Result<(StatusCode, Json<Vec<CreateApiKeyResponse>>), (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(&state, &headers).await?;
+
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    if payload.is_empty() || payload.len() > MAX_BULK_KEYS {
+        return Err(bad_request(format!("Provide between 1 and {} keys", MAX_BULK_KEYS)));
+    }
+    let mut requests = payload
+        .into_iter()
+        .enumerate()
+        .map(|(i, request)| {
+            validate_create_request(request, &state.config).map_err(|e| format!("Key {}: {}", i, e))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(bad_request)?;
+    for request in &mut requests {
+        request.org_id = resolve_org(&state, &admin, request.org_id.take()).await?;
+    }
 
-    let response = service::create_api_key(&state.db, payload.name, payload.expires_at)
+    let pepper = state.config.api_key_pepper.as_deref();
+    let created = service::create_api_keys(&state.db, requests, pepper)
         .await
         .map_err(|e| {
             (
@@ -60,40 +285,500 @@ pub async fn create_api_key(
             )
         })?;
 
+    let mut events = Vec::with_capacity(created.len());
+    for key in &created {
+        let after = audit::snapshot(&state.db, &key.id).await;
+        let event = AuditEvent::new(admin.actor.clone(), AuditAction::KeyCreated, Some(&key.id));
+        events.push(event.snapshots(None, after));
+    }
+    audit(&state, events).await;
+    for key in &created {
+        email_owner(&state, key.owner_email.as_deref(), created_notice(key));
+    }
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+fn validate_create_request(
+    mut request: CreateApiKeyRequest,
+    config: &AppConfig,
+) -> Result<CreateApiKeyRequest, String> {
+    request.expires_at = service::resolve_expiry(
+        request.expires_at.as_deref(),
+        request.expires_in_seconds,
+        request.expires_in.as_deref(),
+        Utc::now(),
+    )?;
+    request.tags = service::normalize_tags(request.tags)?;
+    if let Some(email) = &request.owner_email {
+        service::validate_owner_email(email)?;
+    }
+    match (request.role, request.request_limit) {
+        (KeyRole::Trial, None) => request.request_limit = Some(config.trial_request_limit),
+        (KeyRole::Trial, Some(0)) => return Err("'request_limit' must be at least 1".to_string()),
+        (KeyRole::Trial, Some(_)) => {}
+        (_, Some(_)) => return Err("Only trial keys take a 'request_limit'".to_string()),
+        (_, None) => {}
+    }
+    Ok(request)
+}
+
+// Longest name a trial key may be given at signup
+const MAX_TRIAL_NAME_LENGTH: usize = 100;
+
+// Let anyone create a trial key, a limited number per client address per hour
+pub async fn signup_trial(
+    State(state): State<AppState>,
+    request: Request,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), Response> {
+    let error = |status: StatusCode, message: String| {
+        (status, Json(ErrorResponse::new(message))).into_response()
+    };
+    let config = state.config.clone();
+    if config.trial_signups_per_hour == 0 {
+        let message = "Trial signups are not enabled on this server".to_string();
+        return Err(error(StatusCode::NOT_FOUND, message));
+    }
+    let trusted_proxies = &config.trusted_proxies;
+    let ip = networks::client_ip(request.headers(), request.extensions(), trusted_proxies);
+    let Json(payload) = Json::<TrialSignupRequest>::from_request(request, &state)
+        .await
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e.body_text()))?;
+
+    let email = payload.email.trim().to_string();
+    service::validate_owner_email(&email).map_err(|e| error(StatusCode::BAD_REQUEST, e))?;
+    let name = match payload.name.as_deref().map(str::trim) {
+        None | Some("") => "Trial".to_string(),
+        Some(name) if name.chars().count() > MAX_TRIAL_NAME_LENGTH => {
+            let message = format!("'name' must be at most {} characters", MAX_TRIAL_NAME_LENGTH);
+            return Err(error(StatusCode::BAD_REQUEST, message));
+        }
+        Some(name) => name.to_string(),
+    };
+
+    let now = Utc::now().timestamp();
+    if let Err(retry_after) = state.trial_signups.try_signup(ip, config.trial_signups_per_hour, now)
+    {
+        let message = "Too many trial signups from this address".to_string();
+        let mut response = error(StatusCode::TOO_MANY_REQUESTS, message);
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return Err(response);
+    }
+    let internal_error = |e: String| error(StatusCode::INTERNAL_SERVER_ERROR, e);
+    if trial::has_active_trial(&state.db, email.clone()).await.map_err(internal_error)? {
+        let message = "This address already has an active trial key".to_string();
+        return Err(error(StatusCode::CONFLICT, message));
+    }
+
+    let request = CreateApiKeyRequest {
+        name,
+        expires_in_seconds: Some(u64::from(config.trial_key_days) * 86_400),
+        owner_email: Some(email),
+        role: KeyRole::Trial,
+        ..Default::default()
+    };
+    let request = validate_create_request(request, &config)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e))?;
+    let pepper = config.api_key_pepper.as_deref();
+    let response = service::create_api_key_from(&state.db, request, pepper)
+        .await
+        .map_err(internal_error)?;
+
+    let after = audit::snapshot(&state.db, &response.id).await;
+    let event = AuditEvent::new(audit::SIGNUP_ACTOR, AuditAction::KeyCreated, Some(&response.id));
+    audit(&state, vec![event.snapshots(None, after)]).await;
+    email_owner(&state, response.owner_email.as_deref(), created_notice(&response));
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+// Page size for key listings when no limit is given, and the largest allowed
+const DEFAULT_LIST_LIMIT: u32 = 100;
+const MAX_LIST_LIMIT: u32 = 1000;
+
+// The total number of matching keys is returned in X-Total-Count
 pub async fn list_api_keys(
     State(state): State<AppState>,
     headers: HeaderMap,
-) -> Result<Json<Vec<ApiKeyListItem>>, (StatusCode, Json<ErrorResponse>)> {
-    verify_admin_key(&headers, &state.config.admin_api_key)?;
+    params: Result<Query<ListApiKeysQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(&state, &headers).await?;
+
+    let Query(mut params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+    let limit = params.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!(
+                "'limit' must be between 1 and {}",
+                MAX_LIST_LIMIT
+            ))),
+        ));
+    }
+
+    if admin.org_id.is_some() {
+        params.org_id = admin.org_id;
+    }
 
-    let keys = service::list_api_keys(&state.db).await.map_err(|e| {
+    let (keys, total) = service::list_api_keys(&state.db, &params, limit).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse::new(e)),
         )
     })?;
 
-    Ok(Json(keys))
+    Ok(([("x-total-count", total.to_string())], Json(keys)))
+}
+
+// Change a key's name, tags, or owner
+pub async fn update_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(mut payload): Json<UpdateApiKeyRequest>,
+) -> Result<Json<ApiKeyListItem>, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_key_admin(&state, &headers, &id).await?;
+
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    if let Some(tags) = payload.tags {
+        payload.tags = Some(service::normalize_tags(tags).map_err(bad_request)?);
+    }
+    if let Some(Some(email)) = &payload.owner_email {
+        service::validate_owner_email(email).map_err(bad_request)?;
+    }
+    // Moving keys between organizations is for admins of the whole service
+    if let Some(org_id) = payload.org_id.take() {
+        if admin.org_id.is_some() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::new(
+                    "Organization admin keys cannot move keys between organizations",
+                )),
+            ));
+        }
+        payload.org_id = Some(resolve_org(&state, &admin, org_id).await?);
+    }
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = audit::snapshot(&state.db, &id).await;
+    service::update_api_key(&state.db, id.clone(), payload)
+        .await
+        .map_err(internal_error)?;
+
+    match service::get_api_key(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(key) => {
+            let after = serde_json::to_value(&key).ok();
+            let event = AuditEvent::new(admin.actor, AuditAction::KeyUpdated, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(key))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        )),
+    }
 }
 
 pub async fn revoke_api_key(
     State(state): State<AppState>,
     headers: HeaderMap,
     Path(id): Path<String>,
+    params: Result<Query<RevokeApiKeyQuery>, QueryRejection>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
-    verify_admin_key(&headers, &state.config.admin_api_key)?;
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
 
-    let revoked = service::revoke_api_key(&state.db, id).await.map_err(|e| {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+    ensure_not_last_admin(&state, &id).await?;
+    let internal_error = |e: String| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse::new(e)),
         )
-    })?;
+    };
+    let key = service::get_api_key(&state.db, id.clone())
+        .await
+        .map_err(internal_error)?;
+    let before = key.as_ref().and_then(|key| serde_json::to_value(key).ok());
+    let revoked = if params.purge {
+        state.usage.forget(std::slice::from_ref(&id));
+        service::purge_api_key(&state.db, id.clone()).await
+    } else {
+        service::revoke_api_key(&state.db, id.clone()).await
+    }
+    .map_err(internal_error)?;
 
     if revoked {
+        let (action, after) = if params.purge {
+            (AuditAction::KeyPurged, None)
+        } else {
+            (AuditAction::KeyRevoked, audit::snapshot(&state.db, &id).await)
+        };
+        let event = AuditEvent::new(actor, action, Some(&id));
+        audit(&state, vec![event.snapshots(before, after)]).await;
+        // Owners hear about keys that were still working
+        if let Some(key) = key.filter(|key| key.is_active) {
+            let notice = KeyNotice {
+                event: KeyEvent::Revoked,
+                id: key.id,
+                name: key.name,
+                expires_at: key.expires_at,
+            };
+            email_owner(&state, key.owner_email.as_deref(), notice);
+        }
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ))
+    }
+}
+
+// Without a configured admin key, removing the last admin key of the whole service would
+// lock everyone out
+async fn ensure_not_last_admin(
+    state: &AppState,
+    id: &str,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if state.config.admin_api_key_hash.is_some() {
+        return Ok(());
+    }
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let key = service::get_api_key(&state.db, id.to_string())
+        .await
+        .map_err(internal_error)?;
+    let service_admin = |key: &ApiKeyListItem| {
+        key.role == KeyRole::Admin && key.is_active && key.org_id.is_none()
+    };
+    if !key.as_ref().is_some_and(service_admin) {
+        return Ok(());
+    }
+    if service::active_admin_count(&state.db).await.map_err(internal_error)? <= 1 {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new("Cannot remove the last admin key")),
+        ));
+    }
+    Ok(())
+}
+
+// Permanently delete every revoked or expired key
+pub async fn purge_inactive_api_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<PurgeApiKeysResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_service_admin(&state, &headers).await?;
+
+    let ids = service::purge_inactive_api_keys(&state.db).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+    state.usage.forget(&ids);
+    let events = ids
+        .iter()
+        .map(|id| AuditEvent::new(actor.clone(), AuditAction::KeyPurged, Some(id)))
+        .collect();
+    audit(&state, events).await;
+    Ok(Json(PurgeApiKeysResponse {
+        purged: ids.len(),
+        ids,
+    }))
+}
+
+// Longest grace period a rotation may give the old secret
+const MAX_ROTATION_GRACE_SECONDS: u64 = 7 * 24 * 3600;
+
+pub async fn rotate_api_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    payload: Option<Json<RotateApiKeyRequest>>,
+) -> Result<Json<RotateApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    let Json(payload) = payload.unwrap_or_default();
+    rotate(&state, id, payload, actor).await
+}
+
+// Describe the key that authenticated the request: its limits and what it has used
+pub async fn get_own_api_key(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+) -> Result<Json<KeyIntrospection>, (StatusCode, Json<ErrorResponse>)> {
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let item = service::get_api_key(&state.db, key.id.clone())
+        .await
+        .map_err(internal_error)?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ))?;
+    let previous_key_expires_at = service::previous_key_expiry(&state.db, key.id.clone())
+        .await
+        .map_err(internal_error)?;
+
+    let now = Utc::now();
+    let month = service::usage_month(now);
+    let requests = service::monthly_usage(&state.db, key.id.clone(), month.clone())
+        .await
+        .map_err(internal_error)?;
+
+    let (limit, source) = match item.rate_limit_per_minute {
+        Some(limit) => (limit, RateLimitSource::Key),
+        None => (state.config.rate_limit_per_minute, RateLimitSource::Default),
+    };
+
+    Ok(Json(KeyIntrospection {
+        rate_limit: RateLimitStatus {
+            limit_per_minute: (limit > 0).then_some(limit),
+            source,
+            used_this_minute: used_this_minute(&state, &item.id, now).await,
+        },
+        usage: UsageStatus {
+            month,
+            requests,
+            monthly_quota: item.monthly_quota,
+            remaining: item.monthly_quota.map(|quota| quota.saturating_sub(requests)),
+            resets_at: service::next_month_start(now).to_rfc3339(),
+        },
+        id: item.id,
+        name: item.name,
+        created_at: item.created_at,
+        expires_at: item.expires_at,
+        allowed_routes: item.allowed_routes,
+        allowed_ips: item.allowed_ips,
+        allowed_origins: item.allowed_origins,
+        previous_key_expires_at,
+        org_id: item.org_id,
+    }))
+}
+
+// Rotate the secret of the key that authenticated the request
+pub async fn rotate_own_api_key(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    payload: Option<Json<RotateApiKeyRequest>>,
+) -> Result<Json<RotateApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Json(payload) = payload.unwrap_or_default();
+    let actor = audit::key_actor(&key.id);
+    rotate(&state, key.id, payload, actor).await
+}
+
+async fn rotate(
+    state: &AppState,
+    id: String,
+    payload: RotateApiKeyRequest,
+    actor: String,
+) -> Result<Json<RotateApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if payload.grace_period_seconds > MAX_ROTATION_GRACE_SECONDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!(
+                "grace_period_seconds must be at most {}",
+                MAX_ROTATION_GRACE_SECONDS
+            ))),
+        ));
+    }
+
+    let pepper = state.config.api_key_pepper.as_deref();
+    let before = audit::snapshot(&state.db, &id).await;
+    let grace_period_seconds = payload.grace_period_seconds;
+    let rotated = service::rotate_api_key(&state.db, id.clone(), grace_period_seconds, pepper)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ))?;
+
+    let after = audit::snapshot(&state.db, &id).await;
+    let event = AuditEvent::new(actor, AuditAction::KeyRotated, Some(&id));
+    audit(state, vec![event.snapshots(before, after)]).await;
+    Ok(Json(rotated))
+}
+
+// Turn on signed requests for a key and return its new signing secret. Calling it again
+// replaces the secret.
+pub async fn enable_request_signing(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<SigningSecretResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    // Signing secrets are derived from the pepper, so there are none without it
+    let Some(pepper) = state.config.api_key_pepper.clone() else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("Request signing requires API_KEY_PEPPER to be set")),
+        ));
+    };
+    let salt = service::enable_request_signing(&state.db, id.clone())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ))?;
+
+    audit(&state, vec![AuditEvent::new(actor, AuditAction::SigningEnabled, Some(&id))]).await;
+    Ok(Json(SigningSecretResponse {
+        signing_secret: signing::signing_secret(&pepper, &id, &salt),
+        id,
+    }))
+}
+
+pub async fn disable_request_signing(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    let disabled = service::disable_request_signing(&state.db, id.clone()).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+
+    if disabled {
+        audit(&state, vec![AuditEvent::new(actor, AuditAction::SigningDisabled, Some(&id))]).await;
         Ok(StatusCode::NO_CONTENT)
     } else {
         Err((
@@ -102,3 +787,723 @@ pub async fn revoke_api_key(
         ))
     }
 }
+
+pub async fn set_rate_limit(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SetRateLimitRequest>,
+) -> Result<Json<ApiKeyListItem>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    if payload.rate_limit_per_minute == Some(0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("rate_limit_per_minute must be at least 1")),
+        ));
+    }
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = audit::snapshot(&state.db, &id).await;
+    service::set_rate_limit(&state.db, id.clone(), payload.rate_limit_per_minute)
+        .await
+        .map_err(internal_error)?;
+
+    match service::get_api_key(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(key) => {
+            let after = serde_json::to_value(&key).ok();
+            let event = AuditEvent::new(actor, AuditAction::RateLimitSet, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(key))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        )),
+    }
+}
+
+pub async fn set_monthly_quota(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SetQuotaRequest>,
+) -> Result<Json<ApiKeyListItem>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = audit::snapshot(&state.db, &id).await;
+    service::set_monthly_quota(&state.db, id.clone(), payload.monthly_quota)
+        .await
+        .map_err(internal_error)?;
+
+    match service::get_api_key(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(key) => {
+            let after = serde_json::to_value(&key).ok();
+            let event = AuditEvent::new(actor, AuditAction::QuotaSet, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(key))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        )),
+    }
+}
+
+// Clears the key's usage for the current month
+pub async fn reset_monthly_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    let month = service::usage_month(Utc::now());
+    let reset = service::reset_monthly_usage(&state.db, id.clone(), month)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?;
+
+    if reset {
+        audit(&state, vec![AuditEvent::new(actor, AuditAction::UsageReset, Some(&id))]).await;
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ))
+    }
+}
+
+pub async fn set_allowed_routes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SetAllowedRoutesRequest>,
+) -> Result<Json<ApiKeyListItem>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    for pattern in payload.allowed_routes.iter().flatten() {
+        patterns::validate(pattern)
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+    }
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = audit::snapshot(&state.db, &id).await;
+    service::set_allowed_routes(&state.db, id.clone(), payload.allowed_routes)
+        .await
+        .map_err(internal_error)?;
+
+    match service::get_api_key(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(key) => {
+            let after = serde_json::to_value(&key).ok();
+            let event = AuditEvent::new(actor, AuditAction::RoutesSet, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(key))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        )),
+    }
+}
+
+pub async fn set_allowed_ips(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SetAllowedIpsRequest>,
+) -> Result<Json<ApiKeyListItem>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    let allowed_ips = payload
+        .allowed_ips
+        .map(|entries| networks::normalize(&entries))
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = audit::snapshot(&state.db, &id).await;
+    service::set_allowed_ips(&state.db, id.clone(), allowed_ips)
+        .await
+        .map_err(internal_error)?;
+
+    match service::get_api_key(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(key) => {
+            let after = serde_json::to_value(&key).ok();
+            let event = AuditEvent::new(actor, AuditAction::IpsSet, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(key))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        )),
+    }
+}
+
+pub async fn set_allowed_origins(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SetAllowedOriginsRequest>,
+) -> Result<Json<ApiKeyListItem>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_key_admin(&state, &headers, &id).await?.actor;
+
+    let allowed_origins = payload
+        .allowed_origins
+        .map(|entries| origins::normalize(&entries))
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = audit::snapshot(&state.db, &id).await;
+    service::set_allowed_origins(&state.db, id.clone(), allowed_origins)
+        .await
+        .map_err(internal_error)?;
+    // Let the CORS layer answer preflight requests from the new origins
+    let key_origins = service::key_origins(&state.db).await.map_err(internal_error)?;
+    state.key_origins.replace(key_origins);
+
+    match service::get_api_key(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(key) => {
+            let after = serde_json::to_value(&key).ok();
+            let event = AuditEvent::new(actor, AuditAction::OriginsSet, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(key))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        )),
+    }
+}
+
+// Register a webhook that is warned ahead of key expiry, or sent the world clock on a
+// schedule
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreateWebhookResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_service_admin(&state, &headers).await?;
+
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    webhooks::validate_url(&payload.url).map_err(bad_request)?;
+    let days_before = payload.days_before.unwrap_or(webhooks::DEFAULT_DAYS_BEFORE);
+    if !(1..=webhooks::MAX_DAYS_BEFORE).contains(&days_before) {
+        return Err(bad_request(format!(
+            "days_before must be between 1 and {}",
+            webhooks::MAX_DAYS_BEFORE
+        )));
+    }
+    let schedule = match payload.schedule {
+        Some(cron) => {
+            if payload.days_before.is_some() {
+                return Err(bad_request(
+                    "days_before can't be set on a scheduled webhook".to_string(),
+                ));
+            }
+            crate::cron::Schedule::parse(&cron).map_err(bad_request)?;
+            let timezones = payload.timezones.unwrap_or_else(|| {
+                webhooks::DEFAULT_TIMEZONES.iter().map(|tz| tz.to_string()).collect()
+            });
+            webhooks::validate_timezones(&timezones).map_err(bad_request)?;
+            Some(webhooks::WebhookSchedule { cron, timezones })
+        }
+        None if payload.timezones.is_some() => {
+            return Err(bad_request(
+                "timezones can only be set on a scheduled webhook".to_string(),
+            ));
+        }
+        None => None,
+    };
+
+    let webhook = webhooks::create_webhook(&state.db, payload.url, days_before, schedule)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?;
+
+    let after = webhooks::get_webhook(&state.db, webhook.id.clone())
+        .await
+        .ok()
+        .flatten()
+        .and_then(|webhook| serde_json::to_value(webhook).ok());
+    let event = AuditEvent::new(actor, AuditAction::WebhookCreated, Some(&webhook.id));
+    audit(&state, vec![event.snapshots(None, after)]).await;
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Webhook>>, (StatusCode, Json<ErrorResponse>)> {
+    verify_service_admin(&state, &headers).await?;
+
+    webhooks::list_webhooks(&state.db).await.map(Json).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_service_admin(&state, &headers).await?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = webhooks::get_webhook(&state.db, id.clone())
+        .await
+        .map_err(internal_error)?
+        .and_then(|webhook| serde_json::to_value(webhook).ok());
+    if webhooks::delete_webhook(&state.db, id.clone()).await.map_err(internal_error)? {
+        let event = AuditEvent::new(actor, AuditAction::WebhookDeleted, Some(&id));
+        audit(&state, vec![event.snapshots(before, None)]).await;
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Webhook not found")),
+        ))
+    }
+}
+
+pub async fn create_organization(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> Result<(StatusCode, Json<Organization>), (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_service_admin(&state, &headers).await?;
+
+    let name = payload.name.trim().to_string();
+    orgs::validate_name(&name)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+
+    let org = orgs::create_organization(&state.db, name, payload.monthly_quota)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?
+        .ok_or((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new("An organization with this name already exists")),
+        ))?;
+
+    let after = serde_json::to_value(&org).ok();
+    let event = AuditEvent::new(actor, AuditAction::OrgCreated, Some(&org.id));
+    audit(&state, vec![event.snapshots(None, after)]).await;
+    Ok((StatusCode::CREATED, Json(org)))
+}
+
+pub async fn list_organizations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Organization>>, (StatusCode, Json<ErrorResponse>)> {
+    verify_service_admin(&state, &headers).await?;
+
+    orgs::list_organizations(&state.db).await.map(Json).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })
+}
+
+// An organization and its usage this month. Its own admin keys may look it up too.
+pub async fn get_organization(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<OrganizationDetails>, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(&state, &headers).await?;
+
+    let not_found = (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new("Organization not found")),
+    );
+    if admin.org_id.as_ref().is_some_and(|own| *own != id) {
+        return Err(not_found);
+    }
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let organization = orgs::get_organization(&state.db, id.clone())
+        .await
+        .map_err(internal_error)?
+        .ok_or(not_found)?;
+
+    let now = Utc::now();
+    let month = service::usage_month(now);
+    let requests = orgs::monthly_usage(&state.db, id, month.clone())
+        .await
+        .map_err(internal_error)?;
+    let monthly_quota = organization.monthly_quota;
+    Ok(Json(OrganizationDetails {
+        organization,
+        usage: UsageStatus {
+            month,
+            requests,
+            monthly_quota,
+            remaining: monthly_quota.map(|quota| quota.saturating_sub(requests)),
+            resets_at: service::next_month_start(now).to_rfc3339(),
+        },
+    }))
+}
+
+// Set or remove the monthly quota shared by an organization's keys
+pub async fn set_organization_quota(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<SetQuotaRequest>,
+) -> Result<Json<Organization>, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_service_admin(&state, &headers).await?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let before = orgs::get_organization(&state.db, id.clone())
+        .await
+        .map_err(internal_error)?
+        .and_then(|org| serde_json::to_value(org).ok());
+    orgs::set_monthly_quota(&state.db, id.clone(), payload.monthly_quota)
+        .await
+        .map_err(internal_error)?;
+
+    match orgs::get_organization(&state.db, id.clone()).await.map_err(internal_error)? {
+        Some(org) => {
+            let after = serde_json::to_value(&org).ok();
+            let event = AuditEvent::new(actor, AuditAction::OrgQuotaSet, Some(&id));
+            audit(&state, vec![event.snapshots(before, after)]).await;
+            Ok(Json(org))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Organization not found")),
+        )),
+    }
+}
+
+// Only organizations without keys can be deleted
+pub async fn delete_organization(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let actor = verify_service_admin(&state, &headers).await?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let Some(org) = orgs::get_organization(&state.db, id.clone())
+        .await
+        .map_err(internal_error)?
+    else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Organization not found")),
+        ));
+    };
+    // A key added since the lookup also keeps the organization
+    let deleted = org.key_count == 0
+        && orgs::delete_organization(&state.db, id.clone()).await.map_err(internal_error)?;
+    if !deleted {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse::new(
+                "Organization still has API keys; purge them or move them out first",
+            )),
+        ));
+    }
+
+    let before = serde_json::to_value(&org).ok();
+    let event = AuditEvent::new(actor, AuditAction::OrgDeleted, Some(&id));
+    audit(&state, vec![event.snapshots(before, None)]).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Search recorded admin actions, newest first. The total number of matching events is
+// returned in X-Total-Count.
+pub async fn get_audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    params: Result<Query<AuditLogQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    verify_service_admin(&state, &headers).await?;
+
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    let Query(mut params) = params.map_err(|e| bad_request(e.body_text()))?;
+    let limit = params.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(bad_request(format!(
+            "'limit' must be between 1 and {}",
+            MAX_LIST_LIMIT
+        )));
+    }
+    params.from = params
+        .from
+        .map(|from| audit::range_bound(&from, false))
+        .transpose()
+        .map_err(bad_request)?;
+    params.to = params
+        .to
+        .map(|to| audit::range_bound(&to, true))
+        .transpose()
+        .map_err(bad_request)?;
+    if let (Some(from), Some(to)) = (&params.from, &params.to) {
+        if to < from {
+            return Err(bad_request("'to' must not be before 'from'".to_string()));
+        }
+    }
+
+    let (events, total) = audit::list_events(&state.db, &params, limit).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+
+    Ok(([("x-total-count", total.to_string())], Json(events)))
+}
+
+// Days of usage returned when no range is given
+const DEFAULT_USAGE_DAYS: i64 = 30;
+
+// Failed authentications since the server started, and the addresses blocked for them
+pub async fn get_auth_failures(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<AuthFailureStats>, (StatusCode, Json<ErrorResponse>)> {
+    verify_service_admin(&state, &headers).await?;
+    Ok(Json(state.auth_failures.stats(Utc::now().timestamp())))
+}
+
+pub async fn get_key_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    params: Result<Query<UsageQuery>, QueryRejection>,
+) -> Result<Json<KeyUsageResponse>, (StatusCode, Json<ErrorResponse>)> {
+    verify_key_admin(&state, &headers, &id).await?;
+
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+    let (from, to) = usage_range(params.from.as_deref(), params.to.as_deref())?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    if service::get_api_key(&state.db, id.clone())
+        .await
+        .map_err(internal_error)?
+        .is_none()
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("API key not found")),
+        ));
+    }
+
+    // Include requests still waiting in the buffer
+    state.usage.flush(&state.db).await.map_err(internal_error)?;
+    let days = usage::daily_usage(&state.db, id.clone(), from, to)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(KeyUsageResponse {
+        id,
+        from: from.to_string(),
+        to: to.to_string(),
+        requests: days.iter().map(|day| day.requests).sum(),
+        errors: days.iter().map(|day| day.errors).sum(),
+        days,
+    }))
+}
+
+pub async fn get_aggregate_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    params: Result<Query<AggregateUsageQuery>, QueryRejection>,
+) -> Result<Json<AggregateUsageResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(&state, &headers).await?;
+
+    let Query(mut params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+    let (from, to) = usage_range(params.from.as_deref(), params.to.as_deref())?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    // An organization's admin keys see only its usage
+    if admin.org_id.is_some() {
+        params.org_id = admin.org_id;
+    }
+    state.usage.flush(&state.db).await.map_err(internal_error)?;
+    let groups = usage::aggregate_usage(&state.db, from, to, params.group_by, params.org_id)
+        .await
+        .map_err(internal_error)?;
+
+    let requests = groups.iter().map(|group| group.requests).sum();
+    let errors = groups.iter().map(|group| group.errors).sum();
+    Ok(Json(AggregateUsageResponse {
+        from: from.to_string(),
+        to: to.to_string(),
+        group_by: params.group_by,
+        requests,
+        errors,
+        error_rate: usage::error_rate(requests, errors),
+        groups,
+    }))
+}
+
+pub async fn export_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    params: Result<Query<UsageExportQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let admin = verify_admin_key(&state, &headers).await?;
+
+    let Query(mut params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+    let month = match params.month {
+        Some(month) => {
+            NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse::new(format!(
+                        "Invalid month '{}' (expected YYYY-MM)",
+                        month
+                    ))),
+                )
+            })?;
+            month
+        }
+        None => service::usage_month(Utc::now()),
+    };
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    if admin.org_id.is_some() {
+        params.org_id = admin.org_id;
+    }
+    state.usage.flush(&state.db).await.map_err(internal_error)?;
+    let report = usage::billing_report(&state.db, month.clone(), params.org_id)
+        .await
+        .map_err(internal_error)?;
+
+    let body = match params.format {
+        ExportFormat::Csv => usage::billing_csv(&month, &report),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"usage-{}.csv\"", month),
+            ),
+        ],
+        body,
+    ))
+}
+
+// Inclusive day range from optional YYYY-MM-DD bounds, ending today and spanning
+// DEFAULT_USAGE_DAYS by default
+fn usage_range(
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<(NaiveDate, NaiveDate), (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    let parse_day = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| bad_request(format!("Invalid date '{}' (expected YYYY-MM-DD)", value)))
+    };
+    let to = match to {
+        Some(value) => parse_day(value)?,
+        None => Utc::now().date_naive(),
+    };
+    let from = match from {
+        Some(value) => parse_day(value)?,
+        None => to - Duration::days(DEFAULT_USAGE_DAYS - 1),
+    };
+    if to < from {
+        return Err(bad_request("'to' must not be before 'from'".to_string()));
+    }
+    Ok((from, to))
+}
@@ -0,0 +1,295 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use crate::config::AppConfig;
+
+use super::models::{AuthenticatedKey, Credential, KeyRole};
+use super::usage::ENDPOINT_CLASSES;
+
+// A fetched key set is used for this long before it is fetched again
+const JWKS_MAX_AGE: Duration = Duration::from_secs(3600);
+// A token signed with an unknown key id triggers a refetch at most this often, so bad
+// tokens can't make us hammer the issuer
+const JWKS_MIN_REFRESH: Duration = Duration::from_secs(60);
+// How long a JWKS fetch may take; requests needing the key set wait on it
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Checks `Authorization: Bearer` tokens issued by a trusted gateway, signed either with a
+// shared secret (HS256/384/512) or with keys published at a JWKS URL
+pub struct JwtVerifier {
+    keys: KeySource,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+enum KeySource {
+    Secret(DecodingKey),
+    Jwks {
+        url: String,
+        client: reqwest::Client,
+        cache: RwLock<Option<(JwkSet, Instant)>>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    // OAuth 2.0 style space-separated scopes
+    scope: Option<String>,
+    // The same as an array, as some issuers send it
+    scp: Option<Vec<String>>,
+}
+
+impl JwtVerifier {
+    // None when neither JWT_SECRET nor JWT_JWKS_URL is configured
+    pub fn from_config(config: &AppConfig) -> Option<Self> {
+        let keys = match (&config.jwt_secret, &config.jwt_jwks_url) {
+            (Some(secret), _) => KeySource::Secret(DecodingKey::from_secret(secret.as_bytes())),
+            (None, Some(url)) => KeySource::Jwks {
+                url: url.clone(),
+                client: reqwest::Client::builder()
+                    .timeout(JWKS_FETCH_TIMEOUT)
+                    .build()
+                    .expect("Failed to build JWKS HTTP client"),
+                cache: RwLock::new(None),
+            },
+            (None, None) => return None,
+        };
+        Some(Self {
+            keys,
+            issuer: config.jwt_issuer.clone(),
+            audience: config.jwt_audience.clone(),
+        })
+    }
+
+    // The principal a valid token stands for: its subject, with the token's scopes
+    pub async fn verify(&self, token: &str) -> Result<AuthenticatedKey, String> {
+        let header = decode_header(token).map_err(|e| format!("Invalid token: {}", e))?;
+        let key = match &self.keys {
+            KeySource::Secret(key) => {
+                if !matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+                    return Err("Invalid token: unexpected algorithm".to_string());
+                }
+                key.clone()
+            }
+            KeySource::Jwks { .. } => {
+                let kid = header.kid.as_deref().ok_or("Invalid token: missing key id")?;
+                self.jwks_key(kid).await?
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.validate_aud = self.audience.is_some();
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+        }
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        let claims = decode::<Claims>(token, &key, &validation)
+            .map_err(|e| format!("Invalid token: {}", e))?
+            .claims;
+
+        Ok(AuthenticatedKey {
+            id: format!("jwt:{}", claims.sub),
+            rate_limit_per_minute: None,
+            monthly_quota: None,
+            allowed_routes: None,
+            allowed_ips: None,
+            allowed_origins: None,
+            org_id: None,
+            org_monthly_quota: None,
+            role: KeyRole::Client,
+            scopes: scopes(claims.scope.as_deref(), claims.scp),
+            credential: Credential::BearerToken,
+            expires_at: None,
+        })
+    }
+
+    async fn jwks_key(&self, kid: &str) -> Result<DecodingKey, String> {
+        let KeySource::Jwks { url, client, cache } = &self.keys else {
+            return Err("No JWKS configured".to_string());
+        };
+        if let Some(result) = cached_jwk(cache.read().await.as_ref(), kid) {
+            return result;
+        }
+
+        // Another request may have refreshed the set while we waited for the lock
+        let mut cached = cache.write().await;
+        if let Some(result) = cached_jwk(cached.as_ref(), kid) {
+            return result;
+        }
+        let set: JwkSet = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to fetch JWKS: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to read JWKS: {}", e))?;
+        let key = set.find(kid).map(DecodingKey::from_jwk);
+        *cached = Some((set, Instant::now()));
+        match key {
+            Some(key) => key.map_err(|e| e.to_string()),
+            None => Err("Invalid token: unknown key id".to_string()),
+        }
+    }
+}
+
+// The answer the cached key set gives for a key id, or None when it should be refetched:
+// it is stale, or doesn't have the key and was fetched long enough ago to try again
+fn cached_jwk(
+    cached: Option<&(JwkSet, Instant)>,
+    kid: &str,
+) -> Option<Result<DecodingKey, String>> {
+    let (set, fetched) = cached?;
+    match set.find(kid) {
+        Some(jwk) if fetched.elapsed() < JWKS_MAX_AGE => {
+            Some(DecodingKey::from_jwk(jwk).map_err(|e| e.to_string()))
+        }
+        None if fetched.elapsed() < JWKS_MIN_REFRESH => {
+            Some(Err("Invalid token: unknown key id".to_string()))
+        }
+        _ => None,
+    }
+}
+
+// The endpoint classes a token may call. Tokens without scopes may call everything; scopes
+// other than the class names are ignored.
+fn scopes(scope: Option<&str>, scp: Option<Vec<String>>) -> Option<Vec<String>> {
+    let granted: Vec<String> = match (scope, scp) {
+        (Some(scope), _) => scope.split_whitespace().map(str::to_string).collect(),
+        (None, Some(scp)) => scp,
+        (None, None) => return None,
+    };
+    Some(
+        granted
+            .into_iter()
+            .filter(|scope| ENDPOINT_CLASSES.contains(&scope.as_str()))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    fn verifier() -> JwtVerifier {
+        JwtVerifier {
+            keys: KeySource::Secret(DecodingKey::from_secret(b"gateway-secret")),
+            issuer: Some("https://gateway.example".to_string()),
+            audience: None,
+        }
+    }
+
+    fn token(claims: serde_json::Value) -> String {
+        let key = EncodingKey::from_secret(b"gateway-secret");
+        encode(&Header::new(Algorithm::HS256), &claims, &key).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_token() {
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let claims = json!({
+            "sub": "partner-42",
+            "iss": "https://gateway.example",
+            "exp": exp,
+            "scope": "geo conversion openid",
+        });
+        let key = verifier().verify(&token(claims)).await.unwrap();
+        assert_eq!(key.id, "jwt:partner-42");
+        assert_eq!(key.scopes, Some(vec!["geo".to_string(), "conversion".to_string()]));
+
+        let claims = json!({"sub": "a", "iss": "https://gateway.example", "exp": exp});
+        assert_eq!(verifier().verify(&token(claims)).await.unwrap().scopes, None);
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_tokens() {
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let expired = json!({"sub": "a", "iss": "https://gateway.example", "exp": 1_000_000});
+        let wrong_issuer = json!({"sub": "a", "iss": "https://other.example", "exp": exp});
+        for claims in [expired, wrong_issuer] {
+            assert!(verifier().verify(&token(claims)).await.is_err());
+        }
+        let claims = json!({"sub": "a", "iss": "https://gateway.example", "exp": exp});
+        let forged = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(b"guess"),
+        )
+        .unwrap();
+        assert!(verifier().verify(&forged).await.is_err());
+        assert!(verifier().verify("not-a-token").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jwks_fetched_once_for_concurrent_unknown_keys() {
+        use axum::{routing::get, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = fetches.clone();
+        let issuer = Router::new().route(
+            "/jwks",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                axum::Json(json!({"keys": []}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, issuer).await });
+
+        let verifier = JwtVerifier {
+            keys: KeySource::Jwks {
+                url: format!("http://{}/jwks", addr),
+                client: reqwest::Client::new(),
+                cache: RwLock::new(None),
+            },
+            issuer: None,
+            audience: None,
+        };
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("rotated-away".to_string());
+        let claims = json!({"sub": "a", "exp": chrono::Utc::now().timestamp() + 600});
+        let token = encode(&header, &claims, &EncodingKey::from_secret(b"x")).unwrap();
+
+        let results = tokio::join!(
+            verifier.verify(&token),
+            verifier.verify(&token),
+            verifier.verify(&token),
+            verifier.verify(&token),
+        );
+        for result in [results.0, results.1, results.2, results.3] {
+            assert!(result.unwrap_err().contains("unknown key id"));
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+}
@@ -15,37 +15,423 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 
 use crate::models::ErrorResponse;
 use crate::AppState;
 
-use super::service::validate_api_key;
+use super::audit::{self, AuditAction, AuditEvent, SYSTEM_ACTOR};
+use super::models::{AuthenticatedKey, Credential, KeyRole};
+use super::{networks, origins, patterns};
+use super::rate_limit::RateLimitDecision;
+use super::service::{
+    authenticate_api_key, authenticate_signed, hashes_equal, next_month_start, record_org_request,
+    record_request, usage_month, use_trial_request,
+};
+use super::signing::{
+    canonical_request, sign, signing_secret, MAX_CLOCK_SKEW_SECS, MAX_SIGNED_BODY_BYTES,
+};
+use super::throttle::INVALID_CREDENTIALS;
+use super::usage::endpoint_class;
 
 pub async fn require_api_key(
     State(state): State<AppState>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
-    let api_key = request
-        .headers()
-        .get("X-API-Key")
-        .and_then(|v| v.to_str().ok());
-
-    match api_key {
-        Some(key) if validate_api_key(&state.db, key).await => Ok(next.run(request).await),
-        Some(_) => Err((
+    let api_key = header_value(request.headers(), "X-API-Key");
+    let signed = request.headers().contains_key("X-Signature");
+    // Only looked at when JWT authentication is configured and no API key was sent
+    let bearer = header_value(request.headers(), header::AUTHORIZATION.as_str())
+        .and_then(|v| v.strip_prefix("Bearer ").map(str::to_string));
+
+    let pepper = state.config.api_key_pepper.as_deref();
+    let key = match (api_key, bearer, &state.jwt) {
+        (Some(raw_key), _, _) => authenticate(&state, &raw_key, pepper).await.ok_or((
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse::with_code(INVALID_CREDENTIALS, "Invalid or expired API key")),
+        ))?,
+        _ if signed => {
+            let (key, verified) = verify_signed_request(&state, request).await?;
+            request = verified;
+            key
+        }
+        (None, Some(token), Some(jwt)) => jwt.verify(token.trim()).await.map_err(|e| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse::with_code(INVALID_CREDENTIALS, e)),
+            )
+        })?,
+        _ => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorResponse::new("Missing X-API-Key header")),
+            ))
+        }
+    };
+    // Meter by route pattern so "/api/time/UTC" and "/api/time/Asia%2FTokyo" count together
+    let endpoint = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path().to_string(), |p| p.as_str().to_string());
+
+    // Checked before anything else so a leaked key reveals nothing from outside its networks
+    if let Some(networks) = &key.allowed_ips {
+        let trusted_proxies = &state.config.trusted_proxies;
+        let ip = networks::client_ip(request.headers(), request.extensions(), trusted_proxies);
+        if !ip.is_some_and(|ip| networks::is_allowed(networks, ip)) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::new("This API key is not allowed from this address")),
+            ));
+        }
+    }
+
+    // A browser call must come from one of the key's origins, or the server-wide ones
+    // when the key has none of its own
+    if let Some(origin) = request.headers().get(header::ORIGIN) {
+        let allowed = match (&key.allowed_origins, origin.to_str()) {
+            (Some(allowed), Ok(origin)) => origins::is_allowed(allowed, origin),
+            (Some(_), Err(_)) => false,
+            (None, _) => state.config.cors_allowed_origins.contains(origin),
+        };
+        if !allowed {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::new("This API key is not allowed from this origin")),
+            ));
+        }
+    }
+
+    // The key's own /api/me endpoints stay reachable whatever its route patterns say
+    let path = request.uri().path();
+    let own_key_route = path == "/api/me" || path.starts_with("/api/me/");
+    if let (Some(patterns), false) = (&key.allowed_routes, own_key_route) {
+        if !patterns::is_allowed(patterns, path) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::new("This API key is not allowed to call this route")),
+            ));
+        }
+    }
+    if let (Some(scopes), false) = (&key.scopes, own_key_route) {
+        let class = endpoint_class(&endpoint);
+        if !scopes.iter().any(|scope| scope == class) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ErrorResponse::new(format!("This token lacks the '{}' scope", class))),
+            ));
+        }
+    }
+
+    let now = Utc::now();
+    let limit_headers = match meter_request(&state, &key, now).await {
+        Ok(headers) => headers,
+        Err((status, headers, message)) => return Ok(rejection(status, headers, message)),
+    };
+    let stored_key = key.credential != Credential::BearerToken;
+
+    let key_id = key.id.clone();
+    request.extensions_mut().insert(key);
+    let mut response = next.run(request).await;
+    let is_error = response.status().is_client_error() || response.status().is_server_error();
+    if stored_key {
+        state.usage.record(&key_id, &endpoint, is_error, now);
+    }
+    response.headers_mut().extend(limit_headers);
+    Ok(response)
+}
+
+// A request refused by meter_request: the status, the limit headers, and why
+pub(crate) type Refusal = (StatusCode, HeaderMap, String);
+
+// Count one request by `key` against its per-minute limit, its own and its organization's
+// monthly quotas, and a trial key's allowance. Returns the headers describing those limits,
+// or why the request is refused.
+pub(crate) async fn meter_request(
+    state: &AppState,
+    key: &AuthenticatedKey,
+    now: DateTime<Utc>,
+) -> Result<HeaderMap, Refusal> {
+    let mut limit_headers = HeaderMap::new();
+
+    // A key's own limit wins over the server-wide default; 0 means unlimited
+    let limit = key
+        .rate_limit_per_minute
+        .unwrap_or(state.config.rate_limit_per_minute);
+    if limit > 0 {
+        limit_headers.insert("X-RateLimit-Limit", HeaderValue::from(limit));
+        match check_rate_limit(state, &key.id, limit, now).await {
+            RateLimitDecision::Allowed { remaining } => {
+                limit_headers.insert("X-RateLimit-Remaining", HeaderValue::from(remaining));
+            }
+            RateLimitDecision::Exceeded { retry_after } => {
+                limit_headers.insert("X-RateLimit-Remaining", HeaderValue::from(0));
+                limit_headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+                let message = format!("Rate limit of {} requests per minute exceeded", limit);
+                return Err((StatusCode::TOO_MANY_REQUESTS, limit_headers, message));
+            }
+        }
+    }
+
+    // Requests are counted per UTC calendar month, whether or not the key has a quota.
+    // Bearer tokens have no stored key to count against.
+    let stored_key = key.credential != Credential::BearerToken;
+    let internal_error = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new(), e);
+    let month = usage_month(now);
+    let (counted, org_requests) = match (&key.org_id, key.org_monthly_quota) {
+        _ if !stored_key => (None, None),
+        (Some(org_id), Some(org_quota)) => {
+            let (key_id, org_id) = (key.id.clone(), org_id.clone());
+            let quota = key.monthly_quota;
+            let (counted, org_requests) =
+                record_org_request(&state.db, key_id, org_id, month, quota, org_quota)
+                    .await
+                    .map_err(internal_error)?;
+            (counted, Some(org_requests))
+        }
+        _ => {
+            let counted = record_request(&state.db, key.id.clone(), month, key.monthly_quota)
+                .await
+                .map_err(internal_error)?;
+            (counted, None)
+        }
+    };
+    // An organization's quota is shared by all of its keys
+    if let (Some(quota), Some(org_requests)) = (key.org_monthly_quota, org_requests) {
+        let reset = next_month_start(now);
+        limit_headers.insert("X-Org-Quota-Limit", HeaderValue::from(quota));
+        limit_headers.insert(
+            "X-Org-Quota-Remaining",
+            HeaderValue::from(quota.saturating_sub(org_requests)),
+        );
+        if counted.is_none() && org_requests >= quota {
+            if let Ok(value) = HeaderValue::from_str(&reset.to_rfc3339()) {
+                limit_headers.insert("X-Quota-Reset", value);
+            }
+            let retry_after = (reset - now).num_seconds().max(1) as u64;
+            limit_headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            let message = format!("Organization's monthly quota of {} requests exhausted", quota);
+            return Err((StatusCode::PAYMENT_REQUIRED, limit_headers, message));
+        }
+    }
+    if let Some(quota) = key.monthly_quota {
+        let reset = next_month_start(now);
+        limit_headers.insert("X-Quota-Limit", HeaderValue::from(quota));
+        limit_headers.insert(
+            "X-Quota-Remaining",
+            HeaderValue::from(quota - counted.unwrap_or(quota)),
+        );
+        if let Ok(value) = HeaderValue::from_str(&reset.to_rfc3339()) {
+            limit_headers.insert("X-Quota-Reset", value);
+        }
+        if counted.is_none() {
+            let retry_after = (reset - now).num_seconds().max(1) as u64;
+            limit_headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            let message = format!("Monthly quota of {} requests exhausted", quota);
+            return Err((StatusCode::PAYMENT_REQUIRED, limit_headers, message));
+        }
+    }
+
+    // A trial key stops working once its requests are used up
+    if stored_key && key.role == KeyRole::Trial {
+        let remaining = use_trial_request(&state.db, key.id.clone())
+            .await
+            .map_err(internal_error)?;
+        let Some(remaining) = remaining else {
+            let message = "This trial key has no requests left".to_string();
+            return Err((StatusCode::PAYMENT_REQUIRED, limit_headers, message));
+        };
+        limit_headers.insert("X-Trial-Requests-Remaining", HeaderValue::from(remaining));
+        if remaining == 0 {
+            let event = AuditEvent::new(SYSTEM_ACTOR, AuditAction::TrialExhausted, Some(&key.id));
+            if let Err(e) = audit::record(&state.db, vec![event]).await {
+                tracing::error!("{}", e);
+            }
+            #[cfg(feature = "redis")]
+            if let Some(shared) = &state.shared {
+                if let Err(e) = shared.forget_key(&key.id).await {
+                    tracing::error!("{}", e);
+                }
+            }
+        }
+    }
+
+    Ok(limit_headers)
+}
+
+// Turn away clients that keep failing to authenticate, and count the failures of those
+// that aren't blocked yet. Failures are recognized by their error code, which the admin
+// handlers set too.
+pub async fn throttle_failed_auth(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let trusted_proxies = &state.config.trusted_proxies;
+    let Some(ip) = networks::client_ip(request.headers(), request.extensions(), trusted_proxies)
+    else {
+        return next.run(request).await;
+    };
+    let now = Utc::now().timestamp();
+    if let Some(retry_after) = state.auth_failures.blocked(ip, now) {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        let message = "Too many failed authentication attempts from this address".to_string();
+        return rejection(StatusCode::TOO_MANY_REQUESTS, headers, message);
+    }
+
+    let response = next.run(request).await;
+    if !matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return response;
+    }
+    // Error bodies are small; anything else is passed on as it was
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, 64 * 1024).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    let failed = serde_json::from_slice::<ErrorResponse>(&bytes)
+        .is_ok_and(|error| error.code.as_deref() == Some(INVALID_CREDENTIALS));
+    if failed {
+        if let Some(block) = state.auth_failures.record_failure(ip, now) {
+            tracing::warn!("Blocking {} for {}s after failed authentications", ip, block);
+        }
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+// Look up a presented API key, through the shared cache when Redis is configured
+async fn authenticate(
+    state: &AppState,
+    raw_key: &str,
+    pepper: Option<&str>,
+) -> Option<AuthenticatedKey> {
+    #[cfg(feature = "redis")]
+    if let Some(shared) = &state.shared {
+        let key_hash = super::service::hash_api_key(raw_key, pepper);
+        match shared.cached_key(&key_hash).await {
+            Ok(Some(key)) => return Some(key),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("{}", e),
+        }
+        let key = authenticate_api_key(&state.db, raw_key, pepper).await?;
+        if let Err(e) = shared.cache_key(&key_hash, &key).await {
+            tracing::warn!("{}", e);
+        }
+        return Some(key);
+    }
+    authenticate_api_key(&state.db, raw_key, pepper).await
+}
+
+// Count a request against its key's per-minute limit, in Redis when configured. If Redis
+// can't be reached the request is counted by this instance alone rather than refused.
+async fn check_rate_limit(
+    state: &AppState,
+    key_id: &str,
+    limit: u32,
+    now: DateTime<Utc>,
+) -> RateLimitDecision {
+    #[cfg(feature = "redis")]
+    if let Some(shared) = &state.shared {
+        match shared.check(key_id, limit, now).await {
+            Ok(decision) => return decision,
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    state.rate_limiter.check(key_id, limit, now)
+}
+
+// Record a signed request's nonce, in Redis when configured so it can't be replayed
+// against another instance; false if it was already used. Without Redis, or when it
+// can't be reached, this instance's own record is used.
+async fn use_nonce(state: &AppState, key_id: &str, nonce: &str, now: i64) -> bool {
+    #[cfg(feature = "redis")]
+    if let Some(shared) = &state.shared {
+        match shared.use_nonce(key_id, nonce).await {
+            Ok(fresh) => return fresh,
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    state.nonces.insert(key_id, nonce, now)
+}
+
+// Check a request's signature and freshness. The body is buffered to be hashed, so the
+// request is rebuilt from it for the handler.
+async fn verify_signed_request(
+    state: &AppState,
+    request: Request,
+) -> Result<(AuthenticatedKey, Request), (StatusCode, Json<ErrorResponse>)> {
+    let unauthorized = |message: &str| {
+        (
             StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse::new("Invalid or expired API key")),
-        )),
-        None => Err((
+            Json(ErrorResponse::new(message)),
+        )
+    };
+    let Some(pepper) = state.config.api_key_pepper.as_deref() else {
+        return Err(unauthorized("Signed requests are not enabled on this server"));
+    };
+    let [Some(key_id), Some(timestamp), Some(nonce), Some(signature)] =
+        ["X-Key-Id", "X-Timestamp", "X-Nonce", "X-Signature"]
+            .map(|name| header_value(request.headers(), name))
+    else {
+        return Err(unauthorized(
+            "Signed requests need X-Key-Id, X-Timestamp, X-Nonce, and X-Signature headers",
+        ));
+    };
+
+    let now = Utc::now().timestamp();
+    let sent_at: i64 = timestamp
+        .parse()
+        .map_err(|_| unauthorized("X-Timestamp must be a Unix time in seconds"))?;
+    if now.abs_diff(sent_at) > MAX_CLOCK_SKEW_SECS as u64 {
+        return Err(unauthorized("Request timestamp is too far from the server clock"));
+    }
+    if nonce.is_empty() || nonce.len() > 128 {
+        return Err(unauthorized("X-Nonce must be 1 to 128 characters"));
+    }
+
+    let invalid = |message: &str| {
+        (
             StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse::new("Missing X-API-Key header")),
-        )),
+            Json(ErrorResponse::with_code(INVALID_CREDENTIALS, message)),
+        )
+    };
+    let (key, salt) = authenticate_signed(&state.db, key_id)
+        .await
+        .ok_or_else(|| invalid("Invalid or expired API key"))?;
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_SIGNED_BODY_BYTES).await.map_err(|_| {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(ErrorResponse::new("Request body is too large to sign")),
+        )
+    })?;
+    let path = parts.uri.path_and_query().map_or("/", |p| p.as_str());
+    let canonical = canonical_request(parts.method.as_str(), path, &timestamp, &nonce, &body);
+    let expected = sign(&signing_secret(pepper, &key.id, &salt), &canonical);
+    if !hashes_equal(&expected, &signature.to_ascii_lowercase()) {
+        return Err(invalid("Invalid request signature"));
+    }
+    // Only a correctly signed request may use up a nonce
+    if !use_nonce(state, &key.id, &nonce, now).await {
+        return Err(unauthorized("Nonce has already been used"));
     }
+
+    Ok((key, Request::from_parts(parts, Body::from(body))))
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+fn rejection(status: StatusCode, headers: HeaderMap, message: String) -> Response {
+    (status, headers, Json(ErrorResponse::new(message))).into_response()
 }
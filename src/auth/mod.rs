@@ -14,7 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod audit;
+pub mod cleanup;
+pub mod email;
 pub mod handlers;
+pub mod jwt;
 pub mod middleware;
 pub mod models;
+pub mod networks;
+pub mod orgs;
+pub mod origins;
+pub mod patterns;
+pub mod rate_limit;
 pub mod service;
+#[cfg(feature = "redis")]
+pub mod shared;
+pub mod signing;
+pub mod throttle;
+pub mod trial;
+pub mod usage;
+pub mod webhooks;
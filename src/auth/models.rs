@@ -14,12 +14,67 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Debug, Deserialize)]
+use super::audit::AuditAction;
+
+// What a key may do: call the API, or also manage keys under /admin. A trial key calls
+// the API until its limited number of requests is used up.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyRole {
+    #[default]
+    Client,
+    Admin,
+    Trial,
+}
+
+impl KeyRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeyRole::Client => "client",
+            KeyRole::Admin => "admin",
+            KeyRole::Trial => "trial",
+        }
+    }
+
+    // Anything unknown in the role column is a client key
+    pub fn from_column(value: &str) -> Self {
+        match value {
+            "admin" => KeyRole::Admin,
+            "trial" => KeyRole::Trial,
+            _ => KeyRole::Client,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
 pub struct CreateApiKeyRequest {
     pub name: String,
+    // RFC 3339 or "YYYY-MM-DD HH:MM:SS" (UTC); stored as the latter
     pub expires_at: Option<String>,
+    // Alternatives to expires_at, counted from creation: seconds, or a span such as "30d"
+    pub expires_in_seconds: Option<u64>,
+    pub expires_in: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Who to contact about the key
+    pub owner_email: Option<String>,
+    #[serde(default)]
+    pub role: KeyRole,
+    // Organization to create the key in; an organization's admin keys create keys in their
+    // own
+    pub org_id: Option<String>,
+    // Total requests a trial key may make; TRIAL_REQUEST_LIMIT when unset
+    pub request_limit: Option<u64>,
+}
+
+// Body of POST /signup/trial
+#[derive(Debug, Deserialize)]
+pub struct TrialSignupRequest {
+    // Where the key is sent notices; one active trial per address
+    pub email: String,
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -27,15 +82,455 @@ pub struct CreateApiKeyResponse {
     pub id: String,
     pub name: String,
     pub api_key: String,
+    // The key's id and last four characters, as lists show it
+    pub key_hint: String,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub tags: Vec<String>,
+    pub owner_email: Option<String>,
+    pub role: KeyRole,
+    pub org_id: Option<String>,
+}
+
+// Changes to a key's descriptive fields; absent fields are left alone
+#[derive(Debug, Deserialize, Default)]
+pub struct UpdateApiKeyRequest {
+    pub name: Option<String>,
+    // Replaces the key's tags; [] removes them all
+    pub tags: Option<Vec<String>>,
+    // null removes the owner
+    #[serde(default, deserialize_with = "present")]
+    pub owner_email: Option<Option<String>>,
+    // Moves the key to another organization; null takes it out of its organization
+    #[serde(default, deserialize_with = "present")]
+    pub org_id: Option<Option<String>>,
+}
+
+// Tells a field sent as null (Some(None)) apart from one left out (None)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateApiKeyRequest {
+    // How long the old secret keeps working, in seconds (default 0: it stops at once)
+    #[serde(default)]
+    pub grace_period_seconds: u64,
+}
+
+// A key's request-signing secret, shown once
+#[derive(Debug, Serialize)]
+pub struct SigningSecretResponse {
+    pub id: String,
+    pub signing_secret: String,
+}
+
+// A key's new secret, shown once
+#[derive(Debug, Serialize)]
+pub struct RotateApiKeyResponse {
+    pub id: String,
+    pub name: String,
+    pub api_key: String,
+    pub key_hint: String,
     pub created_at: String,
     pub expires_at: Option<String>,
+    // When the old secret stops working, if a grace period was given
+    pub previous_key_expires_at: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ApiKeyListItem {
     pub id: String,
     pub name: String,
+    // "ez_<keyid>…<last four>", to tell keys apart without the secret; None for keys
+    // created before keys carried an id
+    pub key_hint: Option<String>,
     pub created_at: String,
     pub is_active: bool,
     pub expires_at: Option<String>,
+    // Overrides the server-wide default when set
+    pub rate_limit_per_minute: Option<u32>,
+    // Requests allowed per calendar month (UTC); unlimited when unset
+    pub monthly_quota: Option<u64>,
+    // Path patterns the key may call; every route when unset
+    pub allowed_routes: Option<Vec<String>>,
+    pub tags: Vec<String>,
+    pub owner_email: Option<String>,
+    pub role: KeyRole,
+    // CIDR blocks the key may be used from; any address when unset
+    pub allowed_ips: Option<Vec<String>>,
+    // Browser origins the key may be called from; CORS_ALLOWED_ORIGINS when unset
+    pub allowed_origins: Option<Vec<String>>,
+    pub org_id: Option<String>,
+    // Requests a trial key has left; None for other keys
+    pub requests_remaining: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyStatus {
+    Active,
+    Revoked,
+    // Past its expires_at, whether or not cleanup has deactivated it yet
+    Expired,
+}
+
+// Query parameters for listing keys
+#[derive(Debug, Deserialize, Default)]
+pub struct ListApiKeysQuery {
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: u32,
+    pub status: Option<ApiKeyStatus>,
+    // Case-insensitive substring of the key name
+    pub q: Option<String>,
+    // Keys carrying this tag
+    pub tag: Option<String>,
+    // Keys owned by this address, ignoring case
+    pub owner_email: Option<String>,
+    // Keys in this organization
+    pub org_id: Option<String>,
+    // The key whose "ez_<keyid>" prefix starts this, such as a key's hint
+    pub key_prefix: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRateLimitRequest {
+    // null clears the override
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetQuotaRequest {
+    // null removes the quota
+    pub monthly_quota: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAllowedRoutesRequest {
+    // e.g. ["/api/time/Europe/*"]; null lifts the restriction
+    pub allowed_routes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAllowedIpsRequest {
+    // e.g. ["203.0.113.0/24", "2001:db8::1"]; null lifts the restriction
+    pub allowed_ips: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAllowedOriginsRequest {
+    // e.g. ["https://app.example.com"]; null falls back to CORS_ALLOWED_ORIGINS
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+// Where a key's effective rate limit comes from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitSource {
+    // Set on the key by an admin
+    Key,
+    // The server-wide RATE_LIMIT_PER_MINUTE
+    Default,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitStatus {
+    // None when unlimited
+    pub limit_per_minute: Option<u32>,
+    pub source: RateLimitSource,
+    // Requests in the current clock minute, this one included
+    pub used_this_minute: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageStatus {
+    // UTC calendar month, "YYYY-MM"
+    pub month: String,
+    // Requests this month, this one included
+    pub requests: u64,
+    pub monthly_quota: Option<u64>,
+    pub remaining: Option<u64>,
+    pub resets_at: String,
+}
+
+// What the calling key is, what it may do, and how much it has used
+#[derive(Debug, Serialize)]
+pub struct KeyIntrospection {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    // Path patterns the key is limited to; every route when unset
+    pub allowed_routes: Option<Vec<String>>,
+    // CIDR blocks the key is limited to; any address when unset
+    pub allowed_ips: Option<Vec<String>>,
+    // Browser origins the key is limited to; CORS_ALLOWED_ORIGINS when unset
+    pub allowed_origins: Option<Vec<String>>,
+    // When the secret replaced by the last rotation stops working
+    pub previous_key_expires_at: Option<String>,
+    pub org_id: Option<String>,
+    pub rate_limit: RateLimitStatus,
+    pub usage: UsageStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    // Days before a key's expiry to send the warning (default 7)
+    pub days_before: Option<u32>,
+    // Cron schedule (UTC) on which to send the world clock instead of expiry warnings
+    pub schedule: Option<String>,
+    // Timezones in each scheduled delivery (default UTC)
+    pub timezones: Option<Vec<String>>,
+}
+
+// days_before is set for expiry webhooks; schedule, timezones, and next_delivery_at for
+// scheduled ones
+#[derive(Debug, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub days_before: Option<u32>,
+    pub schedule: Option<String>,
+    pub timezones: Option<Vec<String>>,
+    pub next_delivery_at: Option<String>,
+    pub created_at: String,
+}
+
+// A new webhook with the secret that signs its deliveries, shown once
+#[derive(Debug, Serialize)]
+pub struct CreateWebhookResponse {
+    pub id: String,
+    pub url: String,
+    pub days_before: Option<u32>,
+    pub schedule: Option<String>,
+    pub timezones: Option<Vec<String>>,
+    pub next_delivery_at: Option<String>,
+    pub created_at: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrganizationRequest {
+    pub name: String,
+    // Requests per calendar month (UTC) shared by all of the organization's keys; unlimited
+    // when unset
+    pub monthly_quota: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub monthly_quota: Option<u64>,
+    pub created_at: String,
+    // Stored keys in the organization, revoked ones included
+    pub key_count: u64,
+}
+
+// An organization with its usage this month
+#[derive(Debug, Serialize)]
+pub struct OrganizationDetails {
+    #[serde(flatten)]
+    pub organization: Organization,
+    pub usage: UsageStatus,
+}
+
+// Query parameters for searching the audit log
+#[derive(Debug, Deserialize, Default)]
+pub struct AuditLogQuery {
+    // RFC 3339 times, or YYYY-MM-DD days (UTC) taken whole; both ends inclusive
+    pub from: Option<String>,
+    pub to: Option<String>,
+    // "admin-key", "system", or "key:<id>"
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    // Events about this key
+    pub target_id: Option<String>,
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub created_at: String,
+    pub actor: String,
+    pub action: String,
+    pub target_id: Option<String>,
+    // The key as admins list it, before and after the change
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+// Query parameters for a key's usage series
+#[derive(Debug, Deserialize, Default)]
+pub struct UsageQuery {
+    // First and last UTC day, inclusive, as YYYY-MM-DD (default: the last 30 days)
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointUsage {
+    // Route pattern, e.g. "/api/time/{timezone}"
+    pub endpoint: String,
+    pub requests: u64,
+    pub errors: u64,
+}
+
+// One UTC day of a key's requests
+#[derive(Debug, Serialize)]
+pub struct UsageDay {
+    pub day: String,
+    pub requests: u64,
+    // Responses with a 4xx or 5xx status
+    pub errors: u64,
+    pub endpoints: Vec<EndpointUsage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyUsageResponse {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub requests: u64,
+    pub errors: u64,
+    // Days without requests are left out
+    pub days: Vec<UsageDay>,
+}
+
+// What aggregate usage is grouped by
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGrouping {
+    #[default]
+    Day,
+    Key,
+    Endpoint,
+    // Keys outside any organization are left out
+    Org,
+}
+
+// Query parameters for usage across all keys
+#[derive(Debug, Deserialize, Default)]
+pub struct AggregateUsageQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub group_by: UsageGrouping,
+    // Only keys in this organization
+    pub org_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageGroup {
+    // The day, key id, endpoint, or organization id
+    pub group: String,
+    // Key or organization name, when grouped by key or organization
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub requests: u64,
+    pub errors: u64,
+    // errors / requests
+    pub error_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateUsageResponse {
+    pub from: String,
+    pub to: String,
+    pub group_by: UsageGrouping,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    // Busiest first when grouped by key or endpoint, in date order when grouped by day
+    pub groups: Vec<UsageGroup>,
+}
+
+// Query parameters for deleting a key
+#[derive(Debug, Deserialize, Default)]
+pub struct RevokeApiKeyQuery {
+    // Delete the key and its usage history instead of only deactivating it
+    #[serde(default)]
+    pub purge: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgeApiKeysResponse {
+    pub purged: usize,
+    pub ids: Vec<String>,
+}
+
+// Billing export formats
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+}
+
+// Query parameters for the billing usage export
+#[derive(Debug, Deserialize, Default)]
+pub struct UsageExportQuery {
+    // YYYY-MM, defaulting to the current month
+    pub month: Option<String>,
+    #[serde(default)]
+    pub format: ExportFormat,
+    // Only keys in this organization
+    pub org_id: Option<String>,
+}
+
+// Counters for GET /admin/auth-failures, since the server started
+#[derive(Debug, Serialize)]
+pub struct AuthFailureStats {
+    pub failed_attempts: u64,
+    // Requests turned away because their address was blocked
+    pub rejected_while_blocked: u64,
+    pub blocked: Vec<BlockedAddress>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct BlockedAddress {
+    pub address: String,
+    pub failures: u32,
+    pub retry_after: u64,
+}
+
+// How a request authenticated
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Credential {
+    ApiKey,
+    // A request signed with the key's signing secret
+    SignedRequest,
+    // A JWT; its subject has no row in api_keys
+    BearerToken,
+}
+
+// The key a request authenticated with, stored in the request extensions
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthenticatedKey {
+    pub id: String,
+    pub rate_limit_per_minute: Option<u32>,
+    pub monthly_quota: Option<u64>,
+    pub allowed_routes: Option<Vec<String>>,
+    pub role: KeyRole,
+    pub allowed_ips: Option<Vec<String>>,
+    pub allowed_origins: Option<Vec<String>>,
+    pub org_id: Option<String>,
+    // The monthly quota the key's organization shares among its keys
+    pub org_monthly_quota: Option<u64>,
+    // Endpoint classes a bearer token may call; None allows every class
+    pub scopes: Option<Vec<String>>,
+    pub credential: Credential,
+    // When the presented API key stops being accepted (in EXPIRY_FORMAT): the key's expiry,
+    // or sooner for a secret replaced by rotation. Only set by API key lookups.
+    #[serde(default)]
+    pub expires_at: Option<String>,
 }
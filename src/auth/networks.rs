@@ -0,0 +1,147 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// IP networks that restrict where an API key may be used from
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::ConnectInfo;
+use axum::http::{Extensions, HeaderMap};
+use ipnet::IpNet;
+
+// Most networks a key's allowlist may hold
+pub const MAX_NETWORKS: usize = 100;
+
+// Whether `ip` is inside any of `networks`. Entries that don't parse match nothing.
+pub fn is_allowed(networks: &[String], ip: IpAddr) -> bool {
+    let ip = canonical(ip);
+    networks
+        .iter()
+        .filter_map(|network| network.parse::<IpNet>().ok())
+        .any(|network| network.contains(&ip))
+}
+
+// Check a list of networks before storing it: CIDR blocks or single addresses, stored in
+// CIDR form with host bits cleared ("203.0.113.7/24" becomes "203.0.113.0/24")
+pub fn normalize(entries: &[String]) -> Result<Vec<String>, String> {
+    if entries.len() > MAX_NETWORKS {
+        return Err(format!("At most {} networks are allowed", MAX_NETWORKS));
+    }
+    let mut networks: Vec<String> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let entry = entry.trim();
+        let network = match entry.parse::<IpNet>() {
+            Ok(network) => network.trunc(),
+            Err(_) => entry
+                .parse::<IpAddr>()
+                .map(|ip| IpNet::from(canonical(ip)))
+                .map_err(|_| format!("'{}' is not an IP address or CIDR block", entry))?,
+        };
+        let network = network.to_string();
+        if !networks.contains(&network) {
+            networks.push(network);
+        }
+    }
+    Ok(networks)
+}
+
+// The address the request came from. X-Forwarded-For and X-Real-IP are only believed
+// when the connection comes from one of `trusted_proxies`, and the forwarded chain is
+// read from the right, past any further trusted proxies, so a client can't name itself.
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted_proxies: &[IpNet],
+) -> Option<IpAddr> {
+    let ConnectInfo(peer) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+    let peer = canonical(peer.ip());
+    let trusted = |ip: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+    if !trusted(&peer) {
+        return Some(peer);
+    }
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(forwarded) = header("x-forwarded-for") {
+        let hops: Option<Vec<IpAddr>> = forwarded
+            .split(',')
+            .map(|hop| hop.trim().parse().ok().map(canonical))
+            .collect();
+        // An unreadable chain can't be attributed to anyone
+        let hops = hops?;
+        return hops.iter().rev().find(|ip| !trusted(ip)).or(hops.first()).copied();
+    }
+    match header("x-real-ip") {
+        Some(real_ip) => real_ip.trim().parse().ok().map(canonical),
+        None => Some(peer),
+    }
+}
+
+// IPv4 clients of a dual-stack listener show up as IPv4-mapped IPv6 addresses
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_normalize_and_match() {
+        let entries: Vec<String> =
+            ["203.0.113.7/24", "198.51.100.9", "2001:db8::/32", "203.0.113.0/24"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        let networks = normalize(&entries).unwrap();
+        assert_eq!(networks, vec!["203.0.113.0/24", "198.51.100.9/32", "2001:db8::/32"]);
+
+        assert!(is_allowed(&networks, "203.0.113.200".parse().unwrap()));
+        assert!(is_allowed(&networks, "::ffff:198.51.100.9".parse().unwrap()));
+        assert!(is_allowed(&networks, "2001:db8:1::1".parse().unwrap()));
+        assert!(!is_allowed(&networks, "198.51.100.10".parse().unwrap()));
+        assert!(!is_allowed(&[], "203.0.113.1".parse().unwrap()));
+        assert!(normalize(&["10.0.0.0/33".to_string()]).is_err());
+        assert!(normalize(&["example.com".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let mut extensions = Extensions::new();
+        assert_eq!(client_ip(&HeaderMap::new(), &extensions, &[]), None);
+
+        extensions.insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 2], 4000))));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("1.2.3.4, 203.0.113.7, 10.0.0.1"),
+        );
+        // Forwarding headers from an untrusted peer are ignored
+        let peer = client_ip(&headers, &extensions, &[]);
+        assert_eq!(peer, "10.0.0.2".parse().ok());
+        // A spoofed leftmost entry doesn't win over the address the proxies saw
+        let proxies: Vec<IpNet> = vec!["10.0.0.0/8".parse().unwrap()];
+        let forwarded = client_ip(&headers, &extensions, &proxies);
+        assert_eq!(forwarded, "203.0.113.7".parse().ok());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("2001:db8::1"));
+        let real_ip = client_ip(&headers, &extensions, &proxies);
+        assert_eq!(real_ip, "2001:db8::1".parse().ok());
+    }
+}
@@ -0,0 +1,239 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Customer organizations. Each key belongs to at most one; an organization's admin keys
+// manage only its keys, and its monthly quota is shared by all of them.
+
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+use uuid::Uuid;
+
+use super::models::Organization;
+
+// Columns read by org_from_row, in order
+const ORG_COLUMNS: &str = "id, name, monthly_quota, created_at, \
+    (SELECT COUNT(*) FROM api_keys WHERE api_keys.org_id = organizations.id)";
+
+// Longest organization name
+pub const MAX_NAME_LENGTH: usize = 200;
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Organization name must be 1 to {} characters",
+            MAX_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+// Create an organization. Returns None when the name is taken.
+pub async fn create_organization(
+    db: &Connection,
+    name: String,
+    monthly_quota: Option<u64>,
+) -> Result<Option<Organization>, String> {
+    let id = Uuid::new_v4().to_string();
+    db.call(move |conn| {
+        let created_at: Option<String> = conn
+            .query_row(
+                "INSERT INTO organizations (id, name, monthly_quota) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (name) DO NOTHING
+                 RETURNING created_at",
+                rusqlite::params![id, name, monthly_quota],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(created_at.map(|created_at| Organization {
+            id,
+            name,
+            monthly_quota,
+            created_at,
+            key_count: 0,
+        }))
+    })
+    .await
+    .map_err(|e| format!("Failed to create organization: {}", e))
+}
+
+pub async fn list_organizations(db: &Connection) -> Result<Vec<Organization>, String> {
+    db.call(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM organizations ORDER BY name, id",
+            ORG_COLUMNS
+        ))?;
+        let orgs = stmt
+            .query_map([], org_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(orgs)
+    })
+    .await
+    .map_err(|e| format!("Failed to list organizations: {}", e))
+}
+
+pub async fn get_organization(db: &Connection, id: String) -> Result<Option<Organization>, String> {
+    db.call(move |conn| {
+        let org = conn
+            .query_row(
+                &format!("SELECT {} FROM organizations WHERE id = ?1", ORG_COLUMNS),
+                [id],
+                org_from_row,
+            )
+            .optional()?;
+        Ok(org)
+    })
+    .await
+    .map_err(|e| format!("Failed to read organization: {}", e))
+}
+
+fn org_from_row(row: &rusqlite::Row) -> rusqlite::Result<Organization> {
+    Ok(Organization {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        monthly_quota: row.get(2)?,
+        created_at: row.get(3)?,
+        key_count: row.get(4)?,
+    })
+}
+
+// Set or remove (None) the quota shared by an organization's keys
+pub async fn set_monthly_quota(
+    db: &Connection,
+    id: String,
+    monthly_quota: Option<u64>,
+) -> Result<bool, String> {
+    db.call(move |conn| {
+        let rows_affected = conn.execute(
+            "UPDATE organizations SET monthly_quota = ?1 WHERE id = ?2",
+            rusqlite::params![monthly_quota, id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to set organization quota: {}", e))
+}
+
+// Delete an organization without keys, and its teams. Returns false for unknown
+// organizations and for ones that still have keys.
+pub async fn delete_organization(db: &Connection, id: String) -> Result<bool, String> {
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        let rows_affected = tx.execute(
+            "DELETE FROM organizations
+             WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM api_keys WHERE org_id = ?1)",
+            [&id],
+        )?;
+        if rows_affected > 0 {
+            crate::teams::delete_owner_teams(&tx, &format!("org:{}", id))?;
+        }
+        tx.commit()?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to delete organization: {}", e))
+}
+
+// Requests made by all of an organization's keys in `month` ("YYYY-MM")
+pub async fn monthly_usage(db: &Connection, id: String, month: String) -> Result<u64, String> {
+    db.call(move |conn| {
+        let count = conn.query_row(
+            "SELECT COALESCE(SUM(u.request_count), 0)
+             FROM api_key_usage u JOIN api_keys k ON k.id = u.key_id
+             WHERE k.org_id = ?1 AND u.month = ?2",
+            rusqlite::params![id, month],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    })
+    .await
+    .map_err(|e| format!("Failed to read usage: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::models::CreateApiKeyRequest;
+    use crate::auth::service::{create_api_key_from, record_org_request, record_request};
+    use crate::db::init_db;
+
+    #[tokio::test]
+    async fn test_organization_lifecycle() {
+        let db = init_db(":memory:").await;
+        let org = create_organization(&db, "Acme".to_string(), Some(10))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(create_organization(&db, "Acme".to_string(), None).await.unwrap().is_none());
+
+        let request = CreateApiKeyRequest {
+            name: "acme-web".to_string(),
+            org_id: Some(org.id.clone()),
+            ..Default::default()
+        };
+        let key = create_api_key_from(&db, request, None).await.unwrap();
+        let listed = list_organizations(&db).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key_count, 1);
+
+        // Keys hold on to their organization
+        assert!(!delete_organization(&db, org.id.clone()).await.unwrap());
+        assert!(set_monthly_quota(&db, org.id.clone(), None).await.unwrap());
+        let fetched = get_organization(&db, org.id.clone()).await.unwrap().unwrap();
+        assert_eq!(fetched.monthly_quota, None);
+
+        crate::auth::service::purge_api_key(&db, key.id).await.unwrap();
+        assert!(delete_organization(&db, org.id.clone()).await.unwrap());
+        assert!(get_organization(&db, org.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shared_quota() {
+        let db = init_db(":memory:").await;
+        let org = create_organization(&db, "Acme".to_string(), Some(3))
+            .await
+            .unwrap()
+            .unwrap();
+        let mut ids = Vec::new();
+        for name in ["a", "b"] {
+            let request = CreateApiKeyRequest {
+                name: name.to_string(),
+                org_id: Some(org.id.clone()),
+                ..Default::default()
+            };
+            ids.push(create_api_key_from(&db, request, None).await.unwrap().id);
+        }
+        let month = "2026-03".to_string();
+        let record = |id: &String, quota| {
+            record_org_request(&db, id.clone(), org.id.clone(), month.clone(), quota, 3)
+        };
+
+        assert_eq!(record(&ids[0], None).await.unwrap(), (Some(1), 1));
+        assert_eq!(record(&ids[1], None).await.unwrap(), (Some(1), 2));
+        // The key's own quota still applies
+        assert_eq!(record(&ids[1], Some(1)).await.unwrap(), (None, 2));
+        assert_eq!(record(&ids[0], None).await.unwrap(), (Some(2), 3));
+        // Both keys are stopped once the organization's quota is used up
+        assert_eq!(record(&ids[1], None).await.unwrap(), (None, 3));
+        assert_eq!(monthly_usage(&db, org.id.clone(), month.clone()).await.unwrap(), 3);
+
+        // Keys outside the organization don't count toward it
+        let other = crate::auth::service::create_api_key(&db, "other".to_string(), None)
+            .await
+            .unwrap();
+        record_request(&db, other.id, month.clone(), None).await.unwrap();
+        assert_eq!(monthly_usage(&db, org.id, month).await.unwrap(), 3);
+    }
+}
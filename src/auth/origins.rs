@@ -0,0 +1,115 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Browser origins that may call the API with a particular key
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use axum::http::HeaderValue;
+
+// Most origins a key may list
+pub const MAX_ORIGINS: usize = 50;
+
+// Check a list of origins before storing it. Each is a scheme, host, and optional port,
+// as browsers send them in the Origin header; a trailing '/' is dropped.
+pub fn normalize(entries: &[String]) -> Result<Vec<String>, String> {
+    if entries.len() > MAX_ORIGINS {
+        return Err(format!("At most {} origins are allowed", MAX_ORIGINS));
+    }
+    let mut origins: Vec<String> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let origin = entry.trim().trim_end_matches('/').to_ascii_lowercase();
+        let host = origin
+            .strip_prefix("https://")
+            .or_else(|| origin.strip_prefix("http://"))
+            .filter(|host| !host.is_empty() && !host.contains(['/', '?', '#', '*', ' ']));
+        if host.is_none() || HeaderValue::from_str(&origin).is_err() {
+            return Err(format!(
+                "'{}' is not an origin like https://app.example.com",
+                entry
+            ));
+        }
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+    Ok(origins)
+}
+
+pub fn is_allowed(origins: &[String], origin: &str) -> bool {
+    origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin))
+}
+
+// Every origin listed by an active key. The CORS layer answers preflight requests, which
+// carry no key, for these as well as the server-wide list; the key itself is checked when
+// the actual request arrives.
+#[derive(Debug, Default)]
+pub struct OriginRegistry {
+    origins: RwLock<HashSet<String>>,
+}
+
+impl OriginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn replace(&self, origins: Vec<String>) {
+        let mut current = self.origins.write().unwrap_or_else(|e| e.into_inner());
+        *current = origins.into_iter().collect();
+    }
+
+    pub fn contains(&self, origin: &HeaderValue) -> bool {
+        let Ok(origin) = origin.to_str() else {
+            return false;
+        };
+        let origins = self.origins.read().unwrap_or_else(|e| e.into_inner());
+        origins.contains(&origin.to_ascii_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let entries = vec![
+            "https://App.example.com/".to_string(),
+            "http://localhost:5173".to_string(),
+            "https://app.example.com".to_string(),
+        ];
+        let origins = normalize(&entries).unwrap();
+        assert_eq!(origins, vec!["https://app.example.com", "http://localhost:5173"]);
+        assert!(is_allowed(&origins, "https://APP.example.com"));
+        assert!(!is_allowed(&origins, "https://evil.example.com"));
+
+        for bad in ["app.example.com", "https://", "https://a.com/path", "ftp://a.com", "*"] {
+            assert!(normalize(&[bad.to_string()]).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = OriginRegistry::new();
+        let origin = HeaderValue::from_static("https://app.example.com");
+        assert!(!registry.contains(&origin));
+        registry.replace(vec!["https://app.example.com".to_string()]);
+        assert!(registry.contains(&origin));
+        registry.replace(Vec::new());
+        assert!(!registry.contains(&origin));
+    }
+}
@@ -0,0 +1,85 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Path patterns that restrict which routes an API key may call
+
+use percent_encoding::percent_decode_str;
+
+// Whether `path` matches any of `patterns`. Paths are compared percent-decoded, so
+// "/api/time/Europe/*" covers "/api/time/Europe%2FBelgrade".
+pub fn is_allowed(patterns: &[String], path: &str) -> bool {
+    let decoded = percent_decode_str(path).decode_utf8_lossy();
+    patterns.iter().any(|pattern| matches(pattern, &decoded))
+}
+
+// Check a pattern before storing it
+pub fn validate(pattern: &str) -> Result<(), String> {
+    if !pattern.starts_with('/') {
+        return Err(format!("Route pattern '{}' must start with '/'", pattern));
+    }
+    Ok(())
+}
+
+// Glob match where '*' stands for any run of characters, '/' included
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, path): (Vec<char>, Vec<char>) = (pattern.chars().collect(), path.chars().collect());
+    let (mut p, mut s) = (0, 0);
+    // Where the last '*' was, and how much of the path it has taken so far
+    let mut backtrack: Option<(usize, usize)> = None;
+    while s < path.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, s));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == path[s] {
+            p += 1;
+            s += 1;
+        } else if let Some((star, taken)) = backtrack {
+            // Let the last '*' swallow one more character and retry
+            p = star + 1;
+            s = taken + 1;
+            backtrack = Some((star, taken + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("/api/time/Europe/*", "/api/time/Europe/Belgrade"));
+        assert!(matches("/api/time/*", "/api/time/America/Argentina/Salta"));
+        assert!(matches("/api/*/now", "/api/epoch/now"));
+        assert!(matches("/api/timezones", "/api/timezones"));
+        assert!(!matches("/api/timezones", "/api/timezones/export"));
+        assert!(!matches("/api/time/Europe/*", "/api/time/Asia/Tokyo"));
+        assert!(!matches("/api/time/Europe/*", "/api/time/Europe"));
+    }
+
+    #[test]
+    fn test_is_allowed_decodes_paths() {
+        let patterns = vec!["/api/time/Europe/*".to_string(), "/api/parse".to_string()];
+        assert!(is_allowed(&patterns, "/api/time/Europe%2FBelgrade"));
+        assert!(is_allowed(&patterns, "/api/parse"));
+        assert!(!is_allowed(&patterns, "/api/time/Asia%2FTokyo"));
+        assert!(!is_allowed(&[], "/api/parse"));
+        assert!(validate("api/parse").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+
+// Requests counted per API key in fixed windows of one clock minute. Counts live in
+// memory, so they reset when the server restarts.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    // Minutes since the Unix epoch
+    minute: i64,
+    count: u32,
+}
+
+// Outcome of counting one request against a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed { remaining: u32 },
+    // Seconds until the current window ends
+    Exceeded { retry_after: u64 },
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Count a request by `key_id` at `now` against `limit` requests per minute
+    pub fn check(&self, key_id: &str, limit: u32, now: DateTime<Utc>) -> RateLimitDecision {
+        let minute = now.timestamp().div_euclid(60);
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop finished windows now and then, so idle keys don't pile up
+        if windows.len() > 10_000 {
+            windows.retain(|_, window| window.minute == minute);
+        }
+
+        let window = windows
+            .entry(key_id.to_string())
+            .or_insert(Window { minute, count: 0 });
+        if window.minute != minute {
+            *window = Window { minute, count: 0 };
+        }
+        if window.count >= limit {
+            return RateLimitDecision::Exceeded {
+                retry_after: retry_after(now),
+            };
+        }
+        window.count += 1;
+        RateLimitDecision::Allowed {
+            remaining: limit - window.count,
+        }
+    }
+
+    // Requests counted for `key_id` in the window containing `now`, without adding one
+    pub fn used(&self, key_id: &str, now: DateTime<Utc>) -> u32 {
+        let minute = now.timestamp().div_euclid(60);
+        let windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        windows
+            .get(key_id)
+            .filter(|window| window.minute == minute)
+            .map_or(0, |window| window.count)
+    }
+}
+
+// Seconds from `now` until its one-minute window ends
+pub(crate) fn retry_after(now: DateTime<Utc>) -> u64 {
+    60 - now.timestamp().rem_euclid(60) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn test_limit_resets_each_minute() {
+        let limiter = RateLimiter::new();
+        let start = 1_739_197_800; // on a minute boundary
+        assert_eq!(
+            limiter.check("a", 2, at(start)),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+        assert_eq!(
+            limiter.check("a", 2, at(start + 10)),
+            RateLimitDecision::Allowed { remaining: 0 }
+        );
+        assert_eq!(
+            limiter.check("a", 2, at(start + 45)),
+            RateLimitDecision::Exceeded { retry_after: 15 }
+        );
+        // Other keys have their own windows
+        assert_eq!(
+            limiter.check("b", 2, at(start + 45)),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+        assert_eq!(limiter.used("a", at(start + 59)), 2);
+        assert_eq!(limiter.used("a", at(start + 60)), 0);
+        assert_eq!(
+            limiter.check("a", 2, at(start + 60)),
+            RateLimitDecision::Allowed { remaining: 1 }
+        );
+    }
+}
@@ -14,114 +14,1003 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use rusqlite::OptionalExtension;
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
 use tokio_rusqlite::Connection;
 use uuid::Uuid;
 
-use super::models::{ApiKeyListItem, CreateApiKeyResponse};
+use super::models::{
+    ApiKeyListItem, ApiKeyStatus, AuthenticatedKey, CreateApiKeyRequest, CreateApiKeyResponse,
+    Credential, KeyRole, ListApiKeysQuery, RotateApiKeyResponse, UpdateApiKeyRequest,
+};
 
-pub fn hash_api_key(key: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(key.as_bytes());
-    hex::encode(hasher.finalize())
+// Columns read into an ApiKeyListItem, in list_item_from_row order
+const LIST_ITEM_COLUMNS: &str = "id, name, created_at, is_active, expires_at, \
+    rate_limit_per_minute, monthly_quota, allowed_routes, tags, owner_email, role, allowed_ips, \
+    allowed_origins, org_id, key_prefix, key_last_four, requests_remaining";
+
+// Limits on the tags a key may carry
+pub const MAX_TAGS: usize = 20;
+pub const MAX_TAG_LENGTH: usize = 64;
+
+// HMAC-SHA256 of the key under the server's pepper, so a leaked database alone can't be
+// used to test guesses. Without a pepper this is the plain SHA-256 keys were first stored
+// with.
+pub fn hash_api_key(key: &str, pepper: Option<&str>) -> String {
+    match pepper {
+        Some(pepper) => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(pepper.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(key.as_bytes());
+            hex::encode(mac.finalize().into_bytes())
+        }
+        None => {
+            let mut hasher = Sha256::new();
+            hasher.update(key.as_bytes());
+            hex::encode(hasher.finalize())
+        }
+    }
 }
 
+// Compare two hashes in time that doesn't depend on where they differ
+pub fn hashes_equal(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+// Hex characters of the public id in a key
+const KEY_ID_LENGTH: usize = 8;
+
+// A new key, "ez_<keyid>_<secret>". The key id isn't secret: it is stored in the clear so
+// a key can be recognized by its prefix.
 pub fn generate_api_key() -> String {
-    format!("ez_{}", Uuid::new_v4().simple())
+    let key_id = Uuid::new_v4().simple().to_string();
+    format!("ez_{}_{}", &key_id[..KEY_ID_LENGTH], Uuid::new_v4().simple())
 }
 
+// The "ez_<keyid>" prefix of a key or of its hint. Keys of the older "ez_<secret>" format
+// have none.
+pub fn key_prefix(key: &str) -> Option<&str> {
+    let key_id = key.strip_prefix("ez_")?.get(..KEY_ID_LENGTH)?;
+    let rest = &key[3 + KEY_ID_LENGTH..];
+    let separated = rest.is_empty() || rest.starts_with('_') || rest.starts_with('…');
+    (key_id.bytes().all(|b| b.is_ascii_hexdigit()) && separated)
+        .then(|| &key[..3 + KEY_ID_LENGTH])
+}
+
+// The last four characters of a key, stored next to its prefix
+fn last_four(key: &str) -> &str {
+    &key[key.len().saturating_sub(4)..]
+}
+
+// How lists show a key: its prefix and last four characters, like "ez_ab12cd34…89ef"
+fn key_hint(prefix: &str, last_four: &str) -> String {
+    format!("{}…{}", prefix, last_four)
+}
+
+// Create a key with just a name and expiry, hashed without a pepper
 pub async fn create_api_key(
     db: &Connection,
     name: String,
     expires_at: Option<String>,
 ) -> Result<CreateApiKeyResponse, String> {
-    let raw_key = generate_api_key();
-    let key_hash = hash_api_key(&raw_key);
-    let id = Uuid::new_v4().to_string();
+    let request = CreateApiKeyRequest {
+        name,
+        expires_at,
+        ..Default::default()
+    };
+    create_api_key_from(db, request, None).await
+}
 
-    let name_clone = name.clone();
-    let expires_clone = expires_at.clone();
-    let id_for_insert = id.clone();
-    let id_for_select = id.clone();
+// Create a key with the fields of a creation request, which should already have passed
+// normalize_tags and validate_owner_email
+pub async fn create_api_key_from(
+    db: &Connection,
+    request: CreateApiKeyRequest,
+    pepper: Option<&str>,
+) -> Result<CreateApiKeyResponse, String> {
+    let mut created = create_api_keys(db, vec![request], pepper).await?;
+    Ok(created.remove(0))
+}
 
+// Create several keys at once, all or none, in request order
+pub async fn create_api_keys(
+    db: &Connection,
+    requests: Vec<CreateApiKeyRequest>,
+    pepper: Option<&str>,
+) -> Result<Vec<CreateApiKeyResponse>, String> {
+    let pepper = pepper.map(str::to_string);
     db.call(move |conn| {
-        conn.execute(
-            "INSERT INTO api_keys (id, key_hash, name, expires_at) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![id_for_insert, key_hash, name_clone, expires_clone],
-        )?;
-        Ok(())
+        let tx = conn.transaction()?;
+        let created = requests
+            .into_iter()
+            .map(|request| insert_api_key(&tx, request, pepper.as_deref()))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(created)
     })
     .await
-    .map_err(|e| format!("Failed to create API key: {}", e))?;
-
-    let created_at = db
-        .call(move |conn| {
-            let created: String = conn.query_row(
-                "SELECT created_at FROM api_keys WHERE id = ?1",
-                rusqlite::params![id_for_select],
-                |row| row.get(0),
-            )?;
-            Ok(created)
-        })
-        .await
-        .map_err(|e| format!("Failed to read created_at: {}", e))?;
+    .map_err(|e| format!("Failed to create API key: {}", e))
+}
 
+fn insert_api_key(
+    conn: &rusqlite::Connection,
+    request: CreateApiKeyRequest,
+    pepper: Option<&str>,
+) -> rusqlite::Result<CreateApiKeyResponse> {
+    let raw_key = generate_api_key();
+    let prefix = key_prefix(&raw_key).unwrap_or_default();
+    let id = Uuid::new_v4().to_string();
+    let created_at = conn.query_row(
+        "INSERT INTO api_keys (id, key_hash, name, expires_at, tags, owner_email, role, org_id,
+                               key_prefix, key_last_four, requests_remaining)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+         RETURNING created_at",
+        rusqlite::params![
+            id,
+            hash_api_key(&raw_key, pepper),
+            request.name,
+            request.expires_at,
+            tags_to_column(&request.tags),
+            request.owner_email,
+            request.role.as_str(),
+            request.org_id,
+            prefix,
+            last_four(&raw_key),
+            request.request_limit.filter(|_| request.role == KeyRole::Trial)
+        ],
+        |row| row.get(0),
+    )?;
     Ok(CreateApiKeyResponse {
         id,
-        name,
+        name: request.name,
+        key_hint: key_hint(prefix, last_four(&raw_key)),
         api_key: raw_key,
         created_at,
-        expires_at,
+        expires_at: request.expires_at,
+        tags: request.tags,
+        owner_email: request.owner_email,
+        role: request.role,
+        org_id: request.org_id,
     })
 }
 
-pub async fn list_api_keys(db: &Connection) -> Result<Vec<ApiKeyListItem>, String> {
-    db.call(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT id, name, created_at, is_active, expires_at FROM api_keys ORDER BY created_at DESC",
+// On first run, create an admin key so keys can be managed without a configured admin
+// secret. Returns None when an active admin key for the whole service already exists.
+pub async fn bootstrap_admin_key(
+    db: &Connection,
+    pepper: Option<&str>,
+) -> Result<Option<CreateApiKeyResponse>, String> {
+    let pepper = pepper.map(str::to_string);
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        if count_active_admins(&tx)? > 0 {
+            return Ok(None);
+        }
+        let request = CreateApiKeyRequest {
+            name: "bootstrap admin".to_string(),
+            role: KeyRole::Admin,
+            ..Default::default()
+        };
+        let created = insert_api_key(&tx, request, pepper.as_deref())?;
+        tx.commit()?;
+        Ok(Some(created))
+    })
+    .await
+    .map_err(|e| format!("Failed to create admin key: {}", e))
+}
+
+pub async fn active_admin_count(db: &Connection) -> Result<u64, String> {
+    db.call(|conn| Ok(count_active_admins(conn)?))
+        .await
+        .map_err(|e| format!("Failed to count admin keys: {}", e))
+}
+
+// Admin keys of organizations are left out: they can't manage the rest of the service
+fn count_active_admins(conn: &rusqlite::Connection) -> rusqlite::Result<u64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM api_keys
+         WHERE role = 'admin' AND is_active = 1 AND org_id IS NULL
+           AND (expires_at IS NULL OR expires_at > datetime('now'))",
+        [],
+        |row| row.get(0),
+    )
+}
+
+// The format expires_at is stored in, matching SQLite's datetime('now') so the two compare
+// as text
+pub(crate) const EXPIRY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Work out a new key's expiry from at most one of an absolute time, a number of seconds,
+// or a span like "30d" (units s, m, h, d, w). The result is in the future and in
+// EXPIRY_FORMAT.
+pub fn resolve_expiry(
+    expires_at: Option<&str>,
+    expires_in_seconds: Option<u64>,
+    expires_in: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Option<String>, String> {
+    let given = [expires_at.is_some(), expires_in_seconds.is_some(), expires_in.is_some()];
+    if given.iter().filter(|g| **g).count() > 1 {
+        return Err(
+            "Give only one of 'expires_at', 'expires_in_seconds', and 'expires_in'".to_string()
+        );
+    }
+
+    let expiry = if let Some(value) = expires_at {
+        parse_expiry(value)
+            .ok_or_else(|| format!("Invalid expires_at '{}' (expected RFC 3339)", value))?
+    } else {
+        let seconds = match (expires_in_seconds, expires_in) {
+            (Some(seconds), _) => seconds,
+            (None, Some(span)) => parse_span(span)
+                .ok_or_else(|| format!("Invalid expires_in '{}' (expected e.g. 30d)", span))?,
+            (None, None) => return Ok(None),
+        };
+        i64::try_from(seconds)
+            .ok()
+            .and_then(chrono::Duration::try_seconds)
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .filter(|expiry| expiry.year() <= 9999)
+            .ok_or_else(|| "Expiry is too far in the future".to_string())?
+    };
+
+    if expiry <= now {
+        return Err("Expiry must be in the future".to_string());
+    }
+    Ok(Some(expiry.format(EXPIRY_FORMAT).to_string()))
+}
+
+fn parse_expiry(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.to_utc());
+    }
+    chrono::NaiveDateTime::parse_from_str(value, EXPIRY_FORMAT)
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+// Seconds in a span such as "90m" or "30d"
+fn parse_span(span: &str) -> Option<u64> {
+    let span = span.trim();
+    let unit = span.chars().last()?;
+    let count: u64 = span[..span.len() - unit.len_utf8()].parse().ok()?;
+    let unit_seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        'w' => 7 * 86_400,
+        _ => return None,
+    };
+    count.checked_mul(unit_seconds)
+}
+
+// Trim tags and drop empty and repeated ones, rejecting too many or too long
+pub fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(format!("Tag '{}' is longer than {} characters", tag, MAX_TAG_LENGTH));
+        }
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("A key can have at most {} tags", MAX_TAGS));
+    }
+    Ok(normalized)
+}
+
+// A plausible address: one '@' with something on both sides and no whitespace
+pub fn validate_owner_email(email: &str) -> Result<(), String> {
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid owner email '{}'", email))
+    }
+}
+
+// Apply the fields present in `update`, which should already be validated. Returns false
+// for unknown keys.
+pub async fn update_api_key(
+    db: &Connection,
+    id: String,
+    update: UpdateApiKeyRequest,
+) -> Result<bool, String> {
+    db.call(move |conn| {
+        let mut assignments: Vec<&str> = Vec::new();
+        let mut values: Vec<Option<String>> = Vec::new();
+        if let Some(name) = update.name {
+            assignments.push("name = ?");
+            values.push(Some(name));
+        }
+        if let Some(tags) = update.tags {
+            assignments.push("tags = ?");
+            values.push(tags_to_column(&tags));
+        }
+        if let Some(owner_email) = update.owner_email {
+            assignments.push("owner_email = ?");
+            values.push(owner_email);
+        }
+        if let Some(org_id) = update.org_id {
+            assignments.push("org_id = ?");
+            values.push(org_id);
+        }
+        if assignments.is_empty() {
+            let exists = conn
+                .query_row("SELECT 1 FROM api_keys WHERE id = ?1", [&id], |_| Ok(()))
+                .optional()?;
+            return Ok(exists.is_some());
+        }
+        values.push(Some(id));
+        let rows_affected = conn.execute(
+            &format!("UPDATE api_keys SET {} WHERE id = ?", assignments.join(", ")),
+            rusqlite::params_from_iter(values),
         )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to update API key: {}", e))
+}
+
+// Tags are stored as a JSON array, NULL when there are none
+fn tags_to_column(tags: &[String]) -> Option<String> {
+    if tags.is_empty() {
+        None
+    } else {
+        serde_json::to_string(tags).ok()
+    }
+}
+
+// Up to `limit` keys matching the query's filters, newest first, with the number of
+// matching keys across all pages
+pub async fn list_api_keys(
+    db: &Connection,
+    query: &ListApiKeysQuery,
+    limit: u32,
+) -> Result<(Vec<ApiKeyListItem>, u64), String> {
+    let expired = "(expires_at IS NOT NULL AND expires_at <= datetime('now'))";
+    let status_filter = match query.status {
+        None => "1".to_string(),
+        Some(ApiKeyStatus::Active) => format!("is_active = 1 AND NOT {}", expired),
+        Some(ApiKeyStatus::Revoked) => format!("is_active = 0 AND NOT {}", expired),
+        Some(ApiKeyStatus::Expired) => expired.to_string(),
+    };
+    // LIKE wildcards in the search are matched literally
+    let pattern = query.q.as_ref().map(|q| {
+        let escaped = q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        format!("%{}%", escaped)
+    });
+    let filter = format!(
+        "WHERE {} AND (?1 IS NULL OR name LIKE ?1 ESCAPE '\\')
+           AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(tags) WHERE value = ?2))
+           AND (?3 IS NULL OR owner_email = ?3 COLLATE NOCASE)
+           AND (?4 IS NULL OR org_id = ?4)
+           AND (?5 IS NULL OR key_prefix = ?5)",
+        status_filter
+    );
+    let (tag, owner_email, offset) = (query.tag.clone(), query.owner_email.clone(), query.offset);
+    let org_id = query.org_id.clone();
+    // Text without a key's prefix matches no key
+    let prefix = query
+        .key_prefix
+        .as_deref()
+        .map(|text| key_prefix(text).unwrap_or(text).to_string());
+
+    db.call(move |conn| {
+        let total: u64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM api_keys {}", filter),
+            rusqlite::params![pattern, tag, owner_email, org_id, prefix],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM api_keys {} ORDER BY created_at DESC, id LIMIT ?6 OFFSET ?7",
+            LIST_ITEM_COLUMNS, filter
+        ))?;
+        let params = rusqlite::params![pattern, tag, owner_email, org_id, prefix, limit, offset];
         let keys = stmt
-            .query_map([], |row| {
-                Ok(ApiKeyListItem {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    created_at: row.get(2)?,
-                    is_active: row.get::<_, i32>(3)? == 1,
-                    expires_at: row.get(4)?,
-                })
-            })?
+            .query_map(params, list_item_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(keys)
+        Ok((keys, total))
     })
     .await
     .map_err(|e| format!("Failed to list API keys: {}", e))
 }
 
+pub async fn get_api_key(db: &Connection, id: String) -> Result<Option<ApiKeyListItem>, String> {
+    db.call(move |conn| {
+        let key = conn
+            .query_row(
+                &format!("SELECT {} FROM api_keys WHERE id = ?1", LIST_ITEM_COLUMNS),
+                rusqlite::params![id],
+                list_item_from_row,
+            )
+            .optional()?;
+        Ok(key)
+    })
+    .await
+    .map_err(|e| format!("Failed to read API key: {}", e))
+}
+
+fn list_item_from_row(row: &rusqlite::Row) -> rusqlite::Result<ApiKeyListItem> {
+    let prefix: Option<String> = row.get(14)?;
+    let last_four: Option<String> = row.get(15)?;
+    Ok(ApiKeyListItem {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        key_hint: prefix.zip(last_four).map(|(prefix, last_four)| key_hint(&prefix, &last_four)),
+        created_at: row.get(2)?,
+        is_active: row.get::<_, i32>(3)? == 1,
+        expires_at: row.get(4)?,
+        rate_limit_per_minute: row.get(5)?,
+        monthly_quota: row.get(6)?,
+        allowed_routes: routes_from_column(row.get(7)?),
+        tags: row
+            .get::<_, Option<String>>(8)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        owner_email: row.get(9)?,
+        role: KeyRole::from_column(&row.get::<_, String>(10)?),
+        allowed_ips: routes_from_column(row.get(11)?),
+        allowed_origins: routes_from_column(row.get(12)?),
+        org_id: row.get(13)?,
+        requests_remaining: row.get(16)?,
+    })
+}
+
+// The allowed_routes, allowed_ips, and allowed_origins columns hold JSON arrays; anything
+// unreadable means no restriction was stored
+fn routes_from_column(value: Option<String>) -> Option<Vec<String>> {
+    value.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+// Set or clear (None) a key's own requests-per-minute limit
+pub async fn set_rate_limit(
+    db: &Connection,
+    id: String,
+    rate_limit_per_minute: Option<u32>,
+) -> Result<bool, String> {
+    db.call(move |conn| {
+        let rows_affected = conn.execute(
+            "UPDATE api_keys SET rate_limit_per_minute = ?1 WHERE id = ?2",
+            rusqlite::params![rate_limit_per_minute, id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to set rate limit: {}", e))
+}
+
+// Set or clear (None) a key's monthly request quota
+pub async fn set_monthly_quota(
+    db: &Connection,
+    id: String,
+    monthly_quota: Option<u64>,
+) -> Result<bool, String> {
+    db.call(move |conn| {
+        let rows_affected = conn.execute(
+            "UPDATE api_keys SET monthly_quota = ?1 WHERE id = ?2",
+            rusqlite::params![monthly_quota, id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to set quota: {}", e))
+}
+
+// Restrict a key to routes matching `allowed_routes`, or lift the restriction (None)
+pub async fn set_allowed_routes(
+    db: &Connection,
+    id: String,
+    allowed_routes: Option<Vec<String>>,
+) -> Result<bool, String> {
+    let json = allowed_routes
+        .map(|routes| serde_json::to_string(&routes))
+        .transpose()
+        .map_err(|e| format!("Failed to encode route patterns: {}", e))?;
+    db.call(move |conn| {
+        let rows_affected = conn.execute(
+            "UPDATE api_keys SET allowed_routes = ?1 WHERE id = ?2",
+            rusqlite::params![json, id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to set allowed routes: {}", e))
+}
+
+// Restrict a key to addresses in `allowed_ips`, or lift the restriction (None)
+pub async fn set_allowed_ips(
+    db: &Connection,
+    id: String,
+    allowed_ips: Option<Vec<String>>,
+) -> Result<bool, String> {
+    let json = allowed_ips
+        .map(|networks| serde_json::to_string(&networks))
+        .transpose()
+        .map_err(|e| format!("Failed to encode networks: {}", e))?;
+    db.call(move |conn| {
+        let rows_affected = conn.execute(
+            "UPDATE api_keys SET allowed_ips = ?1 WHERE id = ?2",
+            rusqlite::params![json, id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to set allowed IPs: {}", e))
+}
+
+// Give a key its own browser origins, or go back to the server-wide list (None)
+pub async fn set_allowed_origins(
+    db: &Connection,
+    id: String,
+    allowed_origins: Option<Vec<String>>,
+) -> Result<bool, String> {
+    let json = allowed_origins
+        .map(|origins| serde_json::to_string(&origins))
+        .transpose()
+        .map_err(|e| format!("Failed to encode origins: {}", e))?;
+    db.call(move |conn| {
+        let rows_affected = conn.execute(
+            "UPDATE api_keys SET allowed_origins = ?1 WHERE id = ?2",
+            rusqlite::params![json, id],
+        )?;
+        Ok(rows_affected > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to set allowed origins: {}", e))
+}
+
+// Every origin listed by an active key
+pub async fn key_origins(db: &Connection) -> Result<Vec<String>, String> {
+    db.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT origin.value
+             FROM api_keys, json_each(api_keys.allowed_origins) AS origin
+             WHERE api_keys.is_active = 1 AND json_valid(api_keys.allowed_origins)",
+        )?;
+        let origins = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(origins)
+    })
+    .await
+    .map_err(|e| format!("Failed to read key origins: {}", e))
+}
+
+// Usage is counted per UTC calendar month, keyed "YYYY-MM"
+pub fn usage_month(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+// Midnight UTC on the first of the month after `now`, when monthly quotas reset
+pub fn next_month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+        .and_then(|first| first.checked_add_months(Months::new(1)))
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+        .unwrap_or(now)
+}
+
+// When the secret replaced by a key's last rotation stops working, if it still works
+pub async fn previous_key_expiry(db: &Connection, id: String) -> Result<Option<String>, String> {
+    db.call(move |conn| {
+        let expiry = conn
+            .query_row(
+                "SELECT previous_key_expires_at FROM api_keys
+                 WHERE id = ?1 AND previous_key_expires_at > datetime('now')",
+                rusqlite::params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(expiry)
+    })
+    .await
+    .map_err(|e| format!("Failed to read API key: {}", e))
+}
+
+// Requests a key has made in `month`
+pub async fn monthly_usage(db: &Connection, id: String, month: String) -> Result<u64, String> {
+    db.call(move |conn| {
+        let count = conn
+            .query_row(
+                "SELECT request_count FROM api_key_usage WHERE key_id = ?1 AND month = ?2",
+                rusqlite::params![id, month],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(count.unwrap_or(0))
+    })
+    .await
+    .map_err(|e| format!("Failed to read usage: {}", e))
+}
+
+// Count a request against a key's usage for `month` ("YYYY-MM"). Returns the new count,
+// or None when `monthly_quota` is already used up, in which case nothing is counted.
+pub async fn record_request(
+    db: &Connection,
+    key_id: String,
+    month: String,
+    monthly_quota: Option<u64>,
+) -> Result<Option<u64>, String> {
+    if monthly_quota == Some(0) {
+        return Ok(None);
+    }
+    db.call(move |conn| Ok(count_request(conn, &key_id, &month, monthly_quota)?))
+        .await
+        .map_err(|e| format!("Failed to record usage: {}", e))
+}
+
+// Like record_request, for a key whose organization shares `org_quota` among its keys: the
+// request is counted only while neither quota is used up. Returns the key's new count, or
+// None when nothing was counted, and the organization's requests for the month.
+pub async fn record_org_request(
+    db: &Connection,
+    key_id: String,
+    org_id: String,
+    month: String,
+    monthly_quota: Option<u64>,
+    org_quota: u64,
+) -> Result<(Option<u64>, u64), String> {
+    db.call(move |conn| {
+        let org_requests: u64 = conn.query_row(
+            "SELECT COALESCE(SUM(u.request_count), 0)
+             FROM api_key_usage u JOIN api_keys k ON k.id = u.key_id
+             WHERE k.org_id = ?1 AND u.month = ?2",
+            rusqlite::params![org_id, month],
+            |row| row.get(0),
+        )?;
+        if org_requests >= org_quota || monthly_quota == Some(0) {
+            return Ok((None, org_requests));
+        }
+        let count = count_request(conn, &key_id, &month, monthly_quota)?;
+        Ok((count, org_requests + count.is_some() as u64))
+    })
+    .await
+    .map_err(|e| format!("Failed to record usage: {}", e))
+}
+
+fn count_request(
+    conn: &rusqlite::Connection,
+    key_id: &str,
+    month: &str,
+    monthly_quota: Option<u64>,
+) -> rusqlite::Result<Option<u64>> {
+    conn.query_row(
+        "INSERT INTO api_key_usage (key_id, month, request_count) VALUES (?1, ?2, 1)
+         ON CONFLICT (key_id, month) DO UPDATE SET request_count = request_count + 1
+         WHERE ?3 IS NULL OR request_count < ?3
+         RETURNING request_count",
+        rusqlite::params![key_id, month, monthly_quota],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+// Forget a key's usage for `month`, restoring its full quota. Returns false for unknown keys.
+pub async fn reset_monthly_usage(
+    db: &Connection,
+    id: String,
+    month: String,
+) -> Result<bool, String> {
+    db.call(move |conn| {
+        let exists = conn
+            .query_row("SELECT 1 FROM api_keys WHERE id = ?1", rusqlite::params![id], |_| Ok(()))
+            .optional()?
+            .is_some();
+        conn.execute(
+            "DELETE FROM api_key_usage WHERE key_id = ?1 AND month = ?2",
+            rusqlite::params![id, month],
+        )?;
+        Ok(exists)
+    })
+    .await
+    .map_err(|e| format!("Failed to reset usage: {}", e))
+}
+
 pub async fn revoke_api_key(db: &Connection, id: String) -> Result<bool, String> {
     db.call(move |conn| {
         let rows_affected =
-            conn.execute("UPDATE api_keys SET is_active = 0 WHERE id = ?1", rusqlite::params![id])?;
+            conn.execute(
+            "UPDATE api_keys SET is_active = 0, revoked_at = COALESCE(revoked_at, datetime('now'))
+             WHERE id = ?1",
+            rusqlite::params![id],
+        )?;
         Ok(rows_affected > 0)
     })
     .await
     .map_err(|e| format!("Failed to revoke API key: {}", e))
 }
 
-pub async fn validate_api_key(db: &Connection, raw_key: &str) -> bool {
-    let key_hash = hash_api_key(raw_key);
+// Permanently delete a key and its usage history. Returns false for unknown keys.
+pub async fn purge_api_key(db: &Connection, id: String) -> Result<bool, String> {
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        let purged = delete_key_rows(&tx, &id)?;
+        tx.commit()?;
+        Ok(purged)
+    })
+    .await
+    .map_err(|e| format!("Failed to purge API key: {}", e))
+}
+
+// Permanently delete every revoked or expired key and its usage history, returning the
+// ids of the keys removed
+pub async fn purge_inactive_api_keys(db: &Connection) -> Result<Vec<String>, String> {
+    db.call(|conn| {
+        let tx = conn.transaction()?;
+        let ids = tx
+            .prepare(
+                "SELECT id FROM api_keys
+                 WHERE is_active = 0 OR (expires_at IS NOT NULL AND expires_at <= datetime('now'))
+                 ORDER BY created_at, id",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        for id in &ids {
+            delete_key_rows(&tx, id)?;
+        }
+        tx.commit()?;
+        Ok(ids)
+    })
+    .await
+    .map_err(|e| format!("Failed to purge API keys: {}", e))
+}
+
+pub(super) fn delete_key_rows(conn: &rusqlite::Connection, id: &str) -> rusqlite::Result<bool> {
+    conn.execute("DELETE FROM api_key_usage WHERE key_id = ?1", [id])?;
+    conn.execute("DELETE FROM api_key_daily_usage WHERE key_id = ?1", [id])?;
+    conn.execute("DELETE FROM webhook_notifications WHERE key_id = ?1", [id])?;
+    conn.execute("DELETE FROM expiry_emails WHERE key_id = ?1", [id])?;
+    crate::teams::delete_owner_teams(conn, &format!("key:{}", id))?;
+    Ok(conn.execute("DELETE FROM api_keys WHERE id = ?1", [id])? > 0)
+}
+
+// Give an active key a new secret, keeping its id and settings. With a grace period the
+// old secret keeps working until it ends; otherwise it stops at once. Returns None for
+// unknown or revoked keys.
+pub async fn rotate_api_key(
+    db: &Connection,
+    id: String,
+    grace_period_seconds: u64,
+    pepper: Option<&str>,
+) -> Result<Option<RotateApiKeyResponse>, String> {
+    let raw_key = generate_api_key();
+    let key_hash = hash_api_key(&raw_key, pepper);
+    let prefix = key_prefix(&raw_key).unwrap_or_default().to_string();
+    let last_four = last_four(&raw_key).to_string();
+    let grace = format!("+{} seconds", grace_period_seconds);
+
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        let rotated = tx.execute(
+            "UPDATE api_keys SET
+                previous_key_hash = CASE WHEN ?3 > 0 THEN key_hash END,
+                previous_key_expires_at = CASE WHEN ?3 > 0 THEN datetime('now', ?4) END,
+                key_hash = ?2,
+                key_prefix = ?5,
+                key_last_four = ?6
+             WHERE id = ?1 AND is_active = 1",
+            rusqlite::params![id, key_hash, grace_period_seconds, grace, prefix, last_four],
+        )?;
+        if rotated == 0 {
+            return Ok(None);
+        }
+        let response = tx.query_row(
+            "SELECT id, name, created_at, expires_at, previous_key_expires_at
+             FROM api_keys WHERE id = ?1",
+            rusqlite::params![id],
+            |row| {
+                Ok(RotateApiKeyResponse {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    api_key: raw_key.clone(),
+                    key_hint: key_hint(&prefix, &last_four),
+                    created_at: row.get(2)?,
+                    expires_at: row.get(3)?,
+                    previous_key_expires_at: row.get(4)?,
+                })
+            },
+        )?;
+        tx.commit()?;
+        Ok(Some(response))
+    })
+    .await
+    .map_err(|e| format!("Failed to rotate API key: {}", e))
+}
+
+// Use up one of a trial key's requests, returning how many are left, or None when there
+// were none. The key is deactivated along with its last request.
+pub async fn use_trial_request(db: &Connection, id: String) -> Result<Option<u64>, String> {
     db.call(move |conn| {
-        let exists: bool = conn
+        let remaining = conn
             .query_row(
-                "SELECT COUNT(*) FROM api_keys WHERE key_hash = ?1 AND is_active = 1 AND (expires_at IS NULL OR expires_at > datetime('now'))",
-                rusqlite::params![key_hash],
-                |row| row.get::<_, i32>(0),
+                "UPDATE api_keys SET
+                    requests_remaining = requests_remaining - 1,
+                    is_active = CASE WHEN requests_remaining = 1 THEN 0 ELSE is_active END,
+                    revoked_at = CASE WHEN requests_remaining = 1 THEN datetime('now')
+                                      ELSE revoked_at END
+                 WHERE id = ?1 AND requests_remaining > 0
+                 RETURNING requests_remaining",
+                rusqlite::params![id],
+                |row| row.get(0),
             )
-            .map(|count| count > 0)
-            .unwrap_or(false);
-        Ok(exists)
+            .optional()?;
+        Ok(remaining)
+    })
+    .await
+    .map_err(|e| format!("Failed to count trial request: {}", e))
+}
+
+pub async fn validate_api_key(db: &Connection, raw_key: &str, pepper: Option<&str>) -> bool {
+    authenticate_api_key(db, raw_key, pepper).await.is_some()
+}
+
+// Look up an active, unexpired key by its raw value. With a pepper configured, a key still
+// stored under its plain SHA-256 hash is accepted once and re-hashed with the pepper.
+pub async fn authenticate_api_key(
+    db: &Connection,
+    raw_key: &str,
+    pepper: Option<&str>,
+) -> Option<AuthenticatedKey> {
+    let key_hash = hash_api_key(raw_key, pepper);
+    let legacy_hash = hash_api_key(raw_key, None);
+    db.call(move |conn| {
+        let found = conn
+            .query_row(
+                &format!(
+                    "SELECT {}, key_hash, previous_key_hash, expires_at, previous_key_expires_at
+                     FROM api_keys
+                     WHERE (key_hash IN (?1, ?2)
+                            OR (previous_key_hash IN (?1, ?2)
+                                AND previous_key_expires_at > datetime('now')))
+                       AND is_active = 1
+                       AND (expires_at IS NULL OR expires_at > datetime('now'))",
+                    AUTHENTICATED_COLUMNS
+                ),
+                rusqlite::params![key_hash, legacy_hash],
+                |row| {
+                    let key = authenticated_from_row(row)?;
+                    let current: String = row.get(9)?;
+                    let previous: Option<String> = row.get(10)?;
+                    let expiries: (Option<String>, Option<String>) = (row.get(11)?, row.get(12)?);
+                    Ok((key, current, previous, expiries))
+                },
+            )
+            .optional()?;
+        let Some((mut key, current, previous, (expires_at, previous_expires_at))) = found else {
+            return Ok(None);
+        };
+        // The index lookup found a candidate; confirm it without an early-exit comparison
+        let matches = |stored: &str| {
+            hashes_equal(stored, &key_hash) || hashes_equal(stored, &legacy_hash)
+        };
+        if !matches(&current) && !previous.as_deref().is_some_and(matches) {
+            return Ok(None);
+        }
+        // A replaced secret stops working at the end of its grace period, if the key
+        // doesn't expire first; both are in EXPIRY_FORMAT, so they compare as text
+        key.expires_at = if matches(&current) {
+            expires_at
+        } else {
+            expires_at.into_iter().chain(previous_expires_at).min()
+        };
+        if key_hash != legacy_hash {
+            let column = if hashes_equal(&current, &legacy_hash) {
+                Some("key_hash")
+            } else if previous.as_deref().is_some_and(|p| hashes_equal(p, &legacy_hash)) {
+                Some("previous_key_hash")
+            } else {
+                None
+            };
+            if let Some(column) = column {
+                conn.execute(
+                    &format!("UPDATE api_keys SET {} = ?1 WHERE id = ?2", column),
+                    rusqlite::params![key_hash, key.id],
+                )?;
+            }
+        }
+        Ok(Some(key))
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+// Columns read by authenticated_from_row, in order
+const AUTHENTICATED_COLUMNS: &str = "id, rate_limit_per_minute, monthly_quota, allowed_routes, \
+    role, allowed_ips, allowed_origins, org_id, \
+    (SELECT monthly_quota FROM organizations WHERE organizations.id = api_keys.org_id)";
+
+fn authenticated_from_row(row: &rusqlite::Row) -> rusqlite::Result<AuthenticatedKey> {
+    Ok(AuthenticatedKey {
+        id: row.get(0)?,
+        rate_limit_per_minute: row.get(1)?,
+        monthly_quota: row.get(2)?,
+        allowed_routes: routes_from_column(row.get(3)?),
+        role: KeyRole::from_column(&row.get::<_, String>(4)?),
+        allowed_ips: routes_from_column(row.get(5)?),
+        allowed_origins: routes_from_column(row.get(6)?),
+        org_id: row.get(7)?,
+        org_monthly_quota: row.get(8)?,
+        scopes: None,
+        credential: Credential::ApiKey,
+        expires_at: None,
+    })
+}
+
+// Turn on signed requests for an active key, replacing any earlier signing secret.
+// Returns the new salt, from which the secret is derived.
+pub async fn enable_request_signing(db: &Connection, id: String) -> Result<Option<String>, String> {
+    let salt = Uuid::new_v4().simple().to_string();
+    db.call(move |conn| {
+        let updated = conn.execute(
+            "UPDATE api_keys SET signing_salt = ?2 WHERE id = ?1 AND is_active = 1",
+            rusqlite::params![id, salt],
+        )?;
+        Ok((updated > 0).then_some(salt))
     })
     .await
-    .unwrap_or(false)
+    .map_err(|e| format!("Failed to enable request signing: {}", e))
+}
+
+pub async fn disable_request_signing(db: &Connection, id: String) -> Result<bool, String> {
+    db.call(move |conn| {
+        let updated = conn.execute(
+            "UPDATE api_keys SET signing_salt = NULL WHERE id = ?1",
+            rusqlite::params![id],
+        )?;
+        Ok(updated > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to disable request signing: {}", e))
+}
+
+// Look up an active, unexpired key that accepts signed requests, with its signing salt
+pub async fn authenticate_signed(
+    db: &Connection,
+    id: String,
+) -> Option<(AuthenticatedKey, String)> {
+    db.call(move |conn| {
+        Ok(conn
+            .query_row(
+                &format!(
+                    "SELECT {}, signing_salt
+                     FROM api_keys
+                     WHERE id = ?1
+                       AND signing_salt IS NOT NULL
+                       AND is_active = 1
+                       AND (expires_at IS NULL OR expires_at > datetime('now'))",
+                    AUTHENTICATED_COLUMNS
+                ),
+                rusqlite::params![id],
+                |row| {
+                    let key = AuthenticatedKey {
+                        credential: Credential::SignedRequest,
+                        ..authenticated_from_row(row)?
+                    };
+                    Ok((key, row.get(9)?))
+                },
+            )
+            .optional()?)
+    })
+    .await
+    .ok()
+    .flatten()
 }
 
 #[cfg(test)]
@@ -132,19 +1021,76 @@ mod tests {
     #[test]
     fn test_hash_determinism() {
         let key = "ez_test123";
-        assert_eq!(hash_api_key(key), hash_api_key(key));
+        assert_eq!(hash_api_key(key, None), hash_api_key(key, None));
+        assert_eq!(hash_api_key(key, Some("pepper")), hash_api_key(key, Some("pepper")));
     }
 
     #[test]
     fn test_hash_different_keys() {
-        assert_ne!(hash_api_key("key1"), hash_api_key("key2"));
+        assert_ne!(hash_api_key("key1", None), hash_api_key("key2", None));
+    }
+
+    #[test]
+    fn test_hashes_equal() {
+        let hash = hash_api_key("ez_test123", None);
+        assert!(hashes_equal(&hash, &hash_api_key("ez_test123", None)));
+        assert!(!hashes_equal(&hash, &hash_api_key("ez_test124", None)));
+        assert!(!hashes_equal(&hash, &hash[1..]));
+    }
+
+    #[test]
+    fn test_hash_peppered() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hash_api_key("what do ya want for nothing?", Some("Jefe")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_ne!(hash_api_key("ez_test123", Some("a")), hash_api_key("ez_test123", Some("b")));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_hash_upgraded() {
+        let db = init_db(":memory:").await;
+        let resp = create_api_key(&db, "legacy".to_string(), None).await.unwrap();
+        let pepper = Some("server-side-pepper");
+
+        assert!(validate_api_key(&db, &resp.api_key, pepper).await);
+        let id = resp.id.clone();
+        let stored: String = db
+            .call(move |conn| {
+                Ok(conn.query_row("SELECT key_hash FROM api_keys WHERE id = ?1", [id], |row| {
+                    row.get(0)
+                })?)
+            })
+            .await
+            .unwrap();
+        assert_eq!(stored, hash_api_key(&resp.api_key, pepper));
+        // Once re-hashed, only the peppered hash matches
+        assert!(validate_api_key(&db, &resp.api_key, pepper).await);
+        assert!(!validate_api_key(&db, &resp.api_key, None).await);
+        assert!(!validate_api_key(&db, &resp.api_key, Some("another-pepper")).await);
     }
 
     #[test]
     fn test_generate_api_key_format() {
         let key = generate_api_key();
         assert!(key.starts_with("ez_"));
-        assert_eq!(key.len(), 3 + 32); // "ez_" + 32-char hex UUID
+        assert_eq!(key.len(), 3 + 8 + 1 + 32); // "ez_" + key id + "_" + 32-char hex UUID
+        assert_eq!(key_prefix(&key), Some(&key[..11]));
+    }
+
+    #[test]
+    fn test_key_prefix() {
+        let key = "ez_ab12cd34_0123456789abcdef0123456789ab89ef";
+        assert_eq!(key_prefix(key), Some("ez_ab12cd34"));
+        assert_eq!(key_hint("ez_ab12cd34", last_four(key)), "ez_ab12cd34…89ef");
+        assert_eq!(key_prefix("ez_ab12cd34…89ef"), Some("ez_ab12cd34"));
+        assert_eq!(key_prefix("ez_ab12cd34"), Some("ez_ab12cd34"));
+        // Keys of the older format have no key id
+        assert_eq!(key_prefix("ez_0123456789abcdef0123456789abcdef"), None);
+        assert_eq!(key_prefix("ez_ab12"), None);
+        assert_eq!(key_prefix("ab12cd34_secret"), None);
+        assert_eq!(key_prefix("ez_ab12cd3x_secret"), None);
     }
 
     #[test]
@@ -154,6 +1100,13 @@ mod tests {
         assert_ne!(key1, key2);
     }
 
+    #[test]
+    fn test_usage_month_boundaries() {
+        let now = DateTime::parse_from_rfc3339("2025-12-31T23:59:59Z").unwrap().to_utc();
+        assert_eq!(usage_month(now), "2025-12");
+        assert_eq!(next_month_start(now).to_rfc3339(), "2026-01-01T00:00:00+00:00");
+    }
+
     #[tokio::test]
     async fn test_create_and_validate_api_key() {
         let db = init_db(":memory:").await;
@@ -163,13 +1116,13 @@ mod tests {
 
         assert!(resp.api_key.starts_with("ez_"));
         assert_eq!(resp.name, "test-key");
-        assert!(validate_api_key(&db, &resp.api_key).await);
+        assert!(validate_api_key(&db, &resp.api_key, None).await);
     }
 
     #[tokio::test]
     async fn test_validate_invalid_key() {
         let db = init_db(":memory:").await;
-        assert!(!validate_api_key(&db, "ez_nonexistent").await);
+        assert!(!validate_api_key(&db, "ez_nonexistent", None).await);
     }
 
     #[tokio::test]
@@ -178,8 +1131,148 @@ mod tests {
         create_api_key(&db, "key-1".to_string(), None).await.unwrap();
         create_api_key(&db, "key-2".to_string(), None).await.unwrap();
 
-        let keys = list_api_keys(&db).await.unwrap();
+        let query = ListApiKeysQuery::default();
+        let (keys, total) = list_api_keys(&db, &query, 100).await.unwrap();
         assert_eq!(keys.len(), 2);
+        assert_eq!(total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_api_keys_filters() {
+        let db = init_db(":memory:").await;
+        for name in ["Acme prod", "acme staging", "Globex", "100%_off"] {
+            create_api_key(&db, name.to_string(), None).await.unwrap();
+        }
+        let revoked = create_api_key(&db, "Acme old".to_string(), None).await.unwrap();
+        revoke_api_key(&db, revoked.id.clone()).await.unwrap();
+        let expiry = Some("2000-01-01 00:00:00".to_string());
+        let expired = create_api_key(&db, "Acme trial".to_string(), expiry).await.unwrap();
+
+        async fn search(
+            db: &Connection,
+            status: Option<ApiKeyStatus>,
+            q: &str,
+            limit: u32,
+            offset: u32,
+        ) -> (Vec<ApiKeyListItem>, u64) {
+            let query = ListApiKeysQuery {
+                status,
+                q: Some(q.to_string()),
+                offset,
+                ..Default::default()
+            };
+            list_api_keys(db, &query, limit).await.unwrap()
+        }
+        let (keys, total) = search(&db, Some(ApiKeyStatus::Active), "ACME", 1, 0).await;
+        assert_eq!((keys.len(), total), (1, 2));
+        let (keys, _) = search(&db, Some(ApiKeyStatus::Active), "ACME", 1, 1).await;
+        assert_eq!(keys.len(), 1);
+        let (keys, total) = search(&db, Some(ApiKeyStatus::Revoked), "acme", 10, 0).await;
+        assert_eq!((keys[0].id.as_str(), total), (revoked.id.as_str(), 1));
+        let (keys, total) = search(&db, Some(ApiKeyStatus::Expired), "", 10, 0).await;
+        assert_eq!((keys[0].id.as_str(), total), (expired.id.as_str(), 1));
+        // Wildcards in the search are literal
+        let (_, total) = search(&db, None, "%_", 10, 0).await;
+        assert_eq!(total, 1);
+    }
+
+    #[test]
+    fn test_resolve_expiry() {
+        let now = DateTime::parse_from_rfc3339("2026-01-15T12:00:00Z").unwrap().to_utc();
+        let resolve = |at, seconds, span| resolve_expiry(at, seconds, span, now);
+
+        assert_eq!(resolve(None, None, None), Ok(None));
+        let at = Some("2026-03-01T09:30:00+01:00");
+        assert_eq!(resolve(at, None, None).unwrap().unwrap(), "2026-03-01 08:30:00");
+        let at = Some("2026-03-01 08:30:00");
+        assert_eq!(resolve(at, None, None).unwrap().unwrap(), "2026-03-01 08:30:00");
+        assert_eq!(resolve(None, Some(3600), None).unwrap().unwrap(), "2026-01-15 13:00:00");
+        assert_eq!(resolve(None, None, Some("30d")).unwrap().unwrap(), "2026-02-14 12:00:00");
+        assert_eq!(resolve(None, None, Some("2w")).unwrap().unwrap(), "2026-01-29 12:00:00");
+
+        assert!(resolve(Some("next tuesday"), None, None).is_err());
+        assert!(resolve(Some("2025-01-01T00:00:00Z"), None, None).is_err());
+        assert!(resolve(None, Some(0), None).is_err());
+        assert!(resolve(None, Some(u64::MAX), None).is_err());
+        assert!(resolve(None, None, Some("30x")).is_err());
+        assert!(resolve(None, None, Some("d")).is_err());
+        assert!(resolve(at, Some(60), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_admin_key() {
+        let db = init_db(":memory:").await;
+        create_api_key(&db, "client".to_string(), None).await.unwrap();
+
+        let admin = bootstrap_admin_key(&db, None).await.unwrap().unwrap();
+        assert_eq!(admin.role, KeyRole::Admin);
+        let key = authenticate_api_key(&db, &admin.api_key, None).await.unwrap();
+        assert_eq!(key.role, KeyRole::Admin);
+        assert_eq!(active_admin_count(&db).await.unwrap(), 1);
+        assert!(bootstrap_admin_key(&db, None).await.unwrap().is_none());
+
+        revoke_api_key(&db, admin.id).await.unwrap();
+        assert!(bootstrap_admin_key(&db, None).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_create_api_keys() {
+        let db = init_db(":memory:").await;
+        let requests = (1..=3)
+            .map(|i| CreateApiKeyRequest {
+                name: format!("student-{}", i),
+                ..Default::default()
+            })
+            .collect();
+        let created = create_api_keys(&db, requests, None).await.unwrap();
+        let names: Vec<&str> = created.iter().map(|key| key.name.as_str()).collect();
+        assert_eq!(names, ["student-1", "student-2", "student-3"]);
+        for key in &created {
+            assert!(validate_api_key(&db, &key.api_key, None).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tags_and_owner() {
+        let db = init_db(":memory:").await;
+        let request = CreateApiKeyRequest {
+            name: "billing".to_string(),
+            tags: normalize_tags(vec![" team-a ".into(), "prod".into(), "team-a".into()]).unwrap(),
+            owner_email: Some("Ops@Example.com".to_string()),
+            ..Default::default()
+        };
+        let created = create_api_key_from(&db, request, None).await.unwrap();
+        assert_eq!(created.tags, vec!["team-a", "prod"]);
+        create_api_key(&db, "untagged".to_string(), None).await.unwrap();
+
+        let query = ListApiKeysQuery {
+            tag: Some("prod".to_string()),
+            owner_email: Some("ops@example.com".to_string()),
+            ..Default::default()
+        };
+        let (keys, total) = list_api_keys(&db, &query, 100).await.unwrap();
+        assert_eq!((keys[0].id.as_str(), total), (created.id.as_str(), 1));
+
+        let update = UpdateApiKeyRequest {
+            tags: Some(vec![]),
+            owner_email: Some(None),
+            ..Default::default()
+        };
+        assert!(update_api_key(&db, created.id.clone(), update).await.unwrap());
+        let key = get_api_key(&db, created.id.clone()).await.unwrap().unwrap();
+        assert_eq!((key.name.as_str(), key.tags.len(), key.owner_email), ("billing", 0, None));
+        let update = UpdateApiKeyRequest::default();
+        assert!(!update_api_key(&db, "nonexistent-id".to_string(), update).await.unwrap());
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        assert!(normalize_tags(vec!["x".repeat(MAX_TAG_LENGTH + 1)]).is_err());
+        assert!(normalize_tags((0..=MAX_TAGS).map(|i| i.to_string()).collect()).is_err());
+        assert!(validate_owner_email("ops@example.com").is_ok());
+        for email in ["ops", "@example.com", "ops@", "a@b@c", "o ps@example.com"] {
+            assert!(validate_owner_email(email).is_err(), "{}", email);
+        }
     }
 
     #[tokio::test]
@@ -189,12 +1282,109 @@ mod tests {
             .await
             .unwrap();
 
-        assert!(validate_api_key(&db, &resp.api_key).await);
+        assert!(validate_api_key(&db, &resp.api_key, None).await);
 
         let revoked = revoke_api_key(&db, resp.id).await.unwrap();
         assert!(revoked);
 
-        assert!(!validate_api_key(&db, &resp.api_key).await);
+        assert!(!validate_api_key(&db, &resp.api_key, None).await);
+    }
+
+    #[tokio::test]
+    async fn test_set_rate_limit() {
+        let db = init_db(":memory:").await;
+        let resp = create_api_key(&db, "paid".to_string(), None).await.unwrap();
+
+        assert!(set_rate_limit(&db, resp.id.clone(), Some(600)).await.unwrap());
+        let key = authenticate_api_key(&db, &resp.api_key, None).await.unwrap();
+        assert_eq!(key.rate_limit_per_minute, Some(600));
+
+        assert!(set_rate_limit(&db, resp.id.clone(), None).await.unwrap());
+        let item = get_api_key(&db, resp.id).await.unwrap().unwrap();
+        assert_eq!(item.rate_limit_per_minute, None);
+
+        assert!(!set_rate_limit(&db, "nonexistent-id".to_string(), Some(1)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_monthly_quota() {
+        let db = init_db(":memory:").await;
+        let resp = create_api_key(&db, "trial".to_string(), None).await.unwrap();
+        let (id, month) = (resp.id.clone(), "2025-02".to_string());
+
+        assert!(set_monthly_quota(&db, id.clone(), Some(2)).await.unwrap());
+        let key = authenticate_api_key(&db, &resp.api_key, None).await.unwrap();
+        assert_eq!(key.monthly_quota, Some(2));
+
+        let record = || record_request(&db, id.clone(), month.clone(), Some(2));
+        assert_eq!(record().await.unwrap(), Some(1));
+        assert_eq!(record().await.unwrap(), Some(2));
+        assert_eq!(monthly_usage(&db, id.clone(), month.clone()).await.unwrap(), 2);
+        assert_eq!(record().await.unwrap(), None);
+        // Another month starts from zero
+        let next_month = record_request(&db, id.clone(), "2025-03".to_string(), Some(2));
+        assert_eq!(next_month.await.unwrap(), Some(1));
+
+        assert!(reset_monthly_usage(&db, id.clone(), month.clone()).await.unwrap());
+        assert_eq!(record().await.unwrap(), Some(1));
+        assert!(!reset_monthly_usage(&db, "nonexistent-id".to_string(), month.clone())
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_set_allowed_routes() {
+        let db = init_db(":memory:").await;
+        let resp = create_api_key(&db, "europe-only".to_string(), None).await.unwrap();
+        let routes = vec!["/api/time/Europe/*".to_string()];
+
+        assert!(set_allowed_routes(&db, resp.id.clone(), Some(routes.clone())).await.unwrap());
+        let key = authenticate_api_key(&db, &resp.api_key, None).await.unwrap();
+        assert_eq!(key.allowed_routes, Some(routes));
+
+        assert!(set_allowed_routes(&db, resp.id.clone(), None).await.unwrap());
+        let item = get_api_key(&db, resp.id).await.unwrap().unwrap();
+        assert_eq!(item.allowed_routes, None);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_api_key() {
+        let db = init_db(":memory:").await;
+        let old = create_api_key(&db, "rotate-me".to_string(), None).await.unwrap();
+        set_rate_limit(&db, old.id.clone(), Some(600)).await.unwrap();
+
+        let new = rotate_api_key(&db, old.id.clone(), 0, None).await.unwrap().unwrap();
+        assert_eq!(new.id, old.id);
+        assert_eq!(new.name, "rotate-me");
+        assert_ne!(new.api_key, old.api_key);
+        assert_eq!(new.previous_key_expires_at, None);
+        assert!(!validate_api_key(&db, &old.api_key, None).await);
+        // Settings stay with the key id
+        let key = authenticate_api_key(&db, &new.api_key, None).await.unwrap();
+        assert_eq!(key.rate_limit_per_minute, Some(600));
+        // The key is found by its new prefix, no longer by the old one
+        let find = |text: &str| ListApiKeysQuery {
+            key_prefix: Some(text.to_string()),
+            ..Default::default()
+        };
+        let (keys, _) = list_api_keys(&db, &find(&new.key_hint), 100).await.unwrap();
+        assert_eq!(keys[0].key_hint.as_deref(), Some(new.key_hint.as_str()));
+        let (keys, _) = list_api_keys(&db, &find(&old.key_hint), 100).await.unwrap();
+        assert!(keys.is_empty());
+
+        // With a grace period both secrets work
+        let newer = rotate_api_key(&db, old.id.clone(), 3600, None).await.unwrap().unwrap();
+        assert!(newer.previous_key_expires_at.is_some());
+        assert!(validate_api_key(&db, &new.api_key, None).await);
+        assert!(validate_api_key(&db, &newer.api_key, None).await);
+        // Only the replaced secret is accepted for a limited time
+        let replaced = authenticate_api_key(&db, &new.api_key, None).await.unwrap();
+        assert_eq!(replaced.expires_at, newer.previous_key_expires_at);
+        let current = authenticate_api_key(&db, &newer.api_key, None).await.unwrap();
+        assert_eq!(current.expires_at, None);
+
+        revoke_api_key(&db, old.id.clone()).await.unwrap();
+        assert!(rotate_api_key(&db, old.id, 0, None).await.unwrap().is_none());
     }
 
     #[tokio::test]
@@ -205,4 +1395,34 @@ mod tests {
             .unwrap();
         assert!(!revoked);
     }
+
+    #[tokio::test]
+    async fn test_purge_api_key() {
+        let db = init_db(":memory:").await;
+        let resp = create_api_key(&db, "gone".to_string(), None).await.unwrap();
+        record_request(&db, resp.id.clone(), "2025-01".to_string(), None).await.unwrap();
+
+        assert!(purge_api_key(&db, resp.id.clone()).await.unwrap());
+        assert!(get_api_key(&db, resp.id.clone()).await.unwrap().is_none());
+        assert_eq!(monthly_usage(&db, resp.id.clone(), "2025-01".to_string()).await.unwrap(), 0);
+        assert!(!purge_api_key(&db, resp.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_purge_inactive_api_keys() {
+        let db = init_db(":memory:").await;
+        let active = create_api_key(&db, "active".to_string(), None).await.unwrap();
+        let revoked = create_api_key(&db, "revoked".to_string(), None).await.unwrap();
+        let expired = create_api_key(&db, "expired".to_string(), Some("2000-01-01 00:00:00".into()))
+            .await
+            .unwrap();
+        revoke_api_key(&db, revoked.id.clone()).await.unwrap();
+
+        let purged = purge_inactive_api_keys(&db).await.unwrap();
+        assert_eq!(purged.len(), 2);
+        assert!(purged.contains(&revoked.id) && purged.contains(&expired.id));
+        let (keys, _) = list_api_keys(&db, &ListApiKeysQuery::default(), 100).await.unwrap();
+        let remaining: Vec<String> = keys.into_iter().map(|key| key.id).collect();
+        assert_eq!(remaining, vec![active.id]);
+    }
 }
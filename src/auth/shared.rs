@@ -0,0 +1,234 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+
+use super::models::AuthenticatedKey;
+use super::rate_limit::{retry_after, RateLimitDecision};
+use super::service::EXPIRY_FORMAT;
+use super::signing::NONCE_TTL_SECS;
+
+// Rate-limit windows, key lookups, and signed-request nonces kept in Redis, so that every
+// instance behind a load balancer counts against the same limits, a revoked key stops
+// working everywhere, and a signed request can't be replayed against another instance
+pub struct SharedStore {
+    conn: ConnectionManager,
+    // Seconds a key lookup is cached
+    key_ttl: u64,
+}
+
+impl SharedStore {
+    pub async fn connect(url: &str, key_ttl: u64) -> Result<Self, String> {
+        let client = redis::Client::open(url).map_err(|e| format!("Invalid REDIS_URL: {}", e))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .map_err(|e| format!("Failed to connect to Redis: {}", e))?;
+        Ok(Self { conn, key_ttl })
+    }
+
+    // Count a request by `key_id` at `now` against `limit` requests per minute
+    pub async fn check(
+        &self,
+        key_id: &str,
+        limit: u32,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision, String> {
+        let window = window_key(key_id, now);
+        // Windows outlive their minute a little so clock skew between instances is harmless
+        let (count,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(&window, 1)
+            .expire(&window, 120)
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| format!("Failed to count request in Redis: {}", e))?;
+        Ok(decision(count, limit, now))
+    }
+
+    // Requests counted for `key_id` in the window containing `now`, without adding one
+    pub async fn used(&self, key_id: &str, now: DateTime<Utc>) -> Result<u32, String> {
+        let count: Option<u64> = self
+            .conn
+            .clone()
+            .get(window_key(key_id, now))
+            .await
+            .map_err(|e| format!("Failed to read request count from Redis: {}", e))?;
+        Ok(count.unwrap_or(0).min(u32::MAX as u64) as u32)
+    }
+
+    // The key stored under `key_hash` by an earlier lookup, if still cached
+    pub async fn cached_key(&self, key_hash: &str) -> Result<Option<AuthenticatedKey>, String> {
+        let cached: Option<String> = self
+            .conn
+            .clone()
+            .get(cached_key_name(key_hash))
+            .await
+            .map_err(|e| format!("Failed to read cached key from Redis: {}", e))?;
+        Ok(cached
+            .and_then(|json| serde_json::from_str::<AuthenticatedKey>(&json).ok())
+            .filter(|key| cache_ttl(key, self.key_ttl, Utc::now()) > 0))
+    }
+
+    // Cache a successful lookup by the hash of the presented key; the raw key never
+    // leaves the process. The entry lasts no longer than the key is accepted.
+    pub async fn cache_key(&self, key_hash: &str, key: &AuthenticatedKey) -> Result<(), String> {
+        let ttl = cache_ttl(key, self.key_ttl, Utc::now());
+        if ttl == 0 {
+            return Ok(());
+        }
+        let json = serde_json::to_string(key)
+            .map_err(|e| format!("Failed to serialize key for caching: {}", e))?;
+        let name = cached_key_name(key_hash);
+        // Remember which hashes a key is cached under, so all of them can be forgotten
+        let hashes = hashes_name(&key.id);
+        redis::pipe()
+            .atomic()
+            .set_ex(&name, json, ttl)
+            .ignore()
+            .sadd(&hashes, &name)
+            .ignore()
+            .expire(&hashes, self.key_ttl as i64)
+            .ignore()
+            .query_async::<()>(&mut self.conn.clone())
+            .await
+            .map_err(|e| format!("Failed to cache key in Redis: {}", e))
+    }
+
+    // Record a signed request's nonce, returning false if `key_id` already used it
+    pub async fn use_nonce(&self, key_id: &str, nonce: &str) -> Result<bool, String> {
+        let set: Option<String> = redis::cmd("SET")
+            .arg(nonce_name(key_id, nonce))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(NONCE_TTL_SECS)
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| format!("Failed to record nonce in Redis: {}", e))?;
+        Ok(set.is_some())
+    }
+
+    // Drop every cached lookup of `key_id`, after it was changed or revoked
+    pub async fn forget_key(&self, key_id: &str) -> Result<(), String> {
+        let mut conn = self.conn.clone();
+        let hashes = hashes_name(key_id);
+        let mut names: Vec<String> = conn
+            .smembers(&hashes)
+            .await
+            .map_err(|e| format!("Failed to read cached keys from Redis: {}", e))?;
+        names.push(hashes);
+        conn.del::<_, ()>(names)
+            .await
+            .map_err(|e| format!("Failed to forget cached key in Redis: {}", e))
+    }
+}
+
+// Seconds a key lookup may stay cached at `now`: the configured TTL, cut short by the key's
+// expiry. Zero once the key has expired.
+fn cache_ttl(key: &AuthenticatedKey, key_ttl: u64, now: DateTime<Utc>) -> u64 {
+    let Some(expires_at) = key.expires_at.as_deref() else {
+        return key_ttl;
+    };
+    // An expiry that can't be read isn't trusted to be in the future
+    let Ok(expires_at) = NaiveDateTime::parse_from_str(expires_at, EXPIRY_FORMAT) else {
+        return 0;
+    };
+    let left = (expires_at.and_utc() - now).num_seconds();
+    key_ttl.min(left.max(0) as u64)
+}
+
+fn window_key(key_id: &str, now: DateTime<Utc>) -> String {
+    format!("epochzone:rate:{}:{}", key_id, now.timestamp().div_euclid(60))
+}
+
+fn cached_key_name(key_hash: &str) -> String {
+    format!("epochzone:key:{}", key_hash)
+}
+
+fn nonce_name(key_id: &str, nonce: &str) -> String {
+    format!("epochzone:nonce:{}:{}", key_id, nonce)
+}
+
+fn hashes_name(key_id: &str) -> String {
+    format!("epochzone:key-hashes:{}", key_id)
+}
+
+// The outcome of the `count`th request in a window, the same as the in-memory limiter's:
+// requests past the limit are refused
+fn decision(count: u64, limit: u32, now: DateTime<Utc>) -> RateLimitDecision {
+    if count > limit as u64 {
+        RateLimitDecision::Exceeded {
+            retry_after: retry_after(now),
+        }
+    } else {
+        RateLimitDecision::Allowed {
+            remaining: limit - count as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::models::{Credential, KeyRole};
+
+    #[test]
+    fn test_decision() {
+        let now = DateTime::from_timestamp(1_739_197_845, 0).unwrap(); // 45s into a minute
+        assert_eq!(decision(1, 2, now), RateLimitDecision::Allowed { remaining: 1 });
+        assert_eq!(decision(2, 2, now), RateLimitDecision::Allowed { remaining: 0 });
+        assert_eq!(decision(3, 2, now), RateLimitDecision::Exceeded { retry_after: 15 });
+        assert_eq!(window_key("a", now), window_key("a", now + chrono::Duration::seconds(14)));
+        assert_ne!(window_key("a", now), window_key("a", now + chrono::Duration::seconds(15)));
+    }
+
+    #[test]
+    fn test_cached_key_round_trip() {
+        let key = AuthenticatedKey {
+            id: "key-1".to_string(),
+            rate_limit_per_minute: Some(10),
+            monthly_quota: None,
+            allowed_routes: Some(vec!["/api/time/*".to_string()]),
+            role: KeyRole::Admin,
+            allowed_ips: None,
+            allowed_origins: None,
+            org_id: Some("org-1".to_string()),
+            org_monthly_quota: Some(1000),
+            scopes: None,
+            credential: Credential::ApiKey,
+            expires_at: Some("2025-02-10 14:30:00".to_string()),
+        };
+        let json = serde_json::to_string(&key).unwrap();
+        let cached: AuthenticatedKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(cached.id, "key-1");
+        assert_eq!(cached.role, KeyRole::Admin);
+        assert_eq!(cached.allowed_routes, key.allowed_routes);
+        assert_eq!(cached.org_monthly_quota, Some(1000));
+        assert_eq!(cached.credential, Credential::ApiKey);
+        assert_eq!(cached.expires_at, key.expires_at);
+
+        // Cached no longer than the key lasts
+        let now = DateTime::from_timestamp(1_739_197_800, 0).unwrap(); // 2025-02-10 14:30:00
+        assert_eq!(cache_ttl(&key, 30, now - chrono::Duration::seconds(10)), 10);
+        assert_eq!(cache_ttl(&key, 30, now - chrono::Duration::minutes(5)), 30);
+        assert_eq!(cache_ttl(&key, 30, now), 0);
+        let forever = AuthenticatedKey { expires_at: None, ..key };
+        assert_eq!(cache_ttl(&forever, 30, now), 30);
+    }
+}
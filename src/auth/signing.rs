@@ -0,0 +1,138 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Signed requests let a client prove it holds a key's signing secret without sending any
+// secret. Each request carries X-Key-Id, X-Timestamp (Unix seconds), X-Nonce, and
+// X-Signature: the hex HMAC-SHA256, under the signing secret, of canonical_request.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+// How far a request's timestamp may be from the server clock, in seconds
+pub const MAX_CLOCK_SKEW_SECS: i64 = 300;
+// Largest body a signed request may have, since it is buffered to be hashed
+pub const MAX_SIGNED_BODY_BYTES: usize = 1 << 20;
+// How long a nonce is remembered: long enough that its request's timestamp has left the
+// window on either side of the server clock
+pub const NONCE_TTL_SECS: i64 = 2 * MAX_CLOCK_SKEW_SECS;
+
+// A key's signing secret. It is derived from the server's pepper rather than stored, and
+// `salt` is regenerated whenever signing is (re-)enabled, which replaces the secret.
+pub fn signing_secret(pepper: &str, key_id: &str, salt: &str) -> String {
+    hmac_hex(pepper.as_bytes(), &format!("request-signing:{}:{}", key_id, salt))
+}
+
+// The string a client signs: method, path with query, timestamp, nonce, and the hex
+// SHA-256 of the body, one per line
+pub fn canonical_request(
+    method: &str,
+    path_and_query: &str,
+    timestamp: &str,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    let body_hash = hex::encode(Sha256::digest(body));
+    format!("{}\n{}\n{}\n{}\n{}", method, path_and_query, timestamp, nonce, body_hash)
+}
+
+pub fn sign(secret: &str, canonical: &str) -> String {
+    hmac_hex(secret.as_bytes(), canonical)
+}
+
+fn hmac_hex(key: &[u8], message: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// Nonces seen per key, kept for as long as their requests could still pass the timestamp
+// check so a captured request can't be replayed. With several instances this only covers
+// one of them; the middleware uses Redis instead when it is configured.
+#[derive(Debug, Default)]
+pub struct NonceCache {
+    seen: Mutex<SeenNonces>,
+}
+
+#[derive(Debug, Default)]
+struct SeenNonces {
+    used_at: HashMap<(String, String), i64>,
+    // The same nonces in the order they were used, so expired ones are dropped from the
+    // front without scanning the rest
+    order: VecDeque<(i64, (String, String))>,
+}
+
+impl NonceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Record a nonce used at Unix time `now`, returning false if the key already used it
+    pub fn insert(&self, key_id: &str, nonce: &str, now: i64) -> bool {
+        let mut guard = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let seen = &mut *guard;
+        while let Some((used_at, _)) = seen.order.front() {
+            if now - used_at <= NONCE_TTL_SECS {
+                break;
+            }
+            if let Some((_, entry)) = seen.order.pop_front() {
+                seen.used_at.remove(&entry);
+            }
+        }
+        let entry = (key_id.to_string(), nonce.to_string());
+        match seen.used_at.entry(entry.clone()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(vacant) => {
+                vacant.insert(now);
+                seen.order.push_back((now, entry));
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_request() {
+        let canonical = canonical_request("GET", "/api/time/UTC?x=1", "1700000000", "n1", b"");
+        assert_eq!(
+            canonical,
+            "GET\n/api/time/UTC?x=1\n1700000000\nn1\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        let secret = signing_secret("pepper", "key-1", "salt");
+        assert_eq!(sign(&secret, &canonical), sign(&secret, &canonical));
+        assert_ne!(secret, signing_secret("pepper", "key-1", "new-salt"));
+    }
+
+    #[test]
+    fn test_nonce_cache() {
+        let nonces = NonceCache::new();
+        assert!(nonces.insert("key-1", "abc", 1000));
+        assert!(!nonces.insert("key-1", "abc", 1001));
+        assert!(nonces.insert("key-2", "abc", 1001));
+        // Forgotten once the timestamp window has passed
+        assert!(nonces.insert("key-1", "abc", 1000 + NONCE_TTL_SECS + 1));
+        let seen = nonces.seen.lock().unwrap();
+        assert_eq!((seen.used_at.len(), seen.order.len()), (2, 2));
+    }
+}
@@ -0,0 +1,175 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use super::models::{AuthFailureStats, BlockedAddress};
+
+// Error code of responses refusing a key, token, or signature; the failures counted here
+pub const INVALID_CREDENTIALS: &str = "invalid_credentials";
+
+// Longest an address is blocked for
+pub const MAX_BLOCK_SECS: i64 = 3600;
+
+// Failures are forgotten after this long without another one
+const FORGET_AFTER_SECS: i64 = 2 * MAX_BLOCK_SECS;
+
+// Failed authentications counted per client address, so guessing keys can't go unchecked.
+// Past `free_failures` an address is blocked, for one second after the first extra failure
+// and twice as long after each further one, up to MAX_BLOCK_SECS. Counts live in memory.
+#[derive(Debug, Default)]
+pub struct AuthThrottle {
+    // 0 turns throttling off
+    free_failures: u32,
+    addresses: Mutex<HashMap<IpAddr, Failures>>,
+    failed: AtomicU64,
+    rejected: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    // Unix seconds
+    last_failure: i64,
+    blocked_until: i64,
+}
+
+impl AuthThrottle {
+    pub fn new(free_failures: u32) -> Self {
+        Self {
+            free_failures,
+            ..Self::default()
+        }
+    }
+
+    // Seconds `ip` is still blocked for at Unix time `now`, counting the refused request
+    pub fn blocked(&self, ip: IpAddr, now: i64) -> Option<u64> {
+        let addresses = self.addresses.lock().unwrap_or_else(|e| e.into_inner());
+        let failures = addresses.get(&ip).filter(|f| f.blocked_until > now)?;
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        Some((failures.blocked_until - now) as u64)
+    }
+
+    // Count a failed authentication from `ip`, returning how long it is now blocked for
+    pub fn record_failure(&self, ip: IpAddr, now: i64) -> Option<u64> {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        if self.free_failures == 0 {
+            return None;
+        }
+        let mut addresses = self.addresses.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop forgotten addresses now and then, so a scan from many addresses doesn't pile up
+        if addresses.len() > 10_000 {
+            addresses.retain(|_, f| now - f.last_failure < FORGET_AFTER_SECS);
+        }
+        let failures = addresses.entry(ip).or_insert(Failures {
+            count: 0,
+            last_failure: now,
+            blocked_until: 0,
+        });
+        if now - failures.last_failure >= FORGET_AFTER_SECS {
+            failures.count = 0;
+        }
+        failures.count = failures.count.saturating_add(1);
+        failures.last_failure = now;
+        let extra = failures.count.checked_sub(self.free_failures + 1)?;
+        let block = (1i64 << extra.min(62)).min(MAX_BLOCK_SECS);
+        failures.blocked_until = now + block;
+        Some(block as u64)
+    }
+
+    pub fn stats(&self, now: i64) -> AuthFailureStats {
+        let addresses = self.addresses.lock().unwrap_or_else(|e| e.into_inner());
+        let mut blocked: Vec<BlockedAddress> = addresses
+            .iter()
+            .filter(|(_, f)| f.blocked_until > now)
+            .map(|(ip, f)| BlockedAddress {
+                address: ip.to_string(),
+                failures: f.count,
+                retry_after: (f.blocked_until - now) as u64,
+            })
+            .collect();
+        blocked.sort_by_key(|b| std::cmp::Reverse(b.retry_after));
+        AuthFailureStats {
+            failed_attempts: self.failed.load(Ordering::Relaxed),
+            rejected_while_blocked: self.rejected.load(Ordering::Relaxed),
+            blocked,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_then_caps() {
+        let throttle = AuthThrottle::new(3);
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let now = 1_739_197_800;
+        for _ in 0..3 {
+            assert_eq!(throttle.record_failure(ip, now), None);
+        }
+        assert_eq!(throttle.blocked(ip, now), None);
+        assert_eq!(throttle.record_failure(ip, now), Some(1));
+        assert_eq!(throttle.blocked(ip, now), Some(1));
+        assert_eq!(throttle.blocked(ip, now + 1), None);
+        assert_eq!(throttle.record_failure(ip, now + 1), Some(2));
+        assert_eq!(throttle.record_failure(ip, now + 3), Some(4));
+        for _ in 0..40 {
+            throttle.record_failure(ip, now + 10);
+        }
+        assert_eq!(throttle.blocked(ip, now + 10), Some(MAX_BLOCK_SECS as u64));
+
+        // Other addresses are unaffected
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        assert_eq!(throttle.blocked(other, now + 10), None);
+
+        let stats = throttle.stats(now + 10);
+        assert_eq!(stats.failed_attempts, 46);
+        assert_eq!(stats.rejected_while_blocked, 2);
+        assert_eq!(
+            stats.blocked,
+            vec![BlockedAddress {
+                address: "203.0.113.7".to_string(),
+                failures: 46,
+                retry_after: MAX_BLOCK_SECS as u64,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_failures_are_forgotten() {
+        let throttle = AuthThrottle::new(1);
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(throttle.record_failure(ip, 0), None);
+        assert_eq!(throttle.record_failure(ip, FORGET_AFTER_SECS), None);
+        assert_eq!(throttle.record_failure(ip, FORGET_AFTER_SECS + 1), Some(1));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let throttle = AuthThrottle::new(0);
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        for _ in 0..100 {
+            assert_eq!(throttle.record_failure(ip, 0), None);
+        }
+        assert_eq!(throttle.blocked(ip, 0), None);
+        assert_eq!(throttle.stats(0).failed_attempts, 100);
+    }
+}
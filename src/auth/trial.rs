@@ -0,0 +1,85 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use tokio_rusqlite::Connection;
+
+// Trial keys anyone may sign up for, counted per client address over a sliding hour.
+// Requests whose address is unknown share one count.
+#[derive(Debug, Default)]
+pub struct SignupLimiter {
+    signups: Mutex<HashMap<Option<IpAddr>, Vec<i64>>>,
+}
+
+impl SignupLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Count a signup from `ip` at Unix time `now` if it made fewer than `per_hour` in the
+    // past hour; otherwise the seconds until it may sign up again
+    pub fn try_signup(&self, ip: Option<IpAddr>, per_hour: u32, now: i64) -> Result<(), u64> {
+        let mut signups = self.signups.lock().unwrap_or_else(|e| e.into_inner());
+        signups.retain(|_, times| times.last().is_some_and(|last| now - last < 3600));
+        let times = signups.entry(ip).or_default();
+        times.retain(|time| now - time < 3600);
+        if times.len() >= per_hour as usize {
+            let oldest = times.first().copied().unwrap_or(now);
+            return Err((oldest + 3600 - now).max(1) as u64);
+        }
+        times.push(now);
+        Ok(())
+    }
+}
+
+// Whether `email` already owns an active trial key, ignoring case
+pub async fn has_active_trial(db: &Connection, email: String) -> Result<bool, String> {
+    db.call(move |conn| {
+        let exists = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM api_keys
+                            WHERE role = 'trial' AND is_active = 1
+                              AND owner_email = ?1 COLLATE NOCASE
+                              AND (expires_at IS NULL OR expires_at > datetime('now')))",
+            rusqlite::params![email],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    })
+    .await
+    .map_err(|e| format!("Failed to look up trial keys: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signups_per_hour() {
+        let limiter = SignupLimiter::new();
+        let ip: Option<IpAddr> = Some("203.0.113.7".parse().unwrap());
+        assert_eq!(limiter.try_signup(ip, 2, 1000), Ok(()));
+        assert_eq!(limiter.try_signup(ip, 2, 1600), Ok(()));
+        assert_eq!(limiter.try_signup(ip, 2, 2000), Err(2600));
+        // Other addresses have their own count
+        assert_eq!(limiter.try_signup(None, 2, 2000), Ok(()));
+        // The first signup leaves the window an hour after it was made
+        assert_eq!(limiter.try_signup(ip, 2, 4600), Ok(()));
+        assert_eq!(limiter.try_signup(ip, 2, 4700), Err(500));
+    }
+}
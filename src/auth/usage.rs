@@ -0,0 +1,533 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use tokio_rusqlite::Connection;
+
+use super::models::{EndpointUsage, UsageDay, UsageGroup, UsageGrouping};
+
+// How often buffered counts are written to the database. Counts still in the buffer are
+// lost if the process dies, so keep this short.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+// Requests per key, UTC day, and endpoint (the route pattern, e.g. "/api/time/{timezone}"),
+// buffered in memory and written in batches so requests don't wait on the database
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    pending: Mutex<HashMap<UsageKey, UsageCount>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    key_id: String,
+    day: NaiveDate,
+    endpoint: String,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct UsageCount {
+    requests: u64,
+    // Responses with a 4xx or 5xx status
+    errors: u64,
+}
+
+impl UsageMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, key_id: &str, endpoint: &str, is_error: bool, now: DateTime<Utc>) {
+        let key = UsageKey {
+            key_id: key_id.to_string(),
+            day: now.date_naive(),
+            endpoint: endpoint.to_string(),
+        };
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let count = pending.entry(key).or_default();
+        count.requests += 1;
+        count.errors += is_error as u64;
+    }
+
+    // Drop buffered counts for purged keys so a later flush doesn't bring their usage back
+    pub fn forget(&self, key_ids: &[String]) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|key, _| !key_ids.contains(&key.key_id));
+    }
+
+    // Write the buffered counts to the database, returning how many rows were touched.
+    // On failure the counts go back into the buffer for the next flush.
+    pub async fn flush(&self, db: &Connection) -> Result<usize, String> {
+        let batch: Vec<(UsageKey, UsageCount)> = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.drain().collect()
+        };
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let rows = batch.clone();
+        let written = db
+            .call(move |conn| {
+                let tx = conn.transaction()?;
+                {
+                    let mut upsert = tx.prepare(
+                        "INSERT INTO api_key_daily_usage
+                            (key_id, day, endpoint, request_count, error_count)
+                         VALUES (?1, ?2, ?3, ?4, ?5)
+                         ON CONFLICT (key_id, day, endpoint) DO UPDATE SET
+                            request_count = request_count + excluded.request_count,
+                            error_count = error_count + excluded.error_count",
+                    )?;
+                    for (key, count) in &rows {
+                        upsert.execute(rusqlite::params![
+                            key.key_id,
+                            key.day.to_string(),
+                            key.endpoint,
+                            count.requests,
+                            count.errors
+                        ])?;
+                    }
+                }
+                tx.commit()?;
+                Ok(rows.len())
+            })
+            .await;
+
+        written.map_err(|e| {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            for (key, count) in batch {
+                let merged = pending.entry(key).or_default();
+                merged.requests += count.requests;
+                merged.errors += count.errors;
+            }
+            format!("Failed to write usage: {}", e)
+        })
+    }
+}
+
+// Flush `meter` every FLUSH_INTERVAL for as long as the server runs
+pub fn spawn_flusher(meter: std::sync::Arc<UsageMeter>, db: Connection) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = meter.flush(&db).await {
+                tracing::error!("{}", e);
+            }
+        }
+    });
+}
+
+// A key's daily usage between two dates, inclusive, with a breakdown by endpoint
+pub async fn daily_usage(
+    db: &Connection,
+    key_id: String,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<UsageDay>, String> {
+    let rows: Vec<(String, String, u64, u64)> = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT day, endpoint, request_count, error_count FROM api_key_daily_usage
+                 WHERE key_id = ?1 AND day BETWEEN ?2 AND ?3
+                 ORDER BY day, endpoint",
+            )?;
+            let rows = stmt
+                .query_map(
+                    rusqlite::params![key_id, from.to_string(), to.to_string()],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .await
+        .map_err(|e| format!("Failed to read usage: {}", e))?;
+
+    let mut days: BTreeMap<String, UsageDay> = BTreeMap::new();
+    for (day, endpoint, requests, errors) in rows {
+        let entry = days.entry(day.clone()).or_insert_with(|| UsageDay {
+            day,
+            requests: 0,
+            errors: 0,
+            endpoints: Vec::new(),
+        });
+        entry.requests += requests;
+        entry.errors += errors;
+        entry.endpoints.push(EndpointUsage {
+            endpoint,
+            requests,
+            errors,
+        });
+    }
+    Ok(days.into_values().collect())
+}
+
+// Requests and errors across all keys, or those of one organization, between two dates,
+// inclusive
+pub async fn aggregate_usage(
+    db: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: UsageGrouping,
+    org_id: Option<String>,
+) -> Result<Vec<UsageGroup>, String> {
+    let (column, name, order) = match group_by {
+        UsageGrouping::Day => ("u.day", "NULL", "u.day"),
+        UsageGrouping::Key => ("u.key_id", "MAX(k.name)", "requests DESC, u.key_id"),
+        UsageGrouping::Endpoint => ("u.endpoint", "NULL", "requests DESC, u.endpoint"),
+        UsageGrouping::Org => ("k.org_id", "MAX(o.name)", "requests DESC, k.org_id"),
+    };
+    let sql = format!(
+        "SELECT {column}, {name}, SUM(u.request_count) AS requests, SUM(u.error_count)
+         FROM api_key_daily_usage u LEFT JOIN api_keys k ON k.id = u.key_id
+         LEFT JOIN organizations o ON o.id = k.org_id
+         WHERE u.day BETWEEN ?1 AND ?2 AND (?3 IS NULL OR k.org_id = ?3)
+           AND ({column} IS NOT NULL)
+         GROUP BY {column} ORDER BY {order}"
+    );
+    db.call(move |conn| {
+        let mut stmt = conn.prepare(&sql)?;
+        let params = rusqlite::params![from.to_string(), to.to_string(), org_id];
+        let groups = stmt
+            .query_map(params, |row| {
+                let (requests, errors): (u64, u64) = (row.get(2)?, row.get(3)?);
+                Ok(UsageGroup {
+                    group: row.get(0)?,
+                    name: row.get(1)?,
+                    requests,
+                    errors,
+                    error_rate: error_rate(requests, errors),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(groups)
+    })
+    .await
+    .map_err(|e| format!("Failed to read usage: {}", e))
+}
+
+// Billing classes, in report column order, and the route prefixes that belong to each.
+// Routes not listed are lookups.
+pub const ENDPOINT_CLASSES: [&str; 5] = ["lookup", "conversion", "geo", "planning", "account"];
+const CLASS_PREFIXES: &[(&str, &str)] = &[
+    ("/api/convert", "conversion"),
+    ("/api/add", "conversion"),
+    ("/api/matrix", "conversion"),
+    ("/api/parse", "conversion"),
+    ("/api/julian", "conversion"),
+    ("/api/timescales", "conversion"),
+    ("/api/interval", "conversion"),
+    ("/api/relative", "conversion"),
+    ("/api/duration", "conversion"),
+    ("/api/age", "conversion"),
+    ("/api/timezone-at", "geo"),
+    ("/api/timezones/{timezone}/boundary", "geo"),
+    ("/api/geolocate", "geo"),
+    ("/api/ip", "geo"),
+    ("/api/sun", "geo"),
+    ("/api/airports", "geo"),
+    ("/api/time/auto", "geo"),
+    ("/api/meetings", "planning"),
+    ("/api/teams", "planning"),
+    ("/api/itinerary", "planning"),
+    ("/api/jetlag", "planning"),
+    ("/api/recurrence", "planning"),
+    ("/api/me", "account"),
+];
+
+// The billing class of a metered endpoint (a route pattern)
+pub fn endpoint_class(endpoint: &str) -> &'static str {
+    CLASS_PREFIXES
+        .iter()
+        .find(|(prefix, _)| {
+            endpoint
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .map_or("lookup", |(_, class)| class)
+}
+
+// One key's usage for a month, as billed
+#[derive(Debug, Clone, PartialEq)]
+pub struct BillingRow {
+    pub key_id: String,
+    pub name: String,
+    pub requests: u64,
+    pub errors: u64,
+    // Requests per class, in ENDPOINT_CLASSES order
+    pub by_class: [u64; ENDPOINT_CLASSES.len()],
+    pub monthly_quota: Option<u64>,
+    // Requests beyond the quota (a quota lowered mid-month can leave some)
+    pub overage: u64,
+}
+
+// Key id, name, quota, endpoint (None for keys without usage), requests, errors
+type KeyEndpointTotals = (String, String, Option<u64>, Option<String>, u64, u64);
+
+// Per-key usage for `month` ("YYYY-MM"): every key with requests that month, plus active
+// keys without any, ordered by name. With `org_id`, only that organization's keys.
+pub async fn billing_report(
+    db: &Connection,
+    month: String,
+    org_id: Option<String>,
+) -> Result<Vec<BillingRow>, String> {
+    let rows: Vec<KeyEndpointTotals> = db
+        .call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT k.id, k.name, k.monthly_quota, u.endpoint,
+                        COALESCE(SUM(u.request_count), 0), COALESCE(SUM(u.error_count), 0)
+                 FROM api_keys k
+                 LEFT JOIN api_key_daily_usage u
+                   ON u.key_id = k.id AND substr(u.day, 1, 7) = ?1
+                 WHERE (k.is_active = 1 OR u.key_id IS NOT NULL)
+                   AND (?2 IS NULL OR k.org_id = ?2)
+                 GROUP BY k.id, u.endpoint
+                 ORDER BY k.name, k.id",
+            )?;
+            let rows = stmt
+                .query_map(rusqlite::params![month, org_id], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .await
+        .map_err(|e| format!("Failed to read usage: {}", e))?;
+
+    let mut report: Vec<BillingRow> = Vec::new();
+    for (key_id, name, monthly_quota, endpoint, requests, errors) in rows {
+        if report.last().is_none_or(|row| row.key_id != key_id) {
+            report.push(BillingRow {
+                key_id,
+                name,
+                requests: 0,
+                errors: 0,
+                by_class: [0; ENDPOINT_CLASSES.len()],
+                monthly_quota,
+                overage: 0,
+            });
+        }
+        let Some(row) = report.last_mut() else {
+            continue;
+        };
+        row.requests += requests;
+        row.errors += errors;
+        if let Some(endpoint) = endpoint {
+            let class = endpoint_class(&endpoint);
+            if let Some(index) = ENDPOINT_CLASSES.iter().position(|c| *c == class) {
+                row.by_class[index] += requests;
+            }
+        }
+        row.overage = row.monthly_quota.map_or(0, |quota| row.requests.saturating_sub(quota));
+    }
+    Ok(report)
+}
+
+// Render a billing report as CSV with a header row
+pub fn billing_csv(month: &str, report: &[BillingRow]) -> String {
+    let mut header = vec!["month", "key_id", "name", "requests", "errors"];
+    header.extend(ENDPOINT_CLASSES);
+    header.extend(["monthly_quota", "overage"]);
+    let mut out = header.join(",") + "\r\n";
+    for row in report {
+        let mut fields = vec![
+            csv_field(month),
+            csv_field(&row.key_id),
+            csv_field(&row.name),
+            row.requests.to_string(),
+            row.errors.to_string(),
+        ];
+        fields.extend(row.by_class.iter().map(u64::to_string));
+        fields.push(row.monthly_quota.map(|q| q.to_string()).unwrap_or_default());
+        fields.push(row.overage.to_string());
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+// Quote a CSV field when it holds a delimiter, quote, or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn error_rate(requests: u64, errors: u64) -> f64 {
+    if requests == 0 {
+        0.0
+    } else {
+        errors as f64 / requests as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::models::UpdateApiKeyRequest;
+    use crate::auth::service::create_api_key;
+    use crate::db::init_db;
+
+    #[tokio::test]
+    async fn test_flush_and_read_back() {
+        let db = init_db(":memory:").await;
+        let key = create_api_key(&db, "metered".to_string(), None).await.unwrap();
+        let meter = UsageMeter::new();
+        let day1 = DateTime::parse_from_rfc3339("2025-02-10T12:00:00Z").unwrap().to_utc();
+        let day2 = DateTime::parse_from_rfc3339("2025-02-11T00:30:00Z").unwrap().to_utc();
+
+        meter.record(&key.id, "/api/time/{timezone}", false, day1);
+        meter.record(&key.id, "/api/time/{timezone}", true, day1);
+        meter.record(&key.id, "/api/convert", false, day1);
+        assert_eq!(meter.flush(&db).await.unwrap(), 2);
+        // Later flushes add to the stored counts
+        meter.record(&key.id, "/api/convert", false, day1);
+        meter.record(&key.id, "/api/convert", false, day2);
+        assert_eq!(meter.flush(&db).await.unwrap(), 2);
+        assert_eq!(meter.flush(&db).await.unwrap(), 0);
+
+        let from = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 2, 28).unwrap();
+        let days = daily_usage(&db, key.id.clone(), from, to).await.unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].day, "2025-02-10");
+        assert_eq!((days[0].requests, days[0].errors), (4, 1));
+        assert_eq!(days[0].endpoints[0].endpoint, "/api/convert");
+        assert_eq!(days[0].endpoints[0].requests, 2);
+        assert_eq!(days[1].requests, 1);
+
+        let none = daily_usage(&db, key.id, to, to).await.unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_usage() {
+        let db = init_db(":memory:").await;
+        let a = create_api_key(&db, "a".to_string(), None).await.unwrap();
+        let b = create_api_key(&db, "b".to_string(), None).await.unwrap();
+        let meter = UsageMeter::new();
+        let day1 = DateTime::parse_from_rfc3339("2025-02-10T12:00:00Z").unwrap().to_utc();
+        let day2 = DateTime::parse_from_rfc3339("2025-02-11T12:00:00Z").unwrap().to_utc();
+        meter.record(&a.id, "/api/convert", false, day1);
+        meter.record(&a.id, "/api/convert", true, day2);
+        meter.record(&b.id, "/api/parse", false, day2);
+        meter.record(&b.id, "/api/convert", false, day2);
+        meter.record(&b.id, "/api/convert", false, day2);
+        meter.flush(&db).await.unwrap();
+
+        let (from, to) = (day1.date_naive(), day2.date_naive());
+        let by_day = aggregate_usage(&db, from, to, UsageGrouping::Day, None).await.unwrap();
+        assert_eq!(by_day.len(), 2);
+        assert_eq!((by_day[1].group.as_str(), by_day[1].requests), ("2025-02-11", 4));
+        assert_eq!(by_day[1].error_rate, 0.25);
+
+        let by_key = aggregate_usage(&db, from, to, UsageGrouping::Key, None).await.unwrap();
+        assert_eq!(by_key[0].group, b.id);
+        assert_eq!(by_key[0].name.as_deref(), Some("b"));
+
+        let by_endpoint = aggregate_usage(&db, from, to, UsageGrouping::Endpoint, None)
+            .await
+            .unwrap();
+        assert_eq!(by_endpoint[0].group, "/api/convert");
+        assert_eq!(by_endpoint[0].requests, 4);
+        assert_eq!(by_endpoint[0].name, None);
+
+        let later = to.succ_opt().unwrap();
+        let outside = aggregate_usage(&db, later, later, UsageGrouping::Day, None).await.unwrap();
+        assert!(outside.is_empty());
+
+        // Keys outside any organization are left out of the organization groups
+        let org = crate::auth::orgs::create_organization(&db, "Acme".to_string(), None)
+            .await
+            .unwrap()
+            .unwrap();
+        let update = UpdateApiKeyRequest {
+            org_id: Some(Some(org.id.clone())),
+            ..Default::default()
+        };
+        crate::auth::service::update_api_key(&db, a.id.clone(), update).await.unwrap();
+        let by_org = aggregate_usage(&db, from, to, UsageGrouping::Org, None).await.unwrap();
+        assert_eq!(by_org.len(), 1);
+        assert_eq!((by_org[0].group.as_str(), by_org[0].requests), (org.id.as_str(), 2));
+        assert_eq!(by_org[0].name.as_deref(), Some("Acme"));
+        let in_org = aggregate_usage(&db, from, to, UsageGrouping::Key, Some(org.id))
+            .await
+            .unwrap();
+        assert_eq!(in_org.len(), 1);
+        assert_eq!(in_org[0].group, a.id);
+    }
+
+    #[test]
+    fn test_endpoint_class() {
+        assert_eq!(endpoint_class("/api/convert"), "conversion");
+        assert_eq!(endpoint_class("/api/timezones/{timezone}/boundary"), "geo");
+        assert_eq!(endpoint_class("/api/me"), "account");
+        assert_eq!(endpoint_class("/api/meetings/suggest"), "planning");
+        assert_eq!(endpoint_class("/api/time/{timezone}"), "lookup");
+        assert_eq!(endpoint_class("/api/addresses"), "lookup");
+    }
+
+    #[tokio::test]
+    async fn test_billing_report() {
+        let db = init_db(":memory:").await;
+        let paid = create_api_key(&db, "Acme, Inc.".to_string(), None).await.unwrap();
+        let idle = create_api_key(&db, "idle".to_string(), None).await.unwrap();
+        crate::auth::service::set_monthly_quota(&db, paid.id.clone(), Some(2)).await.unwrap();
+        let meter = UsageMeter::new();
+        let january = DateTime::parse_from_rfc3339("2026-01-15T12:00:00Z").unwrap().to_utc();
+        let february = DateTime::parse_from_rfc3339("2026-02-01T00:00:00Z").unwrap().to_utc();
+        meter.record(&paid.id, "/api/convert", false, january);
+        meter.record(&paid.id, "/api/time/{timezone}", false, january);
+        meter.record(&paid.id, "/api/time/{timezone}", true, january);
+        meter.record(&paid.id, "/api/convert", false, february);
+        meter.flush(&db).await.unwrap();
+
+        let report = billing_report(&db, "2026-01".to_string(), None).await.unwrap();
+        assert_eq!(report.len(), 2);
+        let row = &report[0];
+        assert_eq!(row.key_id, paid.id);
+        assert_eq!((row.requests, row.errors), (3, 1));
+        assert_eq!(row.by_class, [2, 1, 0, 0, 0]);
+        assert_eq!(row.overage, 1);
+        assert_eq!(report[1].key_id, idle.id);
+        assert_eq!(report[1].requests, 0);
+
+        let csv = billing_csv("2026-01", &report);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "month,key_id,name,requests,errors,lookup,conversion,geo,planning,account,monthly_quota,overage"
+        );
+        assert_eq!(
+            lines[1],
+            format!("2026-01,{},\"Acme, Inc.\",3,1,2,1,0,0,0,2,1", paid.id)
+        );
+        assert_eq!(lines[2], format!("2026-01,{},idle,0,0,0,0,0,0,0,,0", idle.id));
+    }
+}
@@ -0,0 +1,700 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Webhooks that warn admins ahead of API key expiry. A background job posts one
+// notification per webhook for each active key that comes within the webhook's
+// days_before of its expires_at.
+//
+// A webhook registered with a cron schedule is instead sent the current time in its
+// timezones at every scheduled run, for systems that can't poll the API themselves.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_rusqlite::Connection;
+use uuid::Uuid;
+
+use super::models::{CreateWebhookResponse, Webhook};
+use super::signing::sign;
+use crate::cron::Schedule;
+use crate::models::BulkTimeResponse;
+use crate::service::EpochZoneService;
+
+// How often keys are checked for coming expiry
+pub const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+// How often scheduled webhooks are checked for a due run
+pub const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// How long a webhook endpoint has to answer
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+// How many scheduled webhooks are posted at once, so one slow endpoint doesn't hold up the rest
+const MAX_CONCURRENT_DELIVERIES: usize = 8;
+
+// Warning period for webhooks that don't set one, and the longest allowed
+pub const DEFAULT_DAYS_BEFORE: u32 = 7;
+pub const MAX_DAYS_BEFORE: u32 = 365;
+
+// Event name sent in the payload and the X-Epochzone-Event header
+pub const KEY_EXPIRING_EVENT: &str = "api_key.expiring";
+pub const WORLD_CLOCK_EVENT: &str = "world_clock.tick";
+
+// Timezones a scheduled webhook reports when none are given
+pub const DEFAULT_TIMEZONES: &[&str] = &["UTC"];
+
+// How scheduled run times are stored, matching SQLite's datetime('now')
+const STORED_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Check a webhook URL before storing it
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| format!("Invalid webhook URL '{}'", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(format!("Webhook URL '{}' must be http or https", url));
+    }
+    Ok(())
+}
+
+// Check the timezones a scheduled webhook reports
+pub fn validate_timezones(timezones: &[String]) -> Result<(), String> {
+    let times = EpochZoneService::get_current_times(timezones)?;
+    match times.results.into_iter().find_map(|result| result.error) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+// When and what a scheduled webhook is sent
+#[derive(Debug, Clone)]
+pub struct WebhookSchedule {
+    pub cron: String,
+    pub timezones: Vec<String>,
+}
+
+// Register a webhook. Its secret, used to sign deliveries, is returned only here. A
+// webhook with a schedule is sent the world clock instead of key expiry warnings.
+pub async fn create_webhook(
+    db: &Connection,
+    url: String,
+    days_before: u32,
+    schedule: Option<WebhookSchedule>,
+) -> Result<CreateWebhookResponse, String> {
+    let id = Uuid::new_v4().to_string();
+    let secret = format!("whsec_{}", Uuid::new_v4().simple());
+    let next_delivery_at = match &schedule {
+        Some(schedule) => Some(next_run(&schedule.cron, Utc::now())?),
+        None => None,
+    };
+    let timezones_json = match &schedule {
+        Some(schedule) => Some(
+            serde_json::to_string(&schedule.timezones)
+                .map_err(|e| format!("Failed to encode timezones: {}", e))?,
+        ),
+        None => None,
+    };
+    let days_before = if schedule.is_some() { 0 } else { days_before };
+    db.call(move |conn| {
+        let created_at: String = conn.query_row(
+            "INSERT INTO webhooks
+                 (id, url, secret, days_before, schedule, timezones, next_delivery_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             RETURNING created_at",
+            rusqlite::params![
+                id,
+                url,
+                secret,
+                days_before,
+                schedule.as_ref().map(|schedule| &schedule.cron),
+                timezones_json,
+                next_delivery_at,
+            ],
+            |row| row.get(0),
+        )?;
+        let (cron, timezones) = schedule.map(|s| (s.cron, s.timezones)).unzip();
+        Ok(CreateWebhookResponse {
+            id,
+            url,
+            days_before: cron.is_none().then_some(days_before),
+            schedule: cron,
+            timezones,
+            next_delivery_at,
+            created_at,
+            secret,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to create webhook: {}", e))
+}
+
+pub async fn list_webhooks(db: &Connection) -> Result<Vec<Webhook>, String> {
+    db.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, url, days_before, created_at, schedule, timezones, next_delivery_at
+             FROM webhooks ORDER BY created_at, id",
+        )?;
+        let webhooks = stmt
+            .query_map([], webhook_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(webhooks)
+    })
+    .await
+    .map_err(|e| format!("Failed to list webhooks: {}", e))
+}
+
+pub async fn get_webhook(db: &Connection, id: String) -> Result<Option<Webhook>, String> {
+    db.call(move |conn| {
+        let webhook = conn
+            .query_row(
+                "SELECT id, url, days_before, created_at, schedule, timezones, next_delivery_at
+                 FROM webhooks WHERE id = ?1",
+                [id],
+                webhook_from_row,
+            )
+            .optional()?;
+        Ok(webhook)
+    })
+    .await
+    .map_err(|e| format!("Failed to read webhook: {}", e))
+}
+
+fn webhook_from_row(row: &rusqlite::Row) -> rusqlite::Result<Webhook> {
+    let schedule: Option<String> = row.get(4)?;
+    Ok(Webhook {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        days_before: if schedule.is_none() { Some(row.get(2)?) } else { None },
+        schedule,
+        timezones: row
+            .get::<_, Option<String>>(5)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        next_delivery_at: row.get(6)?,
+        created_at: row.get(3)?,
+    })
+}
+
+// The first run of a cron schedule after `after`, as stored
+fn next_run(cron: &str, after: DateTime<Utc>) -> Result<String, String> {
+    Schedule::parse(cron)?
+        .next_after(after)
+        .map(|at| at.format(STORED_TIME_FORMAT).to_string())
+        .ok_or_else(|| format!("Schedule '{}' never runs", cron))
+}
+
+pub async fn delete_webhook(db: &Connection, id: String) -> Result<bool, String> {
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM webhook_notifications WHERE webhook_id = ?1", [&id])?;
+        let deleted = tx.execute("DELETE FROM webhooks WHERE id = ?1", [&id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to delete webhook: {}", e))
+}
+
+// A key coming up on its expiry, as sent to webhooks
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiringKey {
+    pub id: String,
+    pub name: String,
+    pub expires_at: String,
+    pub owner_email: Option<String>,
+    pub tags: Vec<String>,
+    // Whole days left, rounded up
+    pub days_remaining: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct ExpiryPayload<'a> {
+    event: &'static str,
+    webhook_id: &'a str,
+    key: &'a ExpiringKey,
+}
+
+// A webhook owed a notification about a key
+struct PendingNotification {
+    webhook_id: String,
+    url: String,
+    secret: String,
+    key: ExpiringKey,
+}
+
+// Keys inside a webhook's warning period that it hasn't been told about. A key whose
+// expiry is moved is announced again.
+async fn pending_notifications(db: &Connection) -> Result<Vec<PendingNotification>, String> {
+    db.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT w.id, w.url, w.secret, k.id, k.name, k.expires_at, k.owner_email, k.tags,
+                    julianday(k.expires_at) - julianday('now')
+             FROM webhooks w
+             JOIN api_keys k
+               ON k.is_active = 1
+              AND k.expires_at IS NOT NULL
+              AND k.expires_at > datetime('now')
+              AND k.expires_at <= datetime('now', '+' || w.days_before || ' days')
+             WHERE w.schedule IS NULL
+               AND NOT EXISTS (
+                 SELECT 1 FROM webhook_notifications n
+                 WHERE n.webhook_id = w.id AND n.key_id = k.id AND n.expires_at = k.expires_at
+             )
+             ORDER BY k.expires_at, w.id",
+        )?;
+        let pending = stmt
+            .query_map([], |row| {
+                let days_left: f64 = row.get(8)?;
+                Ok(PendingNotification {
+                    webhook_id: row.get(0)?,
+                    url: row.get(1)?,
+                    secret: row.get(2)?,
+                    key: ExpiringKey {
+                        id: row.get(3)?,
+                        name: row.get(4)?,
+                        expires_at: row.get(5)?,
+                        owner_email: row.get(6)?,
+                        tags: row
+                            .get::<_, Option<String>>(7)?
+                            .and_then(|json| serde_json::from_str(&json).ok())
+                            .unwrap_or_default(),
+                        days_remaining: days_left.ceil().max(0.0) as u32,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pending)
+    })
+    .await
+    .map_err(|e| format!("Failed to find expiring keys: {}", e))
+}
+
+async fn mark_notified(db: &Connection, pending: &PendingNotification) -> Result<(), String> {
+    let (webhook_id, key_id) = (pending.webhook_id.clone(), pending.key.id.clone());
+    let expires_at = pending.key.expires_at.clone();
+    db.call(move |conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO webhook_notifications (webhook_id, key_id, expires_at)
+             VALUES (?1, ?2, ?3)",
+            rusqlite::params![webhook_id, key_id, expires_at],
+        )?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to record webhook notification: {}", e))
+}
+
+// POST a payload, signed with the webhook's secret: X-Epochzone-Signature is "sha256="
+// and the hex HMAC-SHA256 of the body
+async fn deliver<T: Serialize>(
+    client: &reqwest::Client,
+    webhook_id: &str,
+    url: &str,
+    secret: &str,
+    event: &str,
+    payload: &T,
+) -> Result<(), String> {
+    let body = serde_json::to_string(payload)
+        .map_err(|e| format!("Failed to encode webhook payload: {}", e))?;
+    let signature = format!("sha256={}", sign(secret, &body));
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Epochzone-Event", event)
+        .header("X-Epochzone-Signature", signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Webhook {} delivery failed: {}", webhook_id, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook {} answered {}", webhook_id, response.status()));
+    }
+    Ok(())
+}
+
+// Send every notification that is due, returning how many were delivered. Failed
+// deliveries are retried on the next run.
+pub async fn notify_expiring_keys(
+    db: &Connection,
+    client: &reqwest::Client,
+) -> Result<usize, String> {
+    let mut delivered = 0;
+    for pending in pending_notifications(db).await? {
+        let payload = ExpiryPayload {
+            event: KEY_EXPIRING_EVENT,
+            webhook_id: &pending.webhook_id,
+            key: &pending.key,
+        };
+        let (id, url, secret) = (&pending.webhook_id, &pending.url, &pending.secret);
+        match deliver(client, id, url, secret, KEY_EXPIRING_EVENT, &payload).await {
+            Ok(()) => {
+                mark_notified(db, &pending).await?;
+                delivered += 1;
+            }
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    Ok(delivered)
+}
+
+// Run notify_expiring_keys every EXPIRY_CHECK_INTERVAL for as long as the server runs
+pub fn spawn_expiry_notifier(db: Connection) {
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .expect("Failed to build webhook HTTP client");
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match notify_expiring_keys(&db, &client).await {
+                Ok(0) => {}
+                Ok(delivered) => tracing::info!("Sent {} key expiry notifications", delivered),
+                Err(e) => tracing::error!("{}", e),
+            }
+        }
+    });
+}
+
+#[derive(Debug, Serialize)]
+struct WorldClockPayload<'a> {
+    event: &'static str,
+    webhook_id: &'a str,
+    scheduled_at: &'a str,
+    times: BulkTimeResponse,
+}
+
+// A scheduled webhook whose next run has come
+struct DueWebhook {
+    id: String,
+    url: String,
+    secret: String,
+    schedule: String,
+    timezones: Vec<String>,
+    scheduled_at: String,
+}
+
+async fn due_webhooks(db: &Connection, now: DateTime<Utc>) -> Result<Vec<DueWebhook>, String> {
+    let now = now.format(STORED_TIME_FORMAT).to_string();
+    db.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, url, secret, schedule, timezones, next_delivery_at FROM webhooks
+             WHERE schedule IS NOT NULL AND next_delivery_at <= ?1
+             ORDER BY next_delivery_at, id",
+        )?;
+        let due = stmt
+            .query_map([now], |row| {
+                Ok(DueWebhook {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    secret: row.get(2)?,
+                    schedule: row.get(3)?,
+                    timezones: row
+                        .get::<_, Option<String>>(4)?
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                    scheduled_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(due)
+    })
+    .await
+    .map_err(|e| format!("Failed to find scheduled webhooks: {}", e))
+}
+
+// Store a webhook's next run, or clear it so the webhook is no longer sent
+async fn set_next_delivery(
+    db: &Connection,
+    id: String,
+    next: Option<String>,
+) -> Result<(), String> {
+    db.call(move |conn| {
+        conn.execute(
+            "UPDATE webhooks SET next_delivery_at = ?1 WHERE id = ?2",
+            rusqlite::params![next, id],
+        )?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to schedule webhook: {}", e))
+}
+
+// Send one due webhook and move it on to its next run, returning whether it was delivered.
+// A stored schedule that no longer parses pauses the webhook instead of retrying it forever.
+async fn deliver_due(
+    db: Connection,
+    client: reqwest::Client,
+    due: DueWebhook,
+    now: DateTime<Utc>,
+) -> bool {
+    let next = match next_run(&due.schedule, now) {
+        Ok(next) => next,
+        Err(e) => {
+            tracing::warn!("Pausing webhook {} with a bad schedule: {}", due.id, e);
+            if let Err(e) = set_next_delivery(&db, due.id.clone(), None).await {
+                tracing::error!("Webhook {}: {}", due.id, e);
+            }
+            return false;
+        }
+    };
+    let sent = match EpochZoneService::get_current_times(&due.timezones) {
+        Ok(times) => {
+            let payload = WorldClockPayload {
+                event: WORLD_CLOCK_EVENT,
+                webhook_id: &due.id,
+                scheduled_at: &due.scheduled_at,
+                times,
+            };
+            let (id, url, secret) = (&due.id, &due.url, &due.secret);
+            deliver(&client, id, url, secret, WORLD_CLOCK_EVENT, &payload).await
+        }
+        Err(e) => Err(format!("Webhook {} has bad timezones: {}", due.id, e)),
+    };
+    if let Err(e) = &sent {
+        tracing::warn!("{}", e);
+    }
+    if let Err(e) = set_next_delivery(&db, due.id.clone(), Some(next)).await {
+        tracing::error!("Webhook {}: {}", due.id, e);
+    }
+    sent.is_ok()
+}
+
+// Send the world clock to every scheduled webhook whose run is due, returning how many
+// were delivered. Each moves on to its first run after `now`: runs missed while the
+// server was down and failed deliveries are not made up. Up to MAX_CONCURRENT_DELIVERIES
+// are in flight at once, and a failure on one webhook doesn't stop the others.
+pub async fn deliver_scheduled(
+    db: &Connection,
+    client: &reqwest::Client,
+    now: DateTime<Utc>,
+) -> Result<usize, String> {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES));
+    let mut deliveries = JoinSet::new();
+    for due in due_webhooks(db, now).await? {
+        let permit = permits.clone().acquire_owned().await.expect("semaphore is never closed");
+        let (db, client) = (db.clone(), client.clone());
+        deliveries.spawn(async move {
+            let sent = deliver_due(db, client, due, now).await;
+            drop(permit);
+            sent
+        });
+    }
+    let mut delivered = 0;
+    while let Some(sent) = deliveries.join_next().await {
+        match sent {
+            Ok(true) => delivered += 1,
+            Ok(false) => {}
+            Err(e) => tracing::error!("Scheduled webhook delivery failed: {}", e),
+        }
+    }
+    Ok(delivered)
+}
+
+// Run deliver_scheduled every SCHEDULE_CHECK_INTERVAL for as long as the server runs
+pub fn spawn_scheduled_deliveries(db: Connection) {
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .expect("Failed to build webhook HTTP client");
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match deliver_scheduled(&db, &client, Utc::now()).await {
+                Ok(0) => {}
+                Ok(delivered) => tracing::debug!("Sent {} scheduled webhooks", delivered),
+                Err(e) => tracing::error!("{}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::service::create_api_key;
+    use crate::db::init_db;
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://hooks.example.com/epochzone").is_ok());
+        assert!(validate_url("http://localhost:8080/hook").is_ok());
+        assert!(validate_url("ftp://hooks.example.com").is_err());
+        assert!(validate_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pending_notifications() {
+        let db = init_db(":memory:").await;
+        let soon = (chrono::Utc::now() + chrono::Duration::days(3))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let later = (chrono::Utc::now() + chrono::Duration::days(30))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let expiring = create_api_key(&db, "partner".to_string(), Some(soon)).await.unwrap();
+        create_api_key(&db, "later".to_string(), Some(later)).await.unwrap();
+        create_api_key(&db, "forever".to_string(), None).await.unwrap();
+        let url = "https://hooks.example.com".to_string();
+        let webhook = create_webhook(&db, url, 7, None).await.unwrap();
+        assert!(webhook.secret.starts_with("whsec_"));
+        // Scheduled webhooks aren't warned about keys
+        let schedule = WebhookSchedule {
+            cron: "@hourly".to_string(),
+            timezones: vec!["UTC".to_string()],
+        };
+        let url = "https://clock.example.com".to_string();
+        create_webhook(&db, url, 7, Some(schedule)).await.unwrap();
+
+        let pending = pending_notifications(&db).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].key.id, expiring.id);
+        assert_eq!(pending[0].key.days_remaining, 3);
+
+        // Announced once per expiry
+        mark_notified(&db, &pending[0]).await.unwrap();
+        assert!(pending_notifications(&db).await.unwrap().is_empty());
+
+        assert!(delete_webhook(&db, webhook.id.clone()).await.unwrap());
+        assert_eq!(list_webhooks(&db).await.unwrap().len(), 1);
+        assert!(!delete_webhook(&db, webhook.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_notify_expiring_keys() {
+        use axum::{http::HeaderMap, routing::post, Router};
+        use tokio::sync::mpsc;
+
+        // A receiver that passes each delivery's signature header and body back to the test
+        let (tx, mut rx) = mpsc::unbounded_channel::<(String, String)>();
+        let receiver = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| async move {
+                let signature = headers["x-epochzone-signature"].to_str().unwrap().to_string();
+                tx.send((signature, body)).unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, receiver).await });
+
+        let db = init_db(":memory:").await;
+        let soon = (chrono::Utc::now() + chrono::Duration::hours(30))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let key = create_api_key(&db, "partner".to_string(), Some(soon)).await.unwrap();
+        let url = format!("http://{}/hook", addr);
+        let webhook = create_webhook(&db, url, 2, None).await.unwrap();
+
+        let client = reqwest::Client::new();
+        assert_eq!(notify_expiring_keys(&db, &client).await.unwrap(), 1);
+        assert_eq!(notify_expiring_keys(&db, &client).await.unwrap(), 0);
+
+        let (signature, body) = rx.recv().await.unwrap();
+        assert_eq!(signature, format!("sha256={}", sign(&webhook.secret, &body)));
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["event"], KEY_EXPIRING_EVENT);
+        assert_eq!(payload["key"]["id"], key.id.as_str());
+        assert_eq!(payload["key"]["days_remaining"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_scheduled() {
+        use axum::{http::HeaderMap, routing::post, Router};
+        use tokio::sync::mpsc;
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<(String, String)>();
+        let receiver = Router::new().route(
+            "/clock",
+            post(move |headers: HeaderMap, body: String| async move {
+                let event = headers["x-epochzone-event"].to_str().unwrap().to_string();
+                tx.send((event, body)).unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, receiver).await });
+
+        let db = init_db(":memory:").await;
+        let schedule = WebhookSchedule {
+            cron: "@hourly".to_string(),
+            timezones: vec!["Europe/Belgrade".to_string(), "Asia/Tokyo".to_string()],
+        };
+        let url = format!("http://{}/clock", addr);
+        let webhook = create_webhook(&db, url, 7, Some(schedule)).await.unwrap();
+        assert_eq!(webhook.days_before, None);
+        let next = webhook.next_delivery_at.unwrap();
+        assert!(next.ends_with(":00:00"));
+
+        let client = reqwest::Client::new();
+        let due = DateTime::parse_from_rfc3339(&format!("{}Z", next.replace(' ', "T")))
+            .unwrap()
+            .with_timezone(&Utc);
+        let before = due - chrono::Duration::seconds(1);
+        assert_eq!(deliver_scheduled(&db, &client, before).await.unwrap(), 0);
+        // Three hours late: sent once, then due again an hour after now
+        let late = due + chrono::Duration::minutes(185);
+        assert_eq!(deliver_scheduled(&db, &client, late).await.unwrap(), 1);
+        assert_eq!(deliver_scheduled(&db, &client, late).await.unwrap(), 0);
+        let stored = get_webhook(&db, webhook.id.clone()).await.unwrap().unwrap();
+        let expected = (due + chrono::Duration::hours(4)).format(STORED_TIME_FORMAT);
+        assert_eq!(stored.next_delivery_at, Some(expected.to_string()));
+
+        let (event, body) = rx.recv().await.unwrap();
+        assert_eq!(event, WORLD_CLOCK_EVENT);
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["webhook_id"], webhook.id.as_str());
+        assert_eq!(payload["scheduled_at"], next.as_str());
+        assert_eq!(payload["times"]["results"][1]["timezone"], "Asia/Tokyo");
+        assert_eq!(payload["times"]["errors"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_scheduled_skips_bad_schedule() {
+        use axum::{routing::post, Router};
+
+        let receiver = Router::new().route("/clock", post(|| async {}));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, receiver).await });
+
+        let db = init_db(":memory:").await;
+        let url = format!("http://{}/clock", addr);
+        let hourly = || WebhookSchedule {
+            cron: "@hourly".to_string(),
+            timezones: vec!["UTC".to_string()],
+        };
+        let bad = create_webhook(&db, url.clone(), 7, Some(hourly())).await.unwrap();
+        let good = create_webhook(&db, url, 7, Some(hourly())).await.unwrap();
+        let bad_id = bad.id.clone();
+        db.call(move |conn| {
+            conn.execute("UPDATE webhooks SET schedule = 'bogus' WHERE id = ?1", [bad_id])?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let client = reqwest::Client::new();
+        let late = Utc::now() + chrono::Duration::hours(2);
+        assert_eq!(deliver_scheduled(&db, &client, late).await.unwrap(), 1);
+        let stored = get_webhook(&db, good.id).await.unwrap().unwrap();
+        assert!(stored.next_delivery_at.unwrap() > good.next_delivery_at.unwrap());
+        // The bad webhook is paused rather than retried on every check
+        let stored = get_webhook(&db, bad.id).await.unwrap().unwrap();
+        assert_eq!(stored.next_delivery_at, None);
+        assert_eq!(deliver_scheduled(&db, &client, late).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,188 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::LazyLock;
+use tzf_rs::gen::{Point, Timezones};
+
+// Zone boundary polygons from the reduced timezone-boundary-builder dataset that tzf-rs
+// ships. Decoded on first use, since only the boundary endpoint needs them.
+static BOUNDARIES: LazyLock<Timezones> =
+    LazyLock::new(|| tzf_rs::deserialize_tzs(&tzf_rel::load_reduced()).unwrap_or_default());
+
+// A closed ring of (longitude, latitude) pairs, first point repeated at the end
+pub type Ring = Vec<[f64; 2]>;
+
+// A zone's polygons as GeoJSON MultiPolygon coordinates: each polygon is its outer ring
+// followed by its holes. Rings are simplified with Douglas-Peucker at `tolerance`
+// degrees (0 keeps every point); rings that collapse below a triangle are dropped.
+pub fn zone_boundary(name: &str, tolerance: f64) -> Option<Vec<Vec<Ring>>> {
+    let zone = BOUNDARIES.timezones.iter().find(|tz| tz.name == name)?;
+    let polygons = zone
+        .polygons
+        .iter()
+        .filter_map(|polygon| {
+            let outer = ring(&polygon.points, tolerance)?;
+            let holes = polygon.holes.iter().filter_map(|h| ring(&h.points, tolerance));
+            Some(std::iter::once(outer).chain(holes).collect())
+        })
+        .collect();
+    Some(polygons)
+}
+
+fn ring(points: &[Point], tolerance: f64) -> Option<Ring> {
+    // The dataset stores f32; round off the widening noise (1e-5 degrees is about a metre)
+    let round = |value: f32| (value as f64 * 1e5).round() / 1e5;
+    let mut ring: Ring = points.iter().map(|p| [round(p.lng), round(p.lat)]).collect();
+    if ring.first() != ring.last() {
+        ring.push(ring[0]);
+    }
+    if tolerance > 0.0 {
+        ring = simplify(&ring, tolerance);
+    }
+    // A closed ring needs three distinct points
+    (ring.len() >= 4).then_some(ring)
+}
+
+// Nearest land zone to a point and the distance to its boundary in kilometres. Zones
+// under Etc/ (the nautical ocean zones) are skipped.
+pub fn nearest_land_zone(lng: f64, lat: f64) -> Option<(&'static str, f64)> {
+    // Search in a local equirectangular projection around the point, then measure the
+    // closest boundary point found with the haversine formula
+    let scale = lat.to_radians().cos();
+    let project = |p: &Point| {
+        let dlng = (p.lng as f64 - lng + 540.0).rem_euclid(360.0) - 180.0;
+        [dlng * scale, p.lat as f64 - lat]
+    };
+
+    let mut best: Option<(&'static str, f64, [f64; 2])> = None;
+    for zone in BOUNDARIES.timezones.iter().filter(|tz| !tz.name.starts_with("Etc/")) {
+        for polygon in &zone.polygons {
+            let ring: Vec<[f64; 2]> = polygon.points.iter().map(project).collect();
+            for (i, a) in ring.iter().enumerate() {
+                let b = ring[(i + 1) % ring.len()];
+                let closest = closest_on_segment([0.0, 0.0], *a, b);
+                let distance = closest[0].hypot(closest[1]);
+                if best.is_none_or(|(_, d, _)| distance < d) {
+                    best = Some((zone.name.as_str(), distance, closest));
+                }
+            }
+        }
+    }
+
+    best.map(|(name, _, closest)| {
+        let closest_lng = lng + if scale > 0.0 { closest[0] / scale } else { 0.0 };
+        (name, haversine_km(lng, lat, closest_lng, lat + closest[1]))
+    })
+}
+
+// Great-circle distance in kilometres
+fn haversine_km(lng1: f64, lat1: f64, lng2: f64, lat2: f64) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (dlat, dlng) = ((lat2 - lat1).to_radians(), (lng2 - lng1).to_radians());
+    let a = (dlat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (dlng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+// Douglas-Peucker line simplification, keeping both end points
+pub fn simplify(points: &[[f64; 2]], tolerance: f64) -> Vec<[f64; 2]> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let (index, distance) = (start + 1..end)
+            .map(|i| (i, segment_distance(points[i], points[start], points[end])))
+            .fold((start, 0.0), |best, next| if next.1 > best.1 { next } else { best });
+        if distance > tolerance {
+            keep[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}
+
+// Planar distance from `p` to the segment a-b, in degrees
+fn segment_distance(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let [x, y] = closest_on_segment(p, a, b);
+    (p[0] - x).hypot(p[1] - y)
+}
+
+// Point of the segment a-b closest to `p`, in the plane
+fn closest_on_segment(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+    [a[0] + t * dx, a[1] + t * dy]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplify_drops_collinear_points() {
+        let line = [[0.0, 0.0], [1.0, 0.001], [2.0, 0.0], [3.0, 1.0]];
+        assert_eq!(simplify(&line, 0.01), [[0.0, 0.0], [2.0, 0.0], [3.0, 1.0]]);
+        assert_eq!(simplify(&line, 0.0), line);
+        // The closed ring of a square keeps its corners
+        let square = [[0.0, 0.0], [0.5, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]];
+        assert_eq!(simplify(&square, 0.1).len(), 5);
+    }
+
+    #[test]
+    fn test_zone_boundary() {
+        let full = zone_boundary("Europe/Belgrade", 0.0).unwrap();
+        assert!(!full.is_empty());
+        let outer = &full[0][0];
+        assert_eq!(outer.first(), outer.last());
+        // Belgrade lies within the outline's bounding box
+        let (lngs, lats): (Vec<f64>, Vec<f64>) = outer.iter().map(|p| (p[0], p[1])).unzip();
+        let within = |values: &[f64], v: f64| {
+            values.iter().cloned().fold(f64::MAX, f64::min) < v
+                && v < values.iter().cloned().fold(f64::MIN, f64::max)
+        };
+        assert!(within(&lngs, 20.46) && within(&lats, 44.82));
+
+        let points = |polygons: &[Vec<Ring>]| -> usize {
+            polygons.iter().flatten().map(Vec::len).sum()
+        };
+        let simplified = zone_boundary("Europe/Belgrade", 0.01).unwrap();
+        assert!(points(&simplified) < points(&full));
+        assert!(zone_boundary("Mars/Base", 0.0).is_none());
+    }
+
+    #[test]
+    fn test_nearest_land_zone() {
+        // Southern Adriatic, roughly 100 km from the Italian and Montenegrin coasts
+        let (zone, distance) = nearest_land_zone(18.0, 41.5).unwrap();
+        assert!(!zone.starts_with("Etc/"));
+        assert!(distance > 50.0 && distance < 200.0, "{} km", distance);
+        assert!((haversine_km(0.0, 0.0, 1.0, 0.0) - 111.19).abs() < 0.01);
+    }
+}
@@ -0,0 +1,355 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc};
+use icu_calendar::hebrew::Hebrew;
+use icu_calendar::islamic::IslamicUmmAlQura;
+use icu_calendar::japanese::Japanese;
+use icu_calendar::{AsCalendar, Date, Iso};
+
+use crate::models::{CalendarDate, CalendarDates, CalendarSystem, JulianDateResponse, JulianQuery};
+
+// Julian Day of the Unix epoch (1970-01-01T00:00:00Z)
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+// MJD = JD - 2400000.5, so MJD 0 is 1858-11-17T00:00:00Z
+const MJD_OFFSET: f64 = 2_400_000.5;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+const HIJRI_MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi' al-awwal",
+    "Rabi' al-thani",
+    "Jumada al-awwal",
+    "Jumada al-thani",
+    "Rajab",
+    "Sha'ban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qi'dah",
+    "Dhu al-Hijjah",
+];
+
+const GREGORIAN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+// Express a local date in each requested calendar system.
+// Nothing is filled in when the date is outside the ISO range ICU accepts.
+pub fn to_calendar_dates(date: NaiveDate, systems: &[CalendarSystem]) -> CalendarDates {
+    let mut dates = CalendarDates::default();
+
+    let Ok(iso) = Date::try_new_iso_date(date.year(), date.month() as u8, date.day() as u8) else {
+        return dates;
+    };
+
+    for system in systems {
+        match system {
+            CalendarSystem::Hijri => dates.hijri = Some(to_hijri(&iso)),
+            CalendarSystem::Hebrew => dates.hebrew = Some(to_hebrew(&iso)),
+            CalendarSystem::Japanese => dates.japanese = Some(to_japanese(&iso)),
+        }
+    }
+
+    dates
+}
+
+// Convert between epoch seconds, ISO datetimes, Julian Days, Modified Julian Dates, and
+// ordinal dates. Exactly one input must be given; all representations are returned (UTC).
+pub fn convert_julian(query: &JulianQuery) -> Result<JulianDateResponse, String> {
+    let provided = [
+        query.timestamp.is_some(),
+        query.datetime.is_some(),
+        query.jd.is_some(),
+        query.mjd.is_some(),
+        query.ordinal.is_some(),
+    ]
+    .iter()
+    .filter(|p| **p)
+    .count();
+    if provided != 1 {
+        return Err(
+            "Provide exactly one of 'timestamp', 'datetime', 'jd', 'mjd', or 'ordinal'".to_string(),
+        );
+    }
+
+    let instant = if let Some(ts) = query.timestamp {
+        DateTime::from_timestamp(ts, 0).ok_or_else(|| format!("Invalid timestamp: {}", ts))?
+    } else if let Some(dt) = query.datetime.as_deref() {
+        parse_utc_datetime(dt)?
+    } else if let Some(jd) = query.jd {
+        from_julian_day(jd)?
+    } else if let Some(mjd) = query.mjd {
+        from_julian_day(mjd + MJD_OFFSET)?
+    } else {
+        let ordinal = query.ordinal.as_deref().unwrap_or_default();
+        NaiveDate::parse_from_str(ordinal, "%Y-%j")
+            .map_err(|e| format!("Invalid ordinal date '{}' (expected YYYY-DDD): {}", ordinal, e))?
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc()
+    };
+
+    let julian_day = to_julian_day(&instant);
+    Ok(JulianDateResponse {
+        timestamp: instant.timestamp(),
+        datetime: instant.to_rfc3339(),
+        julian_day,
+        julian_day_number: (julian_day + 0.5).floor() as i64,
+        modified_julian_date: julian_day - MJD_OFFSET,
+        ordinal_date: instant.format("%Y-%j").to_string(),
+        year: instant.year(),
+        day_of_year: instant.ordinal(),
+    })
+}
+
+fn to_julian_day(instant: &DateTime<Utc>) -> f64 {
+    instant.timestamp_millis() as f64 / 1000.0 / SECONDS_PER_DAY + UNIX_EPOCH_JULIAN_DAY
+}
+
+// Julian Day to instant, rounded to the millisecond (the precision an f64 JD carries)
+fn from_julian_day(jd: f64) -> Result<DateTime<Utc>, String> {
+    if !jd.is_finite() {
+        return Err(format!("Invalid Julian Day: {}", jd));
+    }
+    let millis = ((jd - UNIX_EPOCH_JULIAN_DAY) * SECONDS_PER_DAY * 1000.0).round();
+    if millis.abs() >= i64::MAX as f64 {
+        return Err(format!("Julian Day out of range: {}", jd));
+    }
+    DateTime::from_timestamp_millis(millis as i64)
+        .ok_or_else(|| format!("Julian Day out of range: {}", jd))
+}
+
+// RFC3339, or a bare ISO datetime/date taken as UTC
+fn parse_utc_datetime(input: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Ok(naive.and_utc());
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .map_err(|e| format!("Invalid datetime '{}': {}", input, e))
+}
+
+// Hijri date per the Umm al-Qura calendar used in Saudi Arabia
+fn to_hijri(iso: &Date<Iso>) -> CalendarDate {
+    let date = iso.to_calendar(IslamicUmmAlQura::new());
+    let month_name = HIJRI_MONTHS
+        .get(date.month().ordinal as usize - 1)
+        .copied()
+        .unwrap_or_default();
+    build_date(&date, month_name.to_string(), "AH")
+}
+
+fn to_hebrew(iso: &Date<Iso>) -> CalendarDate {
+    // In leap years icu_calendar codes Adar I as M05L and Adar II as M06L
+    let date = iso.to_calendar(Hebrew);
+    let month_name = match date.month().code.0.as_str() {
+        "M01" => "Tishrei",
+        "M02" => "Cheshvan",
+        "M03" => "Kislev",
+        "M04" => "Tevet",
+        "M05" => "Shevat",
+        "M05L" => "Adar I",
+        "M06" => "Adar",
+        "M06L" => "Adar II",
+        "M07" => "Nisan",
+        "M08" => "Iyar",
+        "M09" => "Sivan",
+        "M10" => "Tammuz",
+        "M11" => "Av",
+        "M12" => "Elul",
+        _ => "",
+    };
+    build_date(&date, month_name.to_string(), "")
+}
+
+// Japanese imperial era date; months follow the Gregorian calendar
+fn to_japanese(iso: &Date<Iso>) -> CalendarDate {
+    let date = iso.to_calendar(Japanese::new());
+    let month_name = GREGORIAN_MONTHS
+        .get(date.month().ordinal as usize - 1)
+        .copied()
+        .unwrap_or_default();
+    let mut result = build_date(&date, month_name.to_string(), "");
+    result.formatted = format!(
+        "{} {} {} {}",
+        result.day, result.month_name, result.era, result.year
+    );
+    result
+}
+
+fn build_date<A: AsCalendar>(date: &Date<A>, month_name: String, suffix: &str) -> CalendarDate {
+    let year = date.year();
+    let era = capitalize(year.era.0.as_str());
+    let day = date.day_of_month().0 as u8;
+
+    let formatted = if suffix.is_empty() {
+        format!("{} {} {}", day, month_name, year.number)
+    } else {
+        format!("{} {} {} {}", day, month_name, year.number, suffix)
+    };
+
+    CalendarDate {
+        era,
+        year: year.number,
+        month: date.month().ordinal as u8,
+        month_name,
+        day,
+        formatted,
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_hebrew_date() {
+        let dates = to_calendar_dates(date(2025, 2, 10), &[CalendarSystem::Hebrew]);
+        let hebrew = dates.hebrew.unwrap();
+        assert_eq!(hebrew.year, 5785);
+        assert_eq!(hebrew.month_name, "Shevat");
+        assert_eq!(hebrew.day, 12);
+        assert_eq!(hebrew.formatted, "12 Shevat 5785");
+        assert!(dates.hijri.is_none());
+    }
+
+    #[test]
+    fn test_hebrew_leap_year_adar() {
+        // 5784 is a leap year; 2024-03-20 falls in Adar II
+        let hebrew = to_calendar_dates(date(2024, 3, 20), &[CalendarSystem::Hebrew])
+            .hebrew
+            .unwrap();
+        assert_eq!(hebrew.month_name, "Adar II");
+    }
+
+    #[test]
+    fn test_hijri_date() {
+        // Ramadan 1446 began on 2025-03-01 in the Umm al-Qura calendar
+        let hijri = to_calendar_dates(date(2025, 3, 1), &[CalendarSystem::Hijri])
+            .hijri
+            .unwrap();
+        assert_eq!(hijri.year, 1446);
+        assert_eq!(hijri.month, 9);
+        assert_eq!(hijri.month_name, "Ramadan");
+        assert_eq!(hijri.formatted, "1 Ramadan 1446 AH");
+    }
+
+    #[test]
+    fn test_japanese_era_date() {
+        let japanese = to_calendar_dates(date(2025, 2, 10), &[CalendarSystem::Japanese])
+            .japanese
+            .unwrap();
+        assert_eq!(japanese.era, "Reiwa");
+        assert_eq!(japanese.year, 7);
+        assert_eq!(japanese.formatted, "10 February Reiwa 7");
+
+        let heisei = to_calendar_dates(date(2000, 1, 1), &[CalendarSystem::Japanese])
+            .japanese
+            .unwrap();
+        assert_eq!(heisei.era, "Heisei");
+        assert_eq!(heisei.year, 12);
+    }
+
+    fn julian(query: JulianQuery) -> JulianDateResponse {
+        convert_julian(&query).unwrap()
+    }
+
+    #[test]
+    fn test_julian_from_unix_epoch() {
+        let resp = julian(JulianQuery {
+            timestamp: Some(0),
+            ..Default::default()
+        });
+        assert_eq!(resp.julian_day, 2_440_587.5);
+        assert_eq!(resp.modified_julian_date, 40_587.0);
+        assert_eq!(resp.julian_day_number, 2_440_588);
+        assert_eq!(resp.ordinal_date, "1970-001");
+    }
+
+    #[test]
+    fn test_julian_j2000() {
+        // J2000.0 epoch is JD 2451545.0 (2000-01-01T12:00:00, UTC here)
+        let resp = julian(JulianQuery {
+            jd: Some(2_451_545.0),
+            ..Default::default()
+        });
+        assert_eq!(resp.datetime, "2000-01-01T12:00:00+00:00");
+        assert_eq!(resp.julian_day_number, 2_451_545);
+    }
+
+    #[test]
+    fn test_julian_from_mjd_and_ordinal() {
+        let from_mjd = julian(JulianQuery {
+            mjd: Some(60_716.0),
+            ..Default::default()
+        });
+        assert_eq!(from_mjd.datetime, "2025-02-10T00:00:00+00:00");
+
+        let from_ordinal = julian(JulianQuery {
+            ordinal: Some("2025-041".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(from_ordinal.timestamp, from_mjd.timestamp);
+        assert_eq!(from_ordinal.day_of_year, 41);
+    }
+
+    #[test]
+    fn test_julian_from_datetime_with_offset() {
+        let resp = julian(JulianQuery {
+            datetime: Some("2025-02-10T01:00:00+01:00".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(resp.modified_julian_date, 60_716.0);
+    }
+
+    #[test]
+    fn test_julian_requires_exactly_one_input() {
+        assert!(convert_julian(&JulianQuery::default()).is_err());
+        assert!(convert_julian(&JulianQuery {
+            timestamp: Some(0),
+            jd: Some(2_440_587.5),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -15,13 +15,76 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use axum::http::HeaderValue;
+use ipnet::IpNet;
 use std::env;
+use std::net::IpAddr;
+
+use crate::auth::service::hash_api_key;
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub cors_allowed_origins: Vec<HeaderValue>,
-    pub admin_api_key: String,
+    // SHA-256 (hex) of a static admin key, if configured; the key itself is not kept.
+    // Without one, admins use keys with the admin role, the first created at startup.
+    pub admin_api_key_hash: Option<String>,
     pub database_url: String,
+    // MaxMind GeoIP2/GeoLite2 City database for IP lookups; disabled when unset
+    pub geoip_database: Option<String>,
+    // Requests per minute for keys without their own limit; 0 disables rate limiting
+    pub rate_limit_per_minute: u32,
+    // Failed authentications an address may make before it is blocked with growing
+    // backoff; 0 disables blocking
+    pub auth_failure_limit: u32,
+    // Days a revoked or expired key is kept before it is deleted; 0 keeps them forever
+    pub revoked_key_retention_days: u32,
+    // Secret mixed into API key hashes (HMAC-SHA256); keys are plain SHA-256 hashed without it
+    pub api_key_pepper: Option<String>,
+    // Bearer tokens are accepted alongside API keys when one of these is set: an HMAC
+    // secret shared with the issuer, or the URL of its JWKS
+    pub jwt_secret: Option<String>,
+    pub jwt_jwks_url: Option<String>,
+    // Required `iss` and `aud` claims, if set
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    // Proxies in front of the service whose X-Forwarded-For and X-Real-IP are believed
+    // when checking a key's IP allowlist
+    pub trusted_proxies: Vec<IpNet>,
+    // SMTP relay for emailing key owners; email is off without a host
+    pub smtp: Option<SmtpConfig>,
+    // Days before a key's expiry to email its owner
+    pub expiry_email_days: u32,
+    // Requests a trial key may make, unless an admin gives it another limit
+    pub trial_request_limit: u64,
+    // Trial keys anyone may create per client address per hour; 0 turns signup off
+    pub trial_signups_per_hour: u32,
+    // Days until a trial key from signup expires
+    pub trial_key_days: u32,
+    // Redis shared by every instance for rate-limit counters and cached key lookups;
+    // only used when built with the `redis` feature
+    pub redis_url: Option<String>,
+    // Seconds a key lookup stays cached in Redis
+    pub key_cache_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    // Upgrade a plain connection with STARTTLS (usually port 587)
+    StartTls,
+    // TLS from the start (usually port 465)
+    Tls,
+    // Unencrypted, for local relays only
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    // Sender address, e.g. "Epoch Zone <keys@epoch.zone>"
+    pub from: String,
 }
 
 impl AppConfig {
@@ -45,19 +108,172 @@ impl AppConfig {
             })
             .collect();
 
-        let admin_api_key =
-            env::var("ADMIN_API_KEY").expect("ADMIN_API_KEY environment variable is required");
-        if admin_api_key.len() < 32 {
-            panic!("ADMIN_API_KEY must be at least 32 characters");
-        }
+        // ADMIN_API_KEY_HASH lets the deployment hold only the hash
+        let admin_api_key_hash = match env::var("ADMIN_API_KEY_HASH") {
+            Ok(hash) => {
+                let hash = hash.trim().to_ascii_lowercase();
+                if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                    panic!("ADMIN_API_KEY_HASH must be a hex SHA-256 digest");
+                }
+                Some(hash)
+            }
+            Err(_) => env::var("ADMIN_API_KEY").ok().filter(|key| !key.is_empty()).map(|key| {
+                if key.len() < 32 {
+                    panic!("ADMIN_API_KEY must be at least 32 characters");
+                }
+                hash_api_key(&key, None)
+            }),
+        };
 
         let database_url =
             env::var("DATABASE_URL").unwrap_or_else(|_| "epochzone.db".to_string());
 
+        let geoip_database = env::var("GEOIP_DATABASE").ok().filter(|path| !path.is_empty());
+
+        let rate_limit_per_minute = env::var("RATE_LIMIT_PER_MINUTE")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid RATE_LIMIT_PER_MINUTE: {}", value))
+            })
+            .unwrap_or(60);
+
+        let revoked_key_retention_days = env::var("REVOKED_KEY_RETENTION_DAYS")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid REVOKED_KEY_RETENTION_DAYS: {}", value))
+            })
+            .unwrap_or(90);
+
+        let api_key_pepper = env::var("API_KEY_PEPPER").ok().filter(|pepper| !pepper.is_empty());
+        if api_key_pepper.as_ref().is_some_and(|pepper| pepper.len() < 32) {
+            panic!("API_KEY_PEPPER must be at least 32 characters");
+        }
+
+        let optional = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let jwt_secret = optional("JWT_SECRET");
+        let jwt_jwks_url = optional("JWT_JWKS_URL");
+        if jwt_secret.is_some() && jwt_jwks_url.is_some() {
+            panic!("Set only one of JWT_SECRET and JWT_JWKS_URL");
+        }
+        if jwt_secret.as_ref().is_some_and(|secret| secret.len() < 32) {
+            panic!("JWT_SECRET must be at least 32 characters");
+        }
+        let jwt_issuer = optional("JWT_ISSUER");
+        let jwt_audience = optional("JWT_AUDIENCE");
+
+        let trusted_proxies = optional("TRUSTED_PROXIES")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                    .map(|entry| {
+                        entry
+                            .parse::<IpNet>()
+                            .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                            .unwrap_or_else(|_| panic!("Invalid TRUSTED_PROXIES entry: {}", entry))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let smtp = optional("SMTP_HOST").map(|host| {
+            let tls = match optional("SMTP_TLS").as_deref() {
+                None | Some("starttls") => SmtpTls::StartTls,
+                Some("tls") => SmtpTls::Tls,
+                Some("none") => SmtpTls::None,
+                Some(other) => panic!("SMTP_TLS must be starttls, tls, or none, not {}", other),
+            };
+            let default_port = if tls == SmtpTls::Tls { 465 } else { 587 };
+            let port = optional("SMTP_PORT")
+                .map(|value| {
+                    value
+                        .parse()
+                        .unwrap_or_else(|_| panic!("Invalid SMTP_PORT: {}", value))
+                })
+                .unwrap_or(default_port);
+            let from = optional("SMTP_FROM").expect("SMTP_FROM must be set along with SMTP_HOST");
+            if from.parse::<lettre::message::Mailbox>().is_err() {
+                panic!("Invalid SMTP_FROM address: {}", from);
+            }
+            SmtpConfig {
+                host,
+                port,
+                tls,
+                username: optional("SMTP_USERNAME"),
+                password: optional("SMTP_PASSWORD"),
+                from,
+            }
+        });
+
+        let expiry_email_days = optional("EXPIRY_EMAIL_DAYS")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid EXPIRY_EMAIL_DAYS: {}", value))
+            })
+            .unwrap_or(7);
+
+        let auth_failure_limit = optional("AUTH_FAILURE_LIMIT")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid AUTH_FAILURE_LIMIT: {}", value))
+            })
+            .unwrap_or(10);
+
+        let trial_request_limit = optional("TRIAL_REQUEST_LIMIT")
+            .map(|value| match value.parse() {
+                Ok(limit) if limit > 0 => limit,
+                _ => panic!("Invalid TRIAL_REQUEST_LIMIT: {}", value),
+            })
+            .unwrap_or(1000);
+        let trial_signups_per_hour = optional("TRIAL_SIGNUPS_PER_HOUR")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid TRIAL_SIGNUPS_PER_HOUR: {}", value))
+            })
+            .unwrap_or(0);
+        let trial_key_days = optional("TRIAL_KEY_DAYS")
+            .map(|value| match value.parse() {
+                Ok(days) if days > 0 => days,
+                _ => panic!("Invalid TRIAL_KEY_DAYS: {}", value),
+            })
+            .unwrap_or(14);
+
+        let redis_url = optional("REDIS_URL");
+        let key_cache_seconds = optional("KEY_CACHE_SECONDS")
+            .map(|value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid KEY_CACHE_SECONDS: {}", value))
+            })
+            .unwrap_or(30);
+
         Self {
             cors_allowed_origins,
-            admin_api_key,
+            admin_api_key_hash,
             database_url,
+            geoip_database,
+            rate_limit_per_minute,
+            auth_failure_limit,
+            revoked_key_retention_days,
+            api_key_pepper,
+            jwt_secret,
+            jwt_jwks_url,
+            jwt_issuer,
+            jwt_audience,
+            trusted_proxies,
+            smtp,
+            expiry_email_days,
+            trial_request_limit,
+            trial_signups_per_hour,
+            trial_key_days,
+            redis_url,
+            key_cache_seconds,
         }
     }
 }
@@ -0,0 +1,196 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Five-field cron schedules ("minute hour day-of-month month day-of-week"), evaluated in
+// UTC. Fields take `*`, numbers, ranges (`1-5`), lists (`0,30`), and steps (`*/15`,
+// `9-17/2`); Sunday is 0 or 7. As in classic cron, when both day fields are restricted a
+// day matching either one runs.
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc};
+
+// Years searched for the next run before a schedule is taken to never run
+const SEARCH_YEARS: i64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    // Bit n set when value n matches
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // Whether each day field was anything but `*`
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "Schedule '{}' must have five fields: minute hour day month weekday",
+                expression
+            ));
+        };
+        let mut weekdays = parse_field(weekday, "weekday", 0, 7)?;
+        // 7 is another name for Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let schedule = Self {
+            minutes: parse_field(minute, "minute", 0, 59)?,
+            hours: parse_field(hour, "hour", 0, 23)?,
+            days: parse_field(day, "day", 1, 31)?,
+            months: parse_field(month, "month", 1, 12)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        };
+        if schedule.next_after(Utc::now()).is_none() {
+            return Err(format!("Schedule '{}' never runs", expression));
+        }
+        Ok(schedule)
+    }
+
+    // The first run strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let first_day = start.date_naive();
+        let last_day = first_day + Duration::days(366 * SEARCH_YEARS);
+        for day in first_day.iter_days().take_while(|day| *day <= last_day) {
+            if !self.runs_on(day) {
+                continue;
+            }
+            let from = if day == first_day { start.time() } else { NaiveTime::MIN };
+            for hour in from.hour()..24 {
+                if !has(self.hours, hour) {
+                    continue;
+                }
+                let first_minute = if hour == from.hour() { from.minute() } else { 0 };
+                if let Some(minute) = (first_minute..60).find(|m| has(self.minutes, *m)) {
+                    let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+                    return Some(day.and_time(time).and_utc());
+                }
+            }
+        }
+        None
+    }
+
+    fn runs_on(&self, day: chrono::NaiveDate) -> bool {
+        if !has(self.months, day.month()) {
+            return false;
+        }
+        let by_day = has(self.days, day.day());
+        let by_weekday = has(self.weekdays, day.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => by_day || by_weekday,
+            _ => by_day && by_weekday,
+        }
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+// The values of one field as a bit set
+fn parse_field(field: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("Invalid {} field '{}' in schedule", name, field);
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| invalid())?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse().map_err(|_| invalid())?,
+                    end.parse().map_err(|_| invalid())?,
+                ),
+                // "5/15" runs from 5 to the end of the field
+                None => {
+                    let start = range.parse().map_err(|_| invalid())?;
+                    (start, if item.contains('/') { max } else { start })
+                }
+            },
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expression: &str, after: &str) -> String {
+        let schedule = Schedule::parse(expression).unwrap();
+        schedule.next_after(at(after)).unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn test_next_after() {
+        assert_eq!(next("@hourly", "2026-03-07T10:00:00Z"), "2026-03-07T11:00:00+00:00");
+        assert_eq!(next("0 * * * *", "2026-03-07T10:59:59Z"), "2026-03-07T11:00:00+00:00");
+        assert_eq!(next("*/15 * * * *", "2026-03-07T10:16:00Z"), "2026-03-07T10:30:00+00:00");
+        assert_eq!(next("30 9-17/4 * * *", "2026-03-07T17:31:00Z"), "2026-03-08T09:30:00+00:00");
+        // 2026-03-07 is a Saturday; weekdays only
+        assert_eq!(next("0 9 * * 1-5", "2026-03-07T08:00:00Z"), "2026-03-09T09:00:00+00:00");
+        assert_eq!(next("0 0 * * 7", "2026-03-07T08:00:00Z"), "2026-03-08T00:00:00+00:00");
+        // Both day fields restricted: the 1st of the month or any Monday
+        assert_eq!(next("0 0 1 * 1", "2026-03-07T08:00:00Z"), "2026-03-09T00:00:00+00:00");
+        assert_eq!(next("0 0 29 2 *", "2026-03-07T08:00:00Z"), "2028-02-29T00:00:00+00:00");
+        assert_eq!(next("5/20 0 1 1 *", "2026-03-07T08:00:00Z"), "2027-01-01T00:05:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "0 0 31 2 *",
+        ] {
+            assert!(Schedule::parse(expression).is_err(), "{}", expression);
+        }
+    }
+}
@@ -16,6 +16,44 @@
 
 use tokio_rusqlite::Connection;
 
+// Columns added after the tables were first created, as (table, column, definition).
+// init_db adds any that an existing database is missing.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("api_keys", "rate_limit_per_minute", "INTEGER"),
+    ("api_keys", "monthly_quota", "INTEGER"),
+    // JSON array of path patterns; NULL allows every route
+    ("api_keys", "allowed_routes", "TEXT"),
+    // The secret replaced by the last rotation, accepted until previous_key_expires_at
+    ("api_keys", "previous_key_hash", "TEXT"),
+    ("api_keys", "previous_key_expires_at", "TEXT"),
+    // When the key was revoked, or when it expired; drives the retention of inactive keys
+    ("api_keys", "revoked_at", "TEXT"),
+    // JSON array of labels; NULL when there are none
+    ("api_keys", "tags", "TEXT"),
+    ("api_keys", "owner_email", "TEXT"),
+    // "client", or "admin" for keys that may use the /admin endpoints
+    ("api_keys", "role", "TEXT NOT NULL DEFAULT 'client'"),
+    // Salt of the key's request-signing secret; NULL while signed requests are disabled
+    ("api_keys", "signing_salt", "TEXT"),
+    // JSON array of CIDR blocks the key may be used from; NULL allows any address
+    ("api_keys", "allowed_ips", "TEXT"),
+    // JSON array of browser origins the key may be called from; NULL uses CORS_ALLOWED_ORIGINS
+    ("api_keys", "allowed_origins", "TEXT"),
+    // The customer organization the key belongs to; NULL for keys outside any
+    ("api_keys", "org_id", "TEXT REFERENCES organizations (id)"),
+    // "ez_<keyid>" and the last four characters of the secret, stored in the clear to
+    // identify a key; NULL for keys from before keys carried an id
+    ("api_keys", "key_prefix", "TEXT"),
+    ("api_keys", "key_last_four", "TEXT"),
+    // Requests a trial key has left; it is deactivated when they run out. NULL for other keys
+    ("api_keys", "requests_remaining", "INTEGER"),
+    // Cron schedule of a webhook sent the world clock, the JSON array of timezones it
+    // reports, and when it is next due; NULL for key expiry webhooks
+    ("webhooks", "schedule", "TEXT"),
+    ("webhooks", "timezones", "TEXT"),
+    ("webhooks", "next_delivery_at", "TEXT"),
+];
+
 pub async fn init_db(database_url: &str) -> Connection {
     let conn = if database_url == ":memory:" {
         Connection::open_in_memory().await.expect("Failed to open in-memory database")
@@ -34,7 +72,94 @@ pub async fn init_db(database_url: &str) -> Connection {
                 expires_at TEXT
             );
             CREATE INDEX IF NOT EXISTS idx_api_keys_key_hash ON api_keys (key_hash);
-            CREATE INDEX IF NOT EXISTS idx_api_keys_is_active ON api_keys (is_active);",
+            CREATE INDEX IF NOT EXISTS idx_api_keys_is_active ON api_keys (is_active);
+            CREATE TABLE IF NOT EXISTS organizations (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                monthly_quota INTEGER,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE TABLE IF NOT EXISTS api_key_usage (
+                key_id TEXT NOT NULL REFERENCES api_keys (id),
+                month TEXT NOT NULL,
+                request_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (key_id, month)
+            );
+            CREATE TABLE IF NOT EXISTS api_key_daily_usage (
+                key_id TEXT NOT NULL REFERENCES api_keys (id),
+                day TEXT NOT NULL,
+                endpoint TEXT NOT NULL,
+                request_count INTEGER NOT NULL DEFAULT 0,
+                error_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (key_id, day, endpoint)
+            );
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                actor TEXT NOT NULL,
+                action TEXT NOT NULL,
+                target_id TEXT,
+                before TEXT,
+                after TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at);
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                days_before INTEGER NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE TABLE IF NOT EXISTS webhook_notifications (
+                webhook_id TEXT NOT NULL REFERENCES webhooks (id),
+                key_id TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                sent_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (webhook_id, key_id, expires_at)
+            );
+            CREATE TABLE IF NOT EXISTS teams (
+                id TEXT PRIMARY KEY,
+                owner TEXT NOT NULL,
+                name TEXT NOT NULL COLLATE NOCASE,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                UNIQUE (owner, name)
+            );
+            CREATE TABLE IF NOT EXISTS team_members (
+                id TEXT PRIMARY KEY,
+                team_id TEXT NOT NULL REFERENCES teams (id),
+                name TEXT NOT NULL,
+                timezone TEXT NOT NULL,
+                work_start TEXT NOT NULL,
+                work_end TEXT NOT NULL,
+                include_weekends INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_team_members_team_id ON team_members (team_id);
+            CREATE TABLE IF NOT EXISTS expiry_emails (
+                key_id TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                sent_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (key_id, expires_at)
+            );",
+        )?;
+        for (table, column, definition) in ADDED_COLUMNS {
+            let exists: bool = conn.query_row(
+                &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1", table),
+                rusqlite::params![column],
+                |row| row.get::<_, i32>(0).map(|count| count > 0),
+            )?;
+            if !exists {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table, column, definition
+                ))?;
+            }
+        }
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_api_keys_previous_key_hash
+                ON api_keys (previous_key_hash);
+             CREATE INDEX IF NOT EXISTS idx_api_keys_org_id ON api_keys (org_id);
+             CREATE INDEX IF NOT EXISTS idx_api_keys_key_prefix ON api_keys (key_prefix);",
         )?;
         Ok(())
     })
@@ -0,0 +1,130 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Sparse fieldsets: `?fields=timezone,current_time` trims a JSON response to the named
+// fields. Lists are trimmed per item, and a dotted name ("to.datetime") reaches into a
+// nested object. Unknown names are ignored.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Query, Request},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{Map, Value};
+
+use crate::models::{ErrorResponse, FieldsQuery};
+
+pub async fn sparse_fieldsets(request: Request, next: Next) -> Response {
+    let fields = match Query::<FieldsQuery>::try_from_uri(request.uri()) {
+        Ok(Query(query)) => query.fields.unwrap_or_default(),
+        Err(e) => {
+            let error = Json(ErrorResponse::new(e.body_text()));
+            return (StatusCode::BAD_REQUEST, error).into_response();
+        }
+    };
+    let fields: Vec<&str> = fields
+        .split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .collect();
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if fields.is_empty() || !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let Ok(value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let trimmed = serde_json::to_vec(&select_fields(value, &fields)).unwrap_or_default();
+    Response::from_parts(parts, Body::from(trimmed))
+}
+
+// Keep only `fields` of a JSON object, of each item of a list, or of each item of lists
+// keyed by group (the region-grouped timezone list)
+pub fn select_fields(value: Value, fields: &[&str]) -> Value {
+    match value {
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| select_fields(item, fields))
+            .collect(),
+        Value::Object(map) if !map.is_empty() && map.values().all(Value::is_array) => map
+            .into_iter()
+            .map(|(group, items)| (group, select_fields(items, fields)))
+            .collect::<Map<_, _>>()
+            .into(),
+        Value::Object(map) => map
+            .into_iter()
+            .filter_map(|(key, value)| {
+                if fields.contains(&key.as_str()) {
+                    return Some((key, value));
+                }
+                let nested: Vec<&str> = fields
+                    .iter()
+                    .filter_map(|f| f.strip_prefix(key.as_str())?.strip_prefix('.'))
+                    .collect();
+                (!nested.is_empty()).then(|| (key, select_fields(value, &nested)))
+            })
+            .collect::<Map<_, _>>()
+            .into(),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select_fields() {
+        let info = json!({"timezone": "UTC", "utc_offset": "UTC+00:00", "is_dst": false});
+        assert_eq!(
+            select_fields(info, &["timezone", "is_dst"]),
+            json!({"timezone": "UTC", "is_dst": false})
+        );
+
+        let list = json!([{"name": "UTC", "display_name": "UTC"}]);
+        assert_eq!(select_fields(list, &["name"]), json!([{"name": "UTC"}]));
+        let grouped = json!({"Europe": [{"name": "Europe/Belgrade", "display_name": "Belgrade"}]});
+        assert_eq!(
+            select_fields(grouped, &["name"]),
+            json!({"Europe": [{"name": "Europe/Belgrade"}]})
+        );
+
+        let converted = json!({
+            "from": {"timezone": "UTC", "datetime": "2025-02-10T14:30:00+00:00"},
+            "to": {"timezone": "Asia/Tokyo", "datetime": "2025-02-10T23:30:00+09:00"},
+            "day_shift": 0
+        });
+        assert_eq!(
+            select_fields(converted, &["to.datetime", "day_shift", "missing"]),
+            json!({"to": {"datetime": "2025-02-10T23:30:00+09:00"}, "day_shift": 0})
+        );
+    }
+}
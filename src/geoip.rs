@@ -0,0 +1,162 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Where a caller is: IP geolocation from a MaxMind GeoIP2 or GeoLite2 City database, and
+// the region in their Accept-Language header
+
+use axum::extract::ConnectInfo;
+use axum::http::{header, Extensions, HeaderMap};
+use maxminddb::{geoip2, Reader};
+use std::net::{IpAddr, SocketAddr};
+
+use crate::zones;
+
+// The database is optional; without one every lookup misses
+pub struct GeoIp {
+    reader: Option<Reader<Vec<u8>>>,
+}
+
+// What the database knows about an address
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpLocation {
+    pub timezone: String,
+    pub country_code: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    // Radius in kilometres around the coordinates that the address is likely within
+    pub accuracy_km: Option<u16>,
+}
+
+impl GeoIp {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let reader = Reader::open_readfile(path)
+            .map_err(|e| format!("Cannot open GeoIP database {}: {}", path, e))?;
+        Ok(GeoIp {
+            reader: Some(reader),
+        })
+    }
+
+    pub fn disabled() -> Self {
+        GeoIp { reader: None }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.reader.is_some()
+    }
+
+    // Zone and position for an address; None when there is no database, the address is
+    // not in it, or its record has no zone (anycast and satellite ranges)
+    pub fn lookup(&self, ip: IpAddr) -> Option<IpLocation> {
+        let city: geoip2::City = self.reader.as_ref()?.lookup(ip).ok()?;
+        let location = city.location?;
+        Some(IpLocation {
+            timezone: location.time_zone?.to_string(),
+            country_code: city.country.and_then(|c| c.iso_code).map(str::to_string),
+            latitude: location.latitude,
+            longitude: location.longitude,
+            accuracy_km: location.accuracy_radius,
+        })
+    }
+}
+
+// The caller's address: the first X-Forwarded-For entry or X-Real-IP when a proxy in
+// front of the service sets them, else the connection's peer
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions) -> Option<IpAddr> {
+    let header_ip = |name: &str| {
+        let value = headers.get(name)?.to_str().ok()?;
+        value.split(',').next()?.trim().parse().ok()
+    };
+    header_ip("x-forwarded-for")
+        .or_else(|| header_ip("x-real-ip"))
+        .or_else(|| {
+            let ConnectInfo(addr) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+            Some(addr.ip())
+        })
+}
+
+// Principal zone of the first country named by a region subtag in Accept-Language, by
+// preference ("sr-Latn-RS" and "en-US;q=0.8" name RS and US). Bare languages are skipped:
+// "en" says nothing about where the caller is.
+pub fn accept_language_zone(headers: &HeaderMap) -> Option<&'static str> {
+    let value = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+    let mut tags: Vec<(f32, &str)> = value
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse().ok())?;
+            Some((quality, tag))
+        })
+        .collect();
+    // Stable, so equal weights keep the header's order
+    tags.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    tags.into_iter().find_map(|(_, tag)| {
+        let region = tag
+            .split('-')
+            .skip(1)
+            .find(|s| s.len() == 2 && s.chars().all(|c| c.is_ascii_alphabetic()))?;
+        let zone = zones::country_zones(region).into_iter().next()?;
+        Some(zone.name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let mut extensions = Extensions::new();
+        assert_eq!(client_ip(&HeaderMap::new(), &extensions), None);
+
+        extensions.insert(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))));
+        let peer = client_ip(&HeaderMap::new(), &extensions);
+        assert_eq!(peer, Some(IpAddr::from([192, 0, 2, 1])));
+
+        let forwarded = headers(&[("x-forwarded-for", "203.0.113.7, 10.0.0.1")]);
+        let ip = client_ip(&forwarded, &extensions);
+        assert_eq!(ip, Some(IpAddr::from([203, 0, 113, 7])));
+        let real_ip = headers(&[("x-real-ip", "2001:db8::1")]);
+        assert_eq!(client_ip(&real_ip, &extensions), "2001:db8::1".parse().ok());
+    }
+
+    #[test]
+    fn test_accept_language_zone() {
+        let zone =
+            |value: &'static str| accept_language_zone(&headers(&[("accept-language", value)]));
+        assert_eq!(zone("sr-Latn-RS,sr;q=0.9"), Some("Europe/Belgrade"));
+        assert_eq!(
+            zone("en;q=0.9, en-US;q=0.8, de-DE;q=0.85"),
+            Some("Europe/Berlin")
+        );
+        assert_eq!(zone("en-US"), Some("America/New_York"));
+        assert_eq!(zone("fr, de"), None);
+        assert_eq!(accept_language_zone(&HeaderMap::new()), None);
+        assert!(!GeoIp::disabled().is_enabled());
+        assert_eq!(GeoIp::disabled().lookup(IpAddr::from([8, 8, 8, 8])), None);
+    }
+}
@@ -14,14 +14,45 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::convert::Infallible;
+use std::time::Duration;
+
 use axum::{
-    extract::{Path, Query, State, rejection::QueryRejection},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Extension, Path, Query, State, rejection::QueryRejection},
+    http::{header, Extensions, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     Json,
 };
+use chrono::Utc;
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
 use crate::{
-    models::{ConvertRequest, ConvertResponse, ErrorResponse, GeolocationQuery, TimezoneInfo, TimezoneListItem},
+    auth::{
+        middleware::meter_request,
+        models::{AuthenticatedKey, Credential},
+    },
+    calendar, geoip, humanize, leap, planning, teams,
+    models::{
+        AbbreviationResponse, AddRequest, AddResponse, AgeQuery, AgeResponse, AirportInfo,
+        AutocompleteQuery, AutocompleteResult, BatchGeolocationRequest, BatchGeolocationResponse,
+        BoundaryFeature, BoundaryQuery, BulkTimeResponse, CalendarInfo, CalendarQuery,
+        ConvertRequest, ConvertResponse, CountryZones, CreateTeamRequest, DstAtQuery,
+        DstCalendarQuery, DstCountdown, DstQuery, DstStatus, DstZonesResponse, DumpQuery,
+        DurationQuery, EpochNow, ErrorResponse, GeoJsonInput, GeolocationQuery, HolidayQuery,
+        HolidaysResponse, HumanDurationResponse, IntervalQuery, IntervalResponse, IpInfo,
+        ItineraryRequest, ItineraryResponse, JetLagPlan, JetLagRequest, JulianDateResponse,
+        JulianQuery, LeapSecondsResponse, MatrixRequest, MatrixResponse, MeetingRequest,
+        MeetingSuggestions, OffsetsQuery, OffsetsResponse, ParseQuery, ParseResponse, Participant,
+        RecurrenceRequest, RecurrenceResponse, RelativeTimeQuery, RelativeTimeResponse, SunQuery,
+        SunTimes, Team, TeamMember, TeamMemberRequest, TimeQuery, TimeResponse, TimescaleQuery,
+        TimescaleResponse, TimezoneExport, TimezoneInfo, TimezoneSearchQuery, TimezoneSearchResult,
+        TimezonesQuery, TimezonesResponse, TzdataInfo, WeekInfo, WeekQuery, WindowsZoneMapping,
+        ZoneAlias, ZoneCountries, ZoneDump,
+    },
     service::EpochZoneService,
     AppState,
 };
@@ -29,8 +60,16 @@ use crate::{
 // Handler for getting timezone information
 pub async fn get_timezone_info(
     Path(timezone_name): Path<String>,
-) -> Result<Json<TimezoneInfo>, (StatusCode, Json<ErrorResponse>)> {
-    EpochZoneService::get_timezone_info(&timezone_name)
+    params: Result<Query<TimeQuery>, QueryRejection>,
+) -> Result<Json<TimeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_time(&timezone_name, &params)
         .map(Json)
         .map_err(|e| {
             (
@@ -40,17 +79,41 @@ pub async fn get_timezone_info(
         })
 }
 
-// Handler for getting list of all timezones
-pub async fn get_timezones() -> Json<Vec<TimezoneListItem>> {
-    let timezones = EpochZoneService::get_all_timezones();
-    Json(timezones)
+// Handler for the current time in the caller's own timezone
+pub async fn get_auto_timezone_info(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    extensions: Extensions,
+    params: Result<Query<TimeQuery>, QueryRejection>,
+) -> Result<Json<TimeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    let ip = geoip::client_ip(&headers, &extensions);
+    let language_zone = geoip::accept_language_zone(&headers);
+    EpochZoneService::get_auto_time(&state.geoip, ip, language_zone, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
 }
 
-// Handler for converting time between timezones
-pub async fn convert_timezone(
-    Json(payload): Json<ConvertRequest>,
-) -> Result<Json<ConvertResponse>, (StatusCode, Json<ErrorResponse>)> {
-    EpochZoneService::convert_timezone(&payload)
+// Handler for an IP address's timezone ("self" for the caller's)
+pub async fn get_ip_info(
+    State(state): State<AppState>,
+    Path(addr): Path<String>,
+    headers: HeaderMap,
+    extensions: Extensions,
+) -> Result<Json<IpInfo>, (StatusCode, Json<ErrorResponse>)> {
+    let caller = geoip::client_ip(&headers, &extensions);
+    EpochZoneService::get_ip_info(&state.geoip, &addr, caller)
         .map(Json)
         .map_err(|e| {
             (
@@ -60,11 +123,129 @@ pub async fn convert_timezone(
         })
 }
 
-// Handler for getting timezone by geographic coordinates
-pub async fn get_timezone_by_coordinates(
+// Handler for the current time in several timezones (?tz=Europe/Belgrade&tz=Asia/Tokyo)
+pub async fn get_current_times(
     State(state): State<AppState>,
-    params: Result<Query<GeolocationQuery>, QueryRejection>,
-) -> Result<Json<TimezoneInfo>, (StatusCode, Json<ErrorResponse>)> {
+    Extension(key): Extension<AuthenticatedKey>,
+    params: Result<Query<Vec<(String, String)>>, QueryRejection>,
+) -> Result<Json<BulkTimeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+    let team = params.iter().find(|(key, _)| key == "team").map(|(_, value)| value.clone());
+    let mut timezones: Vec<String> = params
+        .into_iter()
+        .filter(|(key, _)| key == "tz")
+        .map(|(_, value)| value)
+        .collect();
+    // A team's members follow the zones asked for by name
+    let mut members = Vec::new();
+    if let Some(team) = team {
+        for member in find_team(&state, &key, &team).await?.members {
+            timezones.push(member.timezone);
+            members.push(member.name);
+        }
+    }
+
+    let mut response = EpochZoneService::get_current_times(&timezones).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+    let first_member = response.results.len() - members.len();
+    for (result, member) in response.results[first_member..].iter_mut().zip(members) {
+        result.member = Some(member);
+    }
+    Ok(Json(response))
+}
+
+// Bounds on the seconds between ticks of /api/stream/time
+const MIN_STREAM_INTERVAL_SECS: u64 = 1;
+const MAX_STREAM_INTERVAL_SECS: u64 = 3600;
+
+// Handler streaming the current time in several timezones as Server-Sent Events: a `tick`
+// event right away and every `interval` seconds after (?tz=UTC&tz=Asia/Tokyo&interval=5).
+// Every tick after the first, which the request itself paid for, is metered like a request
+// of its own; when one is refused the stream ends with an `error` event saying why.
+pub async fn stream_current_times(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    params: Result<Query<Vec<(String, String)>>, QueryRejection>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, Json<ErrorResponse>)>
+{
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    let Query(params) = params.map_err(|e| bad_request(e.body_text()))?;
+    let mut interval = MIN_STREAM_INTERVAL_SECS;
+    let mut timezones = Vec::new();
+    for (key, value) in params {
+        match key.as_str() {
+            "tz" => timezones.push(value),
+            "interval" => {
+                let allowed = MIN_STREAM_INTERVAL_SECS..=MAX_STREAM_INTERVAL_SECS;
+                interval = value
+                    .parse()
+                    .ok()
+                    .filter(|secs| allowed.contains(secs))
+                    .ok_or_else(|| {
+                        bad_request(format!(
+                            "'interval' must be between {} and {} seconds",
+                            MIN_STREAM_INTERVAL_SECS, MAX_STREAM_INTERVAL_SECS
+                        ))
+                    })?;
+            }
+            _ => {}
+        }
+    }
+    // Checked before the stream starts, so a bad request gets a plain error
+    EpochZoneService::get_current_times(&timezones).map_err(bad_request)?;
+
+    let mut ticks = tokio::time::interval(Duration::from_secs(interval));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let (tx, rx) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let mut first = true;
+        loop {
+            // Stop as soon as the client goes away, not at its next tick
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = tx.closed() => return,
+            }
+            if !std::mem::take(&mut first) {
+                let now = Utc::now();
+                if let Err((_, _, message)) = meter_request(&state, &key, now).await {
+                    let _ = tx.send(Ok(event_error(message))).await;
+                    return;
+                }
+                if key.credential != Credential::BearerToken {
+                    state.usage.record(&key.id, "/api/stream/time", false, now);
+                }
+            }
+            let event = Event::default().event("tick");
+            // get_current_times only fails for the zone counts checked above
+            let event = match EpochZoneService::get_current_times(&timezones) {
+                Ok(times) => event.json_data(times).unwrap_or_else(|e| event_error(e.to_string())),
+                Err(e) => event_error(e),
+            };
+            if tx.send(Ok(event)).await.is_err() {
+                return;
+            }
+        }
+    });
+    Ok(Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default()))
+}
+
+fn event_error(message: String) -> Event {
+    Event::default().event("error").data(message)
+}
+
+// Handler for getting list of all timezones, optionally grouped or enriched
+pub async fn get_timezones(
+    params: Result<Query<TimezonesQuery>, QueryRejection>,
+) -> Result<Json<TimezonesResponse>, (StatusCode, Json<ErrorResponse>)> {
     let Query(params) = params.map_err(|e| {
         (
             StatusCode::BAD_REQUEST,
@@ -72,7 +253,7 @@ pub async fn get_timezone_by_coordinates(
         )
     })?;
 
-    EpochZoneService::get_timezone_by_coordinates(&state.tz_finder, params.lat, params.lng)
+    EpochZoneService::list_timezones(&params)
         .map(Json)
         .map_err(|e| {
             (
@@ -82,13 +263,957 @@ pub async fn get_timezone_by_coordinates(
         })
 }
 
-// Health check handler
-pub async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "service": "timezone-service",
-        "version": env!("CARGO_PKG_VERSION")
-    }))
+// Handler for abbreviation lookup
+pub async fn lookup_abbreviation(
+    Path(abbreviation): Path<String>,
+) -> Result<Json<AbbreviationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::lookup_abbreviation(&abbreviation)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a zone's transition dump
+pub async fn dump_transitions(
+    Path(timezone_name): Path<String>,
+    params: Result<Query<DumpQuery>, QueryRejection>,
+) -> Result<Json<ZoneDump>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::dump_transitions(&timezone_name, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a zone's DST status at an instant
+pub async fn get_dst_status(
+    Path(timezone_name): Path<String>,
+    params: Result<Query<DstAtQuery>, QueryRejection>,
+) -> Result<Json<DstStatus>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_dst_status(&timezone_name, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the countdown to a zone's next DST change
+pub async fn get_next_dst_change(
+    Path(timezone_name): Path<String>,
+    params: Result<Query<DstQuery>, QueryRejection>,
+) -> Result<Json<DstCountdown>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_next_dst_change(&timezone_name, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a zone's compiled TZif file
+pub async fn get_tzif(
+    Path(timezone_name): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::get_tzif(&timezone_name)
+        .map(|data| ([(header::CONTENT_TYPE, "application/tzif")], data))
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a zone's clock-change calendar feed
+pub async fn get_dst_calendar(
+    Path(timezone_name): Path<String>,
+    params: Result<Query<DstCalendarQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_dst_calendar(&timezone_name, &params)
+        .map(|ics| ([(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], ics))
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the Rails friendly-name table
+pub async fn get_rails_names() -> Json<Vec<ZoneAlias>> {
+    Json(EpochZoneService::get_rails_names())
+}
+
+// Handler for Windows timezone ID to IANA lookup
+pub async fn windows_to_iana(
+    Path(windows_id): Path<String>,
+) -> Result<Json<WindowsZoneMapping>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::windows_to_iana(&windows_id)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for IANA to Windows timezone ID lookup
+pub async fn iana_to_windows(
+    Path(timezone_name): Path<String>,
+) -> Result<Json<WindowsZoneMapping>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::iana_to_windows(&timezone_name)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the legacy alias table
+pub async fn get_aliases() -> Json<Vec<ZoneAlias>> {
+    Json(EpochZoneService::get_aliases())
+}
+
+// Handler for timezone search
+pub async fn search_timezones(
+    params: Result<Query<TimezoneSearchQuery>, QueryRejection>,
+) -> Result<Json<Vec<TimezoneSearchResult>>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::search_timezones(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for fuzzy timezone autocomplete
+pub async fn autocomplete_timezones(
+    State(state): State<AppState>,
+    params: Result<Query<AutocompleteQuery>, QueryRejection>,
+) -> Result<Json<Vec<AutocompleteResult>>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::autocomplete(&state.search_index, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for zones currently observing DST
+pub async fn get_dst_zones(
+    params: Result<Query<DstQuery>, QueryRejection>,
+) -> Result<Json<DstZonesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_dst_zones(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the directory of UTC offsets in use
+pub async fn get_offsets(
+    params: Result<Query<OffsetsQuery>, QueryRejection>,
+) -> Result<Json<OffsetsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_offsets(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for ISO week information in a timezone
+pub async fn get_week_info(
+    Path(timezone_name): Path<String>,
+    params: Result<Query<WeekQuery>, QueryRejection>,
+) -> Result<Json<WeekInfo>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_week_info(&timezone_name, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a country's public holidays
+pub async fn get_holidays(
+    Path(country): Path<String>,
+    params: Result<Query<HolidayQuery>, QueryRejection>,
+) -> Result<Json<HolidaysResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_holidays(&country, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the server's current epoch
+pub async fn get_epoch_now(State(state): State<AppState>) -> Json<EpochNow> {
+    Json(EpochZoneService::get_epoch_now(&state.clock))
+}
+
+// Handler for converting time between timezones
+pub async fn convert_timezone(
+    Json(payload): Json<ConvertRequest>,
+) -> Result<Json<ConvertResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::convert_timezone(&payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the timezone offset difference matrix
+pub async fn get_offset_matrix(
+    Json(payload): Json<MatrixRequest>,
+) -> Result<Json<MatrixResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::get_offset_matrix(&payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for DST-aware date arithmetic
+pub async fn add_duration(
+    Json(payload): Json<AddRequest>,
+) -> Result<Json<AddResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::add_duration(&payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for RRULE recurrence expansion
+pub async fn expand_recurrence(
+    Json(payload): Json<RecurrenceRequest>,
+) -> Result<Json<RecurrenceResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::expand_recurrence(&payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for meeting slot suggestions
+pub async fn suggest_meetings(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Json(mut payload): Json<MeetingRequest>,
+) -> Result<Json<MeetingSuggestions>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(team) = &payload.team {
+        let members = find_team(&state, &key, team).await?.members;
+        payload.participants.extend(members.into_iter().map(Participant::from));
+    }
+    planning::suggest_meetings(&payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for getting timezone by geographic coordinates
+pub async fn get_timezone_by_coordinates(
+    State(state): State<AppState>,
+    params: Result<Query<GeolocationQuery>, QueryRejection>,
+) -> Result<Json<TimezoneInfo>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    let (lat, lng) =
+        EpochZoneService::validate_coordinates(params.lat, params.lng, params.normalize)
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse::with_code(e.code(), e.to_string())),
+                )
+            })?;
+
+    EpochZoneService::get_timezone_by_coordinates(&state.tz_finder, lat, lng)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for getting timezone by a GeoJSON Point or Point Feature
+pub async fn get_timezone_by_geojson(
+    State(state): State<AppState>,
+    Json(payload): Json<GeoJsonInput>,
+) -> Result<Json<TimezoneInfo>, (StatusCode, Json<ErrorResponse>)> {
+    let (lat, lng) = EpochZoneService::geojson_coordinates(&payload).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e)),
+        )
+    })?;
+    let (lat, lng) = EpochZoneService::validate_coordinates(lat, lng, false).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::with_code(e.code(), e.to_string())),
+        )
+    })?;
+
+    EpochZoneService::get_timezone_by_coordinates(&state.tz_finder, lat, lng)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for resolving the zones at a batch of coordinates
+pub async fn geolocate_batch(
+    State(state): State<AppState>,
+    Json(payload): Json<BatchGeolocationRequest>,
+) -> Result<Json<BatchGeolocationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::geolocate_batch(&state.tz_finder, &payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the zones used in a country
+pub async fn get_country_zones(
+    Path(country): Path<String>,
+) -> Result<Json<CountryZones>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::get_country_zones(&country)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a zone's boundary polygon as GeoJSON
+pub async fn get_boundary(
+    Path(timezone_name): Path<String>,
+    params: Result<Query<BoundaryQuery>, QueryRejection>,
+) -> Result<Json<BoundaryFeature>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_boundary(&timezone_name, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for local times at both ends of a trip
+pub async fn plan_itinerary(
+    State(state): State<AppState>,
+    Json(payload): Json<ItineraryRequest>,
+) -> Result<Json<ItineraryResponse>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::plan_itinerary(&state.tz_finder, &payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for a jet lag adjustment schedule
+pub async fn plan_jet_lag(
+    State(state): State<AppState>,
+    Json(payload): Json<JetLagRequest>,
+) -> Result<Json<JetLagPlan>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::plan_jet_lag(&state.tz_finder, &payload)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for sunrise and sunset at a location
+pub async fn get_sun_times(
+    State(state): State<AppState>,
+    params: Result<Query<SunQuery>, QueryRejection>,
+) -> Result<Json<SunTimes>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_sun_times(&state.tz_finder, &params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the countries using a zone
+pub async fn get_zone_countries(
+    Path(timezone_name): Path<String>,
+) -> Result<Json<ZoneCountries>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::get_zone_countries(&timezone_name)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for looking up an airport's timezone by IATA or ICAO code
+pub async fn get_airport(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> Result<Json<AirportInfo>, (StatusCode, Json<ErrorResponse>)> {
+    EpochZoneService::get_airport(&state.tz_finder, &code)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the full timezone database export. Building it scans every zone's
+// transitions, so it runs off the async runtime.
+pub async fn export_timezones() -> Result<Json<TimezoneExport>, (StatusCode, Json<ErrorResponse>)> {
+    tokio::task::spawn_blocking(EpochZoneService::export_timezones)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(format!("Export failed: {}", e))),
+            )
+        })
+}
+
+// Handler for the compiled-in tzdata and boundary data versions
+pub async fn get_tzdata_info(State(state): State<AppState>) -> Json<TzdataInfo> {
+    Json(EpochZoneService::get_tzdata_info(&state.tz_finder))
+}
+
+// Handler for the leap second table
+pub async fn get_leap_seconds() -> Json<LeapSecondsResponse> {
+    Json(EpochZoneService::get_leap_seconds())
+}
+
+// Handler for month calendar lookups
+pub async fn get_calendar_info(
+    params: Result<Query<CalendarQuery>, QueryRejection>,
+) -> Result<Json<CalendarInfo>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_calendar_info(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for ISO 8601 interval resolution
+pub async fn get_interval(
+    params: Result<Query<IntervalQuery>, QueryRejection>,
+) -> Result<Json<IntervalResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_interval(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for the age calculator
+pub async fn get_age(
+    params: Result<Query<AgeQuery>, QueryRejection>,
+) -> Result<Json<AgeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::get_age(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for UTC, TAI, and GPS time conversion
+pub async fn convert_timescales(
+    params: Result<Query<TimescaleQuery>, QueryRejection>,
+) -> Result<Json<TimescaleResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    leap::convert_timescales(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for parsing a datetime in any supported format
+pub async fn parse_datetime(
+    params: Result<Query<ParseQuery>, QueryRejection>,
+) -> Result<Json<ParseResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    EpochZoneService::parse_datetime(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for Julian Day, MJD, and ordinal date conversion
+pub async fn convert_julian(
+    params: Result<Query<JulianQuery>, QueryRejection>,
+) -> Result<Json<JulianDateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    calendar::convert_julian(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for humanized relative time
+pub async fn relative_time(
+    params: Result<Query<RelativeTimeQuery>, QueryRejection>,
+) -> Result<Json<RelativeTimeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    humanize::relative_time(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for humanized durations
+pub async fn humanize_duration(
+    params: Result<Query<DurationQuery>, QueryRejection>,
+) -> Result<Json<HumanDurationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let Query(params) = params.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(e.body_text())),
+        )
+    })?;
+
+    humanize::humanize_duration(&params)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Health check handler
+pub async fn health_check() -> impl IntoResponse {
+    Json(serde_json::json!({
+        "status": "healthy",
+        "service": "timezone-service",
+        "version": env!("CARGO_PKG_VERSION")
+    }))
+}
+
+// A saved team of the calling key's organization, or of the key itself
+async fn find_team(
+    state: &AppState,
+    key: &AuthenticatedKey,
+    name: &str,
+) -> Result<Team, (StatusCode, Json<ErrorResponse>)> {
+    teams::get_team(&state.db, teams::owner(key), name.to_string())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse::new(format!("Team '{}' not found", name))),
+            )
+        })
+}
+
+// Handler for saving a team, with any first members
+pub async fn create_team(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Json(payload): Json<CreateTeamRequest>,
+) -> Result<(StatusCode, Json<Team>), (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    teams::validate_team_name(&payload.name).map_err(bad_request)?;
+    if payload.members.len() > teams::MAX_MEMBERS {
+        return Err(bad_request(format!(
+            "At most {} members per team",
+            teams::MAX_MEMBERS
+        )));
+    }
+    let members = payload
+        .members
+        .into_iter()
+        .enumerate()
+        .map(|(i, member)| {
+            teams::normalize_member(member).map_err(|e| format!("Member {}: {}", i, e))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(bad_request)?;
+
+    let internal_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(e)),
+        )
+    };
+    let owner = teams::owner(&key);
+    let saved = teams::team_count(&state.db, owner.clone()).await.map_err(internal_error)?;
+    if saved >= teams::MAX_TEAMS {
+        return Err(bad_request(format!("At most {} teams may be saved", teams::MAX_TEAMS)));
+    }
+    let team = teams::create_team(&state.db, owner, payload.name.clone(), members)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse::new(format!("Team '{}' already exists", payload.name))),
+            )
+        })?;
+    Ok((StatusCode::CREATED, Json(team)))
+}
+
+// Handler for listing saved teams by name
+pub async fn list_teams(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+) -> Result<Json<Vec<Team>>, (StatusCode, Json<ErrorResponse>)> {
+    teams::list_teams(&state.db, teams::owner(&key))
+        .await
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for one saved team
+pub async fn get_team(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Path(team): Path<String>,
+) -> Result<Json<Team>, (StatusCode, Json<ErrorResponse>)> {
+    find_team(&state, &key, &team).await.map(Json)
+}
+
+// Handler for deleting a team and its members
+pub async fn delete_team(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Path(team): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let deleted = teams::delete_team(&state.db, teams::owner(&key), team.clone())
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!("Team '{}' not found", team))),
+        ))
+    }
+}
+
+// Handler for adding a member to a team
+pub async fn add_team_member(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Path(team): Path<String>,
+    Json(payload): Json<TeamMemberRequest>,
+) -> Result<(StatusCode, Json<TeamMember>), (StatusCode, Json<ErrorResponse>)> {
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    let member = teams::normalize_member(payload).map_err(bad_request)?;
+    let team = find_team(&state, &key, &team).await?;
+    if team.members.len() >= teams::MAX_MEMBERS {
+        return Err(bad_request(format!(
+            "At most {} members per team",
+            teams::MAX_MEMBERS
+        )));
+    }
+
+    teams::add_member(&state.db, team.id, member)
+        .await
+        .map(|member| (StatusCode::CREATED, Json(member)))
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })
+}
+
+// Handler for replacing a team member's details
+pub async fn update_team_member(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Path((team, member_id)): Path<(String, String)>,
+    Json(payload): Json<TeamMemberRequest>,
+) -> Result<Json<TeamMember>, (StatusCode, Json<ErrorResponse>)> {
+    let member = teams::normalize_member(payload)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+    let team = find_team(&state, &key, &team).await?;
+
+    teams::update_member(&state.db, team.id, member_id, member)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Team member not found")),
+        ))
+}
+
+// Handler for removing a member from a team
+pub async fn delete_team_member(
+    State(state): State<AppState>,
+    Extension(key): Extension<AuthenticatedKey>,
+    Path((team, member_id)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let team = find_team(&state, &key, &team).await?;
+    let deleted = teams::delete_member(&state.db, team.id, member_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(e)),
+            )
+        })?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Team member not found")),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -104,25 +1229,29 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_timezones() {
-        let Json(timezones) = get_timezones().await;
-        assert!(!timezones.is_empty());
+        let params = Ok(Query(TimezonesQuery::default()));
+        let Json(timezones) = get_timezones(params).await.unwrap();
+        assert!(matches!(timezones, TimezonesResponse::List(list) if !list.is_empty()));
     }
 
     #[tokio::test]
     async fn test_get_timezone_info_success() {
-        let result = get_timezone_info(Path("UTC".to_string())).await;
+        let params = Ok(Query(TimeQuery::default()));
+        let result = get_timezone_info(Path("UTC".to_string()), params).await;
         assert!(result.is_ok());
     }
     
     #[tokio::test]
     async fn test_get_timezone_info_success_belgrade() {
-        let result = get_timezone_info(Path("Europe/Belgrade".to_string())).await;
+        let params = Ok(Query(TimeQuery::default()));
+        let result = get_timezone_info(Path("Europe/Belgrade".to_string()), params).await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_get_timezone_info_failure() {
-        let result = get_timezone_info(Path("Invalid/Zone".to_string())).await;
+        let params = Ok(Query(TimeQuery::default()));
+        let result = get_timezone_info(Path("Invalid/Zone".to_string()), params).await;
         assert!(result.is_err());
 
         if let Err((status, _)) = result {
@@ -137,6 +1266,7 @@ mod tests {
             datetime: None,
             from: None,
             to: "America/New_York".to_string(),
+            ..Default::default()
         };
         let result = convert_timezone(Json(payload)).await;
         assert!(result.is_ok());
@@ -149,6 +1279,7 @@ mod tests {
             datetime: None,
             from: None,
             to: "America/New_York".to_string(),
+            ..Default::default()
         };
         let result = convert_timezone(Json(payload)).await;
         assert!(result.is_err());
@@ -0,0 +1,462 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+// Years the embedded rules are served for. The rules describe current law, so
+// results far from the present are approximations.
+pub const MIN_HOLIDAY_YEAR: i32 = 1970;
+pub const MAX_HOLIDAY_YEAR: i32 = 2100;
+
+// How a holiday's date is derived each year
+enum Rule {
+    Fixed(u32, u32),
+    // nth weekday of a month; negative n counts from the end (-1 = last)
+    NthWeekday(u32, Weekday, i32),
+    // Last given weekday on or before a day, e.g. Victoria Day: Monday on or before May 24
+    WeekdayOnOrBefore(u32, u32, Weekday),
+    // Days relative to Western Easter Sunday
+    Easter(i64),
+    // Days relative to Orthodox Easter Sunday
+    OrthodoxEaster(i64),
+}
+
+struct HolidayRule {
+    name: &'static str,
+    local_name: &'static str,
+    rule: Rule,
+    // First year the holiday was observed
+    since: i32,
+    // Whether a weekend date gives a substitute day off
+    substitutable: bool,
+}
+
+// How substitute days are granted when a holiday falls on a weekend
+#[derive(Clone, Copy)]
+enum Observance {
+    None,
+    // Saturday moves to Friday, Sunday to Monday (US federal)
+    NearestWeekday,
+    // Saturday or Sunday moves to the next free weekday (UK bank holidays)
+    NextWeekday,
+    // Only Sunday moves, to the next free day (Serbia)
+    SundayToNextDay,
+}
+
+pub struct CountryCalendar {
+    pub code: &'static str,
+    pub name: &'static str,
+    // Zone used for day boundaries when the request does not name one
+    pub timezone: &'static str,
+    observance: Observance,
+    rules: &'static [HolidayRule],
+}
+
+// One holiday in a given year
+#[derive(Debug, Clone)]
+pub struct HolidayDate {
+    pub name: &'static str,
+    pub local_name: &'static str,
+    pub date: NaiveDate,
+    // Substitute day off when it differs from `date`
+    pub observed: Option<NaiveDate>,
+}
+
+const fn holiday(name: &'static str, local_name: &'static str, rule: Rule) -> HolidayRule {
+    HolidayRule {
+        name,
+        local_name,
+        rule,
+        since: MIN_HOLIDAY_YEAR,
+        substitutable: true,
+    }
+}
+
+const fn since(mut holiday: HolidayRule, year: i32) -> HolidayRule {
+    holiday.since = year;
+    holiday
+}
+
+const fn fixed_date(mut holiday: HolidayRule) -> HolidayRule {
+    holiday.substitutable = false;
+    holiday
+}
+
+const COUNTRIES: &[CountryCalendar] = &[
+    CountryCalendar {
+        code: "CA",
+        name: "Canada",
+        timezone: "America/Toronto",
+        observance: Observance::NextWeekday,
+        rules: &[
+            holiday("New Year's Day", "New Year's Day", Rule::Fixed(1, 1)),
+            holiday("Good Friday", "Good Friday", Rule::Easter(-2)),
+            holiday(
+                "Victoria Day",
+                "Victoria Day",
+                Rule::WeekdayOnOrBefore(5, 24, Weekday::Mon),
+            ),
+            holiday("Canada Day", "Canada Day", Rule::Fixed(7, 1)),
+            holiday("Labour Day", "Labour Day", Rule::NthWeekday(9, Weekday::Mon, 1)),
+            since(
+                holiday(
+                    "National Day for Truth and Reconciliation",
+                    "National Day for Truth and Reconciliation",
+                    Rule::Fixed(9, 30),
+                ),
+                2021,
+            ),
+            holiday("Thanksgiving", "Thanksgiving", Rule::NthWeekday(10, Weekday::Mon, 2)),
+            holiday("Remembrance Day", "Remembrance Day", Rule::Fixed(11, 11)),
+            holiday("Christmas Day", "Christmas Day", Rule::Fixed(12, 25)),
+            holiday("Boxing Day", "Boxing Day", Rule::Fixed(12, 26)),
+        ],
+    },
+    CountryCalendar {
+        code: "DE",
+        name: "Germany",
+        timezone: "Europe/Berlin",
+        observance: Observance::None,
+        rules: &[
+            holiday("New Year's Day", "Neujahr", Rule::Fixed(1, 1)),
+            holiday("Good Friday", "Karfreitag", Rule::Easter(-2)),
+            holiday("Easter Monday", "Ostermontag", Rule::Easter(1)),
+            holiday("Labour Day", "Tag der Arbeit", Rule::Fixed(5, 1)),
+            holiday("Ascension Day", "Christi Himmelfahrt", Rule::Easter(39)),
+            holiday("Whit Monday", "Pfingstmontag", Rule::Easter(50)),
+            since(
+                holiday("German Unity Day", "Tag der Deutschen Einheit", Rule::Fixed(10, 3)),
+                1990,
+            ),
+            holiday("Christmas Day", "1. Weihnachtstag", Rule::Fixed(12, 25)),
+            holiday("St. Stephen's Day", "2. Weihnachtstag", Rule::Fixed(12, 26)),
+        ],
+    },
+    CountryCalendar {
+        code: "FR",
+        name: "France",
+        timezone: "Europe/Paris",
+        observance: Observance::None,
+        rules: &[
+            holiday("New Year's Day", "Jour de l'an", Rule::Fixed(1, 1)),
+            holiday("Easter Monday", "Lundi de Pâques", Rule::Easter(1)),
+            holiday("Labour Day", "Fête du Travail", Rule::Fixed(5, 1)),
+            holiday("Victory in Europe Day", "Victoire 1945", Rule::Fixed(5, 8)),
+            holiday("Ascension Day", "Ascension", Rule::Easter(39)),
+            holiday("Whit Monday", "Lundi de Pentecôte", Rule::Easter(50)),
+            holiday("Bastille Day", "Fête nationale", Rule::Fixed(7, 14)),
+            holiday("Assumption Day", "Assomption", Rule::Fixed(8, 15)),
+            holiday("All Saints' Day", "Toussaint", Rule::Fixed(11, 1)),
+            holiday("Armistice Day", "Armistice 1918", Rule::Fixed(11, 11)),
+            holiday("Christmas Day", "Noël", Rule::Fixed(12, 25)),
+        ],
+    },
+    CountryCalendar {
+        code: "GB",
+        name: "United Kingdom (England and Wales)",
+        timezone: "Europe/London",
+        observance: Observance::NextWeekday,
+        rules: &[
+            holiday("New Year's Day", "New Year's Day", Rule::Fixed(1, 1)),
+            holiday("Good Friday", "Good Friday", Rule::Easter(-2)),
+            holiday("Easter Monday", "Easter Monday", Rule::Easter(1)),
+            since(
+                holiday(
+                    "Early May Bank Holiday",
+                    "Early May Bank Holiday",
+                    Rule::NthWeekday(5, Weekday::Mon, 1),
+                ),
+                1978,
+            ),
+            holiday(
+                "Spring Bank Holiday",
+                "Spring Bank Holiday",
+                Rule::NthWeekday(5, Weekday::Mon, -1),
+            ),
+            holiday(
+                "Summer Bank Holiday",
+                "Summer Bank Holiday",
+                Rule::NthWeekday(8, Weekday::Mon, -1),
+            ),
+            holiday("Christmas Day", "Christmas Day", Rule::Fixed(12, 25)),
+            holiday("Boxing Day", "Boxing Day", Rule::Fixed(12, 26)),
+        ],
+    },
+    CountryCalendar {
+        code: "RS",
+        name: "Serbia",
+        timezone: "Europe/Belgrade",
+        observance: Observance::SundayToNextDay,
+        rules: &[
+            holiday("New Year's Day", "Nova godina", Rule::Fixed(1, 1)),
+            holiday("New Year's Day (second day)", "Nova godina", Rule::Fixed(1, 2)),
+            fixed_date(holiday("Orthodox Christmas", "Božić", Rule::Fixed(1, 7))),
+            holiday("Statehood Day", "Dan državnosti Srbije", Rule::Fixed(2, 15)),
+            holiday("Statehood Day (second day)", "Dan državnosti Srbije", Rule::Fixed(2, 16)),
+            fixed_date(holiday("Orthodox Good Friday", "Veliki petak", Rule::OrthodoxEaster(-2))),
+            fixed_date(holiday("Orthodox Easter", "Vaskrs", Rule::OrthodoxEaster(0))),
+            fixed_date(holiday(
+                "Orthodox Easter Monday",
+                "Vaskršnji ponedeljak",
+                Rule::OrthodoxEaster(1),
+            )),
+            holiday("Labour Day", "Praznik rada", Rule::Fixed(5, 1)),
+            holiday("Labour Day (second day)", "Praznik rada", Rule::Fixed(5, 2)),
+            holiday("Armistice Day", "Dan primirja u Prvom svetskom ratu", Rule::Fixed(11, 11)),
+        ],
+    },
+    CountryCalendar {
+        code: "US",
+        name: "United States (federal)",
+        timezone: "America/New_York",
+        observance: Observance::NearestWeekday,
+        rules: &[
+            holiday("New Year's Day", "New Year's Day", Rule::Fixed(1, 1)),
+            since(
+                holiday(
+                    "Martin Luther King Jr. Day",
+                    "Martin Luther King Jr. Day",
+                    Rule::NthWeekday(1, Weekday::Mon, 3),
+                ),
+                1986,
+            ),
+            holiday(
+                "Washington's Birthday",
+                "Washington's Birthday",
+                Rule::NthWeekday(2, Weekday::Mon, 3),
+            ),
+            holiday("Memorial Day", "Memorial Day", Rule::NthWeekday(5, Weekday::Mon, -1)),
+            since(
+                holiday(
+                    "Juneteenth National Independence Day",
+                    "Juneteenth National Independence Day",
+                    Rule::Fixed(6, 19),
+                ),
+                2021,
+            ),
+            holiday("Independence Day", "Independence Day", Rule::Fixed(7, 4)),
+            holiday("Labor Day", "Labor Day", Rule::NthWeekday(9, Weekday::Mon, 1)),
+            holiday("Columbus Day", "Columbus Day", Rule::NthWeekday(10, Weekday::Mon, 2)),
+            holiday("Veterans Day", "Veterans Day", Rule::Fixed(11, 11)),
+            holiday("Thanksgiving Day", "Thanksgiving Day", Rule::NthWeekday(11, Weekday::Thu, 4)),
+            holiday("Christmas Day", "Christmas Day", Rule::Fixed(12, 25)),
+        ],
+    },
+];
+
+// Look up a country by ISO 3166-1 alpha-2 code (case-insensitive)
+pub fn find_country(code: &str) -> Result<&'static CountryCalendar, String> {
+    COUNTRIES
+        .iter()
+        .find(|c| c.code.eq_ignore_ascii_case(code))
+        .ok_or_else(|| {
+            let supported: Vec<&str> = COUNTRIES.iter().map(|c| c.code).collect();
+            format!(
+                "Unsupported country: {} (supported: {})",
+                code,
+                supported.join(", ")
+            )
+        })
+}
+
+impl CountryCalendar {
+    // Holidays of a year in date order, with substitute days applied
+    pub fn holidays(&self, year: i32) -> Result<Vec<HolidayDate>, String> {
+        if !(MIN_HOLIDAY_YEAR..=MAX_HOLIDAY_YEAR).contains(&year) {
+            return Err(format!(
+                "Year {} is outside the supported range ({}-{})",
+                year, MIN_HOLIDAY_YEAR, MAX_HOLIDAY_YEAR
+            ));
+        }
+
+        let mut dated: Vec<(&HolidayRule, NaiveDate)> = self
+            .rules
+            .iter()
+            .filter(|h| year >= h.since)
+            .filter_map(|h| Some((h, rule_date(&h.rule, year)?)))
+            .collect();
+        dated.sort_by_key(|(_, date)| *date);
+
+        // Substitute days must not land on another holiday or an earlier substitute
+        let mut taken: Vec<NaiveDate> = dated.iter().map(|(_, date)| *date).collect();
+        let holidays = dated
+            .into_iter()
+            .map(|(rule, date)| {
+                let observed = if rule.substitutable {
+                    substitute_day(self.observance, date, &taken)
+                } else {
+                    None
+                };
+                taken.extend(observed);
+                HolidayDate {
+                    name: rule.name,
+                    local_name: rule.local_name,
+                    date,
+                    observed,
+                }
+            })
+            .collect();
+        Ok(holidays)
+    }
+}
+
+fn rule_date(rule: &Rule, year: i32) -> Option<NaiveDate> {
+    match *rule {
+        Rule::Fixed(month, day) => NaiveDate::from_ymd_opt(year, month, day),
+        Rule::NthWeekday(month, weekday, n) if n > 0 => {
+            NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+        }
+        Rule::NthWeekday(month, weekday, n) => {
+            // Count back from the last occurrence in the month
+            let last = (1..=5)
+                .rev()
+                .find_map(|k| NaiveDate::from_weekday_of_month_opt(year, month, weekday, k))?;
+            last.checked_sub_signed(Duration::weeks((-n - 1) as i64))
+                .filter(|d| d.month() == month)
+        }
+        Rule::WeekdayOnOrBefore(month, day, weekday) => {
+            let date = NaiveDate::from_ymd_opt(year, month, day)?;
+            let back = date.weekday().days_since(weekday);
+            date.checked_sub_signed(Duration::days(back as i64))
+        }
+        Rule::Easter(offset) => {
+            western_easter(year)?.checked_add_signed(Duration::days(offset))
+        }
+        Rule::OrthodoxEaster(offset) => {
+            orthodox_easter(year)?.checked_add_signed(Duration::days(offset))
+        }
+    }
+}
+
+fn substitute_day(
+    observance: Observance,
+    date: NaiveDate,
+    taken: &[NaiveDate],
+) -> Option<NaiveDate> {
+    let weekday = date.weekday();
+    let is_free = |d: &NaiveDate| !taken.contains(d);
+    match observance {
+        Observance::None => None,
+        Observance::NearestWeekday => match weekday {
+            Weekday::Sat => date.pred_opt(),
+            Weekday::Sun => date.succ_opt(),
+            _ => None,
+        },
+        Observance::NextWeekday if matches!(weekday, Weekday::Sat | Weekday::Sun) => date
+            .iter_days()
+            .skip(1)
+            .find(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun) && is_free(d)),
+        Observance::SundayToNextDay if weekday == Weekday::Sun => date
+            .iter_days()
+            .skip(1)
+            .find(|d| d.weekday() != Weekday::Sun && is_free(d)),
+        _ => None,
+    }
+}
+
+// Gregorian Easter Sunday (anonymous Gregorian algorithm)
+fn western_easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+// Orthodox Easter Sunday: the Julian computus (Meeus), shifted onto the Gregorian calendar
+fn orthodox_easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 4;
+    let b = year % 7;
+    let c = year % 19;
+    let d = (19 * c + 15) % 30;
+    let e = (2 * a + 4 * b - d + 34) % 7;
+    let month = (d + e + 114) / 31;
+    let day = (d + e + 114) % 31 + 1;
+    let julian_gap = year / 100 - year / 400 - 2;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)?
+        .checked_add_signed(Duration::days(julian_gap as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn find<'a>(holidays: &'a [HolidayDate], name: &str) -> &'a HolidayDate {
+        holidays.iter().find(|h| h.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_easter_dates() {
+        assert_eq!(western_easter(2024), Some(date(2024, 3, 31)));
+        assert_eq!(western_easter(2025), Some(date(2025, 4, 20)));
+        assert_eq!(orthodox_easter(2024), Some(date(2024, 5, 5)));
+        assert_eq!(orthodox_easter(2025), Some(date(2025, 4, 20)));
+        assert_eq!(orthodox_easter(2026), Some(date(2026, 4, 12)));
+    }
+
+    #[test]
+    fn test_us_holidays_and_observance() {
+        let us = find_country("us").unwrap();
+        let holidays = us.holidays(2026).unwrap();
+        assert_eq!(holidays.len(), 11);
+        assert_eq!(find(&holidays, "Thanksgiving Day").date, date(2026, 11, 26));
+        assert_eq!(find(&holidays, "Memorial Day").date, date(2026, 5, 25));
+        // July 4th 2026 is a Saturday
+        assert_eq!(find(&holidays, "Independence Day").observed, Some(date(2026, 7, 3)));
+
+        // Juneteenth only from 2021
+        assert_eq!(us.holidays(2020).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_gb_substitute_days_do_not_collide() {
+        let holidays = find_country("GB").unwrap().holidays(2021).unwrap();
+        // Christmas 2021 is a Saturday and Boxing Day a Sunday
+        assert_eq!(find(&holidays, "Christmas Day").observed, Some(date(2021, 12, 27)));
+        assert_eq!(find(&holidays, "Boxing Day").observed, Some(date(2021, 12, 28)));
+    }
+
+    #[test]
+    fn test_rs_holidays() {
+        let holidays = find_country("RS").unwrap().holidays(2023).unwrap();
+        assert_eq!(find(&holidays, "Orthodox Easter").date, date(2023, 4, 16));
+        // Statehood Day 2023: Feb 15 is a Wednesday, nothing moves
+        assert_eq!(find(&holidays, "Statehood Day").observed, None);
+        // Jan 1 2023 is a Sunday and Jan 2 already a holiday
+        assert_eq!(find(&holidays, "New Year's Day").observed, Some(date(2023, 1, 3)));
+        assert!(holidays.windows(2).all(|w| w[0].date <= w[1].date));
+    }
+
+    #[test]
+    fn test_unsupported_country_and_year() {
+        assert!(find_country("XX").is_err());
+        assert!(find_country("DE").unwrap().holidays(1900).is_err());
+    }
+}
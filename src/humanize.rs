@@ -0,0 +1,391 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::Utc;
+
+use crate::models::{
+    DurationPart, DurationQuery, HumanDurationResponse, RelativeTimeQuery, RelativeTimeResponse,
+    TimeUnit,
+};
+
+// Singular and plural forms of a unit name
+struct UnitNames {
+    one: &'static str,
+    other: &'static str,
+}
+
+const fn names(one: &'static str, other: &'static str) -> UnitNames {
+    UnitNames { one, other }
+}
+
+// Phrases for one supported locale. Unit arrays follow TimeUnit order (second..year).
+struct Locale {
+    code: &'static str,
+    past: &'static str,
+    future: &'static str,
+    now: &'static str,
+    // Unit names as they appear inside the past/future phrases
+    relative_units: [UnitNames; 7],
+    // Unit names standing alone, as in "2 days, 4 hours"
+    duration_units: [UnitNames; 7],
+    // French treats 0 and 1 as singular; the others only 1
+    zero_is_singular: bool,
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        code: "en",
+        past: "{} ago",
+        future: "in {}",
+        now: "just now",
+        relative_units: [
+            names("second", "seconds"),
+            names("minute", "minutes"),
+            names("hour", "hours"),
+            names("day", "days"),
+            names("week", "weeks"),
+            names("month", "months"),
+            names("year", "years"),
+        ],
+        duration_units: [
+            names("second", "seconds"),
+            names("minute", "minutes"),
+            names("hour", "hours"),
+            names("day", "days"),
+            names("week", "weeks"),
+            names("month", "months"),
+            names("year", "years"),
+        ],
+        zero_is_singular: false,
+    },
+    Locale {
+        code: "de",
+        past: "vor {}",
+        future: "in {}",
+        now: "gerade eben",
+        // Dative after "vor"/"in": vor 3 Tagen
+        relative_units: [
+            names("Sekunde", "Sekunden"),
+            names("Minute", "Minuten"),
+            names("Stunde", "Stunden"),
+            names("Tag", "Tagen"),
+            names("Woche", "Wochen"),
+            names("Monat", "Monaten"),
+            names("Jahr", "Jahren"),
+        ],
+        duration_units: [
+            names("Sekunde", "Sekunden"),
+            names("Minute", "Minuten"),
+            names("Stunde", "Stunden"),
+            names("Tag", "Tage"),
+            names("Woche", "Wochen"),
+            names("Monat", "Monate"),
+            names("Jahr", "Jahre"),
+        ],
+        zero_is_singular: false,
+    },
+    Locale {
+        code: "fr",
+        past: "il y a {}",
+        future: "dans {}",
+        now: "à l'instant",
+        relative_units: [
+            names("seconde", "secondes"),
+            names("minute", "minutes"),
+            names("heure", "heures"),
+            names("jour", "jours"),
+            names("semaine", "semaines"),
+            names("mois", "mois"),
+            names("an", "ans"),
+        ],
+        duration_units: [
+            names("seconde", "secondes"),
+            names("minute", "minutes"),
+            names("heure", "heures"),
+            names("jour", "jours"),
+            names("semaine", "semaines"),
+            names("mois", "mois"),
+            names("an", "ans"),
+        ],
+        zero_is_singular: true,
+    },
+    Locale {
+        code: "es",
+        past: "hace {}",
+        future: "dentro de {}",
+        now: "ahora mismo",
+        relative_units: [
+            names("segundo", "segundos"),
+            names("minuto", "minutos"),
+            names("hora", "horas"),
+            names("día", "días"),
+            names("semana", "semanas"),
+            names("mes", "meses"),
+            names("año", "años"),
+        ],
+        duration_units: [
+            names("segundo", "segundos"),
+            names("minuto", "minutos"),
+            names("hora", "horas"),
+            names("día", "días"),
+            names("semana", "semanas"),
+            names("mes", "meses"),
+            names("año", "años"),
+        ],
+        zero_is_singular: false,
+    },
+];
+
+// Units from largest to smallest with their length in seconds.
+// Months and years are calendar averages (30 and 365 days).
+const UNIT_SECONDS: [(TimeUnit, i64); 7] = [
+    (TimeUnit::Year, 365 * 86_400),
+    (TimeUnit::Month, 30 * 86_400),
+    (TimeUnit::Week, 7 * 86_400),
+    (TimeUnit::Day, 86_400),
+    (TimeUnit::Hour, 3_600),
+    (TimeUnit::Minute, 60),
+    (TimeUnit::Second, 1),
+];
+
+// Find a supported locale by BCP 47 tag, matching on the primary language ("de-AT" -> "de")
+fn find_locale(tag: &str) -> Result<&'static Locale, String> {
+    let language = tag.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    LOCALES.iter().find(|l| l.code == language).ok_or_else(|| {
+        let supported: Vec<&str> = LOCALES.iter().map(|l| l.code).collect();
+        format!(
+            "Unsupported locale: {} (supported: {})",
+            tag,
+            supported.join(", ")
+        )
+    })
+}
+
+fn unit_index(unit: TimeUnit) -> usize {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Minute => 1,
+        TimeUnit::Hour => 2,
+        TimeUnit::Day => 3,
+        TimeUnit::Week => 4,
+        TimeUnit::Month => 5,
+        TimeUnit::Year => 6,
+    }
+}
+
+fn unit_seconds(unit: TimeUnit) -> i64 {
+    UNIT_SECONDS[UNIT_SECONDS.len() - 1 - unit_index(unit)].1
+}
+
+fn count_with_unit(locale: &Locale, units: &[UnitNames; 7], count: i64, unit: TimeUnit) -> String {
+    let names = &units[unit_index(unit)];
+    let singular = count == 1 || (count == 0 && locale.zero_is_singular);
+    let name = if singular { names.one } else { names.other };
+    format!("{} {}", count, name)
+}
+
+// Describe a timestamp relative to now (or `query.now`), e.g. "3 hours ago" / "vor 3 Stunden".
+// Uses the largest whole unit, never going finer than `granularity`.
+pub fn relative_time(query: &RelativeTimeQuery) -> Result<RelativeTimeResponse, String> {
+    let locale = find_locale(query.locale.as_deref().unwrap_or("en"))?;
+    let granularity = query.granularity.unwrap_or(TimeUnit::Second);
+    let now = query.now.unwrap_or_else(|| Utc::now().timestamp());
+
+    let seconds = query
+        .timestamp
+        .checked_sub(now)
+        // i64::MIN has no positive counterpart to take the magnitude of
+        .filter(|seconds| *seconds != i64::MIN)
+        .ok_or_else(|| "Timestamp difference is out of range".to_string())?;
+    let magnitude = seconds.unsigned_abs() as i64;
+
+    let (unit, unit_len) = UNIT_SECONDS
+        .iter()
+        .copied()
+        .filter(|(u, _)| unit_index(*u) >= unit_index(granularity))
+        .find(|(_, len)| magnitude >= *len)
+        .unwrap_or((granularity, unit_seconds(granularity)));
+    let count = magnitude / unit_len;
+
+    let text = if count == 0 {
+        locale.now.to_string()
+    } else {
+        let amount = count_with_unit(locale, &locale.relative_units, count, unit);
+        let pattern = if seconds < 0 { locale.past } else { locale.future };
+        pattern.replace("{}", &amount)
+    };
+
+    Ok(RelativeTimeResponse {
+        text,
+        value: if seconds < 0 { -count } else { count },
+        unit,
+        seconds,
+        locale: locale.code.to_string(),
+    })
+}
+
+// Break a duration into whole units between `max_unit` and `min_unit`, e.g.
+// "2 days, 4 hours, 3 minutes". The sign is ignored; any remainder below `min_unit` is dropped.
+pub fn humanize_duration(query: &DurationQuery) -> Result<HumanDurationResponse, String> {
+    let locale = find_locale(query.locale.as_deref().unwrap_or("en"))?;
+    let max_unit = query.max_unit.unwrap_or(TimeUnit::Year);
+    let min_unit = query.min_unit.unwrap_or(TimeUnit::Second);
+    if unit_index(max_unit) < unit_index(min_unit) {
+        return Err("'max_unit' must not be smaller than 'min_unit'".to_string());
+    }
+
+    let mut remaining = query.seconds.unsigned_abs();
+    let mut parts = Vec::new();
+    for (unit, len) in UNIT_SECONDS {
+        let index = unit_index(unit);
+        if index > unit_index(max_unit) || index < unit_index(min_unit) {
+            continue;
+        }
+        let count = remaining / len as u64;
+        remaining %= len as u64;
+        if count > 0 {
+            parts.push(DurationPart {
+                value: count as i64,
+                unit,
+            });
+        }
+    }
+
+    let text = if parts.is_empty() {
+        count_with_unit(locale, &locale.duration_units, 0, min_unit)
+    } else {
+        parts
+            .iter()
+            .map(|p| count_with_unit(locale, &locale.duration_units, p.value, p.unit))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    Ok(HumanDurationResponse {
+        text,
+        seconds: query.seconds,
+        parts,
+        locale: locale.code.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(offset: i64, locale: &str, granularity: Option<TimeUnit>) -> RelativeTimeResponse {
+        let now = 1_739_197_800;
+        relative_time(&RelativeTimeQuery {
+            timestamp: now + offset,
+            now: Some(now),
+            locale: Some(locale.to_string()),
+            granularity,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_relative_time_past_and_future() {
+        assert_eq!(relative(-3 * 3600, "en", None).text, "3 hours ago");
+        assert_eq!(relative(86_400, "en", None).text, "in 1 day");
+        assert_eq!(relative(-45, "en", None).text, "45 seconds ago");
+    }
+
+    #[test]
+    fn test_relative_time_locales() {
+        assert_eq!(relative(-3 * 3600, "de", None).text, "vor 3 Stunden");
+        assert_eq!(relative(-2 * 86_400, "de-AT", None).text, "vor 2 Tagen");
+        assert_eq!(relative(-3 * 3600, "fr", None).text, "il y a 3 heures");
+        assert_eq!(relative(14 * 86_400, "es", None).text, "dentro de 2 semanas");
+    }
+
+    #[test]
+    fn test_relative_time_granularity() {
+        let resp = relative(-30, "en", Some(TimeUnit::Minute));
+        assert_eq!(resp.text, "just now");
+        assert_eq!(resp.unit, TimeUnit::Minute);
+
+        let resp = relative(-90, "de", Some(TimeUnit::Minute));
+        assert_eq!(resp.text, "vor 1 Minute");
+        assert_eq!(resp.value, -1);
+    }
+
+    #[test]
+    fn test_relative_time_unsupported_locale() {
+        let result = relative_time(&RelativeTimeQuery {
+            timestamp: 0,
+            now: Some(0),
+            locale: Some("xx".to_string()),
+            granularity: None,
+        });
+        assert!(result.unwrap_err().contains("Unsupported locale"));
+    }
+
+    #[test]
+    fn test_relative_time_out_of_range() {
+        for (timestamp, now) in [(i64::MIN + 1, 1), (i64::MIN, 0), (i64::MAX, -1)] {
+            let result = relative_time(&RelativeTimeQuery {
+                timestamp,
+                now: Some(now),
+                locale: None,
+                granularity: None,
+            });
+            assert!(result.unwrap_err().contains("out of range"));
+        }
+    }
+
+    fn duration(seconds: i64, locale: &str, max_unit: Option<TimeUnit>) -> String {
+        humanize_duration(&DurationQuery {
+            seconds,
+            locale: Some(locale.to_string()),
+            max_unit,
+            min_unit: None,
+        })
+        .unwrap()
+        .text
+    }
+
+    #[test]
+    fn test_humanize_duration() {
+        let seconds = 2 * 86_400 + 4 * 3600 + 3 * 60;
+        assert_eq!(duration(seconds, "en", None), "2 days, 4 hours, 3 minutes");
+        assert_eq!(duration(seconds, "de", None), "2 Tage, 4 Stunden, 3 Minuten");
+        assert_eq!(duration(3601, "fr", None), "1 heure, 1 seconde");
+        assert_eq!(duration(0, "en", None), "0 seconds");
+        assert_eq!(duration(0, "fr", None), "0 seconde");
+    }
+
+    #[test]
+    fn test_humanize_duration_max_unit() {
+        assert_eq!(duration(10 * 86_400, "en", None), "1 week, 3 days");
+        assert_eq!(duration(10 * 86_400, "en", Some(TimeUnit::Day)), "10 days");
+        assert_eq!(duration(2 * 86_400, "es", Some(TimeUnit::Hour)), "48 horas");
+    }
+
+    #[test]
+    fn test_humanize_duration_min_unit() {
+        let resp = humanize_duration(&DurationQuery {
+            seconds: -(3 * 3600 + 59),
+            locale: None,
+            max_unit: None,
+            min_unit: Some(TimeUnit::Minute),
+        })
+        .unwrap();
+        assert_eq!(resp.text, "3 hours");
+        assert_eq!(resp.parts.len(), 1);
+        assert_eq!(resp.seconds, -(3 * 3600 + 59));
+    }
+}
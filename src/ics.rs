@@ -0,0 +1,133 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Encoder for iCalendar (RFC 5545) feeds of all-day events
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+// One all-day event. `uid` must stay the same across feed refreshes so calendar clients
+// update events instead of duplicating them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllDayEvent {
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: String,
+}
+
+// Encode a VCALENDAR named `name`, stamped with `generated` as each event's DTSTAMP.
+// Lines end in CRLF and are folded at 75 octets.
+pub fn encode(name: &str, generated: DateTime<Utc>, events: &[AllDayEvent]) -> String {
+    let stamp = generated.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Epoch Zone//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+        // Subscribed calendars refresh daily
+        "REFRESH-INTERVAL;VALUE=DURATION:P1D".to_string(),
+        "X-PUBLISHED-TTL:P1D".to_string(),
+    ];
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", escape(&event.uid)),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (event.date + Duration::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", escape(&event.summary)),
+            format!("DESCRIPTION:{}", escape(&event.description)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+// Escape a TEXT value
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Split a content line into 75-octet pieces, never inside a UTF-8 sequence; continuation
+// lines start with a space
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let event = AllDayEvent {
+            uid: "1741503600@example".to_string(),
+            date: NaiveDate::from_ymd_opt(2025, 3, 9).unwrap(),
+            summary: "Clocks go forward, 1 hour".to_string(),
+            description: "Line one\nLine two; end".to_string(),
+        };
+        let generated = DateTime::from_timestamp(1_735_689_600, 0).unwrap();
+        let ics = encode("Zone", generated, &[event]);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20250101T000000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART;VALUE=DATE:20250309\r\nDTEND;VALUE=DATE:20250310\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Clocks go forward\\, 1 hour\r\n"));
+        assert!(ics.contains("\r\nDESCRIPTION:Line one\\nLine two\\; end\r\n"));
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold(&line);
+        let pieces: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces.iter().all(|p| p.len() <= 75));
+        assert_eq!(pieces.concat().replacen(" é", "é", 1), line);
+        assert_eq!(fold("SHORT"), "SHORT");
+    }
+}
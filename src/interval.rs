@@ -0,0 +1,211 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// ISO 8601 durations ("P1Y2M10DT2H") and the parts of an interval ("start/end",
+// "start/duration", "duration/end", or a bare duration)
+
+use std::fmt;
+
+// A parsed ISO 8601 duration. Weeks are folded into days; fractional values are not
+// supported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IsoDuration {
+    pub years: i64,
+    pub months: i64,
+    pub days: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub seconds: i64,
+}
+
+impl IsoDuration {
+    // Parse a duration such as "P3D", "PT90M", or "P1Y2M10DT2H". The designator letters
+    // are case-insensitive but must appear in order.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid ISO 8601 duration '{}'", input);
+        let upper = input.trim().to_ascii_uppercase();
+        let body = upper.strip_prefix('P').ok_or_else(invalid)?;
+        let (date_part, time_part) = match body.split_once('T') {
+            Some((_, "")) => return Err(invalid()),
+            Some((date, time)) => (date, Some(time)),
+            None => (body, None),
+        };
+        if date_part.is_empty() && time_part.is_none() {
+            return Err(invalid());
+        }
+
+        let mut duration = IsoDuration::default();
+        let mut weeks = 0;
+        for (value, unit) in components(date_part, "YMWD").ok_or_else(invalid)? {
+            match unit {
+                'Y' => duration.years = value,
+                'M' => duration.months = value,
+                'W' => weeks = value,
+                _ => duration.days = value,
+            }
+        }
+        for (value, unit) in components(time_part.unwrap_or(""), "HMS").ok_or_else(invalid)? {
+            match unit {
+                'H' => duration.hours = value,
+                'M' => duration.minutes = value,
+                _ => duration.seconds = value,
+            }
+        }
+        duration.days = weeks
+            .checked_mul(7)
+            .and_then(|days| days.checked_add(duration.days))
+            .ok_or_else(|| "Duration is out of range".to_string())?;
+        Ok(duration)
+    }
+
+    // The calendar part as whole months, or None if it overflows
+    pub fn total_months(&self) -> Option<i64> {
+        self.years.checked_mul(12)?.checked_add(self.months)
+    }
+
+    // The clock part (hours, minutes, seconds) in seconds, or None if it overflows
+    pub fn clock_seconds(&self) -> Option<i64> {
+        self.hours
+            .checked_mul(3600)?
+            .checked_add(self.minutes.checked_mul(60)?)?
+            .checked_add(self.seconds)
+    }
+}
+
+// Canonical form, e.g. "P1Y2M10DT2H"; the zero duration is "PT0S"
+impl fmt::Display for IsoDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P")?;
+        for (value, unit) in [(self.years, 'Y'), (self.months, 'M'), (self.days, 'D')] {
+            if value != 0 {
+                write!(f, "{}{}", value, unit)?;
+            }
+        }
+        if self.hours != 0 || self.minutes != 0 || self.seconds != 0 {
+            write!(f, "T")?;
+            for (value, unit) in [(self.hours, 'H'), (self.minutes, 'M'), (self.seconds, 'S')] {
+                if value != 0 {
+                    write!(f, "{}{}", value, unit)?;
+                }
+            }
+        } else if self.years == 0 && self.months == 0 && self.days == 0 {
+            write!(f, "T0S")?;
+        }
+        Ok(())
+    }
+}
+
+// The two halves of an interval; either may be a duration, but not both
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntervalPart {
+    DateTime(String),
+    Duration(IsoDuration),
+}
+
+// Split an interval on '/' (or the "--" alternative separator). A bare duration comes back
+// as a single part.
+pub fn parse_interval(input: &str) -> Result<Vec<IntervalPart>, String> {
+    let input = input.trim();
+    let halves: Vec<&str> = match input.split_once('/').or_else(|| input.split_once("--")) {
+        Some((start, end)) => vec![start, end],
+        None => vec![input],
+    };
+    let parts = halves
+        .into_iter()
+        .map(|half| {
+            let half = half.trim();
+            if half.is_empty() {
+                Err(format!("Invalid ISO 8601 interval '{}'", input))
+            } else if half.starts_with(['P', 'p']) {
+                IsoDuration::parse(half).map(IntervalPart::Duration)
+            } else {
+                Ok(IntervalPart::DateTime(half.to_string()))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if parts.len() == 2 && parts.iter().all(|p| matches!(p, IntervalPart::Duration(_))) {
+        return Err("An interval cannot have two durations".to_string());
+    }
+    Ok(parts)
+}
+
+// Split "1Y2M10D" into (value, unit) pairs, checking units come from `units` in order
+// and appear at most once
+fn components(input: &str, units: &str) -> Option<Vec<(i64, char)>> {
+    let mut result = Vec::new();
+    let mut remaining_units = units;
+    let mut digits = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let position = remaining_units.find(c)?;
+        if digits.is_empty() {
+            return None;
+        }
+        result.push((digits.parse().ok()?, c));
+        digits.clear();
+        remaining_units = &remaining_units[position + 1..];
+    }
+    digits.is_empty().then_some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let duration = IsoDuration::parse("P1Y2M10DT2H").unwrap();
+        assert_eq!(
+            duration,
+            IsoDuration {
+                years: 1,
+                months: 2,
+                days: 10,
+                hours: 2,
+                ..Default::default()
+            }
+        );
+        assert_eq!(duration.to_string(), "P1Y2M10DT2H");
+        assert_eq!(IsoDuration::parse("p2w1d").unwrap().days, 15);
+        assert_eq!(IsoDuration::parse("PT90M").unwrap().clock_seconds(), Some(5400));
+        assert_eq!(IsoDuration::parse("PT0S").unwrap().to_string(), "PT0S");
+
+        for invalid in ["P", "PT", "1D", "P1H", "PT1D", "P1D2Y", "P1.5D", "P1DD", "P-1D"] {
+            assert!(IsoDuration::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(
+            parse_interval("2025-01-01T00:00Z/P3D").unwrap(),
+            [
+                IntervalPart::DateTime("2025-01-01T00:00Z".to_string()),
+                IntervalPart::Duration(IsoDuration {
+                    days: 3,
+                    ..Default::default()
+                }),
+            ]
+        );
+        assert_eq!(parse_interval("2025-01-01--2025-02-01").unwrap().len(), 2);
+        assert_eq!(parse_interval("PT1H").unwrap().len(), 1);
+        assert!(parse_interval("P1D/PT1H").is_err());
+        assert!(parse_interval("2025-01-01/").is_err());
+    }
+}
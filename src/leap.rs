@@ -0,0 +1,262 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Leap seconds from the IERS list that tzdata distributes as leap-seconds.list, and
+// conversion between UTC, TAI, and GPS time
+
+use chrono::DateTime;
+use std::sync::LazyLock;
+
+use crate::models::{TimescaleQuery, TimescaleResponse};
+
+const LEAP_SECONDS_LIST: &str = include_str!("../data/leap-seconds.list");
+
+// Seconds from the NTP epoch (1900-01-01) to the Unix epoch
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+// GPS time started at 1980-01-06T00:00:00Z and has stayed 19 seconds behind TAI
+const GPS_EPOCH: i64 = 315_964_800;
+const GPS_MINUS_TAI: i32 = -19;
+const SECONDS_PER_WEEK: i64 = 604_800;
+
+// TAI - UTC from `timestamp` (the Unix time of the first second after the change) onward
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeapSecond {
+    pub timestamp: i64,
+    pub tai_minus_utc: i32,
+}
+
+// The parsed list. The first entry is the 10-second offset UTC started with in 1972;
+// each later one follows an inserted leap second.
+#[derive(Debug, Default)]
+pub struct LeapSecondList {
+    pub entries: Vec<LeapSecond>,
+    // Unix times of the last update and of when the list stops being valid
+    pub updated: i64,
+    pub expires: i64,
+}
+
+pub static LEAP_SECONDS: LazyLock<LeapSecondList> = LazyLock::new(|| {
+    let mut list = LeapSecondList::default();
+    for line in LEAP_SECONDS_LIST.lines() {
+        let ntp = |value: &str| {
+            value
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .map(|t| t - NTP_UNIX_OFFSET)
+        };
+        if let Some(value) = line.strip_prefix("#$") {
+            list.updated = ntp(value).unwrap_or_default();
+        } else if let Some(value) = line.strip_prefix("#@") {
+            list.expires = ntp(value).unwrap_or_default();
+        } else if !line.starts_with('#') {
+            let mut fields = line.split_whitespace();
+            let timestamp = fields.next().and_then(ntp);
+            let entry = timestamp.zip(fields.next().and_then(|v| v.parse().ok()));
+            if let Some((timestamp, tai_minus_utc)) = entry {
+                list.entries.push(LeapSecond {
+                    timestamp,
+                    tai_minus_utc,
+                });
+            }
+        }
+    }
+    list
+});
+
+// TAI - UTC in effect at a Unix time; None before 1972, when UTC was not yet stepped
+// in whole seconds
+pub fn tai_minus_utc(timestamp: i64) -> Option<i32> {
+    LEAP_SECONDS
+        .entries
+        .iter()
+        .rev()
+        .find(|leap| leap.timestamp <= timestamp)
+        .map(|leap| leap.tai_minus_utc)
+}
+
+// Error for instants before UTC was stepped in leap seconds
+fn before_1972() -> String {
+    "Leap seconds are only defined from 1972-01-01 onward".to_string()
+}
+
+// UTC for a TAI count: the Unix time and whether it is an inserted leap second (shown
+// as 23:59:60 with the Unix time of 23:59:59)
+fn utc_from_tai(tai: i64) -> Result<(i64, bool), String> {
+    let entries = &LEAP_SECONDS.entries;
+    for (i, leap) in entries.iter().enumerate().rev() {
+        let timestamp = tai
+            .checked_sub(leap.tai_minus_utc as i64)
+            .ok_or_else(|| format!("Instant out of range: TAI {}", tai))?;
+        if timestamp >= leap.timestamp {
+            return Ok((timestamp, false));
+        }
+        if i > 0 && timestamp == leap.timestamp - 1 {
+            return Ok((timestamp, true));
+        }
+    }
+    Err(before_1972())
+}
+
+// Convert between Unix time (UTC), TAI, and GPS time. TAI counts like CLOCK_TAI: Unix
+// time plus TAI - UTC. GPS is given as seconds since its epoch or as week and seconds
+// into the week. Exactly one input must be given; all representations are returned.
+pub fn convert_timescales(query: &TimescaleQuery) -> Result<TimescaleResponse, String> {
+    let gps_week = match (query.gps_week, query.gps_seconds) {
+        (Some(week), Some(seconds)) if (0..SECONDS_PER_WEEK).contains(&seconds) => Some(
+            week.checked_mul(SECONDS_PER_WEEK)
+                .and_then(|start| start.checked_add(seconds))
+                .ok_or_else(|| format!("Invalid GPS week: {}", week))?,
+        ),
+        (Some(_), Some(_)) => {
+            return Err(format!("'gps_seconds' must be below {}", SECONDS_PER_WEEK))
+        }
+        (None, None) => None,
+        _ => return Err("'gps_week' and 'gps_seconds' go together".to_string()),
+    };
+    let provided = [
+        query.timestamp.is_some(),
+        query.tai.is_some(),
+        query.gps.is_some(),
+        gps_week.is_some(),
+    ]
+    .iter()
+    .filter(|p| **p)
+    .count();
+    if provided != 1 {
+        return Err(
+            "Provide exactly one of 'timestamp', 'tai', 'gps', or 'gps_week'+'gps_seconds'"
+                .to_string(),
+        );
+    }
+
+    let tai = match (query.timestamp, query.tai, query.gps.or(gps_week)) {
+        (Some(timestamp), _, _) => {
+            let offset = tai_minus_utc(timestamp).ok_or_else(before_1972)?;
+            timestamp
+                .checked_add(offset.into())
+                .ok_or_else(|| format!("Invalid timestamp: {}", timestamp))?
+        }
+        (_, Some(tai), _) => tai,
+        (_, _, Some(gps)) => gps
+            .checked_add(GPS_EPOCH - GPS_MINUS_TAI as i64)
+            .ok_or_else(|| format!("Invalid GPS time: {}", gps))?,
+        _ => unreachable!("exactly one input is set"),
+    };
+    let (timestamp, is_leap_second) = utc_from_tai(tai)?;
+    let utc = DateTime::from_timestamp(timestamp, if is_leap_second { 1_000_000_000 } else { 0 })
+        .ok_or_else(|| format!("Instant out of range: TAI {}", tai))?;
+    let offset = (tai - timestamp) as i32;
+    let gps = tai - GPS_EPOCH + GPS_MINUS_TAI as i64;
+
+    Ok(TimescaleResponse {
+        utc: utc.to_rfc3339(),
+        timestamp,
+        is_leap_second,
+        tai,
+        tai_minus_utc: offset,
+        gps,
+        gps_week: gps.div_euclid(SECONDS_PER_WEEK),
+        gps_seconds: gps.rem_euclid(SECONDS_PER_WEEK),
+        gps_minus_utc: offset + GPS_MINUS_TAI,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leap_second_list() {
+        let entries = &LEAP_SECONDS.entries;
+        assert_eq!(entries[0].timestamp, 63_072_000); // 1972-01-01
+        assert_eq!(entries[0].tai_minus_utc, 10);
+        assert!(entries.len() >= 28);
+        assert!(LEAP_SECONDS.expires > LEAP_SECONDS.updated);
+
+        assert_eq!(tai_minus_utc(0), None);
+        // 2016-12-31T23:59:59Z, then the first second of 2017 after the last leap second
+        assert_eq!(tai_minus_utc(1_483_228_799), Some(36));
+        assert_eq!(tai_minus_utc(1_483_228_800), Some(37));
+    }
+
+    fn convert(query: TimescaleQuery) -> TimescaleResponse {
+        convert_timescales(&query).unwrap()
+    }
+
+    #[test]
+    fn test_convert_timescales() {
+        // 2025-02-10T00:00:00Z
+        let utc = convert(TimescaleQuery {
+            timestamp: Some(1_739_145_600),
+            ..Default::default()
+        });
+        assert_eq!((utc.tai, utc.tai_minus_utc, utc.gps_minus_utc), (1_739_145_637, 37, 18));
+        assert_eq!((utc.gps_week, utc.gps_seconds), (2353, 86_418));
+
+        let from_gps = convert(TimescaleQuery {
+            gps_week: Some(2353),
+            gps_seconds: Some(86_418),
+            ..Default::default()
+        });
+        assert_eq!(from_gps.utc, "2025-02-10T00:00:00+00:00");
+        // The GPS epoch itself
+        let epoch = convert(TimescaleQuery {
+            gps: Some(0),
+            ..Default::default()
+        });
+        assert_eq!((epoch.timestamp, epoch.gps_minus_utc), (GPS_EPOCH, 0));
+    }
+
+    #[test]
+    fn test_convert_timescales_leap_second() {
+        // TAI for 2017-01-01T00:00:00Z, minus one: the leap second before it
+        let leap = convert(TimescaleQuery {
+            tai: Some(1_483_228_800 + 37 - 1),
+            ..Default::default()
+        });
+        assert!(leap.is_leap_second);
+        assert_eq!(leap.utc, "2016-12-31T23:59:60+00:00");
+        assert_eq!(leap.timestamp, 1_483_228_799);
+        let before = convert(TimescaleQuery {
+            tai: Some(1_483_228_800 + 37 - 2),
+            ..Default::default()
+        });
+        assert_eq!((before.timestamp, before.is_leap_second), (1_483_228_799, false));
+
+        let errors = [
+            TimescaleQuery::default(),
+            TimescaleQuery {
+                timestamp: Some(0),
+                ..Default::default()
+            },
+            TimescaleQuery {
+                gps_week: Some(1),
+                ..Default::default()
+            },
+            TimescaleQuery {
+                tai: Some(i64::MIN),
+                ..Default::default()
+            },
+            TimescaleQuery {
+                gps_week: Some(i64::MAX),
+                gps_seconds: Some(0),
+                ..Default::default()
+            },
+        ];
+        assert!(errors.iter().all(|query| convert_timescales(query).is_err()));
+    }
+}
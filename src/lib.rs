@@ -17,12 +17,29 @@
 use std::sync::Arc;
 
 pub mod auth;
+pub mod boundaries;
+pub mod calendar;
 pub mod config;
+pub mod cron;
 pub mod db;
+pub mod fields;
+pub mod geoip;
 pub mod handlers;
+pub mod holidays;
+pub mod humanize;
+pub mod ics;
+pub mod interval;
+pub mod leap;
 pub mod models;
+pub mod names;
+pub mod planning;
+pub mod recurrence;
 pub mod routes;
 pub mod service;
+pub mod solar;
+pub mod teams;
+pub mod tzif;
+pub mod zones;
 
 pub use models::*;
 pub use service::EpochZoneService;
@@ -32,4 +49,24 @@ pub struct AppState {
     pub db: tokio_rusqlite::Connection,
     pub config: Arc<config::AppConfig>,
     pub tz_finder: Arc<tzf_rs::DefaultFinder>,
+    pub clock: service::ClockAnchor,
+    pub search_index: Arc<service::SearchIndex>,
+    pub geoip: Arc<geoip::GeoIp>,
+    pub rate_limiter: Arc<auth::rate_limit::RateLimiter>,
+    // Failed authentications per client address, to slow down key guessing
+    pub auth_failures: Arc<auth::throttle::AuthThrottle>,
+    // Trial key signups per client address
+    pub trial_signups: Arc<auth::trial::SignupLimiter>,
+    pub usage: Arc<auth::usage::UsageMeter>,
+    // Nonces of recent signed requests, to reject replays
+    pub nonces: Arc<auth::signing::NonceCache>,
+    // Origins listed by active keys, for answering CORS preflight requests
+    pub key_origins: Arc<auth::origins::OriginRegistry>,
+    // Emails key owners, when SMTP is configured
+    pub mailer: Option<Arc<auth::email::Mailer>>,
+    // Verifies bearer tokens, when JWT authentication is configured
+    pub jwt: Option<Arc<auth::jwt::JwtVerifier>>,
+    // Rate limits and key lookups shared with other instances, when REDIS_URL is set
+    #[cfg(feature = "redis")]
+    pub shared: Option<Arc<auth::shared::SharedStore>>,
 }
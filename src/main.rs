@@ -18,9 +18,25 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use epochzone::auth::audit::{self, AuditAction, AuditEvent, SYSTEM_ACTOR};
+use epochzone::auth::cleanup;
+use epochzone::auth::email::{self, Mailer};
+use epochzone::auth::jwt::JwtVerifier;
+use epochzone::auth::origins::OriginRegistry;
+use epochzone::auth::rate_limit::RateLimiter;
+use epochzone::auth::service;
+#[cfg(feature = "redis")]
+use epochzone::auth::shared::SharedStore;
+use epochzone::auth::signing::NonceCache;
+use epochzone::auth::throttle::AuthThrottle;
+use epochzone::auth::trial::SignupLimiter;
+use epochzone::auth::usage::{self, UsageMeter};
+use epochzone::auth::webhooks;
 use epochzone::config::AppConfig;
 use epochzone::db::init_db;
+use epochzone::geoip::GeoIp;
 use epochzone::routes::create_router;
+use epochzone::service::{ClockAnchor, EpochZoneService};
 use epochzone::AppState;
 
 #[tokio::main]
@@ -40,19 +56,126 @@ async fn main() {
     // Load configuration from environment
     let config = AppConfig::from_env();
     tracing::info!("CORS allowed origins: {:?}", config.cors_allowed_origins);
+    if config.api_key_pepper.is_none() {
+        tracing::warn!("API_KEY_PEPPER not set, API keys are stored as plain SHA-256 hashes");
+    }
 
     // Initialize database
     let db = init_db(&config.database_url).await;
     tracing::info!("Database initialized at: {}", config.database_url);
 
+    // Without a static admin key, the first run creates an admin key and shows it once
+    if config.admin_api_key_hash.is_none() {
+        let pepper = config.api_key_pepper.as_deref();
+        match service::bootstrap_admin_key(&db, pepper).await.unwrap_or_else(|e| panic!("{}", e)) {
+            Some(admin) => {
+                println!(
+                    "Created admin API key {} (id {}). It will not be shown again.",
+                    admin.api_key, admin.id
+                );
+                let after = audit::snapshot(&db, &admin.id).await;
+                let event =
+                    AuditEvent::new(SYSTEM_ACTOR, AuditAction::KeyCreated, Some(&admin.id));
+                if let Err(e) = audit::record(&db, vec![event.snapshots(None, after)]).await {
+                    tracing::error!("{}", e);
+                }
+            }
+            None => tracing::info!("Admin access uses admin-role API keys"),
+        }
+    }
+
     // Initialize timezone finder (offline coordinate → timezone lookup)
     let tz_finder = Arc::new(tzf_rs::DefaultFinder::new());
     tracing::info!("Timezone finder initialized");
 
+    // Build the fuzzy autocomplete index
+    let search_index = Arc::new(EpochZoneService::build_search_index());
+    tracing::info!("Timezone search index built");
+
+    // Open the GeoIP database, if configured (IP → timezone lookup)
+    let geoip = match config.geoip_database.as_deref() {
+        Some(path) => {
+            let geoip = GeoIp::open(path).unwrap_or_else(|e| panic!("{}", e));
+            tracing::info!("GeoIP database loaded from: {}", path);
+            geoip
+        }
+        None => {
+            tracing::info!("GEOIP_DATABASE not set, IP lookups disabled");
+            GeoIp::disabled()
+        }
+    };
+
+    // Write buffered usage counts to the database in the background
+    let usage_meter = Arc::new(UsageMeter::new());
+    usage::spawn_flusher(usage_meter.clone(), db.clone());
+
+    // Deactivate expired keys and delete ones past the retention period
+    cleanup::spawn_cleanup(db.clone(), usage_meter.clone(), config.revoked_key_retention_days);
+
+    // Warn registered webhooks ahead of key expiry
+    webhooks::spawn_expiry_notifier(db.clone());
+    // and send scheduled webhooks the world clock
+    webhooks::spawn_scheduled_deliveries(db.clone());
+
+    // Email key owners about their keys, if SMTP is configured
+    let mailer = config.smtp.as_ref().map(|smtp| {
+        Arc::new(Mailer::from_config(smtp).unwrap_or_else(|e| panic!("{}", e)))
+    });
+    match &mailer {
+        Some(mailer) => {
+            email::spawn_expiry_emails(db.clone(), mailer.clone(), config.expiry_email_days);
+            tracing::info!("Key owner emails enabled");
+        }
+        None => tracing::info!("SMTP_HOST not set, key owner emails disabled"),
+    }
+
+    // Accept gateway-issued bearer tokens, if configured
+    let jwt = JwtVerifier::from_config(&config).map(Arc::new);
+    if jwt.is_some() {
+        tracing::info!("JWT bearer authentication enabled");
+    }
+
+    // Share rate limits and key lookups with other instances, if configured
+    #[cfg(feature = "redis")]
+    let shared = match &config.redis_url {
+        Some(url) => {
+            let store = SharedStore::connect(url, config.key_cache_seconds)
+                .await
+                .unwrap_or_else(|e| panic!("{}", e));
+            tracing::info!("Sharing rate limits and key lookups through Redis");
+            Some(Arc::new(store))
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "redis"))]
+    if config.redis_url.is_some() {
+        panic!("REDIS_URL is set, but this build lacks the redis feature");
+    }
+
+    // Origins that keys list for themselves, for answering CORS preflight requests
+    let key_origins = Arc::new(OriginRegistry::new());
+    key_origins.replace(service::key_origins(&db).await.expect("Failed to read key origins"));
+
+    // Slow down clients that keep failing to authenticate
+    let auth_failures = Arc::new(AuthThrottle::new(config.auth_failure_limit));
+
     let state = AppState {
         db,
         config: Arc::new(config),
         tz_finder,
+        clock: ClockAnchor::now(),
+        search_index,
+        geoip: Arc::new(geoip),
+        rate_limiter: Arc::new(RateLimiter::new()),
+        auth_failures,
+        trial_signups: Arc::new(SignupLimiter::new()),
+        usage: usage_meter,
+        nonces: Arc::new(NonceCache::new()),
+        key_origins,
+        mailer,
+        jwt,
+        #[cfg(feature = "redis")]
+        shared,
     };
 
     let app = create_router(state);
@@ -63,5 +186,7 @@ async fn main() {
 
     // Start the server
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
 }
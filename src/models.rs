@@ -15,16 +15,140 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Unit of an epoch timestamp, e.g. "ms" for JavaScript's Date.now()
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampUnit {
+    #[default]
+    #[serde(rename = "s")]
+    Seconds,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "us")]
+    Microseconds,
+    #[serde(rename = "ns")]
+    Nanoseconds,
+}
+
+// Query parameters for current-time lookup
+#[derive(Debug, Deserialize, Default)]
+pub struct TimeQuery {
+    #[serde(default)]
+    pub unit: TimestampUnit,
+    // strftime pattern for the `formatted` field, e.g. "%A %d %B %Y %H:%M"
+    pub format: Option<String>,
+    // Return only this representation instead of the full TimezoneInfo
+    #[serde(default)]
+    pub output: Option<TimeOutput>,
+}
+
+// `?fields=` on endpoints that support sparse fieldsets
+#[derive(Debug, Deserialize, Default)]
+pub struct FieldsQuery {
+    // Comma-separated field names to keep, e.g. "timezone,current_time"
+    pub fields: Option<String>,
+}
+
+// Representation selected by `?output=` on the current-time lookup
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeOutput {
+    Unix,
+    UnixMs,
+    Iso,
+    Rfc2822,
+    // Every representation above in one object
+    All,
+}
+
+// The representations of the current time picked by `output`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeRepresentations {
+    pub timezone: String,
+    // Epoch seconds, regardless of `unit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_ms: Option<i64>,
+    // RFC 3339 in the zone's local time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iso: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rfc2822: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<TimezoneResolution>,
+}
+
+// The current time in full, or only the representations asked for
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TimeResponse {
+    Full(Box<TimezoneInfo>),
+    Selected(TimeRepresentations),
+}
 
 // Response containing timezone information
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimezoneInfo {
     pub timezone: String,
+    // The zone `timezone` links to ("Asia/Calcutta" -> "Asia/Kolkata"), or itself
+    #[serde(default)]
+    pub canonical: String,
+    // False when the requested name is a backward-compatibility link
+    #[serde(default)]
+    pub is_canonical: bool,
     pub current_time: String,
     pub utc_offset: String,
     pub abbreviation: String,
     pub is_dst: bool,
     pub timestamp: i64,
+    #[serde(default)]
+    pub unit: TimestampUnit,
+    // Millisecond-precision epoch, regardless of `unit`
+    #[serde(default)]
+    pub timestamp_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    // "Mon, 10 Feb 2025 15:30:00 +0100"; both dates are absent outside the years 0 to 9999,
+    // which they can't express
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rfc2822: Option<String>,
+    // IMF-fixdate as used in HTTP headers, always GMT: "Mon, 10 Feb 2025 14:30:00 GMT"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_date: Option<String>,
+    // Coordinate lookups only: every zone whose boundary contains the point, the chosen
+    // zone first. More than one near disputed or imprecise borders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidates: Option<Vec<String>>,
+    // Coordinate lookups at sea: `timezone` is then a nautical Etc/GMT±X zone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocean: Option<OceanInfo>,
+    // Set when the zone was requested as "auto"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<TimezoneResolution>,
+}
+
+// How an "auto" timezone was picked for the caller
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimezoneResolution {
+    // GeoIP lookup of the caller's address
+    Geoip,
+    // Region subtag of the Accept-Language header
+    AcceptLanguage,
+    // Neither was conclusive, so UTC
+    Default,
+}
+
+// Where the nearest land zone is for a point at sea
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OceanInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nearest_land_zone: Option<String>,
+    // Distance to that zone's boundary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nearest_land_km: Option<f64>,
 }
 
 // A single timezone item in the list
@@ -32,15 +156,506 @@ pub struct TimezoneInfo {
 pub struct TimezoneListItem {
     pub name: String,
     pub display_name: String,
+    // Present with include=offset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset_seconds: Option<i32>,
+    // Present with include=dst
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_dst: Option<bool>,
+    // Present with include=country; empty for zones not tied to a country ("UTC")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_codes: Option<Vec<String>>,
 }
 
-// Request for timezone conversion
+// A legacy zone name and the canonical zone it links to
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ZoneAlias {
+    pub alias: String,
+    pub canonical: String,
+}
+
+// A Windows timezone ID and the IANA zones it covers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WindowsZoneMapping {
+    pub windows_id: String,
+    // CLDR's default IANA zone for the Windows ID
+    pub timezone: String,
+    pub zones: Vec<String>,
+}
+
+// Query parameters for the timezone list
+#[derive(Debug, Deserialize, Default)]
+pub struct TimezonesQuery {
+    pub group_by: Option<TimezoneGrouping>,
+    // Comma-separated extra fields per zone: offset, dst, country
+    pub include: Option<String>,
+    // Language for CLDR display names ("fr", "de-CH"); English identifier names when absent
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimezoneGrouping {
+    // Leading area of the zone name: Africa, America, Asia, ...
+    Region,
+}
+
+// The timezone list, flat or keyed by region
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TimezonesResponse {
+    List(Vec<TimezoneListItem>),
+    // Zones without a region ("UTC", "EST5EDT") are grouped under "Other"
+    Grouped(BTreeMap<String, Vec<TimezoneListItem>>),
+}
+
+// A zone used in a country, with its current offset
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CountryZone {
+    pub timezone: String,
+    // Part of the country the zone covers, e.g. "Eastern (most areas)"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub abbreviation: String,
+    pub utc_offset: String,
+    pub offset_seconds: i32,
+    pub is_dst: bool,
+}
+
+// All zones used in a country, as listed in zone.tab
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CountryZones {
+    pub country_code: String,
+    pub country_name: String,
+    pub zones: Vec<CountryZone>,
+}
+
+// A country using a zone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneCountry {
+    pub code: String,
+    pub name: String,
+}
+
+// Countries whose clocks follow a zone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneCountries {
+    pub timezone: String,
+    pub canonical: String,
+    pub countries: Vec<ZoneCountry>,
+}
+
+// Query parameters for a zone's boundary polygon
+#[derive(Debug, Deserialize, Default)]
+pub struct BoundaryQuery {
+    // Douglas-Peucker simplification tolerance in degrees (default 0.01; 0 for full detail)
+    pub tolerance: Option<f64>,
+}
+
+// A zone's boundary as a GeoJSON Feature with a MultiPolygon geometry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoundaryFeature {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub properties: BoundaryProperties,
+    pub geometry: BoundaryGeometry,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoundaryProperties {
+    pub timezone: String,
+    // The zone the boundary belongs to when `timezone` is a link
+    pub canonical: String,
+    pub tolerance: f64,
+    pub points: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BoundaryGeometry {
+    #[serde(rename = "type")]
+    pub kind: String,
+    // Polygons of rings of [longitude, latitude]; each polygon's first ring is its outline
+    pub coordinates: Vec<Vec<Vec<[f64; 2]>>>,
+}
+
+// Query parameters for zones observing DST
+#[derive(Debug, Deserialize, Default)]
+pub struct DstQuery {
+    // Instant to check (defaults to now)
+    pub at: Option<String>,
+}
+
+// A zone observing DST at the checked instant
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DstZone {
+    pub timezone: String,
+    pub abbreviation: String,
+    pub utc_offset: String,
+    // DST adjustment on top of standard time, e.g. "+01:00"
+    pub dst_offset: String,
+    // When the zone reverts to standard time (absent if not within a year)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds_until_end: Option<i64>,
+    // Humanized, e.g. "2 months, 5 days, 3 hours"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_in: Option<String>,
+}
+
+// All zones observing DST at an instant
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DstZonesResponse {
+    pub at: String,
+    pub count: usize,
+    pub zones: Vec<DstZone>,
+}
+
+// Query parameters for a zone's DST status at one instant
+#[derive(Debug, Deserialize, Default)]
+pub struct DstAtQuery {
+    // Any format /api/parse accepts (defaults to now); wall-clock values are read in the zone
+    pub timestamp: Option<String>,
+}
+
+// Whether a zone observes DST at an instant, with the offset in effect
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DstStatus {
+    pub timezone: String,
+    // The instant in the zone's local time
+    pub at: String,
+    pub timestamp: i64,
+    pub is_dst: bool,
+    pub abbreviation: String,
+    pub utc_offset: String,
+    // Standard offset and the DST adjustment on top of it ("+00:00" outside DST)
+    pub standard_offset: String,
+    pub dst_offset: String,
+}
+
+// One transition in the export: the local time type in effect from `timestamp`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTransition {
+    pub timestamp: i64,
+    pub offset_seconds: i32,
+    pub abbreviation: String,
+    pub is_dst: bool,
+}
+
+// A canonical zone with its metadata, current offset, and transitions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportZone {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_name: Option<String>,
+    pub utc_offset: String,
+    pub offset_seconds: i32,
+    pub abbreviation: String,
+    pub is_dst: bool,
+    pub transitions: Vec<ExportTransition>,
+}
+
+// The whole timezone database, for offline clients
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimezoneExport {
+    pub tzdata_version: String,
+    pub generated_at: String,
+    // Transitions cover 1 January `from_year` up to 1 January after `to_year`
+    pub from_year: i32,
+    pub to_year: i32,
+    pub zones: Vec<ExportZone>,
+    pub aliases: Vec<ZoneAlias>,
+}
+
+// Versions of the rule and boundary data compiled into the service
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TzdataInfo {
+    // IANA tz database release, e.g. "2025b"
+    pub tzdata_version: String,
+    pub tzdata_source: String,
+    // Timezone boundary dataset used for coordinate lookups
+    pub boundaries_version: String,
+    pub boundaries_source: String,
+    pub zone_count: usize,
+    pub link_count: usize,
+}
+
+// One row of the leap second table
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeapSecondEntry {
+    // UTC date the offset takes effect; the leap second is 23:59:60 the day before
+    pub date: String,
+    pub timestamp: i64,
+    pub tai_minus_utc: i32,
+}
+
+// Leap seconds since 1972 and where UTC stands relative to TAI
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeapSecondsResponse {
+    pub tai_minus_utc: i32,
+    // The first row is UTC's initial 10-second offset in 1972
+    pub leap_seconds: Vec<LeapSecondEntry>,
+    // A leap second the list schedules after now
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announced: Option<LeapSecondEntry>,
+    // Date of the list's last update and until when it rules out unannounced leap
+    // seconds; past `expires`, a newer list may have announced one
+    pub updated: String,
+    pub expires: String,
+    pub is_expired: bool,
+}
+
+// Query parameters for a zone's transition dump
+#[derive(Debug, Deserialize, Default)]
+pub struct DumpQuery {
+    // First and last year to list, inclusive (default 1970-2040)
+    pub from: Option<i32>,
+    pub to: Option<i32>,
+}
+
+// Query parameters for a zone's clock-change calendar feed
+#[derive(Debug, Deserialize, Default)]
+pub struct DstCalendarQuery {
+    // Years of upcoming changes to include (default 2)
+    pub years: Option<i32>,
+}
+
+// Wall-clock reading on one side of a transition
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WallTime {
+    pub local: String,
+    pub utc_offset: String,
+    pub abbreviation: String,
+    pub is_dst: bool,
+}
+
+// One offset transition, with the last second before it and the first after, as
+// `zdump -v` prints them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneTransition {
+    pub at: String,
+    pub timestamp: i64,
+    pub before: WallTime,
+    pub after: WallTime,
+    // Positive when clocks jump forward
+    pub change_seconds: i32,
+}
+
+// Which way clocks move at a DST change
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockChange {
+    Forward,
+    Back,
+}
+
+// Countdown to a zone's next DST change; the change fields are absent for zones
+// without DST in the coming years
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DstCountdown {
+    pub timezone: String,
+    // The instant counted from, in the zone's local time
+    pub from: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<ZoneTransition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds_until: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clocks: Option<ClockChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneDump {
+    pub timezone: String,
+    pub from: i32,
+    pub to: i32,
+    pub count: usize,
+    pub transitions: Vec<ZoneTransition>,
+}
+
+// Query parameters for the offsets directory
+#[derive(Debug, Deserialize, Default)]
+pub struct OffsetsQuery {
+    // Instant to take offsets at (defaults to now)
+    pub at: Option<String>,
+}
+
+// One UTC offset in use, with the zones on it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OffsetEntry {
+    pub utc_offset: String,
+    pub offset_seconds: i32,
+    pub zone_count: usize,
+    // True when every zone on this offset is only there because of DST
+    pub dst_only: bool,
+    pub abbreviations: Vec<String>,
+    pub representative_zones: Vec<String>,
+}
+
+// Every distinct UTC offset in use at an instant, west to east
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OffsetsResponse {
+    pub at: String,
+    pub offsets: Vec<OffsetEntry>,
+}
+
+// A zone that uses an abbreviation, and the offset it stands for there
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AbbreviationCandidate {
+    pub timezone: String,
+    pub utc_offset: String,
+    pub offset_seconds: i32,
+    pub is_dst: bool,
+    // Whether the zone is using the abbreviation right now
+    pub in_use: bool,
+}
+
+// Zones using an abbreviation now or within the next year
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AbbreviationResponse {
+    pub abbreviation: String,
+    // True when the abbreviation stands for more than one offset (IST: India, Ireland, Israel)
+    pub ambiguous: bool,
+    pub offsets: Vec<String>,
+    pub count: usize,
+    pub candidates: Vec<AbbreviationCandidate>,
+}
+
+// Query parameters for timezone search
+#[derive(Debug, Deserialize, Default)]
+pub struct TimezoneSearchQuery {
+    // Case-insensitive substring of the zone name or its country's name
+    pub q: Option<String>,
+    // ISO 3166-1 alpha-2 country code, e.g. "RS"
+    pub country: Option<String>,
+    // Leading area of the zone name, e.g. "Europe"
+    pub region: Option<String>,
+    pub limit: Option<usize>,
+}
+
+// How a search result matched the query, best first
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Exact,
+    // The city part starts with the query ("bel" -> Europe/Belgrade)
+    Prefix,
+    Substring,
+    // Only the country name matched ("serbia" -> Europe/Belgrade)
+    Country,
+}
+
+// A ranked timezone search result
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimezoneSearchResult {
+    pub name: String,
+    pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_name: Option<String>,
+    // Absent when no `q` was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched: Option<MatchKind>,
+}
+
+// Query parameters for fuzzy timezone autocomplete
 #[derive(Debug, Deserialize)]
+pub struct AutocompleteQuery {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+// An autocomplete suggestion
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutocompleteResult {
+    pub name: String,
+    pub display_name: String,
+    // Similarity between 0 and 1
+    pub score: f64,
+    // The indexed name that matched: zone, city, country, or local city name
+    pub matched: String,
+}
+
+// How to handle a local datetime that falls in a DST gap (e.g. 02:30 on a spring-forward day)
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GapPolicy {
+    #[default]
+    Reject,
+    ShiftForward,
+}
+
+// Alternative calendar systems that conversions can report
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarSystem {
+    Hijri,
+    Hebrew,
+    Japanese,
+}
+
+// A date expressed in an alternative calendar system
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalendarDate {
+    pub era: String,
+    pub year: i32,
+    pub month: u8,
+    pub month_name: String,
+    pub day: u8,
+    pub formatted: String,
+}
+
+// Alternative calendar representations of a local date
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CalendarDates {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hijri: Option<CalendarDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hebrew: Option<CalendarDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub japanese: Option<CalendarDate>,
+}
+
+// Request for timezone conversion
+#[derive(Debug, Deserialize, Default)]
 pub struct ConvertRequest {
     pub timestamp: Option<i64>,
     pub datetime: Option<String>,
     pub from: Option<String>,
     pub to: String,
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+    // Unit of `timestamp` in the request and of both timestamps in the response
+    #[serde(default)]
+    pub unit: TimestampUnit,
+    // strftime pattern for the `formatted` field on both sides
+    pub format: Option<String>,
+    // Alternative calendars to include on both sides, e.g. ["hijri", "hebrew"]
+    #[serde(default)]
+    pub calendars: Vec<CalendarSystem>,
+    // Local business hours to check both sides against
+    pub business_hours: Option<BusinessHours>,
+}
+
+// Daily business hours, applied in each side's own timezone
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BusinessHours {
+    // "HH:MM" local time; an end at or before the start wraps past midnight
+    #[serde(default = "default_work_start")]
+    pub start: String,
+    #[serde(default = "default_work_end")]
+    pub end: String,
+    #[serde(default)]
+    pub include_weekends: bool,
 }
 
 // Timezone info for one side of a conversion
@@ -52,6 +667,51 @@ pub struct ConvertTimezoneInfo {
     pub abbreviation: String,
     pub is_dst: bool,
     pub timestamp: i64,
+    #[serde(default)]
+    pub unit: TimestampUnit,
+    #[serde(default)]
+    pub timestamp_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    // Absent outside the years 0 to 9999, as in TimezoneInfo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rfc2822: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendars: Option<CalendarDates>,
+    // Set when the requested local time did not exist and was shifted forward
+    #[serde(default)]
+    pub was_adjusted: bool,
+    #[serde(default)]
+    pub adjustment_seconds: i64,
+    // Present when the request includes `business_hours`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_within_business_hours: Option<bool>,
+    // Next start of business hours after this instant, in this timezone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_opening: Option<String>,
+}
+
+// Current time for one zone of a bulk request, or why it could not be resolved
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZoneTimeResult {
+    // The name as requested
+    pub timezone: String,
+    // The team member whose home zone this is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<TimezoneInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Current time in several timezones, in request order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkTimeResponse {
+    pub results: Vec<ZoneTimeResult>,
+    pub errors: usize,
 }
 
 // Response for timezone conversion
@@ -59,6 +719,685 @@ pub struct ConvertTimezoneInfo {
 pub struct ConvertResponse {
     pub from: ConvertTimezoneInfo,
     pub to: ConvertTimezoneInfo,
+    // Target UTC offset minus the source's: -32400 reads "9 hours behind"
+    #[serde(default)]
+    pub offset_difference_seconds: i32,
+    // Target local date minus the source's: -1 is the previous day, 1 the next. Only the
+    // 26-hour spread between UTC-12 and UTC+14 can make it 2.
+    #[serde(default)]
+    pub day_shift: i64,
+}
+
+// Query parameters for flexible datetime parsing
+#[derive(Debug, Deserialize)]
+pub struct ParseQuery {
+    pub value: String,
+    // Timezone for inputs without an offset (defaults to UTC)
+    pub tz: Option<String>,
+}
+
+// Response for flexible datetime parsing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParseResponse {
+    pub input: String,
+    pub normalized: String,
+    pub timezone: String,
+    pub timestamp: i64,
+    // Name or strftime layout of the format that matched, e.g. "rfc2822" or "%Y/%m/%d %H:%M"
+    pub pattern: String,
+}
+
+// Query parameters for Julian/ordinal date conversion; exactly one must be set
+#[derive(Debug, Deserialize, Default)]
+pub struct JulianQuery {
+    pub timestamp: Option<i64>,
+    pub datetime: Option<String>,
+    pub jd: Option<f64>,
+    pub mjd: Option<f64>,
+    // Year and day of year, e.g. "2025-041"
+    pub ordinal: Option<String>,
+}
+
+// An instant expressed as epoch, ISO datetime, Julian Day, MJD, and ordinal date (all UTC)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JulianDateResponse {
+    pub timestamp: i64,
+    pub datetime: String,
+    pub julian_day: f64,
+    // Integer day number of the Julian Day that contains the instant (days start at noon)
+    pub julian_day_number: i64,
+    pub modified_julian_date: f64,
+    pub ordinal_date: String,
+    pub year: i32,
+    pub day_of_year: u32,
+}
+
+// Query parameters for UTC/TAI/GPS conversion; exactly one input must be set
+#[derive(Debug, Deserialize, Default)]
+pub struct TimescaleQuery {
+    // Unix seconds (UTC)
+    pub timestamp: Option<i64>,
+    // Unix seconds plus TAI - UTC, as CLOCK_TAI counts
+    pub tai: Option<i64>,
+    // Seconds since the GPS epoch, or week number and seconds into the week
+    pub gps: Option<i64>,
+    pub gps_week: Option<i64>,
+    pub gps_seconds: Option<i64>,
+}
+
+// An instant on the UTC, TAI, and GPS time scales
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimescaleResponse {
+    // "23:59:60" during a leap second
+    pub utc: String,
+    pub timestamp: i64,
+    pub is_leap_second: bool,
+    pub tai: i64,
+    pub tai_minus_utc: i32,
+    pub gps: i64,
+    pub gps_week: i64,
+    pub gps_seconds: i64,
+    pub gps_minus_utc: i32,
+}
+
+// Query parameters for ISO week lookup
+#[derive(Debug, Deserialize, Default)]
+pub struct WeekQuery {
+    // Instant to look up (defaults to now); wall-clock values are read in the path timezone
+    pub at: Option<String>,
+}
+
+// ISO 8601 week information in a timezone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeekInfo {
+    pub timezone: String,
+    pub at: String,
+    pub iso_year: i32,
+    pub iso_week: u32,
+    // e.g. "2025-W01-3"
+    pub iso_week_date: String,
+    // 1 = Monday ... 7 = Sunday
+    pub day_of_week: u32,
+    pub day_name: String,
+    // Local Monday 00:00 of the week
+    pub week_start: String,
+    pub week_start_timestamp: i64,
+    // Local Monday 00:00 of the following week (exclusive end)
+    pub week_end: String,
+    pub week_end_timestamp: i64,
+}
+
+// Query parameters for the month calendar lookup
+#[derive(Debug, Deserialize, Default)]
+pub struct CalendarQuery {
+    pub year: i32,
+    // 1 = January ... 12 = December
+    pub month: u32,
+    // Zone whose calendar the month is laid out in (defaults to UTC)
+    pub tz: Option<String>,
+}
+
+// An ISO week overlapping a month, from local Monday 00:00 to the next Monday 00:00
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarWeek {
+    pub iso_year: i32,
+    pub iso_week: u32,
+    pub start: String,
+    pub start_timestamp: i64,
+    pub end: String,
+    pub end_timestamp: i64,
+}
+
+// Month edges and week boundaries in a timezone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarInfo {
+    pub timezone: String,
+    pub year: i32,
+    pub month: u32,
+    pub month_name: String,
+    pub is_leap_year: bool,
+    pub days_in_month: u32,
+    pub days_in_year: u32,
+    // Local midnight starting the first day of the month
+    pub first_instant: String,
+    pub first_timestamp: i64,
+    // Last whole second of the month; the next month starts one second later
+    pub last_instant: String,
+    pub last_timestamp: i64,
+    pub weeks: Vec<CalendarWeek>,
+}
+
+// Query parameters for ISO 8601 interval resolution
+#[derive(Debug, Deserialize, Default)]
+pub struct IntervalQuery {
+    // "start/end", "start/duration", "duration/end", or a bare duration like "P1Y2M10DT2H"
+    pub interval: String,
+    // Zone for wall-clock values and calendar arithmetic (defaults to UTC)
+    pub tz: Option<String>,
+    // Start of a bare duration (defaults to now)
+    pub at: Option<String>,
+}
+
+// An interval resolved to instants in a timezone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntervalResponse {
+    pub timezone: String,
+    pub interval: String,
+    // Canonical form of the interval's duration, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    pub start: String,
+    pub start_timestamp: i64,
+    pub end: String,
+    pub end_timestamp: i64,
+    pub total_seconds: i64,
+}
+
+// Query parameters for the age calculator
+#[derive(Debug, Deserialize, Default)]
+pub struct AgeQuery {
+    // Date or datetime of birth; wall-clock values are read in `tz`
+    pub birth: String,
+    // Zone whose calendar the age is counted in (defaults to UTC)
+    pub tz: Option<String>,
+    // Instant to measure the age at (defaults to now)
+    pub at: Option<String>,
+}
+
+// Exact age on the local calendar and in elapsed time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgeResponse {
+    pub timezone: String,
+    pub birth: String,
+    pub at: String,
+    pub years: i32,
+    pub months: u32,
+    pub days: u32,
+    pub total_days: i64,
+    pub total_seconds: i64,
+    // A 29 February birthday falls on 28 February in common years
+    pub next_birthday: String,
+    pub days_until_birthday: i64,
+}
+
+// Current server epoch in several units plus clock-quality metadata
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpochNow {
+    pub seconds: i64,
+    pub milliseconds: i64,
+    pub microseconds: i64,
+    pub nanoseconds: i64,
+    pub iso8601: String,
+    // Time since server start by the monotonic clock
+    pub monotonic_uptime_ms: u64,
+    // Wall-clock elapsed minus monotonic elapsed since start; non-zero after clock steps
+    pub wall_clock_drift_ms: i64,
+    // Monotonic time spent reading the wall clock, an upper bound on sampling error
+    pub sample_jitter_ns: u64,
+}
+
+// How a duration is applied to a zoned datetime
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AddMode {
+    // Move the local clock reading ("1 day" keeps the same time of day across DST changes)
+    #[default]
+    WallClock,
+    // Add elapsed time ("1 day" is always 86400 seconds)
+    Absolute,
+}
+
+// Request for DST-aware date arithmetic
+#[derive(Debug, Deserialize, Default)]
+pub struct AddRequest {
+    pub datetime: String,
+    pub timezone: String,
+    #[serde(default)]
+    pub days: i64,
+    #[serde(default)]
+    pub hours: i64,
+    #[serde(default)]
+    pub minutes: i64,
+    #[serde(default)]
+    pub seconds: i64,
+    #[serde(default)]
+    pub mode: AddMode,
+    // Applies when a wall-clock result lands in a DST gap
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+// Result of date arithmetic, both ends in the request timezone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddResponse {
+    pub mode: AddMode,
+    pub start: ConvertTimezoneInfo,
+    pub result: ConvertTimezoneInfo,
+    // Actual elapsed time between start and result
+    pub elapsed_seconds: i64,
+}
+
+// One end of a trip: a timezone, Rails name, or airport code, and optionally a time there
+#[derive(Debug, Deserialize)]
+pub struct ItineraryStop {
+    pub place: String,
+    // Any format /api/parse accepts; wall-clock values are read in the place's zone
+    #[serde(default)]
+    pub local_time: Option<String>,
+}
+
+// A trip from a departure time to either an arrival time or a duration
+#[derive(Debug, Deserialize)]
+pub struct ItineraryRequest {
+    // `local_time` is required here
+    pub departure: ItineraryStop,
+    pub arrival: ItineraryStop,
+    // Travel time, when the arrival has no `local_time`
+    #[serde(default)]
+    pub duration_minutes: Option<i64>,
+}
+
+// One end of a trip in local time and UTC
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItineraryEnd {
+    pub place: String,
+    pub local: ConvertTimezoneInfo,
+    pub utc: String,
+}
+
+// Both ends of a trip with the elapsed time between them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItineraryResponse {
+    pub departure: ItineraryEnd,
+    pub arrival: ItineraryEnd,
+    pub duration_minutes: i64,
+    // "11h 25m"
+    pub duration: String,
+    // Local arrival date minus local departure date: 1 lands "the next day", -1 the day
+    // before (westbound over the date line)
+    pub day_offset: i64,
+    // Arrival zone's UTC offset minus the departure zone's at the respective times
+    pub offset_change_seconds: i32,
+}
+
+// Request for a jet lag adjustment schedule
+#[derive(Debug, Deserialize)]
+pub struct JetLagRequest {
+    // Timezone, Rails name, or airport code
+    pub origin: String,
+    pub destination: String,
+    // Local date of arrival, "YYYY-MM-DD"
+    pub travel_date: String,
+    // Usual "HH:MM" sleep times at home
+    #[serde(default = "default_bedtime")]
+    pub bedtime: String,
+    #[serde(default = "default_wake_time")]
+    pub wake_time: String,
+    // Days before travel to start shifting
+    #[serde(default = "default_prepare_days")]
+    pub prepare_days: u32,
+    // Defaults to 60 when advancing the body clock and 90 when delaying it
+    #[serde(default)]
+    pub shift_minutes_per_day: Option<u32>,
+}
+
+fn default_bedtime() -> String {
+    "23:00".to_string()
+}
+
+fn default_wake_time() -> String {
+    "07:00".to_string()
+}
+
+fn default_prepare_days() -> u32 {
+    3
+}
+
+// Which way the body clock has to move
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockShift {
+    // Sleep earlier (usually flying east)
+    Advance,
+    // Sleep later (usually flying west)
+    Delay,
+    None,
+}
+
+// One day of a jet lag schedule, in the local time of wherever the traveler is that day
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JetLagDay {
+    pub date: String,
+    pub timezone: String,
+    // "HH:MM"; a wake time before the bedtime is the next morning
+    pub bedtime: String,
+    pub wake_time: String,
+    // Shift applied on this day and still left afterwards
+    pub shift_minutes: u32,
+    pub remaining_minutes: u32,
+    // "HH:MM-HH:MM" windows that help the body clock move the right way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seek_light: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avoid_light: Option<String>,
+}
+
+// A day-by-day plan for moving sleep from the origin's clock to the destination's
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JetLagPlan {
+    pub origin: String,
+    pub destination: String,
+    // Destination offset minus origin offset on the travel date, taken the short way
+    // around the clock (within +-12 hours)
+    pub offset_difference_seconds: i32,
+    pub offset_difference: String,
+    pub direction: ClockShift,
+    pub shift_minutes_per_day: u32,
+    pub days: Vec<JetLagDay>,
+}
+
+// A meeting participant and their local working hours
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Participant {
+    pub name: Option<String>,
+    pub timezone: String,
+    // "HH:MM" local time; an end at or before the start wraps past midnight
+    #[serde(default = "default_work_start")]
+    pub work_start: String,
+    #[serde(default = "default_work_end")]
+    pub work_end: String,
+    #[serde(default)]
+    pub include_weekends: bool,
+}
+
+fn default_work_start() -> String {
+    "09:00".to_string()
+}
+
+fn default_work_end() -> String {
+    "17:00".to_string()
+}
+
+// Request for meeting slot suggestions
+#[derive(Debug, Deserialize)]
+pub struct MeetingRequest {
+    #[serde(default)]
+    pub participants: Vec<Participant>,
+    // Name of a saved team whose members join the participants
+    pub team: Option<String>,
+    // First UTC day to search, "YYYY-MM-DD" (defaults to today)
+    pub date: Option<String>,
+    #[serde(default = "default_meeting_days")]
+    pub days: u32,
+    #[serde(default = "default_meeting_duration")]
+    pub duration_minutes: u32,
+    // Spacing between candidate start times
+    #[serde(default = "default_meeting_step")]
+    pub step_minutes: u32,
+    #[serde(default = "default_meeting_limit")]
+    pub limit: usize,
+}
+
+fn default_meeting_days() -> u32 {
+    1
+}
+
+fn default_meeting_duration() -> u32 {
+    60
+}
+
+fn default_meeting_step() -> u32 {
+    30
+}
+
+fn default_meeting_limit() -> usize {
+    10
+}
+
+// One participant's view of a candidate slot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttendeeTime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub timezone: String,
+    pub local_start: String,
+    pub local_end: String,
+    // Whether the slot is inside this participant's working hours
+    pub available: bool,
+}
+
+// A candidate meeting slot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeetingSlot {
+    pub start: String,
+    pub start_timestamp: i64,
+    pub end: String,
+    pub end_timestamp: i64,
+    // Number of participants inside their working hours
+    pub available: usize,
+    pub all_available: bool,
+    pub attendees: Vec<AttendeeTime>,
+}
+
+// Ranked meeting slots, best first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeetingSuggestions {
+    pub duration_minutes: u32,
+    pub participants: usize,
+    pub slots: Vec<MeetingSlot>,
+}
+
+// A team member to add, or the new details of one
+#[derive(Debug, Deserialize)]
+pub struct TeamMemberRequest {
+    pub name: String,
+    // Home timezone; stored under its canonical IANA name
+    pub timezone: String,
+    // "HH:MM" local time; an end at or before the start wraps past midnight
+    #[serde(default = "default_work_start")]
+    pub work_start: String,
+    #[serde(default = "default_work_end")]
+    pub work_end: String,
+    #[serde(default)]
+    pub include_weekends: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TeamMember {
+    pub id: String,
+    pub name: String,
+    pub timezone: String,
+    pub work_start: String,
+    pub work_end: String,
+    pub include_weekends: bool,
+}
+
+impl From<TeamMember> for Participant {
+    fn from(member: TeamMember) -> Self {
+        Participant {
+            name: Some(member.name),
+            timezone: member.timezone,
+            work_start: member.work_start,
+            work_end: member.work_end,
+            include_weekends: member.include_weekends,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTeamRequest {
+    // Letters, digits, '-', '_', and '.', for use in URLs: "engineering"
+    pub name: String,
+    #[serde(default)]
+    pub members: Vec<TeamMemberRequest>,
+}
+
+// A saved team, shared by the keys of an organization or private to one key
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Team {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub members: Vec<TeamMember>,
+}
+
+// Request for a timezone offset difference matrix
+#[derive(Debug, Deserialize)]
+pub struct MatrixRequest {
+    pub timezones: Vec<String>,
+    // Instant to compare at (defaults to now)
+    pub at: Option<String>,
+}
+
+// N×N offset differences; [i][j] is how far timezones[j] is ahead of timezones[i]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatrixResponse {
+    pub at: String,
+    pub timezones: Vec<String>,
+    pub utc_offsets: Vec<String>,
+    pub offset_seconds: Vec<Vec<i32>>,
+    // Same as offset_seconds, formatted as "+05:30"
+    pub differences: Vec<Vec<String>>,
+    // Whether the difference for a pair changes over the next 30 days
+    pub changes_within_30_days: Vec<Vec<bool>>,
+    pub any_changes: bool,
+}
+
+// Query parameters for the holiday calendar
+#[derive(Debug, Deserialize, Default)]
+pub struct HolidayQuery {
+    // Defaults to the current year in the timezone
+    pub year: Option<i32>,
+    // Zone for day boundaries (defaults to the country's primary zone)
+    pub tz: Option<String>,
+}
+
+// A public holiday with its local day bounds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Holiday {
+    pub name: String,
+    pub local_name: String,
+    // Local calendar date, e.g. "2025-12-25"
+    pub date: String,
+    pub day_name: String,
+    // Substitute day off when the holiday falls on a weekend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed: Option<String>,
+    // Local 00:00 of the date
+    pub start: String,
+    pub start_timestamp: i64,
+    // Local 00:00 of the following day (exclusive end)
+    pub end: String,
+    pub end_timestamp: i64,
+}
+
+// Public holidays of a country for one year
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HolidaysResponse {
+    pub country: String,
+    pub country_name: String,
+    pub year: i32,
+    pub timezone: String,
+    pub holidays: Vec<Holiday>,
+}
+
+// Request for RRULE (RFC 5545) recurrence expansion
+#[derive(Debug, Deserialize, Default)]
+pub struct RecurrenceRequest {
+    // e.g. "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10"
+    pub rrule: String,
+    // First occurrence, as wall-clock time in `timezone`
+    pub dtstart: String,
+    pub timezone: String,
+    // Window to return occurrences for; `start` defaults to dtstart
+    pub start: Option<String>,
+    pub end: String,
+    // Extra timezones to show each occurrence in
+    #[serde(default)]
+    pub to: Vec<String>,
+}
+
+// One occurrence of a recurrence rule
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Occurrence {
+    pub timestamp: i64,
+    pub local: ConvertTimezoneInfo,
+    pub conversions: Vec<ConvertTimezoneInfo>,
+}
+
+// Expanded occurrences within the requested window
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecurrenceResponse {
+    pub rrule: String,
+    pub timezone: String,
+    pub count: usize,
+    // Set when the window held more occurrences than are returned
+    pub truncated: bool,
+    pub occurrences: Vec<Occurrence>,
+}
+
+// Calendar units used by humanized output
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+// Query parameters for relative time formatting
+#[derive(Debug, Deserialize)]
+pub struct RelativeTimeQuery {
+    pub timestamp: i64,
+    // Reference instant (defaults to now)
+    pub now: Option<i64>,
+    // BCP 47 language tag, e.g. "de" (defaults to "en")
+    pub locale: Option<String>,
+    // Finest unit to report; smaller differences read as "just now"
+    pub granularity: Option<TimeUnit>,
+}
+
+// Humanized relative time, e.g. "3 hours ago"
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelativeTimeResponse {
+    pub text: String,
+    // Signed count of `unit` (negative in the past)
+    pub value: i64,
+    pub unit: TimeUnit,
+    // Exact signed difference from the reference instant
+    pub seconds: i64,
+    pub locale: String,
+}
+
+// Query parameters for humanized duration formatting
+#[derive(Debug, Deserialize)]
+pub struct DurationQuery {
+    pub seconds: i64,
+    pub locale: Option<String>,
+    // Largest unit to use (defaults to year), e.g. "day" gives "10 days" not "1 week, 3 days"
+    pub max_unit: Option<TimeUnit>,
+    // Smallest unit to use (defaults to second)
+    pub min_unit: Option<TimeUnit>,
+}
+
+// One component of a humanized duration
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DurationPart {
+    pub value: i64,
+    pub unit: TimeUnit,
+}
+
+// Humanized duration, e.g. "2 days, 4 hours, 3 minutes"
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HumanDurationResponse {
+    pub text: String,
+    pub seconds: i64,
+    pub parts: Vec<DurationPart>,
+    pub locale: String,
 }
 
 // Query parameters for coordinate-based timezone lookup
@@ -66,18 +1405,185 @@ pub struct ConvertResponse {
 pub struct GeolocationQuery {
     pub lat: f64,
     pub lng: f64,
+    // Wrap longitudes outside [-180, 180] (200 -> -160) instead of rejecting them
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+// GeoJSON body for coordinate lookups: a Point geometry, or a Feature wrapping one.
+// Other members (properties, id, bbox) are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum GeoJsonInput {
+    Point { coordinates: Vec<f64> },
+    Feature { geometry: GeoJsonGeometry },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum GeoJsonGeometry {
+    Point { coordinates: Vec<f64> },
+}
+
+// Query parameters for sunrise and sunset at a location
+#[derive(Debug, Deserialize)]
+pub struct SunQuery {
+    pub lat: f64,
+    pub lng: f64,
+    // Local date as YYYY-MM-DD (defaults to today in the location's zone)
+    pub date: Option<String>,
+}
+
+// Whether the sun stays up or down all day at high latitudes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolarCondition {
+    PolarDay,
+    PolarNight,
+}
+
+// Sunrise, sunset, day length, solar noon, and twilight in the location's zone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SunTimes {
+    pub timezone: String,
+    pub date: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    // Absent on days without a sunrise or sunset (see `polar`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sunrise: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sunrise_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sunset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sunset_timestamp: Option<i64>,
+    pub day_length_seconds: i64,
+    // "15:38:12"
+    pub day_length: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polar: Option<PolarCondition>,
+    // When the sun is highest
+    pub solar_noon: String,
+    pub solar_noon_timestamp: i64,
+    pub twilight: TwilightTimes,
+}
+
+// Why a twilight has no dawn or dusk on a date
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TwilightCondition {
+    // The sun never sinks below this twilight's depth (e.g. white nights)
+    NeverDark,
+    // The sun never rises above this twilight's depth
+    NeverLight,
+}
+
+// Start of morning twilight and end of evening twilight for one depth
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Twilight {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dawn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dawn_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dusk: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dusk_timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<TwilightCondition>,
+}
+
+// Civil (sun 6° below the horizon), nautical (12°), and astronomical (18°) twilight
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwilightTimes {
+    pub civil: Twilight,
+    pub nautical: Twilight,
+    pub astronomical: Twilight,
+}
+
+// Coordinates to resolve in one batch geolocation request
+#[derive(Debug, Deserialize)]
+pub struct BatchGeolocationRequest {
+    pub points: Vec<GeolocationQuery>,
+    // Applies `normalize` to every point
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+// The zone at one point of a batch, or why it could not be resolved
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GeolocationResult {
+    pub lat: f64,
+    pub lng: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<TimezoneInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Machine-readable reason for rejected coordinates, e.g. "latitude_out_of_range"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+}
+
+// Zones for a batch of coordinates, in request order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchGeolocationResponse {
+    pub results: Vec<GeolocationResult>,
+    pub errors: usize,
+}
+
+// An airport looked up by IATA or ICAO code, with the time at its zone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirportInfo {
+    pub iata: String,
+    pub icao: String,
+    pub name: String,
+    pub city: String,
+    pub country_code: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: TimezoneInfo,
+}
+
+// An IP address located with the GeoIP database, with the time at its zone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IpInfo {
+    pub ip: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+    // Radius around the coordinates that the address is likely within
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy_km: Option<u16>,
+    pub timezone: TimezoneInfo,
 }
 
 // Error response structure
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    // Machine-readable error code, for errors clients are expected to branch on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 
 impl ErrorResponse {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             error: message.into(),
+            code: None,
+        }
+    }
+
+    pub fn with_code(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            error: message.into(),
+            code: Some(code.into()),
         }
     }
 }
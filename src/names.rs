@@ -0,0 +1,144 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use chrono_tz::Tz;
+use icu_datetime::provider::time_zones::{
+    ExemplarCitiesV1Marker, MetazoneGenericNamesLongV1Marker,
+};
+use icu_datetime::provider::Baked;
+use icu_locid::Locale;
+use icu_provider::prelude::*;
+use icu_timezone::{MetazoneCalculator, TimeZoneIdMapper};
+
+// Localized zone display names from the CLDR data compiled into ICU4X, e.g.
+// "Heure d’Europe centrale – Paris" for Europe/Paris in French
+pub struct ZoneNames {
+    cities: DataPayload<ExemplarCitiesV1Marker>,
+    generic: DataPayload<MetazoneGenericNamesLongV1Marker>,
+    ids: TimeZoneIdMapper,
+    metazones: MetazoneCalculator,
+}
+
+impl ZoneNames {
+    // Load names for a BCP 47 language tag ("fr", "de-CH", "sr-Latn"). Regional variants
+    // fall back to their language; languages CLDR has no zone names for are rejected.
+    pub fn for_language(lang: &str) -> Result<Self, String> {
+        let locale: Locale = lang
+            .parse()
+            .map_err(|_| format!("Invalid language tag: {}", lang))?;
+        let data_locale = DataLocale::from(&locale);
+        let request = DataRequest {
+            locale: &data_locale,
+            metadata: Default::default(),
+        };
+        let unsupported = |_| format!("Unsupported language: {}", lang);
+        let cities: DataResponse<ExemplarCitiesV1Marker> =
+            Baked.load(request).map_err(unsupported)?;
+        let generic: DataResponse<MetazoneGenericNamesLongV1Marker> =
+            Baked.load(request).map_err(unsupported)?;
+        let (cities, generic) = match (cities.payload, generic.payload) {
+            (Some(cities), Some(generic)) => (cities, generic),
+            _ => return Err(format!("Unsupported language: {}", lang)),
+        };
+        // Root data has exemplar cities but no metazone names
+        if generic.get().defaults.is_empty() {
+            return Err(format!("Unsupported language: {}", lang));
+        }
+
+        Ok(ZoneNames {
+            cities,
+            generic,
+            ids: TimeZoneIdMapper::new(),
+            metazones: MetazoneCalculator::new(),
+        })
+    }
+
+    // "<generic zone name> – <exemplar city>" for the zone's metazone at `at`. Zones
+    // without a city (UTC) get the name alone; None when CLDR has neither.
+    pub fn display_name(&self, tz: &Tz, at: &DateTime<Utc>) -> Option<String> {
+        let id = self.ids.as_borrowed().iana_to_bcp47(tz.name())?;
+        let city = self.cities.get().0.get(&id).map(str::to_string);
+
+        let local = at.with_timezone(tz);
+        let local = icu_calendar::DateTime::try_new_iso_datetime(
+            local.year(),
+            local.month() as u8,
+            local.day() as u8,
+            local.hour() as u8,
+            local.minute() as u8,
+            local.second() as u8,
+        )
+        .ok()?;
+        let generic = self.generic.get();
+        let name = generic.overrides.get(&id).map(str::to_string).or_else(|| {
+            let metazone = self.metazones.compute_metazone_from_time_zone(id, &local)?;
+            generic.defaults.get(&metazone).map(str::to_string)
+        });
+
+        match (name.map(capitalize), city) {
+            (Some(name), Some(city)) => Some(format!("{} – {}", name, city)),
+            (Some(name), None) => Some(name),
+            (None, city) => city,
+        }
+    }
+}
+
+fn capitalize(value: String) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn name(lang: &str, zone: &str) -> Option<String> {
+        let at = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        ZoneNames::for_language(lang)
+            .unwrap()
+            .display_name(&zone.parse().unwrap(), &at)
+    }
+
+    #[test]
+    fn test_localized_names() {
+        assert_eq!(
+            name("fr", "Europe/Paris").unwrap(),
+            "Heure d’Europe centrale – Paris"
+        );
+        assert_eq!(
+            name("de", "Europe/Belgrade").unwrap(),
+            "Mitteleuropäische Zeit – Belgrad"
+        );
+        assert_eq!(
+            name("sr-Latn", "Europe/Belgrade").unwrap(),
+            "Srednjeevropsko vreme – Beograd"
+        );
+        // Links resolve to the same CLDR zone
+        assert_eq!(name("de", "Asia/Calcutta"), name("de", "Asia/Kolkata"));
+        assert_eq!(name("en", "UTC").unwrap(), "Coordinated Universal Time");
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(ZoneNames::for_language("xx").is_err());
+        assert!(ZoneNames::for_language("not a tag").is_err());
+    }
+}
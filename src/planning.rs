@@ -0,0 +1,346 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::models::{AttendeeTime, MeetingRequest, MeetingSlot, MeetingSuggestions};
+use crate::zones;
+
+const MAX_PARTICIPANTS: usize = 50;
+const MAX_SEARCH_DAYS: u32 = 14;
+const MAX_SUGGESTIONS: usize = 100;
+
+// A daily local working window. An end at or before the start (e.g. 22:00-06:00) runs
+// past midnight; each window belongs to the day it opens on.
+pub struct WorkingHours {
+    start: NaiveTime,
+    end: NaiveTime,
+    include_weekends: bool,
+}
+
+impl WorkingHours {
+    pub fn new(start: NaiveTime, end: NaiveTime, include_weekends: bool) -> Self {
+        WorkingHours {
+            start,
+            end,
+            include_weekends,
+        }
+    }
+
+    // Whether the local span [from, to] lies inside a single window
+    pub fn contains(&self, from: NaiveDateTime, to: NaiveDateTime) -> bool {
+        self.windows_around(from).any(|(open, close)| open <= from && to <= close)
+    }
+
+    // Whether a window is open at the local time `at`; it is closed from the closing time on
+    pub fn is_open_at(&self, at: NaiveDateTime) -> bool {
+        self.windows_around(at).any(|(open, close)| open <= at && at < close)
+    }
+
+    // The windows opening on the day of `at` and the day before, which may run past midnight
+    fn windows_around(
+        &self,
+        at: NaiveDateTime,
+    ) -> impl Iterator<Item = (NaiveDateTime, NaiveDateTime)> + '_ {
+        let day = at.date();
+        [day.pred_opt(), Some(day)]
+            .into_iter()
+            .flatten()
+            .filter(|d| self.opens_on(*d))
+            .map(|d| self.window_on(d))
+    }
+
+    // First window opening strictly after `instant`, in the instant's own zone.
+    // An opening time skipped by a DST gap is taken an hour later.
+    pub fn next_opening<T: TimeZone>(&self, instant: &DateTime<T>) -> Option<DateTime<T>> {
+        let tz = instant.timezone();
+        instant
+            .naive_local()
+            .date()
+            .iter_days()
+            .take(9)
+            .filter(|d| self.opens_on(*d))
+            .filter_map(|d| {
+                let open = d.and_time(self.start);
+                tz.from_local_datetime(&open)
+                    .earliest()
+                    .or_else(|| tz.from_local_datetime(&(open + Duration::hours(1))).earliest())
+            })
+            .find(|open| open > instant)
+    }
+
+    fn opens_on(&self, day: NaiveDate) -> bool {
+        self.include_weekends || !matches!(day.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+
+    fn window_on(&self, day: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        let open = day.and_time(self.start);
+        let close_day = if self.end <= self.start {
+            day.succ_opt().unwrap_or(day)
+        } else {
+            day
+        };
+        (open, close_day.and_time(self.end))
+    }
+}
+
+// A participant with parsed zone and working hours
+struct Availability {
+    name: Option<String>,
+    tz: Tz,
+    hours: WorkingHours,
+}
+
+impl Availability {
+    // Whether [start, end) lies inside one of this participant's working windows
+    fn is_available(&self, start: &DateTime<Utc>, end: &DateTime<Utc>) -> bool {
+        let local_start = start.with_timezone(&self.tz).naive_local();
+        let local_end = end.with_timezone(&self.tz).naive_local();
+        self.hours.contains(local_start, local_end)
+    }
+}
+
+// Parse an "HH:MM" local time, naming the field in the error
+pub fn parse_time(value: &str, field: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("Invalid {} '{}' (expected HH:MM)", field, value))
+}
+
+// Suggest meeting slots for participants in different timezones, ranked by how many
+// participants are inside their working hours (ties go to the earlier slot).
+pub fn suggest_meetings(request: &MeetingRequest) -> Result<MeetingSuggestions, String> {
+    if request.participants.is_empty() {
+        return Err("At least one participant is required".to_string());
+    }
+    if request.participants.len() > MAX_PARTICIPANTS {
+        return Err(format!("At most {} participants are supported", MAX_PARTICIPANTS));
+    }
+    if !(1..=MAX_SEARCH_DAYS).contains(&request.days) {
+        return Err(format!("'days' must be between 1 and {}", MAX_SEARCH_DAYS));
+    }
+    if !(5..=24 * 60).contains(&request.duration_minutes) {
+        return Err("'duration_minutes' must be between 5 and 1440".to_string());
+    }
+    if !(5..=24 * 60).contains(&request.step_minutes) {
+        return Err("'step_minutes' must be between 5 and 1440".to_string());
+    }
+    let limit = request.limit.clamp(1, MAX_SUGGESTIONS);
+
+    let participants = request
+        .participants
+        .iter()
+        .map(|p| {
+            Ok(Availability {
+                name: p.name.clone(),
+                tz: zones::parse_timezone(&p.timezone)
+                    .ok_or_else(|| zones::invalid_timezone(&p.timezone))?,
+                hours: WorkingHours::new(
+                    parse_time(&p.work_start, "work_start")?,
+                    parse_time(&p.work_end, "work_end")?,
+                    p.include_weekends,
+                ),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let first_day = match &request.date {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .filter(|day| (1800..=9999).contains(&day.year()))
+            .ok_or_else(|| {
+                format!("Invalid date '{}' (expected YYYY-MM-DD, 1800 to 9999)", date)
+            })?,
+        None => Utc::now().date_naive(),
+    };
+    let search_start = first_day.and_time(NaiveTime::MIN).and_utc();
+    let search_end = search_start
+        .checked_add_signed(Duration::days(request.days as i64))
+        .ok_or_else(|| "Search window is out of range".to_string())?;
+    let duration = Duration::minutes(request.duration_minutes as i64);
+    let step = Duration::minutes(request.step_minutes as i64);
+
+    let mut candidates: Vec<(usize, DateTime<Utc>)> = Vec::new();
+    let mut start = search_start;
+    while start + duration <= search_end {
+        let end = start + duration;
+        let available = participants
+            .iter()
+            .filter(|p| p.is_available(&start, &end))
+            .count();
+        if available > 0 {
+            candidates.push((available, start));
+        }
+        start += step;
+    }
+    // Stable sort keeps chronological order within the same availability
+    candidates.sort_by_key(|(available, _)| std::cmp::Reverse(*available));
+
+    let slots = candidates
+        .into_iter()
+        .take(limit)
+        .map(|(available, start)| {
+            let end = start + duration;
+            let attendees = participants
+                .iter()
+                .map(|p| AttendeeTime {
+                    name: p.name.clone(),
+                    timezone: p.tz.name().to_string(),
+                    local_start: start.with_timezone(&p.tz).to_rfc3339(),
+                    local_end: end.with_timezone(&p.tz).to_rfc3339(),
+                    available: p.is_available(&start, &end),
+                })
+                .collect();
+            MeetingSlot {
+                start: start.to_rfc3339(),
+                start_timestamp: start.timestamp(),
+                end: end.to_rfc3339(),
+                end_timestamp: end.timestamp(),
+                available,
+                all_available: available == participants.len(),
+                attendees,
+            }
+        })
+        .collect();
+
+    Ok(MeetingSuggestions {
+        duration_minutes: request.duration_minutes,
+        participants: participants.len(),
+        slots,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Participant;
+
+    fn participant(timezone: &str, work_start: &str, work_end: &str) -> Participant {
+        Participant {
+            name: None,
+            timezone: timezone.to_string(),
+            work_start: work_start.to_string(),
+            work_end: work_end.to_string(),
+            include_weekends: false,
+        }
+    }
+
+    fn request(participants: Vec<Participant>) -> MeetingRequest {
+        MeetingRequest {
+            participants,
+            team: None,
+            // A Wednesday
+            date: Some("2025-06-11".to_string()),
+            days: 1,
+            duration_minutes: 60,
+            step_minutes: 30,
+            limit: 5,
+        }
+    }
+
+    #[test]
+    fn test_suggest_full_overlap() {
+        // Belgrade 09-17 is 07-15 UTC, New York 09-17 is 13-21 UTC: overlap 13:00-15:00 UTC
+        let result = suggest_meetings(&request(vec![
+            participant("Europe/Belgrade", "09:00", "17:00"),
+            participant("America/New_York", "09:00", "17:00"),
+        ]))
+        .unwrap();
+
+        let best: Vec<&str> = result
+            .slots
+            .iter()
+            .filter(|s| s.all_available)
+            .map(|s| s.start.as_str())
+            .collect();
+        assert_eq!(
+            best,
+            [
+                "2025-06-11T13:00:00+00:00",
+                "2025-06-11T13:30:00+00:00",
+                "2025-06-11T14:00:00+00:00"
+            ]
+        );
+        assert_eq!(result.slots[0].attendees[1].local_start, "2025-06-11T09:00:00-04:00");
+    }
+
+    #[test]
+    fn test_suggest_partial_overlap_ranks_by_count() {
+        // Tokyo and New York working hours do not overlap on a weekday in June
+        let result = suggest_meetings(&request(vec![
+            participant("Asia/Tokyo", "09:00", "17:00"),
+            participant("America/New_York", "09:00", "17:00"),
+            participant("Europe/London", "09:00", "17:00"),
+        ]))
+        .unwrap();
+        assert!(result.slots.iter().all(|s| !s.all_available));
+        assert_eq!(result.slots[0].available, 2);
+    }
+
+    #[test]
+    fn test_overnight_window_and_weekends() {
+        let mut night = participant("UTC", "22:00", "06:00");
+        let slots = suggest_meetings(&request(vec![night.clone()])).unwrap().slots;
+        assert_eq!(slots[0].start, "2025-06-11T00:00:00+00:00");
+
+        // 2025-06-14 is a Saturday
+        let mut req = request(vec![participant("UTC", "09:00", "17:00")]);
+        req.date = Some("2025-06-14".to_string());
+        assert!(suggest_meetings(&req).unwrap().slots.is_empty());
+
+        night.include_weekends = true;
+        req.participants = vec![night];
+        assert!(!suggest_meetings(&req).unwrap().slots.is_empty());
+    }
+
+    #[test]
+    fn test_suggest_validation() {
+        assert!(suggest_meetings(&request(vec![])).is_err());
+        let invalid_zone = participant("Mars/Base", "09:00", "17:00");
+        assert!(suggest_meetings(&request(vec![invalid_zone])).is_err());
+        let invalid_time = participant("UTC", "9am", "17:00");
+        assert!(suggest_meetings(&request(vec![invalid_time])).is_err());
+        for date in ["+262142-12-31", "1700-01-01"] {
+            let mut req = request(vec![participant("UTC", "09:00", "17:00")]);
+            req.date = Some(date.to_string());
+            assert!(suggest_meetings(&req).is_err());
+        }
+    }
+
+    #[test]
+    fn test_working_hours_next_opening() {
+        let hours = WorkingHours::new(
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            false,
+        );
+        let tz: Tz = "Europe/Belgrade".parse().unwrap();
+        // Friday evening opens again on Monday morning
+        let friday = tz.with_ymd_and_hms(2025, 6, 13, 18, 0, 0).unwrap();
+        let next = hours.next_opening(&friday).unwrap();
+        assert_eq!(next.to_rfc3339(), "2025-06-16T09:00:00+02:00");
+        // While open, the next opening is the following day's
+        let open = tz.with_ymd_and_hms(2025, 6, 11, 10, 0, 0).unwrap();
+        assert!(hours.is_open_at(open.naive_local()));
+        // Closed from 17:00 on, though a meeting may still end then
+        let closing = tz.with_ymd_and_hms(2025, 6, 11, 17, 0, 0).unwrap().naive_local();
+        assert!(!hours.is_open_at(closing));
+        assert!(hours.contains(open.naive_local(), closing));
+        assert_eq!(hours.next_opening(&open).unwrap().to_rfc3339(), "2025-06-12T09:00:00+02:00");
+    }
+}
@@ -0,0 +1,590 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday,
+};
+
+// Most candidate times a rule may expand one period to (days times BYHOUR, BYMINUTE, and
+// BYSECOND combinations), and the most periods plus candidates one expansion may go through
+pub const MAX_PERIOD_CANDIDATES: usize = 10_000;
+pub const MAX_EXPANSION_STEPS: usize = 500_000;
+
+// Recurrence frequencies we expand. Sub-daily frequencies are not supported because
+// stepping wall-clock hours across a DST change has no single sensible answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Yearly,
+    Monthly,
+    Weekly,
+    Daily,
+}
+
+// End bound of a rule: UTC when the UNTIL value ends in 'Z', otherwise local wall-clock time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    Utc(DateTime<Utc>),
+    Local(NaiveDateTime),
+}
+
+// A parsed RFC 5545 RRULE value
+#[derive(Debug, Clone)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    by_month: Vec<u32>,
+    by_month_day: Vec<i32>,
+    by_year_day: Vec<i32>,
+    // Weekday with an optional ordinal, e.g. -1FR is (Some(-1), Fri)
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_hour: Vec<u32>,
+    by_minute: Vec<u32>,
+    by_second: Vec<u32>,
+    by_set_pos: Vec<i32>,
+    week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    // Parse a rule such as "FREQ=MONTHLY;BYDAY=-1FR;COUNT=6". An "RRULE:" prefix is accepted.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let trimmed = input.trim();
+        let body = match trimmed.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &trimmed[6..],
+            _ => trimmed,
+        };
+
+        let mut freq = None;
+        let mut rule = RecurrenceRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_year_day: Vec::new(),
+            by_day: Vec::new(),
+            by_hour: Vec::new(),
+            by_minute: Vec::new(),
+            by_second: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+        let mut seen: Vec<String> = Vec::new();
+
+        for part in body.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid RRULE part '{}' (expected NAME=VALUE)", part))?;
+            let key = key.trim().to_ascii_uppercase();
+            let value = value.trim();
+            if seen.contains(&key) {
+                return Err(format!("Duplicate RRULE part '{}'", key));
+            }
+            seen.push(key.clone());
+
+            match key.as_str() {
+                "FREQ" => freq = Some(parse_frequency(value)?),
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|i| *i >= 1)
+                        .ok_or_else(|| format!("Invalid INTERVAL '{}'", value))?;
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|c| *c >= 1)
+                            .ok_or_else(|| format!("Invalid COUNT '{}'", value))?,
+                    );
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                "BYMONTH" => rule.by_month = parse_list(&key, value, 1, 12)?,
+                "BYMONTHDAY" => rule.by_month_day = parse_signed_list(&key, value, 31)?,
+                "BYYEARDAY" => rule.by_year_day = parse_signed_list(&key, value, 366)?,
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?;
+                }
+                "BYHOUR" => rule.by_hour = parse_list(&key, value, 0, 23)?,
+                "BYMINUTE" => rule.by_minute = parse_list(&key, value, 0, 59)?,
+                "BYSECOND" => rule.by_second = parse_list(&key, value, 0, 59)?,
+                "BYSETPOS" => rule.by_set_pos = parse_signed_list(&key, value, 366)?,
+                "WKST" => rule.week_start = parse_weekday(value)?,
+                "BYWEEKNO" => return Err("BYWEEKNO is not supported".to_string()),
+                _ => return Err(format!("Unknown RRULE part '{}'", key)),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| "RRULE is missing FREQ".to_string())?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL cannot both be set".to_string());
+        }
+        let ordinals_allowed = matches!(rule.freq, Frequency::Monthly | Frequency::Yearly);
+        if !ordinals_allowed && rule.by_day.iter().any(|(n, _)| n.is_some()) {
+            return Err(
+                "BYDAY ordinals (e.g. 2MO) require FREQ=MONTHLY or FREQ=YEARLY".to_string(),
+            );
+        }
+        if rule.freq == Frequency::Weekly && !rule.by_month_day.is_empty() {
+            return Err("BYMONTHDAY cannot be used with FREQ=WEEKLY".to_string());
+        }
+        if matches!(rule.freq, Frequency::Monthly | Frequency::Weekly | Frequency::Daily)
+            && !rule.by_year_day.is_empty()
+        {
+            return Err("BYYEARDAY requires FREQ=YEARLY".to_string());
+        }
+        let days = match rule.freq {
+            Frequency::Yearly => 366,
+            Frequency::Monthly => 31,
+            Frequency::Weekly => 7,
+            Frequency::Daily => 1,
+        };
+        let times: usize = [&rule.by_hour, &rule.by_minute, &rule.by_second]
+            .iter()
+            .map(|values| defaulted(values, 0).len())
+            .product();
+        if days * times > MAX_PERIOD_CANDIDATES {
+            return Err(format!(
+                "RRULE has too many times per period (at most {} days times BYHOUR, BYMINUTE, \
+                 and BYSECOND values)",
+                MAX_PERIOD_CANDIDATES
+            ));
+        }
+        Ok(rule)
+    }
+
+    // Local wall-clock occurrences starting at `dtstart`, in order. COUNT and UNTIL are left
+    // to the caller (UNTIL may be a UTC instant); generation stops after the period that
+    // contains `horizon`.
+    pub fn occurrences(&self, dtstart: NaiveDateTime, horizon: NaiveDate) -> Occurrences<'_> {
+        let date = dtstart.date();
+        let period = match self.freq {
+            Frequency::Yearly => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+            Frequency::Monthly => NaiveDate::from_ymd_opt(date.year(), date.month(), 1),
+            Frequency::Weekly => {
+                let offset = date.weekday().days_since(self.week_start);
+                date.checked_sub_signed(Duration::days(offset as i64))
+            }
+            Frequency::Daily => Some(date),
+        };
+        Occurrences {
+            rule: self,
+            dtstart,
+            horizon,
+            period,
+            buffer: VecDeque::new(),
+            steps: 0,
+            exhausted: false,
+        }
+    }
+
+    // Candidate times of one period (before BYSETPOS and the DTSTART cut-off)
+    fn expand_period(&self, period: NaiveDate, dtstart: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let days: Vec<NaiveDate> = match self.freq {
+            Frequency::Yearly => period
+                .iter_days()
+                .take_while(|d| d.year() == period.year())
+                .collect(),
+            Frequency::Monthly => period
+                .iter_days()
+                .take_while(|d| d.month() == period.month())
+                .collect(),
+            Frequency::Weekly => period.iter_days().take(7).collect(),
+            Frequency::Daily => vec![period],
+        };
+
+        let mut times = Vec::new();
+        for hour in defaulted(&self.by_hour, dtstart.hour()) {
+            for minute in defaulted(&self.by_minute, dtstart.minute()) {
+                for second in defaulted(&self.by_second, dtstart.second()) {
+                    if let Some(time) = NaiveTime::from_hms_opt(hour, minute, second) {
+                        times.push(time);
+                    }
+                }
+            }
+        }
+
+        days.into_iter()
+            .filter(|d| self.matches_day(*d, dtstart.date()))
+            .flat_map(|d| times.iter().map(move |t| d.and_time(*t)))
+            .collect()
+    }
+
+    fn matches_day(&self, date: NaiveDate, dtstart: NaiveDate) -> bool {
+        if !self.by_month.is_empty() && !self.by_month.contains(&date.month()) {
+            return false;
+        }
+        if !self.by_year_day.is_empty() {
+            let len = days_in_year(date.year());
+            if !matches_signed(&self.by_year_day, date.ordinal() as i32, len) {
+                return false;
+            }
+        }
+        if !self.by_month_day.is_empty() {
+            let len = days_in_month(date);
+            if !matches_signed(&self.by_month_day, date.day() as i32, len) {
+                return false;
+            }
+        }
+        if !self.by_day.is_empty() {
+            // Ordinals count within the month for MONTHLY (and YEARLY with BYMONTH),
+            // otherwise within the year
+            let within_month = self.freq == Frequency::Monthly
+                || (self.freq == Frequency::Yearly && !self.by_month.is_empty());
+            let (position, len) = if within_month {
+                (date.day() as i32, days_in_month(date))
+            } else {
+                (date.ordinal() as i32, days_in_year(date.year()))
+            };
+            let from_start = (position - 1) / 7 + 1;
+            let from_end = -((len - position) / 7 + 1);
+            let matched = self.by_day.iter().any(|(ordinal, weekday)| {
+                *weekday == date.weekday()
+                    && ordinal.is_none_or(|n| n == from_start || n == from_end)
+            });
+            if !matched {
+                return false;
+            }
+        }
+
+        // Without day-level BY* parts the rule repeats on DTSTART's day
+        let no_day_rules =
+            self.by_month_day.is_empty() && self.by_year_day.is_empty() && self.by_day.is_empty();
+        match self.freq {
+            Frequency::Yearly if no_day_rules => {
+                date.day() == dtstart.day()
+                    && (!self.by_month.is_empty() || date.month() == dtstart.month())
+            }
+            Frequency::Monthly if no_day_rules => date.day() == dtstart.day(),
+            Frequency::Weekly if no_day_rules => date.weekday() == dtstart.weekday(),
+            _ => true,
+        }
+    }
+
+    fn next_period(&self, period: NaiveDate) -> Option<NaiveDate> {
+        let interval = self.interval as i64;
+        match self.freq {
+            Frequency::Yearly => {
+                let year = i32::try_from(period.year() as i64 + interval).ok()?;
+                NaiveDate::from_ymd_opt(year, 1, 1)
+            }
+            Frequency::Monthly => {
+                let months = period.year() as i64 * 12 + period.month0() as i64 + interval;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                NaiveDate::from_ymd_opt(year, months.rem_euclid(12) as u32 + 1, 1)
+            }
+            Frequency::Weekly => period.checked_add_signed(Duration::try_weeks(interval)?),
+            Frequency::Daily => period.checked_add_signed(Duration::try_days(interval)?),
+        }
+    }
+}
+
+// Iterator over the local occurrences of a rule, one period at a time
+pub struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    dtstart: NaiveDateTime,
+    horizon: NaiveDate,
+    period: Option<NaiveDate>,
+    buffer: VecDeque<NaiveDateTime>,
+    steps: usize,
+    exhausted: bool,
+}
+
+impl Occurrences<'_> {
+    // Whether generation stopped at MAX_EXPANSION_STEPS rather than at the horizon
+    pub fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<NaiveDateTime> {
+        loop {
+            if let Some(next) = self.buffer.pop_front() {
+                return Some(next);
+            }
+            let period = self.period.filter(|p| *p <= self.horizon)?;
+            self.period = self.rule.next_period(period);
+
+            let candidates = self.rule.expand_period(period, self.dtstart);
+            self.steps += 1 + candidates.len();
+            if self.steps > MAX_EXPANSION_STEPS {
+                self.period = None;
+                self.exhausted = true;
+                return None;
+            }
+            let selected = if self.rule.by_set_pos.is_empty() {
+                candidates
+            } else {
+                let len = candidates.len() as i32;
+                let mut picked: Vec<NaiveDateTime> = self
+                    .rule
+                    .by_set_pos
+                    .iter()
+                    .filter_map(|&pos| {
+                        let index = if pos > 0 { pos - 1 } else { len + pos };
+                        candidates.get(usize::try_from(index).ok()?).copied()
+                    })
+                    .collect();
+                picked.sort();
+                picked.dedup();
+                picked
+            };
+            self.buffer
+                .extend(selected.into_iter().filter(|dt| *dt >= self.dtstart));
+        }
+    }
+}
+
+// The BY* values if given, otherwise DTSTART's own value
+fn defaulted(values: &[u32], fallback: u32) -> Vec<u32> {
+    if values.is_empty() {
+        vec![fallback]
+    } else {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        sorted
+    }
+}
+
+// Match a 1-based position against values where negatives count back from the end
+fn matches_signed(values: &[i32], position: i32, len: i32) -> bool {
+    values
+        .iter()
+        .any(|&v| v == position || v == position - len - 1)
+}
+
+fn days_in_year(year: i32) -> i32 {
+    if NaiveDate::from_ymd_opt(year, 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> i32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day() as i32)
+}
+
+fn parse_frequency(value: &str) -> Result<Frequency, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "YEARLY" => Ok(Frequency::Yearly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "WEEKLY" => Ok(Frequency::Weekly),
+        "DAILY" => Ok(Frequency::Daily),
+        "HOURLY" | "MINUTELY" | "SECONDLY" => Err(format!("FREQ={} is not supported", value)),
+        _ => Err(format!("Invalid FREQ '{}'", value)),
+    }
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Invalid weekday '{}'", value)),
+    }
+}
+
+// "MO", "2TU", "-1FR"
+fn parse_by_day(value: &str) -> Result<(Option<i32>, Weekday), String> {
+    let value = value.trim();
+    let split = value.len().saturating_sub(2);
+    let (ordinal, day) = value
+        .split_at_checked(split)
+        .ok_or_else(|| format!("Invalid BYDAY value '{}'", value))?;
+    let weekday = parse_weekday(day)?;
+    if ordinal.is_empty() {
+        return Ok((None, weekday));
+    }
+    let n: i32 = ordinal
+        .parse()
+        .ok()
+        .filter(|n: &i32| (1..=53).contains(&n.abs()))
+        .ok_or_else(|| format!("Invalid BYDAY value '{}'", value))?;
+    Ok((Some(n), weekday))
+}
+
+fn parse_list(key: &str, value: &str, min: u32, max: u32) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| format!("Invalid {} value '{}'", key, v))
+        })
+        .collect()
+}
+
+// Lists like BYMONTHDAY where values are 1..=max or -max..=-1
+fn parse_signed_list(key: &str, value: &str, max: i32) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse()
+                .ok()
+                .filter(|n: &i32| *n != 0 && n.abs() <= max)
+                .ok_or_else(|| format!("Invalid {} value '{}'", key, v))
+        })
+        .collect()
+}
+
+// UNTIL is a DATE (inclusive, so the end of that day) or a DATE-TIME, optionally in UTC
+fn parse_until(value: &str) -> Result<Until, String> {
+    let invalid = || format!("Invalid UNTIL '{}' (expected YYYYMMDD or YYYYMMDDTHHMMSS[Z])", value);
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok(Until::Utc(naive.and_utc()));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Ok(Until::Local(naive));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+    let end_of_day = NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN);
+    Ok(Until::Local(date.and_time(end_of_day)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").unwrap()
+    }
+
+    fn expand(rule: &str, dtstart: &str, n: usize) -> Vec<String> {
+        let rule = RecurrenceRule::parse(rule).unwrap();
+        let horizon = NaiveDate::from_ymd_opt(2100, 1, 1).unwrap();
+        rule.occurrences(start(dtstart), horizon)
+            .take(n)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(RecurrenceRule::parse("INTERVAL=2").is_err());
+        assert!(RecurrenceRule::parse("FREQ=HOURLY").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;COUNT=3;UNTIL=20250101").is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=2MO").is_err());
+        assert!(RecurrenceRule::parse("FREQ=MONTHLY;BYMONTHDAY=32").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;FREQ=WEEKLY").is_err());
+        assert!(RecurrenceRule::parse("RRULE:FREQ=WEEKLY;BYDAY=MO,WE").is_ok());
+    }
+
+    #[test]
+    fn test_parse_until() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY;UNTIL=20250301T120000Z").unwrap();
+        assert_eq!(
+            rule.until,
+            Some(Until::Utc(start("2025-03-01T12:00:00").and_utc()))
+        );
+        let rule = RecurrenceRule::parse("FREQ=DAILY;UNTIL=20250301").unwrap();
+        assert_eq!(rule.until, Some(Until::Local(start("2025-03-01T23:59:59"))));
+    }
+
+    #[test]
+    fn test_weekly_by_day() {
+        // 2025-02-03 is a Monday
+        let got = expand("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE", "2025-02-03T09:00:00", 4);
+        assert_eq!(
+            got,
+            ["2025-02-03 09:00", "2025-02-05 09:00", "2025-02-17 09:00", "2025-02-19 09:00"]
+        );
+    }
+
+    #[test]
+    fn test_monthly_last_friday() {
+        let got = expand("FREQ=MONTHLY;BYDAY=-1FR", "2025-01-01T17:00:00", 3);
+        assert_eq!(got, ["2025-01-31 17:00", "2025-02-28 17:00", "2025-03-28 17:00"]);
+    }
+
+    #[test]
+    fn test_monthly_skips_short_months() {
+        let got = expand("FREQ=MONTHLY", "2025-01-31T08:00:00", 3);
+        assert_eq!(got, ["2025-01-31 08:00", "2025-03-31 08:00", "2025-05-31 08:00"]);
+    }
+
+    #[test]
+    fn test_last_weekday_of_month_with_setpos() {
+        let got = expand(
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+            "2025-05-01T12:00:00",
+            2,
+        );
+        assert_eq!(got, ["2025-05-30 12:00", "2025-06-30 12:00"]);
+    }
+
+    #[test]
+    fn test_yearly_thanksgiving_and_by_hour() {
+        let got = expand("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", "2024-01-01T10:00:00", 2);
+        assert_eq!(got, ["2024-11-28 10:00", "2025-11-27 10:00"]);
+
+        let got = expand("FREQ=DAILY;BYHOUR=9,17;BYMINUTE=30", "2025-06-01T10:00:00", 3);
+        assert_eq!(got, ["2025-06-01 17:30", "2025-06-02 09:30", "2025-06-02 17:30"]);
+    }
+
+    #[test]
+    fn test_horizon_stops_impossible_rule() {
+        let rule = RecurrenceRule::parse("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30").unwrap();
+        let horizon = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        assert_eq!(rule.occurrences(start("2025-01-01T00:00:00"), horizon).count(), 0);
+    }
+
+    #[test]
+    fn test_expansion_limits() {
+        let every_second = "FREQ=YEARLY;BYDAY=MO,TU,WE,TH,FR,SA,SU;BYSETPOS=-1;\
+            BYHOUR=0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23;\
+            BYMINUTE=0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,\
+            27,28,29,30,31,32,33,34,35,36,37,38,39,40,41,42,43,44,45,46,47,48,49,50,51,52,53,\
+            54,55,56,57,58,59;BYSECOND=0,30";
+        assert!(RecurrenceRule::parse(every_second).is_err());
+        assert!(RecurrenceRule::parse("FREQ=YEARLY;BYHOUR=0,6,12,18;BYMINUTE=0,30").is_ok());
+
+        // A rule that never matches gives up instead of walking every day to the horizon
+        let rule = RecurrenceRule::parse("FREQ=DAILY;BYMONTH=2;BYMONTHDAY=30").unwrap();
+        let horizon = NaiveDate::from_ymd_opt(9999, 12, 31).unwrap();
+        let mut occurrences = rule.occurrences(start("1800-01-01T00:00:00"), horizon);
+        assert_eq!(occurrences.next(), None);
+        assert!(occurrences.exhausted());
+    }
+}
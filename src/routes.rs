@@ -17,37 +17,139 @@
 use axum::{
     http::{header, Method},
     middleware,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
 use crate::auth;
+use crate::fields;
 use crate::handlers;
 use crate::AppState;
 
 pub fn create_router(state: AppState) -> Router {
+    // The server-wide origins, plus those that keys list for themselves
+    let global_origins = state.config.cors_allowed_origins.clone();
+    let key_origins = state.key_origins.clone();
     let cors = CorsLayer::new()
-        .allow_origin(AllowOrigin::list(
-            state.config.cors_allowed_origins.clone(),
-        ))
-        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE, header::HeaderName::from_static("x-api-key")]);
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            global_origins.contains(origin) || key_origins.contains(origin)
+        }))
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::HeaderName::from_static("x-api-key"),
+            header::HeaderName::from_static("x-key-id"),
+            header::HeaderName::from_static("x-timestamp"),
+            header::HeaderName::from_static("x-nonce"),
+            header::HeaderName::from_static("x-signature"),
+        ]);
 
     // Public routes - no auth required
     let public_routes = Router::new()
         .route("/", get(handlers::health_check))
-        .route("/health", get(handlers::health_check));
+        .route("/health", get(handlers::health_check))
+        .route("/signup/trial", post(auth::handlers::signup_trial));
 
     // API routes - protected by API key middleware
     let api_routes = Router::new()
-        .route("/api/timezones", get(handlers::get_timezones))
-        .route("/api/time/{timezone}", get(handlers::get_timezone_info))
-        .route("/api/timezone-at", get(handlers::get_timezone_by_coordinates))
-        .route("/api/convert", post(handlers::convert_timezone))
+        .route(
+            "/api/timezones",
+            get(handlers::get_timezones).layer(middleware::from_fn(fields::sparse_fieldsets)),
+        )
+        .route("/api/timezones/search", get(handlers::search_timezones))
+        .route("/api/timezones/autocomplete", get(handlers::autocomplete_timezones))
+        .route("/api/timezones/aliases", get(handlers::get_aliases))
+        .route("/api/timezones/rails", get(handlers::get_rails_names))
+        .route(
+            "/api/timezones/export",
+            get(handlers::export_timezones).layer(CompressionLayer::new()),
+        )
+        .route("/api/timezones/{timezone}/tzif", get(handlers::get_tzif))
+        .route("/api/timezones/{timezone}/dump", get(handlers::dump_transitions))
+        .route("/api/timezones/{timezone}/countries", get(handlers::get_zone_countries))
+        .route(
+            "/api/timezones/{timezone}/boundary",
+            get(handlers::get_boundary).layer(CompressionLayer::new()),
+        )
+        .route("/api/timezones/dst", get(handlers::get_dst_zones))
+        .route("/api/dst/{timezone}/at", get(handlers::get_dst_status))
+        .route("/api/dst/{timezone}/next", get(handlers::get_next_dst_change))
+        .route("/api/dst/{timezone}/calendar.ics", get(handlers::get_dst_calendar))
+        .route("/api/tzdata", get(handlers::get_tzdata_info))
+        .route("/api/leap-seconds", get(handlers::get_leap_seconds))
+        .route("/api/timescales", get(handlers::convert_timescales))
+        .route("/api/offsets", get(handlers::get_offsets))
+        .route("/api/abbreviations/{abbr}", get(handlers::lookup_abbreviation))
+        .route("/api/windows/{windows_id}", get(handlers::windows_to_iana))
+        .route("/api/windows/from-iana/{timezone}", get(handlers::iana_to_windows))
+        .route(
+            "/api/time/auto",
+            get(handlers::get_auto_timezone_info)
+                .layer(middleware::from_fn(fields::sparse_fieldsets)),
+        )
+        .route(
+            "/api/time/{timezone}",
+            get(handlers::get_timezone_info).layer(middleware::from_fn(fields::sparse_fieldsets)),
+        )
+        .route("/api/now", get(handlers::get_current_times))
+        .route("/api/stream/time", get(handlers::stream_current_times))
+        .route("/api/week/{timezone}", get(handlers::get_week_info))
+        .route("/api/calendar-info", get(handlers::get_calendar_info))
+        .route("/api/interval", get(handlers::get_interval))
+        .route("/api/age", get(handlers::get_age))
+        .route("/api/holidays/{country}", get(handlers::get_holidays))
+        .route("/api/countries/{country}", get(handlers::get_country_zones))
+        .route(
+            "/api/timezone-at",
+            get(handlers::get_timezone_by_coordinates)
+                .post(handlers::get_timezone_by_geojson)
+                .layer(middleware::from_fn(fields::sparse_fieldsets)),
+        )
+        .route("/api/airports/{code}", get(handlers::get_airport))
+        .route("/api/ip/{addr}", get(handlers::get_ip_info))
+        .route("/api/geolocate/batch", post(handlers::geolocate_batch))
+        .route("/api/sun", get(handlers::get_sun_times))
+        .route("/api/epoch/now", get(handlers::get_epoch_now))
+        .route(
+            "/api/convert",
+            post(handlers::convert_timezone).layer(middleware::from_fn(fields::sparse_fieldsets)),
+        )
+        .route("/api/matrix", post(handlers::get_offset_matrix))
+        .route("/api/add", post(handlers::add_duration))
+        .route("/api/recurrence/expand", post(handlers::expand_recurrence))
+        .route("/api/meetings/suggest", post(handlers::suggest_meetings))
+        .route("/api/teams", post(handlers::create_team).get(handlers::list_teams))
+        .route("/api/teams/{team}", get(handlers::get_team).delete(handlers::delete_team))
+        .route("/api/teams/{team}/members", post(handlers::add_team_member))
+        .route(
+            "/api/teams/{team}/members/{id}",
+            put(handlers::update_team_member).delete(handlers::delete_team_member),
+        )
+        .route("/api/itinerary", post(handlers::plan_itinerary))
+        .route("/api/jetlag", post(handlers::plan_jet_lag))
+        .route("/api/parse", get(handlers::parse_datetime))
+        .route("/api/julian", get(handlers::convert_julian))
+        .route("/api/relative", get(handlers::relative_time))
+        .route("/api/duration", get(handlers::humanize_duration))
+        .route("/api/me", get(auth::handlers::get_own_api_key))
+        .route("/api/me/rotate", post(auth::handlers::rotate_own_api_key))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             auth::middleware::require_api_key,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::middleware::throttle_failed_auth,
         ));
 
     // Admin routes - admin key checked in handlers
@@ -56,7 +158,47 @@ pub fn create_router(state: AppState) -> Router {
             "/admin/api-keys",
             post(auth::handlers::create_api_key).get(auth::handlers::list_api_keys),
         )
-        .route("/admin/api-keys/{id}", delete(auth::handlers::revoke_api_key));
+        .route("/admin/api-keys/bulk", post(auth::handlers::create_api_keys_bulk))
+        .route("/admin/api-keys/purge-revoked", post(auth::handlers::purge_inactive_api_keys))
+        .route(
+            "/admin/api-keys/{id}",
+            delete(auth::handlers::revoke_api_key).patch(auth::handlers::update_api_key),
+        )
+        .route("/admin/api-keys/{id}/rotate", post(auth::handlers::rotate_api_key))
+        .route(
+            "/admin/api-keys/{id}/signing",
+            post(auth::handlers::enable_request_signing)
+                .delete(auth::handlers::disable_request_signing),
+        )
+        .route("/admin/api-keys/{id}/rate-limit", put(auth::handlers::set_rate_limit))
+        .route("/admin/api-keys/{id}/quota", put(auth::handlers::set_monthly_quota))
+        .route("/admin/api-keys/{id}/quota/reset", post(auth::handlers::reset_monthly_usage))
+        .route("/admin/api-keys/{id}/routes", put(auth::handlers::set_allowed_routes))
+        .route("/admin/api-keys/{id}/ips", put(auth::handlers::set_allowed_ips))
+        .route("/admin/api-keys/{id}/origins", put(auth::handlers::set_allowed_origins))
+        .route("/admin/api-keys/{id}/usage", get(auth::handlers::get_key_usage))
+        .route("/admin/usage", get(auth::handlers::get_aggregate_usage))
+        .route("/admin/usage/export", get(auth::handlers::export_usage))
+        .route("/admin/audit-log", get(auth::handlers::get_audit_log))
+        .route("/admin/auth-failures", get(auth::handlers::get_auth_failures))
+        .route(
+            "/admin/webhooks",
+            post(auth::handlers::create_webhook).get(auth::handlers::list_webhooks),
+        )
+        .route("/admin/webhooks/{id}", delete(auth::handlers::delete_webhook))
+        .route(
+            "/admin/orgs",
+            post(auth::handlers::create_organization).get(auth::handlers::list_organizations),
+        )
+        .route(
+            "/admin/orgs/{id}",
+            get(auth::handlers::get_organization).delete(auth::handlers::delete_organization),
+        )
+        .route("/admin/orgs/{id}/quota", put(auth::handlers::set_organization_quota))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::middleware::throttle_failed_auth,
+        ));
 
     public_routes
         .merge(api_routes)
@@ -79,13 +221,43 @@ mod tests {
         let db = init_db(":memory:").await;
         let config = AppConfig {
             cors_allowed_origins: vec![],
-            admin_api_key: "a]".repeat(16), // 32 chars
+            admin_api_key_hash: Some(crate::auth::service::hash_api_key(&admin_key(), None)),
             database_url: ":memory:".to_string(),
+            geoip_database: None,
+            rate_limit_per_minute: 60,
+            auth_failure_limit: 10,
+            revoked_key_retention_days: 90,
+            api_key_pepper: None,
+            jwt_secret: None,
+            jwt_jwks_url: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            trusted_proxies: Vec::new(),
+            smtp: None,
+            expiry_email_days: 7,
+            trial_request_limit: 1000,
+            trial_signups_per_hour: 0,
+            trial_key_days: 14,
+            redis_url: None,
+            key_cache_seconds: 30,
         };
         AppState {
             db,
             config: Arc::new(config),
             tz_finder: Arc::new(tzf_rs::DefaultFinder::new()),
+            clock: crate::service::ClockAnchor::now(),
+            search_index: Arc::new(crate::service::EpochZoneService::build_search_index()),
+            geoip: Arc::new(crate::geoip::GeoIp::disabled()),
+            rate_limiter: Arc::new(crate::auth::rate_limit::RateLimiter::new()),
+            auth_failures: Arc::new(crate::auth::throttle::AuthThrottle::new(10)),
+            trial_signups: Arc::new(crate::auth::trial::SignupLimiter::new()),
+            usage: Arc::new(crate::auth::usage::UsageMeter::new()),
+            nonces: Arc::new(crate::auth::signing::NonceCache::new()),
+            key_origins: Arc::new(crate::auth::origins::OriginRegistry::new()),
+            mailer: None,
+            jwt: None,
+            #[cfg(feature = "redis")]
+            shared: None,
         }
     }
 
@@ -304,6 +476,45 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_admin_list_keys_paginated() {
+        let state = test_state().await;
+        for name in ["alpha", "beta", "gamma"] {
+            crate::auth::service::create_api_key(&state.db, name.to_string(), None)
+                .await
+                .unwrap();
+        }
+        let app = create_router(state);
+
+        let list = |uri: &'static str| {
+            Request::builder()
+                .uri(uri)
+                .header("X-API-Key", admin_key())
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(list("/admin/api-keys?limit=2&offset=1&status=active"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-total-count"], "3");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let keys: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(keys.as_array().unwrap().len(), 2);
+
+        let response = app.clone().oneshot(list("/admin/api-keys?q=ET")).await.unwrap();
+        assert_eq!(response.headers()["x-total-count"], "1");
+
+        let response = app.clone().oneshot(list("/admin/api-keys?limit=0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.oneshot(list("/admin/api-keys?status=paused")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn test_convert_requires_key() {
         let state = test_state().await;
@@ -461,6 +672,36 @@ mod tests {
         assert!(err.error.contains("missing field"));
     }
 
+    #[tokio::test]
+    async fn test_timezone_at_out_of_range() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/timezone-at?lat=35.6762&lng=200")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let err: crate::models::ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(err.code.as_deref(), Some("longitude_out_of_range"));
+    }
+
     #[tokio::test]
     async fn test_convert_with_invalid_timezone() {
         let state = test_state().await;
@@ -488,4 +729,1682 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[tokio::test]
+    async fn test_timezone_info_invalid_unit() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/time/UTC?unit=minutes")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_timezone_info_output_selector() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/time/UTC?output=unix_ms")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, ["timezone", "unix_ms"]);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/time/UTC?output=xml")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_auto_timezone() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/time/auto")
+                    .header("X-API-Key", &resp.api_key)
+                    .header("Accept-Language", "sr-Latn-RS,en;q=0.5")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["timezone"], "Europe/Belgrade");
+        assert_eq!(json["resolved_by"], "accept-language");
+    }
+
+    #[tokio::test]
+    async fn test_sparse_fieldsets() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/time/Europe%2FBelgrade?fields=timezone,is_dst")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, ["is_dst", "timezone"]);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/convert?fields=to.datetime,day_shift")
+                    .header("X-API-Key", &resp.api_key)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"timestamp": 1707580800, "to": "Asia/Tokyo"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"to": {"datetime": "2024-02-11T01:00:00+09:00"}, "day_shift": 1})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_with_valid_key() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/parse?value=2025%2F02%2F10%2015%3A30&tz=Europe%2FBelgrade")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let parsed: crate::models::ParseResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(parsed.normalized, "2025-02-10T15:30:00+01:00");
+    }
+
+    #[tokio::test]
+    async fn test_bulk_now_with_invalid_zone() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/now?tz=Europe%2FBelgrade&tz=Nowhere&tz=Asia%2FTokyo")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let bulk: crate::models::BulkTimeResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(bulk.results.len(), 3);
+        assert_eq!(bulk.errors, 1);
+        assert_eq!(bulk.results[2].info.as_ref().unwrap().timezone, "Asia/Tokyo");
+    }
+
+    #[tokio::test]
+    async fn test_export_is_gzipped_on_request() {
+        let state = test_state().await;
+
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/timezones/export")
+                    .header("X-API-Key", &resp.api_key)
+                    .header("Accept-Encoding", "gzip")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-encoding"], "gzip");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        // gzip magic number
+        assert_eq!(&body[..2], [0x1f, 0x8b]);
+    }
+
+    #[tokio::test]
+    async fn test_per_key_rate_limit() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "trial".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(format!("/admin/api-keys/{}/rate-limit", resp.id))
+                    .header("content-type", "application/json")
+                    .header("X-API-Key", admin_key())
+                    .body(Body::from(r#"{"rate_limit_per_minute":2}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = || {
+            Request::builder()
+                .uri("/api/epoch/now")
+                .header("X-API-Key", &resp.api_key)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let first = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()["x-ratelimit-limit"], "2");
+        assert_eq!(first.headers()["x-ratelimit-remaining"], "1");
+        // The third request may land in the next minute; stop at the first rejection
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let response = app.clone().oneshot(request()).await.unwrap();
+            statuses.push(response.status());
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                assert!(response.headers().contains_key("retry-after"));
+                break;
+            }
+        }
+        assert_eq!(statuses.last(), Some(&StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn test_set_rate_limit_unknown_key() {
+        let app = create_router(test_state().await);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/admin/api-keys/nonexistent-id/rate-limit")
+                    .header("content-type", "application/json")
+                    .header("X-API-Key", admin_key())
+                    .body(Body::from(r#"{"rate_limit_per_minute":100}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_monthly_quota() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "trial".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let admin = |method: &str, uri: String, body: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let quota = admin(
+            "PUT",
+            format!("/admin/api-keys/{}/quota", resp.id),
+            r#"{"monthly_quota":1}"#,
+        );
+        let response = app.clone().oneshot(quota).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = || {
+            Request::builder()
+                .uri("/api/epoch/now")
+                .header("X-API-Key", &resp.api_key)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let first = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()["x-quota-limit"], "1");
+        assert_eq!(first.headers()["x-quota-remaining"], "0");
+        let second = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(second.status(), StatusCode::PAYMENT_REQUIRED);
+        assert!(second.headers().contains_key("retry-after"));
+
+        let reset = admin("POST", format!("/admin/api-keys/{}/quota/reset", resp.id), "");
+        let response = app.clone().oneshot(reset).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let third = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_routes() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "europe".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(format!("/admin/api-keys/{}/routes", resp.id))
+                    .header("content-type", "application/json")
+                    .header("X-API-Key", admin_key())
+                    .body(Body::from(r#"{"allowed_routes":["/api/time/Europe/*"]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header("X-API-Key", &resp.api_key)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let allowed = app.clone().oneshot(get("/api/time/Europe%2FBelgrade")).await.unwrap();
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let denied = app.clone().oneshot(get("/api/time/Asia%2FTokyo")).await.unwrap();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+        let denied = app.oneshot(get("/api/timezones")).await.unwrap();
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_rotate_key() {
+        let state = test_state().await;
+        let old = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/admin/api-keys/{}/rotate", old.id))
+                    .header("X-API-Key", admin_key())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let rotated: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rotated["id"], old.id.as_str());
+
+        let get = |key: &str| {
+            Request::builder()
+                .uri("/api/epoch/now")
+                .header("X-API-Key", key)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(get(&old.api_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let new_key = rotated["api_key"].as_str().unwrap();
+        let response = app.oneshot(get(new_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_self_rotate_key() {
+        let state = test_state().await;
+        let old = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+        crate::auth::service::set_allowed_routes(
+            &state.db,
+            old.id.clone(),
+            Some(vec!["/api/time/*".to_string()]),
+        )
+        .await
+        .unwrap();
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/me/rotate")
+                    .header("content-type", "application/json")
+                    .header("X-API-Key", &old.api_key)
+                    .body(Body::from(r#"{"grace_period_seconds":60}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let rotated: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rotated["id"], old.id.as_str());
+        assert!(rotated["previous_key_expires_at"].is_string());
+
+        // Both secrets work during the grace period
+        for key in [old.api_key.as_str(), rotated["api_key"].as_str().unwrap()] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/time/UTC")
+                        .header("X-API-Key", key)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_me_introspection() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "debug".to_string(), None)
+            .await
+            .unwrap();
+        crate::auth::service::set_monthly_quota(&state.db, resp.id.clone(), Some(100))
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/me")
+                    .header("X-API-Key", &resp.api_key)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let me: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(me["id"], resp.id.as_str());
+        assert_eq!(me["name"], "debug");
+        assert_eq!(me["rate_limit"]["limit_per_minute"], 60);
+        assert_eq!(me["rate_limit"]["source"], "default");
+        assert_eq!(me["rate_limit"]["used_this_minute"], 1);
+        // The introspection request itself is counted
+        assert_eq!(me["usage"]["requests"], 1);
+        assert_eq!(me["usage"]["remaining"], 99);
+    }
+
+    #[tokio::test]
+    async fn test_admin_key_usage() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "metered".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        for uri in ["/api/time/UTC", "/api/time/Asia%2FTokyo", "/api/time/Nowhere"] {
+            let request = Request::builder()
+                .uri(uri)
+                .header("X-API-Key", &resp.api_key)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/admin/api-keys/{}/usage", resp.id))
+                    .header("X-API-Key", admin_key())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let usage: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(usage["requests"], 3);
+        assert_eq!(usage["errors"], 1);
+        let endpoints = &usage["days"][0]["endpoints"];
+        assert_eq!(endpoints[0]["endpoint"], "/api/time/{timezone}");
+        assert_eq!(endpoints[0]["requests"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_admin_aggregate_usage() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        for uri in ["/api/time/UTC", "/api/time/Nowhere", "/api/epoch/now"] {
+            let request = Request::builder()
+                .uri(uri)
+                .header("X-API-Key", &resp.api_key)
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/usage?group_by=endpoint")
+                    .header("X-API-Key", admin_key())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let usage: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(usage["group_by"], "endpoint");
+        assert_eq!(usage["requests"], 3);
+        assert_eq!(usage["groups"][0]["group"], "/api/time/{timezone}");
+        assert_eq!(usage["groups"][0]["error_rate"], 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_admin_usage_export() {
+        let state = test_state().await;
+        let resp = crate::auth::service::create_api_key(&state.db, "test".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let request = Request::builder()
+            .uri("/api/convert?from=UTC&to=Asia/Tokyo")
+            .header("X-API-Key", &resp.api_key)
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap();
+
+        let month = crate::auth::service::usage_month(chrono::Utc::now());
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/admin/usage/export?month={}&format=csv", month))
+                    .header("X-API-Key", admin_key())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/csv; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        let row = format!("{},{},test,1,", month, resp.id);
+        assert!(csv.lines().nth(1).unwrap().starts_with(&row));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/usage/export?month=2026-13")
+                    .header("X-API-Key", admin_key())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_admin_purge_api_keys() {
+        let state = test_state().await;
+        let db = state.db.clone();
+        let kept = crate::auth::service::create_api_key(&db, "kept".to_string(), None)
+            .await
+            .unwrap();
+        let revoked = crate::auth::service::create_api_key(&db, "revoked".to_string(), None)
+            .await
+            .unwrap();
+        let erased = crate::auth::service::create_api_key(&db, "erased".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let admin = |method: &str, uri: String| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("X-API-Key", admin_key())
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(admin("DELETE", format!("/admin/api-keys/{}", revoked.id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app
+            .clone()
+            .oneshot(admin("DELETE", format!("/admin/api-keys/{}?purge=true", erased.id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let lookup = crate::auth::service::get_api_key(&db, erased.id.clone());
+        assert!(lookup.await.unwrap().is_none());
+
+        let response = app
+            .oneshot(admin("POST", "/admin/api-keys/purge-revoked".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let purged: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(purged["purged"], 1);
+        assert_eq!(purged["ids"][0], revoked.id.as_str());
+        let lookup = crate::auth::service::get_api_key(&db, kept.id.clone());
+        assert!(lookup.await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admin_key_metadata() {
+        let state = test_state().await;
+        let app = create_router(state);
+
+        let admin = |method: &str, uri: String, body: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let create = admin(
+            "POST",
+            "/admin/api-keys".to_string(),
+            r#"{"name":"ops","tags":["team-a"],"owner_email":"ops@example.com"}"#,
+        );
+        let response = app.clone().oneshot(create).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap();
+
+        let patch = admin("PATCH", format!("/admin/api-keys/{}", id), r#"{"tags":["team-b"]}"#);
+        let response = app.clone().oneshot(patch).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let key: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(key["tags"], serde_json::json!(["team-b"]));
+        assert_eq!(key["owner_email"], "ops@example.com");
+
+        let list = admin("GET", "/admin/api-keys?tag=team-b".to_string(), "");
+        let response = app.clone().oneshot(list).await.unwrap();
+        assert_eq!(response.headers()["x-total-count"], "1");
+
+        let patch = admin("PATCH", format!("/admin/api-keys/{}", id), r#"{"owner_email":"ops"}"#);
+        let response = app.oneshot(patch).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_admin_bulk_create_keys() {
+        let state = test_state().await;
+        let app = create_router(state);
+
+        let bulk = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/admin/api-keys/bulk")
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(bulk(
+                r#"[{"name":"student-1"},{"name":"student-2","expires_at":"2030-06-30 00:00:00"}]"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created[1]["name"], "student-2");
+        assert_eq!(created[1]["expires_at"], "2030-06-30 00:00:00");
+        assert!(created[0]["api_key"].as_str().unwrap().starts_with("ez_"));
+
+        // One bad entry rejects the whole batch
+        let response = app
+            .clone()
+            .oneshot(bulk(r#"[{"name":"ok"},{"name":"bad","owner_email":"nobody"}]"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.clone().oneshot(bulk("[]")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let list = Request::builder()
+            .uri("/admin/api-keys")
+            .header("X-API-Key", admin_key())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(list).await.unwrap();
+        assert_eq!(response.headers()["x-total-count"], "2");
+    }
+
+    #[tokio::test]
+    async fn test_admin_create_key_expiry() {
+        let state = test_state().await;
+        let app = create_router(state);
+
+        let create = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/admin/api-keys")
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(create(r#"{"name":"trial","expires_in":"30d"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let expected = (chrono::Utc::now() + chrono::Duration::days(30)).format("%Y-%m-%d");
+        assert!(created["expires_at"].as_str().unwrap().starts_with(&expected.to_string()));
+
+        for body in [
+            r#"{"name":"bad","expires_at":"soon"}"#,
+            r#"{"name":"past","expires_at":"2001-01-01T00:00:00Z"}"#,
+        ] {
+            let response = app.clone().oneshot(create(body)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_admin_role_keys() {
+        let mut state = test_state().await;
+        state.config = Arc::new(AppConfig {
+            admin_api_key_hash: None,
+            ..(*state.config).clone()
+        });
+        let db = state.db.clone();
+        let admin = crate::auth::service::bootstrap_admin_key(&db, None)
+            .await
+            .unwrap()
+            .unwrap();
+        let client = crate::auth::service::create_api_key(&db, "client".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let request = |method: &str, uri: String, key: &str, body: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", key)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let list = request("GET", "/admin/api-keys".to_string(), &admin.api_key, "");
+        let response = app.clone().oneshot(list).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let list = request("GET", "/admin/api-keys".to_string(), &client.api_key, "");
+        let response = app.clone().oneshot(list).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let list = request("GET", "/admin/api-keys".to_string(), &admin_key(), "");
+        let response = app.clone().oneshot(list).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // The only admin key can't be revoked until another exists
+        let revoke = format!("/admin/api-keys/{}", admin.id);
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", revoke.clone(), &admin.api_key, ""))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let create = request(
+            "POST",
+            "/admin/api-keys".to_string(),
+            &admin.api_key,
+            r#"{"name":"second admin","role":"admin"}"#,
+        );
+        let response = app.clone().oneshot(create).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = app
+            .oneshot(request("DELETE", revoke, &admin.api_key, ""))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_jwt_bearer_auth() {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+
+        let secret = "a-secret-shared-with-the-gateway-0123";
+        let mut state = test_state().await;
+        let config = AppConfig {
+            jwt_secret: Some(secret.to_string()),
+            ..(*state.config).clone()
+        };
+        state.jwt = crate::auth::jwt::JwtVerifier::from_config(&config).map(Arc::new);
+        state.config = Arc::new(config);
+        let app = create_router(state);
+
+        let token = |scope: &str| {
+            let claims = serde_json::json!({
+                "sub": "gateway-user",
+                "exp": chrono::Utc::now().timestamp() + 600,
+                "scope": scope,
+            });
+            let key = EncodingKey::from_secret(secret.as_bytes());
+            encode(&Header::default(), &claims, &key).unwrap()
+        };
+        let request = |authorization: String| {
+            Request::builder()
+                .uri("/api/time/UTC")
+                .header("Authorization", authorization)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(format!("Bearer {}", token("lookup geo"))))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(request(format!("Bearer {}", token("geo"))))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app
+            .oneshot(request("Bearer not-a-token".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_signed_requests() {
+        use crate::auth::signing::{canonical_request, sign};
+
+        let mut state = test_state().await;
+        state.config = Arc::new(AppConfig {
+            api_key_pepper: Some("p".repeat(32)),
+            ..(*state.config).clone()
+        });
+        let db = state.db.clone();
+        let key = crate::auth::service::create_api_key(&db, "signer".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let enable = Request::builder()
+            .method("POST")
+            .uri(format!("/admin/api-keys/{}/signing", key.id))
+            .header("X-API-Key", admin_key())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(enable).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let enabled: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let secret = enabled["signing_secret"].as_str().unwrap().to_string();
+
+        let signed = |nonce: &str, secret: &str| {
+            let timestamp = chrono::Utc::now().timestamp().to_string();
+            let uri = "/api/time/UTC";
+            let signature = sign(secret, &canonical_request("GET", uri, &timestamp, nonce, b""));
+            Request::builder()
+                .uri(uri)
+                .header("X-Key-Id", &key.id)
+                .header("X-Timestamp", timestamp)
+                .header("X-Nonce", nonce)
+                .header("X-Signature", signature)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(signed("nonce-1", &secret)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        // A replayed nonce is rejected
+        let response = app.clone().oneshot(signed("nonce-1", &secret)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(signed("nonce-2", "wrong")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        // A timestamp as far as possible from the clock is refused, not overflowed
+        let mut ancient = signed("nonce-4", &secret);
+        let min = i64::MIN.to_string().parse().unwrap();
+        ancient.headers_mut().insert("X-Timestamp", min);
+        let response = app.clone().oneshot(ancient).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let disable = Request::builder()
+            .method("DELETE")
+            .uri(format!("/admin/api-keys/{}/signing", key.id))
+            .header("X-API-Key", admin_key())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(disable).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(signed("nonce-3", &secret)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_key_ip_allowlist() {
+        use axum::extract::ConnectInfo;
+        use std::net::SocketAddr;
+
+        let state = test_state().await;
+        let db = state.db.clone();
+        let key = crate::auth::service::create_api_key(&db, "office".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let set_ips = |body: &'static str| {
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/admin/api-keys/{}/ips", key.id))
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let response = app.clone().oneshot(set_ips(r#"{"allowed_ips":["nope"]}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = r#"{"allowed_ips":["203.0.113.7/24"]}"#;
+        let response = app.clone().oneshot(set_ips(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let item: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(item["allowed_ips"], serde_json::json!(["203.0.113.0/24"]));
+
+        let request = |peer: [u8; 4], forwarded_for: &str| {
+            Request::builder()
+                .uri("/api/time/UTC")
+                .header("X-API-Key", &key.api_key)
+                .header("X-Forwarded-For", forwarded_for)
+                .extension(ConnectInfo(SocketAddr::from((peer, 4000))))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(request([203, 0, 113, 9], "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(request([198, 51, 100, 1], "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        // X-Forwarded-For is ignored without TRUSTED_PROXIES
+        let response = app.oneshot(request([198, 51, 100, 1], "203.0.113.9")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_key_cors_origins() {
+        let state = test_state().await;
+        let db = state.db.clone();
+        let tenant = crate::auth::service::create_api_key(&db, "tenant".to_string(), None)
+            .await
+            .unwrap();
+        let other = crate::auth::service::create_api_key(&db, "other".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let set_origins = Request::builder()
+            .method("PUT")
+            .uri(format!("/admin/api-keys/{}/origins", tenant.id))
+            .header("content-type", "application/json")
+            .header("X-API-Key", admin_key())
+            .body(Body::from(r#"{"allowed_origins":["https://app.tenant.example/"]}"#))
+            .unwrap();
+        let response = app.clone().oneshot(set_origins).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let origin = "https://app.tenant.example";
+        let preflight = Request::builder()
+            .method("OPTIONS")
+            .uri("/api/time/UTC")
+            .header("Origin", origin)
+            .header("Access-Control-Request-Method", "GET")
+            .header("Access-Control-Request-Headers", "x-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(preflight).await.unwrap();
+        assert_eq!(
+            response.headers().get("access-control-allow-origin").unwrap(),
+            origin
+        );
+
+        let request = |key: &str, origin: &str| {
+            Request::builder()
+                .uri("/api/time/UTC")
+                .header("X-API-Key", key)
+                .header("Origin", origin)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(request(&tenant.api_key, origin)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("access-control-allow-origin").unwrap(),
+            origin
+        );
+        let response = app
+            .clone()
+            .oneshot(request(&tenant.api_key, "https://evil.example"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        // Keys without origins of their own keep to the server-wide list
+        let response = app.oneshot(request(&other.api_key, origin)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_actions_audited() {
+        let state = test_state().await;
+        let db = state.db.clone();
+        let app = create_router(state);
+
+        let request = |method: &str, uri: String, body: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let create = request("POST", "/admin/api-keys".to_string(), r#"{"name":"audited"}"#);
+        let response = app.clone().oneshot(create).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let quota_uri = format!("/admin/api-keys/{}/quota", id);
+        let quota = request("PUT", quota_uri, r#"{"monthly_quota":5}"#);
+        let response = app.clone().oneshot(quota).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let revoke = request("DELETE", format!("/admin/api-keys/{}", id), "");
+        let response = app.oneshot(revoke).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let events: Vec<(String, String, Option<String>, Option<String>)> = db
+            .call(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT actor, action, before, after FROM audit_log ORDER BY id",
+                )?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+            .unwrap();
+        let actions: Vec<&str> = events.iter().map(|event| event.1.as_str()).collect();
+        assert_eq!(actions, vec!["key_created", "quota_set", "key_revoked"]);
+        assert!(events.iter().all(|event| event.0 == "admin-key"));
+        let snapshot = |json: &Option<String>| -> serde_json::Value {
+            serde_json::from_str(json.as_deref().unwrap()).unwrap()
+        };
+        assert_eq!(snapshot(&events[1].2)["monthly_quota"], serde_json::Value::Null);
+        assert_eq!(snapshot(&events[1].3)["monthly_quota"], 5);
+        assert_eq!(snapshot(&events[2].3)["is_active"], false);
+        let secret = created["api_key"].as_str().unwrap();
+        assert!(!events[0].3.as_deref().unwrap().contains(secret));
+    }
+
+    #[tokio::test]
+    async fn test_admin_audit_log_query() {
+        let app = create_router(test_state().await);
+        let request = |method: &str, uri: &str, body: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+        for body in [r#"{"name":"first"}"#, r#"{"name":"second"}"#] {
+            let response = app.clone().oneshot(request("POST", "/admin/api-keys", body)).await;
+            assert_eq!(response.unwrap().status(), StatusCode::CREATED);
+        }
+
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let uri = format!("/admin/audit-log?action=key_created&from={}&limit=1", today);
+        let response = app.clone().oneshot(request("GET", &uri, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-total-count"], "2");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let events: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(events[0]["after"]["name"], "second");
+        assert_eq!(events[0]["actor"], "admin-key");
+
+        for uri in ["/admin/audit-log?action=nope", "/admin/audit-log?from=yesterday"] {
+            let response = app.clone().oneshot(request("GET", uri, "")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        let uri = "/admin/audit-log?from=2026-02-01&to=2026-01-01";
+        let response = app.oneshot(request("GET", uri, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_admin_webhooks() {
+        let app = create_router(test_state().await);
+        let request = |method: &str, uri: String, body: &'static str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", admin_key())
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let invalid = [
+            r#"{"url":"ftp://example.com"}"#,
+            r#"{"url":"https://a.example","days_before":0}"#,
+            r#"{"url":"https://a.example","schedule":"every hour"}"#,
+            r#"{"url":"https://a.example","schedule":"@hourly","timezones":["Mars/Olympus"]}"#,
+            r#"{"url":"https://a.example","schedule":"@hourly","days_before":7}"#,
+            r#"{"url":"https://a.example","timezones":["UTC"]}"#,
+        ];
+        for body in invalid {
+            let create = request("POST", "/admin/webhooks".into(), body);
+            let response = app.clone().oneshot(create).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        let body = r#"{"url":"https://hooks.example.com/expiry","days_before":14}"#;
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/admin/webhooks".into(), body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(created["secret"].as_str().unwrap().starts_with("whsec_"));
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/admin/webhooks".into(), ""))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(listed[0]["days_before"], 14);
+        assert!(listed[0]["schedule"].is_null());
+        assert!(listed[0].get("secret").is_none());
+
+        let body = r#"{"url":"https://hooks.example.com/clock","schedule":"0 9 * * 1-5",
+            "timezones":["America/New_York","Europe/Belgrade"]}"#;
+        let response = app
+            .clone()
+            .oneshot(request("POST", "/admin/webhooks".into(), body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let scheduled: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(scheduled["days_before"].is_null());
+        assert_eq!(scheduled["timezones"][1], "Europe/Belgrade");
+        assert!(scheduled["next_delivery_at"].as_str().unwrap().ends_with("09:00:00"));
+
+        let uri = format!("/admin/webhooks/{}", created["id"].as_str().unwrap());
+        let response = app.clone().oneshot(request("DELETE", uri.clone(), "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(request("DELETE", uri, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_organizations() {
+        let state = test_state().await;
+        let other = crate::auth::service::create_api_key(&state.db, "other".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let call = |key: &str, method: &str, uri: String, body: String| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", key)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let request = call(
+            &admin_key(),
+            "POST",
+            "/admin/orgs".to_string(),
+            r#"{"name":"Acme","monthly_quota":2}"#.to_string(),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let org_id = body(response).await["id"].as_str().unwrap().to_string();
+
+        // An admin key inside the organization
+        let request = call(
+            &admin_key(),
+            "POST",
+            "/admin/api-keys".to_string(),
+            format!(r#"{{"name":"acme-admin","role":"admin","org_id":"{}"}}"#, org_id),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let org_admin = body(response).await["api_key"].as_str().unwrap().to_string();
+
+        // Keys it creates land in the organization
+        let request = call(
+            &org_admin,
+            "POST",
+            "/admin/api-keys".to_string(),
+            r#"{"name":"acme-web"}"#.to_string(),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created = body(response).await;
+        assert_eq!(created["org_id"], org_id.as_str());
+        let client_key = created["api_key"].as_str().unwrap().to_string();
+
+        // It sees only the organization's keys, and nothing of the rest of the service
+        let request = call(&org_admin, "GET", "/admin/api-keys".to_string(), String::new());
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["x-total-count"], "2");
+        let uri = format!("/admin/api-keys/{}/quota", other.id);
+        let request = call(&org_admin, "PUT", uri, r#"{"monthly_quota":1}"#.to_string());
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let request = call(&org_admin, "GET", "/admin/orgs".to_string(), String::new());
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let request = call(
+            &org_admin,
+            "POST",
+            "/admin/api-keys".to_string(),
+            r#"{"name":"stray","org_id":"elsewhere"}"#.to_string(),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // The organization's quota is shared by its keys
+        for (key, status) in [
+            (&client_key, StatusCode::OK),
+            (&org_admin, StatusCode::OK),
+            (&client_key, StatusCode::PAYMENT_REQUIRED),
+        ] {
+            let request = call(key, "GET", "/api/epoch/now".to_string(), String::new());
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), status);
+        }
+
+        let uri = format!("/admin/orgs/{}", org_id);
+        let request = call(&org_admin, "GET", uri.clone(), String::new());
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let details = body(response).await;
+        assert_eq!(details["key_count"], 2);
+        assert_eq!(details["usage"]["requests"], 2);
+        assert_eq!(details["usage"]["remaining"], 0);
+
+        let request = call(&admin_key(), "DELETE", uri, String::new());
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_teams() {
+        let state = test_state().await;
+        let owner = crate::auth::service::create_api_key(&state.db, "owner".to_string(), None)
+            .await
+            .unwrap();
+        let stranger = crate::auth::service::create_api_key(&state.db, "other".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+
+        let call = |key: &str, method: &str, uri: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-API-Key", key)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let team = r#"{"name": "engineering", "members": [
+            {"name": "Ana", "timezone": "Europe/Belgrade"},
+            {"name": "Ben", "timezone": "Asia/Calcutta", "work_start": "10:00"}
+        ]}"#;
+        let response = app
+            .clone()
+            .oneshot(call(&owner.api_key, "POST", "/api/teams", team))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(body(response).await["members"][1]["timezone"], "Asia/Kolkata");
+        let response = app
+            .clone()
+            .oneshot(call(&owner.api_key, "POST", "/api/teams", team))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let member = r#"{"name": "Chi", "timezone": "America/New_York"}"#;
+        let uri = "/api/teams/engineering/members";
+        let request = call(&owner.api_key, "POST", uri, member);
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // The world clock and meeting planner take the team by name
+        let uri = "/api/now?tz=UTC&team=engineering";
+        let response = app.clone().oneshot(call(&owner.api_key, "GET", uri, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let now = body(response).await;
+        assert_eq!(now["results"].as_array().unwrap().len(), 4);
+        assert!(now["results"][0].get("member").is_none());
+        assert_eq!(now["results"][3]["member"], "Chi");
+        assert_eq!(now["results"][3]["timezone"], "America/New_York");
+
+        let meeting = r#"{"team": "engineering", "date": "2026-03-04"}"#;
+        let uri = "/api/meetings/suggest";
+        let request = call(&owner.api_key, "POST", uri, meeting);
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await["participants"], 3);
+
+        // Teams are private to the key that saved them
+        let uri = "/api/now?team=engineering";
+        let response = app.clone().oneshot(call(&stranger.api_key, "GET", uri, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let uri = "/api/teams/engineering";
+        let response = app.clone().oneshot(call(&owner.api_key, "DELETE", uri, "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = app.oneshot(call(&owner.api_key, "GET", "/api/teams", "")).await.unwrap();
+        assert_eq!(body(response).await, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_failed_auth_throttling() {
+        use axum::extract::ConnectInfo;
+        use std::net::SocketAddr;
+
+        let mut state = test_state().await;
+        state.auth_failures = Arc::new(crate::auth::throttle::AuthThrottle::new(2));
+        let key = crate::auth::service::create_api_key(&state.db, "app".to_string(), None)
+            .await
+            .unwrap();
+        let throttle = state.auth_failures.clone();
+        let app = create_router(state);
+
+        let request = |uri: &str, peer: [u8; 4], api_key: &str| {
+            Request::builder()
+                .uri(uri)
+                .header("X-API-Key", api_key)
+                .extension(ConnectInfo(SocketAddr::from((peer, 4000))))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let (client, blocked_peer) = ([203, 0, 113, 7], [198, 51, 100, 1]);
+        // Wrong keys for the API and for admin endpoints count as failures
+        let guess = format!("ez_{}", uuid::Uuid::new_v4().simple());
+        let response = app.clone().oneshot(request("/api/time/UTC", client, &guess));
+        let response = response.await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(request("/admin/api-keys", client, &guess));
+        assert_eq!(response.await.unwrap().status(), StatusCode::FORBIDDEN);
+        // A route the key may not call is not a failed authentication
+        let response = app.clone().oneshot(request("/admin/api-keys", client, &key.api_key));
+        assert_eq!(response.await.unwrap().status(), StatusCode::FORBIDDEN);
+        assert_eq!(throttle.stats(chrono::Utc::now().timestamp()).failed_attempts, 2);
+
+        // A blocked address is turned away even with a valid key
+        let now = chrono::Utc::now().timestamp();
+        let blocked: std::net::IpAddr = "198.51.100.1".parse().unwrap();
+        for _ in 0..12 {
+            throttle.record_failure(blocked, now);
+        }
+        let response = app.clone().oneshot(request("/api/time/UTC", blocked_peer, &key.api_key));
+        let response = response.await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+        let response = app.clone().oneshot(request("/api/time/UTC", client, &key.api_key));
+        assert_eq!(response.await.unwrap().status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(request("/admin/auth-failures", [192, 0, 2, 1], &admin_key()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stats["failed_attempts"], 14);
+        assert_eq!(stats["rejected_while_blocked"], 1);
+        assert_eq!(stats["blocked"][0]["address"], "198.51.100.1");
+        assert_eq!(stats["blocked"][0]["failures"], 12);
+    }
+
+    #[tokio::test]
+    async fn test_trial_signup() {
+        use axum::extract::ConnectInfo;
+        use std::net::SocketAddr;
+
+        let mut state = test_state().await;
+        let signup = |email: &str, peer: [u8; 4]| {
+            Request::builder()
+                .method("POST")
+                .uri("/signup/trial")
+                .header("content-type", "application/json")
+                .extension(ConnectInfo(SocketAddr::from((peer, 4000))))
+                .body(Body::from(format!(r#"{{"email":"{}"}}"#, email)))
+                .unwrap()
+        };
+        // Signup is off unless TRIAL_SIGNUPS_PER_HOUR is set
+        let app = create_router(state.clone());
+        let response = app.oneshot(signup("a@example.com", [203, 0, 113, 7])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let mut config = (*state.config).clone();
+        config.trial_signups_per_hour = 1;
+        config.trial_request_limit = 2;
+        state.config = Arc::new(config);
+        let app = create_router(state);
+
+        let response = app.clone().oneshot(signup("nope", [203, 0, 113, 7])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app.clone().oneshot(signup("a@example.com", [203, 0, 113, 7])).await;
+        let response = response.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["role"], "trial");
+        assert!(created["expires_at"].is_string());
+        let api_key = created["api_key"].as_str().unwrap().to_string();
+
+        // One active trial per address, and a limited number of signups per client
+        let response = app.clone().oneshot(signup("A@example.com", [203, 0, 113, 8])).await;
+        assert_eq!(response.unwrap().status(), StatusCode::CONFLICT);
+        let response = app.clone().oneshot(signup("b@example.com", [203, 0, 113, 7])).await;
+        let response = response.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+
+        // The key works for its two requests, then is deactivated
+        let call = || {
+            Request::builder()
+                .uri("/api/time/UTC")
+                .header("X-API-Key", &api_key)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(call()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-trial-requests-remaining"], "1");
+        let response = app.clone().oneshot(call()).await.unwrap();
+        assert_eq!(response.headers()["x-trial-requests-remaining"], "0");
+        let response = app.clone().oneshot(call()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let list = Request::builder()
+            .uri("/admin/api-keys?status=revoked")
+            .header("X-API-Key", admin_key())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(list).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let keys: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(keys[0]["role"], "trial");
+        assert_eq!(keys[0]["requests_remaining"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_current_times() {
+        use tokio_stream::StreamExt;
+
+        let state = test_state().await;
+        let key = crate::auth::service::create_api_key(&state.db, "dash".to_string(), None)
+            .await
+            .unwrap();
+        let app = create_router(state);
+        let request = |query: &str| {
+            Request::builder()
+                .uri(format!("/api/stream/time?{}", query))
+                .header("X-API-Key", &key.api_key)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        for query in ["", "tz=UTC&interval=0", "tz=UTC&interval=soon"] {
+            let response = app.clone().oneshot(request(query)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", query);
+        }
+
+        let response = app.oneshot(request("tz=UTC&tz=Nowhere/City&interval=60")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        // The first tick comes at once
+        let mut body = response.into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+        let chunk = std::str::from_utf8(&chunk).unwrap();
+        let data = chunk.strip_prefix("event: tick\ndata: ").unwrap().trim_end();
+        let times: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(times["results"][0]["timezone"], "UTC");
+        assert!(times["results"][0]["info"].is_object());
+        assert!(times["results"][1]["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_stream_meters_each_tick() {
+        use tokio_stream::StreamExt;
+
+        let state = test_state().await;
+        let key = crate::auth::service::create_api_key(&state.db, "dash".to_string(), None)
+            .await
+            .unwrap();
+        let quota = crate::auth::service::set_monthly_quota(&state.db, key.id.clone(), Some(2));
+        assert!(quota.await.unwrap());
+        let app = create_router(state);
+
+        let request = Request::builder()
+            .uri("/api/stream/time?tz=UTC&interval=1")
+            .header("X-API-Key", &key.api_key)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let events: Vec<String> = response
+            .into_body()
+            .into_data_stream()
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+            .filter(|chunk| !chunk.starts_with(':'))
+            .collect()
+            .await;
+        // The request and one more tick fit in the quota; the stream then ends
+        assert_eq!(events.len(), 3);
+        assert!(events[1].starts_with("event: tick\n"));
+        assert!(events[2].starts_with("event: error\ndata: Monthly quota of 2 requests"));
+    }
 }
@@ -14,21 +14,242 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::models::{ConvertRequest, ConvertResponse, ConvertTimezoneInfo, TimezoneInfo, TimezoneListItem};
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use crate::boundaries;
+use crate::calendar;
+use crate::geoip::GeoIp;
+use crate::holidays;
+use crate::humanize;
+use crate::ics;
+use crate::interval::{self, IntervalPart, IsoDuration};
+use crate::leap;
+use crate::planning::{self, WorkingHours};
+use crate::models::{
+    AbbreviationCandidate, AbbreviationResponse, AddMode, AddRequest, AddResponse, AgeQuery,
+    AgeResponse, AirportInfo, AutocompleteQuery, AutocompleteResult, BatchGeolocationRequest,
+    BatchGeolocationResponse, BoundaryFeature, BoundaryGeometry, BoundaryProperties, BoundaryQuery,
+    BulkTimeResponse, BusinessHours, CalendarDates, CalendarInfo, CalendarQuery, CalendarWeek,
+    ClockChange, ClockShift, ConvertRequest, ConvertResponse, ConvertTimezoneInfo, CountryZone,
+    CountryZones, DstAtQuery, DstCalendarQuery, DstCountdown, DstQuery, DstStatus, DstZone,
+    DstZonesResponse, DumpQuery, DurationQuery, EpochNow, ExportTransition, ExportZone, GapPolicy,
+    GeoJsonGeometry, GeoJsonInput, GeolocationResult, Holiday, HolidayQuery, HolidaysResponse,
+    IntervalQuery, IntervalResponse, IpInfo, ItineraryEnd, ItineraryRequest, ItineraryResponse,
+    JetLagDay, JetLagPlan, JetLagRequest, LeapSecondEntry, LeapSecondsResponse, MatchKind,
+    MatrixRequest, MatrixResponse, Occurrence, OceanInfo, OffsetEntry, OffsetsQuery,
+    OffsetsResponse, ParseQuery, ParseResponse, PolarCondition, RecurrenceRequest,
+    RecurrenceResponse, SunQuery, SunTimes, TimeOutput, TimeQuery, TimeRepresentations,
+    TimeResponse, TimeUnit, TimestampUnit, TimezoneExport, TimezoneGrouping, TimezoneInfo,
+    TimezoneListItem, TimezoneResolution, TimezoneSearchQuery, TimezoneSearchResult, TimezonesQuery,
+    TimezonesResponse, Twilight, TwilightCondition, TwilightTimes, TzdataInfo, WallTime, WeekInfo,
+    WeekQuery, WindowsZoneMapping, ZoneAlias, ZoneCountries, ZoneCountry, ZoneDump, ZoneTimeResult,
+    ZoneTransition,
+};
+use crate::names::ZoneNames;
+use crate::recurrence::{RecurrenceRule, Until};
+use crate::solar;
+use crate::tzif::{self, LocalTimeType};
+use crate::zones;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, Months, NaiveDate, NaiveDateTime,
+    NaiveTime, Offset, SecondsFormat, TimeZone, Utc,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::IpAddr;
+use std::time::Instant;
 use chrono_tz::{Tz, TZ_VARIANTS};
 use chrono_tz::OffsetComponents;
 
+// Supported range for conversions: 1800-01-01T00:00:00Z (covers the LMT era of every
+// zone) through 9999-12-31T23:59:59Z (last instant representable in RFC3339)
+const MIN_SUPPORTED_TIMESTAMP: i64 = -5_364_662_400;
+const MAX_SUPPORTED_TIMESTAMP: i64 = 253_402_300_799;
+
+// Upper bound on client-supplied strftime patterns
+const MAX_FORMAT_LENGTH: usize = 256;
+
+// Local datetime layouts tried, in order, by the flexible parser
+const NAIVE_DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+    "%d %b %Y %I:%M %p",
+    "%d %b %Y %H:%M:%S",
+    "%d %b %Y %H:%M",
+    "%b %d, %Y %I:%M %p",
+    "%b %d, %Y %H:%M",
+];
+
+// Date-only layouts, interpreted as local midnight
+const NAIVE_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d %b %Y", "%b %d, %Y"];
+
+// Epoch strings at or above this magnitude are read as milliseconds (1e11 s is year 5138)
+const EPOCH_MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+// Timezone search result limits
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+
+// Months ahead sampled when looking up which zones use an abbreviation
+const ABBREVIATION_SAMPLE_MONTHS: i64 = 12;
+
+// Autocomplete results: default count, and the lowest score worth returning
+const DEFAULT_AUTOCOMPLETE_LIMIT: usize = 10;
+const MIN_AUTOCOMPLETE_SCORE: f64 = 0.8;
+
+// Local names of cities whose zone uses the English exonym
+const CITY_ENDONYMS: &[(&str, &str)] = &[
+    ("Europe/Athens", "Athína"),
+    ("Europe/Belgrade", "Beograd"),
+    ("Europe/Brussels", "Bruxelles"),
+    ("Europe/Brussels", "Brussel"),
+    ("Europe/Bucharest", "București"),
+    ("Europe/Copenhagen", "København"),
+    ("Europe/Kyiv", "Kyïv"),
+    ("Europe/Lisbon", "Lisboa"),
+    ("Europe/Moscow", "Moskva"),
+    ("Europe/Prague", "Praha"),
+    ("Europe/Rome", "Roma"),
+    ("Europe/Sofia", "Sofiya"),
+    ("Europe/Tirane", "Tirana"),
+    ("Europe/Vienna", "Wien"),
+    ("Europe/Warsaw", "Warszawa"),
+    ("Europe/Zurich", "Zürich"),
+    ("America/Mexico_City", "Ciudad de México"),
+    ("America/Sao_Paulo", "São Paulo"),
+    ("Asia/Kolkata", "Calcutta"),
+    ("Asia/Ho_Chi_Minh", "Saigon"),
+];
+
+// Example zones listed per offset in the offsets directory
+const REPRESENTATIVE_ZONES: usize = 5;
+
+// How far ahead to look for the next offset transition
+const TRANSITION_SEARCH_DAYS: usize = 400;
+
+// Most zones in one bulk current-time request
+const MAX_BULK_ZONES: usize = 100;
+
+// Boundary simplification tolerance in degrees, about a kilometre
+const DEFAULT_BOUNDARY_TOLERANCE: f64 = 0.01;
+const MAX_BOUNDARY_TOLERANCE: f64 = 1.0;
+
+// Most points in one batch geolocation request
+const MAX_BATCH_POINTS: usize = 1000;
+
+// Most occurrences returned by one recurrence expansion
+const MAX_OCCURRENCES: usize = 1000;
+
+// Transition dump: default year range and the widest range per request
+const DEFAULT_DUMP_YEARS: (i32, i32) = (1970, 2040);
+const MAX_DUMP_YEARS: i32 = 300;
+
+// Clock-change calendar feed: default and widest look-ahead in years
+const DEFAULT_DST_CALENDAR_YEARS: i32 = 2;
+const MAX_DST_CALENDAR_YEARS: i32 = 10;
+
+// Years of transitions on either side of the current year in the database export
+const EXPORT_YEARS: i32 = 10;
+
+// Last year covered by explicit transitions in generated TZif files
+const TZIF_END_YEAR: i32 = 2037;
+
+// Offset matrix limits: zones per request, and the hourly look-ahead for offset changes
+const MAX_MATRIX_ZONES: usize = 100;
+const MATRIX_LOOKAHEAD_HOURS: i64 = 30 * 24;
+
+// Result of flexible datetime parsing
+enum ParsedDateTime {
+    // Input carried its own offset (RFC3339, RFC2822), so it pins an instant
+    Instant(DateTime<FixedOffset>),
+    // Epoch value given as a string
+    Epoch(DateTime<Utc>),
+    // Wall-clock time that still needs a timezone
+    Local(NaiveDateTime),
+}
+
+// A conversion source or target: a named zone with rules, or a bare UTC offset
+enum ConvertZone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+// Why a coordinate pair was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateError {
+    LatitudeNotFinite,
+    LongitudeNotFinite,
+    LatitudeOutOfRange,
+    LongitudeOutOfRange,
+}
+
+impl CoordinateError {
+    // Stable code returned to clients alongside the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            CoordinateError::LatitudeNotFinite => "latitude_not_finite",
+            CoordinateError::LongitudeNotFinite => "longitude_not_finite",
+            CoordinateError::LatitudeOutOfRange => "latitude_out_of_range",
+            CoordinateError::LongitudeOutOfRange => "longitude_out_of_range",
+        }
+    }
+}
+
+impl std::fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CoordinateError::LatitudeNotFinite => "Latitude must be a finite number",
+            CoordinateError::LongitudeNotFinite => "Longitude must be a finite number",
+            CoordinateError::LatitudeOutOfRange => "Latitude must be between -90 and 90",
+            CoordinateError::LongitudeOutOfRange => {
+                "Longitude must be between -180 and 180 (set normalize=true to wrap it)"
+            }
+        })
+    }
+}
+
+// Wall-clock and monotonic readings taken together at startup, so later samples can
+// detect system clock steps (NTP corrections, manual changes) since the server started
+#[derive(Debug, Clone, Copy)]
+pub struct ClockAnchor {
+    wall: DateTime<Utc>,
+    monotonic: Instant,
+}
+
+impl ClockAnchor {
+    pub fn now() -> Self {
+        Self {
+            wall: Utc::now(),
+            monotonic: Instant::now(),
+        }
+    }
+}
+
+// Fuzzy autocomplete index over zone names, their cities, countries, and local city names.
+// Built once at startup by EpochZoneService::build_search_index.
+pub struct SearchIndex {
+    // (zone name, normalized search key)
+    entries: Vec<(&'static str, String)>,
+}
+
 // Core timezone service handling all timezone operations
 pub struct EpochZoneService;
 
 impl EpochZoneService {
     // Get current time and metadata for a specific timezone
-    pub fn get_timezone_info(timezone_name: &str) -> Result<TimezoneInfo, String> {
+    pub fn get_timezone_info(
+        timezone_name: &str,
+        options: &TimeQuery,
+    ) -> Result<TimezoneInfo, String> {
+        if let Some(pattern) = options.format.as_deref() {
+            Self::validate_format(pattern)?;
+        }
+
         // Parse the timezone
-        let tz: Tz = timezone_name
-            .parse()
-            .map_err(|_| format!("Invalid timezone: {}", timezone_name))?;
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
 
         // Get current time in UTC
         let utc_now: DateTime<Utc> = Utc::now();
@@ -36,17 +257,8 @@ impl EpochZoneService {
         // Convert to the requested timezone
         let local_time = utc_now.with_timezone(&tz);
 
-        // Get UTC offset string using format (always works)
-        let offset_str = format!("{}", local_time.format("%z"));
-        // Parse it: +0530 or -0800
-        let offset_string = if offset_str.len() >= 5 {
-            let sign = &offset_str[0..1];
-            let hours = &offset_str[1..3];
-            let minutes = &offset_str[3..5];
-            format!("UTC{}{}:{}", sign, hours, minutes)
-        } else {
-            "UTC+00:00".to_string()
-        };
+        // Get UTC offset string, e.g. UTC+05:30 or UTC-08:00
+        let offset_string = Self::format_utc_offset(&local_time);
 
         // Get timezone abbreviation (e.g., PST, EST)
         let abbreviation = Self::format_abbreviation(&local_time);
@@ -56,11 +268,123 @@ impl EpochZoneService {
 
         Ok(TimezoneInfo {
             timezone: timezone_name.to_string(),
+            canonical: zones::canonical_name(tz.name()).to_string(),
+            is_canonical: zones::canonical_name(tz.name()) == timezone_name,
             current_time: local_time.to_rfc3339(),
             utc_offset: offset_string,
             abbreviation,
             is_dst,
-            timestamp: utc_now.timestamp(),
+            timestamp: Self::timestamp_in_unit(&utc_now, options.unit)
+                .ok_or_else(|| "Current time is out of range for the requested unit".to_string())?,
+            unit: options.unit,
+            timestamp_ms: utc_now.timestamp_millis(),
+            formatted: options
+                .format
+                .as_deref()
+                .map(|pattern| local_time.format(pattern).to_string()),
+            rfc2822: Self::format_rfc2822(&local_time),
+            http_date: Self::format_http_date(&utc_now),
+            candidates: None,
+            ocean: None,
+            resolved_by: None,
+        })
+    }
+
+    // Current time for a zone, cut down to the representations in `options.output` if set
+    pub fn get_time(timezone_name: &str, options: &TimeQuery) -> Result<TimeResponse, String> {
+        let info = Self::get_timezone_info(timezone_name, options)?;
+        Ok(Self::select_output(info, options.output))
+    }
+
+    // Current time in the caller's own zone: the GeoIP zone for their address, else the
+    // country in their Accept-Language header, else UTC
+    pub fn get_auto_time(
+        geoip: &GeoIp,
+        ip: Option<IpAddr>,
+        accept_language_zone: Option<&str>,
+        options: &TimeQuery,
+    ) -> Result<TimeResponse, String> {
+        let (timezone_name, resolved_by) = match ip.and_then(|ip| geoip.lookup(ip)) {
+            Some(location) => (location.timezone, TimezoneResolution::Geoip),
+            None => match accept_language_zone {
+                Some(zone) => (zone.to_string(), TimezoneResolution::AcceptLanguage),
+                None => ("UTC".to_string(), TimezoneResolution::Default),
+            },
+        };
+        let mut info = Self::get_timezone_info(&timezone_name, options)?;
+        info.resolved_by = Some(resolved_by);
+        Ok(Self::select_output(info, options.output))
+    }
+
+    fn select_output(info: TimezoneInfo, output: Option<TimeOutput>) -> TimeResponse {
+        let Some(output) = output else {
+            return TimeResponse::Full(Box::new(info));
+        };
+        let wanted = |kind: TimeOutput| output == kind || output == TimeOutput::All;
+        TimeResponse::Selected(TimeRepresentations {
+            unix: wanted(TimeOutput::Unix).then(|| info.timestamp_ms.div_euclid(1000)),
+            unix_ms: wanted(TimeOutput::UnixMs).then_some(info.timestamp_ms),
+            iso: wanted(TimeOutput::Iso).then(|| info.current_time.clone()),
+            rfc2822: info.rfc2822.clone().filter(|_| wanted(TimeOutput::Rfc2822)),
+            resolved_by: info.resolved_by,
+            timezone: info.timezone,
+        })
+    }
+
+    // Current epoch in every common unit, with clock-quality metadata relative to `anchor`
+    pub fn get_epoch_now(anchor: &ClockAnchor) -> EpochNow {
+        // Bracket the wall-clock read with monotonic reads to bound its uncertainty
+        let before = Instant::now();
+        let now = Utc::now();
+        let after = Instant::now();
+
+        let monotonic_elapsed = before.duration_since(anchor.monotonic);
+        let wall_elapsed = now - anchor.wall;
+        let drift = wall_elapsed - Duration::from_std(monotonic_elapsed).unwrap_or_default();
+
+        EpochNow {
+            seconds: now.timestamp(),
+            milliseconds: now.timestamp_millis(),
+            microseconds: now.timestamp_micros(),
+            nanoseconds: now.timestamp_nanos_opt().unwrap_or_default(),
+            iso8601: now.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            monotonic_uptime_ms: monotonic_elapsed.as_millis() as u64,
+            wall_clock_drift_ms: drift.num_milliseconds(),
+            sample_jitter_ns: after.duration_since(before).as_nanos() as u64,
+        }
+    }
+
+    // Current time in each of the given zones. Invalid names get a per-zone error
+    // instead of failing the whole request.
+    pub fn get_current_times(timezones: &[String]) -> Result<BulkTimeResponse, String> {
+        if timezones.is_empty() {
+            return Err("At least one 'tz' parameter is required".to_string());
+        }
+        if timezones.len() > MAX_BULK_ZONES {
+            return Err(format!("At most {} timezones are supported", MAX_BULK_ZONES));
+        }
+
+        let results: Vec<ZoneTimeResult> = timezones
+            .iter()
+            .map(|name| match Self::get_timezone_info(name, &TimeQuery::default()) {
+                Ok(info) => ZoneTimeResult {
+                    timezone: name.clone(),
+                    member: None,
+                    info: Some(info),
+                    error: None,
+                },
+                Err(e) => ZoneTimeResult {
+                    timezone: name.clone(),
+                    member: None,
+                    info: None,
+                    error: Some(e),
+                },
+            })
+            .collect();
+
+        Ok(BulkTimeResponse {
+            errors: results.iter().filter(|r| r.error.is_some()).count(),
+            results,
         })
     }
 
@@ -71,11 +395,134 @@ impl EpochZoneService {
             .map(|tz| {
                 let name = tz.name().to_string();
                 let display_name = name.replace('_', " ");
-                TimezoneListItem { name, display_name }
+                TimezoneListItem {
+                    name,
+                    display_name,
+                    utc_offset: None,
+                    offset_seconds: None,
+                    is_dst: None,
+                    country_codes: None,
+                }
+            })
+            .collect()
+    }
+
+    // The tz database's backward-compatibility links ("US/Eastern" -> "America/New_York").
+    // Every alias is accepted wherever a timezone name is.
+    pub fn get_aliases() -> Vec<ZoneAlias> {
+        zones::links()
+            .into_iter()
+            .map(|(alias, canonical)| ZoneAlias {
+                alias: alias.to_string(),
+                canonical: canonical.to_string(),
+            })
+            .collect()
+    }
+
+    // Rails friendly names and the canonical IANA zones they map to
+    pub fn get_rails_names() -> Vec<ZoneAlias> {
+        zones::rails_zones()
+            .iter()
+            .map(|(name, zone)| ZoneAlias {
+                alias: name.to_string(),
+                canonical: zones::canonical_name(zone).to_string(),
             })
             .collect()
     }
 
+    // IANA zones for a Windows timezone ID ("Pacific Standard Time"), per CLDR
+    pub fn windows_to_iana(windows_id: &str) -> Result<WindowsZoneMapping, String> {
+        let (id, zones) = zones::windows_zones(windows_id)
+            .ok_or_else(|| format!("Unknown Windows timezone ID: {}", windows_id))?;
+        Ok(Self::windows_mapping(id, zones))
+    }
+
+    // Windows timezone ID for an IANA zone or alias, per CLDR
+    pub fn iana_to_windows(timezone_name: &str) -> Result<WindowsZoneMapping, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let (id, zones) = zones::windows_id(tz.name())
+            .and_then(zones::windows_zones)
+            .ok_or_else(|| format!("No Windows timezone ID maps to {}", timezone_name))?;
+        Ok(Self::windows_mapping(id, zones))
+    }
+
+    // Mapping with zone names canonicalized, since CLDR keeps some legacy spellings
+    fn windows_mapping(id: &str, zones: &[&str]) -> WindowsZoneMapping {
+        let zones: Vec<String> =
+            zones.iter().map(|zone| zones::canonical_name(zone).to_string()).collect();
+        WindowsZoneMapping {
+            windows_id: id.to_string(),
+            timezone: zones[0].clone(),
+            zones,
+        }
+    }
+
+    // List all timezones, optionally grouped by region and enriched with the current
+    // offset, DST flag, and country codes
+    pub fn list_timezones(query: &TimezonesQuery) -> Result<TimezonesResponse, String> {
+        let (mut offset, mut dst, mut country) = (false, false, false);
+        for field in query.include.iter().flat_map(|include| include.split(',')) {
+            match field.trim() {
+                "offset" => offset = true,
+                "dst" => dst = true,
+                "country" => country = true,
+                other => {
+                    return Err(format!(
+                        "Unknown include field '{}' (expected offset, dst, country)",
+                        other
+                    ))
+                }
+            }
+        }
+
+        let names = query
+            .lang
+            .as_deref()
+            .map(ZoneNames::for_language)
+            .transpose()?;
+
+        let now = Utc::now();
+        let mut timezones = Self::get_all_timezones();
+        for item in &mut timezones {
+            let tz: Tz = item
+                .name
+                .parse()
+                .map_err(|_| format!("Invalid timezone: {}", item.name))?;
+            if let Some(display_name) = names.as_ref().and_then(|n| n.display_name(&tz, &now)) {
+                item.display_name = display_name;
+            }
+            let local = now.with_timezone(&tz);
+            if offset {
+                item.utc_offset = Some(Self::format_utc_offset(&local));
+                item.offset_seconds = Some(local.offset().fix().local_minus_utc());
+            }
+            if dst {
+                item.is_dst = Some(Self::is_daylight_saving_time(&tz, &now));
+            }
+            if country {
+                item.country_codes = Some(
+                    zones::zone_countries(&item.name)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                );
+            }
+        }
+
+        Ok(match query.group_by {
+            None => TimezonesResponse::List(timezones),
+            Some(TimezoneGrouping::Region) => {
+                let mut groups: BTreeMap<String, Vec<TimezoneListItem>> = BTreeMap::new();
+                for item in timezones {
+                    let region = zones::region(&item.name).unwrap_or("Other").to_string();
+                    groups.entry(region).or_default().push(item);
+                }
+                TimezonesResponse::Grouped(groups)
+            }
+        })
+    }
+
     // Return timezone abbreviation, or "N/A" if chrono only provides a numeric offset
     fn format_abbreviation<T: chrono::TimeZone>(dt: &DateTime<T>) -> String
     where
@@ -89,284 +536,4263 @@ impl EpochZoneService {
         }
     }
 
-    // Check if a timezone is currently observing daylight saving time
-    fn is_daylight_saving_time(tz: &Tz, utc_now: &DateTime<Utc>) -> bool {
-        utc_now.with_timezone(tz).offset().dst_offset().num_seconds() != 0
-    }
+    // Zones that use `abbreviation` (case-insensitive) at any point in the next year,
+    // sampled monthly so both standard and daylight names are seen. Links are skipped.
+    pub fn lookup_abbreviation(abbreviation: &str) -> Result<AbbreviationResponse, String> {
+        let wanted = abbreviation.trim().to_uppercase();
+        if wanted.is_empty() || wanted == "N/A" {
+            return Err("Abbreviation must not be empty".to_string());
+        }
 
-    // Look up timezone from geographic coordinates and return full timezone info
-    pub fn get_timezone_by_coordinates(
-        finder: &tzf_rs::DefaultFinder,
-        lat: f64,
-        lng: f64,
-    ) -> Result<TimezoneInfo, String> {
-        let tz_name = finder.get_tz_name(lng, lat);
-        Self::get_timezone_info(tz_name)
+        let now = Utc::now();
+        let mut candidates: Vec<AbbreviationCandidate> = Vec::new();
+        for tz in TZ_VARIANTS.iter().filter(|tz| !zones::is_link(tz.name())) {
+            for month in 0..=ABBREVIATION_SAMPLE_MONTHS {
+                let at = now + Duration::days(30 * month);
+                let local = at.with_timezone(tz);
+                if Self::format_abbreviation(&local).to_uppercase() != wanted {
+                    continue;
+                }
+                let offset_seconds = local.offset().fix().local_minus_utc();
+                if candidates
+                    .iter()
+                    .any(|c| c.timezone == tz.name() && c.offset_seconds == offset_seconds)
+                {
+                    continue;
+                }
+                let now_local = now.with_timezone(tz);
+                candidates.push(AbbreviationCandidate {
+                    timezone: tz.name().to_string(),
+                    utc_offset: Self::format_utc_offset(&local),
+                    offset_seconds,
+                    is_dst: Self::is_daylight_saving_time(tz, &at),
+                    in_use: Self::format_abbreviation(&now_local).to_uppercase() == wanted
+                        && now_local.offset().fix().local_minus_utc() == offset_seconds,
+                });
+            }
+        }
+        if candidates.is_empty() {
+            return Err(format!("Unknown abbreviation: {}", abbreviation));
+        }
+        candidates.sort_by(|a, b| {
+            (a.offset_seconds, &a.timezone).cmp(&(b.offset_seconds, &b.timezone))
+        });
+
+        let mut offsets: Vec<String> = Vec::new();
+        for candidate in &candidates {
+            if !offsets.contains(&candidate.utc_offset) {
+                offsets.push(candidate.utc_offset.clone());
+            }
+        }
+        Ok(AbbreviationResponse {
+            abbreviation: wanted,
+            ambiguous: offsets.len() > 1,
+            offsets,
+            count: candidates.len(),
+            candidates,
+        })
     }
 
-    // Validate if a timezone name is valid
-    pub fn is_valid_timezone(timezone_name: &str) -> bool {
-        timezone_name.parse::<Tz>().is_ok()
+    // Search zones by name or country name, optionally narrowed to a country and region.
+    // Results are ranked exact > prefix > substring > country match, then by name.
+    pub fn search_timezones(
+        query: &TimezoneSearchQuery,
+    ) -> Result<Vec<TimezoneSearchResult>, String> {
+        let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        if !(1..=MAX_SEARCH_LIMIT).contains(&limit) {
+            return Err(format!("'limit' must be between 1 and {}", MAX_SEARCH_LIMIT));
+        }
+        let needle = query
+            .q
+            .as_deref()
+            .map(|q| q.trim().to_lowercase().replace(' ', "_"))
+            .filter(|q| !q.is_empty());
+
+        let mut results: Vec<TimezoneSearchResult> = TZ_VARIANTS
+            .iter()
+            .filter_map(|tz| {
+                let name = tz.name();
+                let record = zones::find_zone(name);
+                let country_code = record.map(|r| r.country_code);
+                let region = zones::region(name);
+
+                if let Some(country) = query.country.as_deref() {
+                    if !country_code.is_some_and(|c| c.eq_ignore_ascii_case(country)) {
+                        return None;
+                    }
+                }
+                if let Some(wanted) = query.region.as_deref() {
+                    if !region.is_some_and(|r| r.eq_ignore_ascii_case(wanted)) {
+                        return None;
+                    }
+                }
+
+                let country_name = country_code.and_then(zones::country_name);
+                let matched = match &needle {
+                    Some(needle) => Some(Self::match_zone_name(name, country_name, needle)?),
+                    None => None,
+                };
+                Some(TimezoneSearchResult {
+                    name: name.to_string(),
+                    display_name: name.replace('_', " "),
+                    region: region.map(|r| r.to_string()),
+                    country_code: country_code.map(|c| c.to_string()),
+                    country_name: country_name.map(|c| c.to_string()),
+                    matched,
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| (a.matched, &a.name).cmp(&(b.matched, &b.name)));
+        results.truncate(limit);
+        Ok(results)
     }
 
-    // Convert a time between timezones
-    pub fn convert_timezone(request: &ConvertRequest) -> Result<ConvertResponse, String> {
-        // Parse target timezone
-        let to_tz: Tz = request
-            .to
-            .parse()
-            .map_err(|_| format!("Invalid target timezone: {}", request.to))?;
+    // How a lowercase, underscore-separated query matches a zone name or its country
+    fn match_zone_name(name: &str, country: Option<&str>, needle: &str) -> Option<MatchKind> {
+        let lower = name.to_lowercase();
+        let city = lower.rsplit('/').next().unwrap_or(&lower);
+        if lower == needle || city == needle {
+            Some(MatchKind::Exact)
+        } else if lower.split('/').any(|part| part.starts_with(needle)) {
+            Some(MatchKind::Prefix)
+        } else if lower.contains(needle) {
+            Some(MatchKind::Substring)
+        } else if country.is_some_and(|c| c.to_lowercase().replace(' ', "_").contains(needle)) {
+            Some(MatchKind::Country)
+        } else {
+            None
+        }
+    }
 
-        // Determine the UTC instant and source timezone
-        let (utc_instant, from_tz): (DateTime<Utc>, Tz) = match (
-            request.timestamp,
-            request.datetime.as_deref(),
-            request.from.as_deref(),
-        ) {
-            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
-                return Err(
-                    "Provide either 'timestamp' or 'datetime'+'from', not both".to_string(),
-                );
+    // Build the autocomplete index: for every zone its full name, city, country name, and
+    // any local city names, all lowercased with accents and underscores folded away
+    pub fn build_search_index() -> SearchIndex {
+        let mut entries = Vec::new();
+        for tz in TZ_VARIANTS.iter() {
+            let name = tz.name();
+            entries.push((name, Self::fold_search_key(name)));
+            if let Some((_, city)) = name.rsplit_once('/') {
+                entries.push((name, Self::fold_search_key(city)));
             }
-            (Some(ts), None, None) => {
-                let utc = DateTime::from_timestamp(ts, 0)
-                    .ok_or_else(|| format!("Invalid timestamp: {}", ts))?;
-                (utc, chrono_tz::UTC)
-            }
-            (None, Some(dt_str), Some(from_str)) => {
-                let from_tz: Tz = from_str
-                    .parse()
-                    .map_err(|_| format!("Invalid source timezone: {}", from_str))?;
-                let naive = NaiveDateTime::parse_from_str(dt_str, "%Y-%m-%dT%H:%M:%S")
-                    .or_else(|_| NaiveDateTime::parse_from_str(dt_str, "%Y-%m-%dT%H:%M"))
-                    .map_err(|e| format!("Invalid datetime '{}': {}", dt_str, e))?;
-                let local = from_tz
-                    .from_local_datetime(&naive)
-                    .single()
-                    .ok_or_else(|| {
-                        format!("Ambiguous or invalid local time '{}' in {}", dt_str, from_str)
-                    })?;
-                (local.with_timezone(&Utc), from_tz)
+            if let Some(country) = zones::find_zone(name)
+                .and_then(|record| zones::country_name(record.country_code))
+            {
+                entries.push((name, Self::fold_search_key(country)));
             }
-            (None, None, _) => {
-                return Err("Either 'timestamp' or 'datetime'+'from' is required".to_string());
+        }
+        for (zone, local_name) in CITY_ENDONYMS {
+            entries.push((*zone, Self::fold_search_key(local_name)));
+        }
+        SearchIndex { entries }
+    }
+
+    // Typo-tolerant autocomplete ("Beograd", "New Yrok"), best matches first with a
+    // 0-1 score. Each zone appears once, under its best-scoring key.
+    pub fn autocomplete(
+        index: &SearchIndex,
+        query: &AutocompleteQuery,
+    ) -> Result<Vec<AutocompleteResult>, String> {
+        let needle = Self::fold_search_key(&query.q);
+        if needle.is_empty() {
+            return Err("'q' must not be empty".to_string());
+        }
+        let limit = query.limit.unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT);
+        if !(1..=MAX_SEARCH_LIMIT).contains(&limit) {
+            return Err(format!("'limit' must be between 1 and {}", MAX_SEARCH_LIMIT));
+        }
+
+        let mut best: HashMap<&'static str, (f64, &str)> = HashMap::new();
+        for (zone, key) in &index.entries {
+            let score = Self::fuzzy_score(&needle, key);
+            if score < MIN_AUTOCOMPLETE_SCORE {
+                continue;
             }
-            (None, Some(_), None) => {
-                return Err("'from' timezone is required when using 'datetime'".to_string());
+            let entry = best.entry(zone).or_insert((score, key));
+            if score > entry.0 {
+                *entry = (score, key);
             }
-        };
+        }
 
-        let from_info = Self::build_convert_info(&utc_instant, &from_tz);
-        let to_info = Self::build_convert_info(&utc_instant, &to_tz);
+        let mut results: Vec<AutocompleteResult> = best
+            .into_iter()
+            .map(|(zone, (score, key))| AutocompleteResult {
+                name: zone.to_string(),
+                display_name: zone.replace('_', " "),
+                score: (score * 1000.0).round() / 1000.0,
+                matched: key.to_string(),
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+        results.truncate(limit);
+        Ok(results)
+    }
 
-        Ok(ConvertResponse {
-            from: from_info,
-            to: to_info,
-        })
+    // Similarity of a query to a key in [0, 1]. A prefix counts as a strong match so
+    // partially typed names rank well; otherwise Jaro-Winkler against the whole key or,
+    // for a partially typed name with a typo, against the key's leading characters.
+    fn fuzzy_score(needle: &str, key: &str) -> f64 {
+        if key.starts_with(needle) {
+            return 0.9 + 0.1 * needle.len() as f64 / key.len() as f64;
+        }
+        let whole = strsim::jaro_winkler(needle, key);
+        let head: String = key.chars().take(needle.chars().count()).collect();
+        let partial = strsim::jaro_winkler(needle, &head) * 0.95;
+        whole.max(partial)
     }
 
-    // Build a ConvertTimezoneInfo for a given UTC instant in a given timezone
-    fn build_convert_info(utc: &DateTime<Utc>, tz: &Tz) -> ConvertTimezoneInfo {
-        let local = utc.with_timezone(tz);
+    // Lowercase, turn underscores into spaces, and fold common Latin accents to ASCII
+    fn fold_search_key(value: &str) -> String {
+        value
+            .trim()
+            .chars()
+            .flat_map(char::to_lowercase)
+            .map(|c| match c {
+                '_' => ' ',
+                'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ă' => 'a',
+                'é' | 'è' | 'ê' | 'ë' => 'e',
+                'í' | 'ì' | 'î' | 'ï' => 'i',
+                'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' | 'ō' => 'o',
+                'ú' | 'ù' | 'û' | 'ü' => 'u',
+                'ñ' => 'n',
+                'ç' | 'ć' | 'č' => 'c',
+                'š' | 'ș' | 'ş' => 's',
+                'ž' => 'z',
+                'đ' => 'd',
+                'ț' => 't',
+                other => other,
+            })
+            .collect()
+    }
 
-        let offset_str = format!("{}", local.format("%z"));
-        let utc_offset = if offset_str.len() >= 5 {
-            let sign = &offset_str[0..1];
-            let hours = &offset_str[1..3];
-            let minutes = &offset_str[3..5];
-            format!("UTC{}{}:{}", sign, hours, minutes)
-        } else {
-            "UTC+00:00".to_string()
-        };
+    // Check if a timezone is currently observing daylight saving time
+    fn is_daylight_saving_time(tz: &Tz, utc_now: &DateTime<Utc>) -> bool {
+        utc_now.with_timezone(tz).offset().dst_offset().num_seconds() != 0
+    }
 
-        let abbreviation = Self::format_abbreviation(&local);
-        let is_dst = Self::is_daylight_saving_time(tz, utc);
+    // Zones observing DST at `query.at` (default now), with when each reverts to standard time
+    pub fn get_dst_zones(query: &DstQuery) -> Result<DstZonesResponse, String> {
+        let at = Self::resolve_instant(query.at.as_deref(), &Tz::UTC)?;
 
-        ConvertTimezoneInfo {
+        let zones: Vec<DstZone> = TZ_VARIANTS
+            .iter()
+            .filter(|tz| Self::is_daylight_saving_time(tz, &at))
+            .map(|tz| {
+                let local = at.with_timezone(tz);
+                let dst_seconds = local.offset().dst_offset().num_seconds() as i32;
+                let ends = Self::next_dst_change(tz, &at, false);
+                let seconds_until_end = ends.map(|end| (end - at).num_seconds());
+                DstZone {
+                    timezone: tz.name().to_string(),
+                    abbreviation: Self::format_abbreviation(&local),
+                    utc_offset: Self::format_utc_offset(&local),
+                    dst_offset: Self::format_offset_seconds(dst_seconds),
+                    ends_at: ends.map(|end| end.with_timezone(tz).to_rfc3339()),
+                    ends_at_timestamp: ends.map(|end| end.timestamp()),
+                    seconds_until_end,
+                    ends_in: seconds_until_end.and_then(|seconds| {
+                        let query = DurationQuery {
+                            seconds,
+                            locale: None,
+                            max_unit: Some(TimeUnit::Month),
+                            min_unit: Some(TimeUnit::Hour),
+                        };
+                        humanize::humanize_duration(&query).ok().map(|d| d.text)
+                    }),
+                }
+            })
+            .collect();
+
+        Ok(DstZonesResponse {
+            at: at.to_rfc3339(),
+            count: zones.len(),
+            zones,
+        })
+    }
+
+    // Whether a zone observes DST at `query.timestamp` (default now), past or future
+    pub fn get_dst_status(timezone_name: &str, query: &DstAtQuery) -> Result<DstStatus, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let at = Self::resolve_instant(query.timestamp.as_deref(), &tz)?;
+        let local = at.with_timezone(&tz);
+        let offset = local.offset();
+
+        Ok(DstStatus {
             timezone: tz.name().to_string(),
-            datetime: local.to_rfc3339(),
-            utc_offset,
-            abbreviation,
-            is_dst,
-            timestamp: utc.timestamp(),
-        }
+            at: local.to_rfc3339(),
+            timestamp: at.timestamp(),
+            is_dst: Self::is_daylight_saving_time(&tz, &at),
+            abbreviation: Self::format_abbreviation(&local),
+            utc_offset: Self::format_utc_offset(&local),
+            standard_offset: Self::format_offset_seconds(
+                offset.base_utc_offset().num_seconds() as i32,
+            ),
+            dst_offset: Self::format_offset_seconds(offset.dst_offset().num_seconds() as i32),
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Every distinct UTC offset in use at `query.at` (default now), with example zones
+    pub fn get_offsets(query: &OffsetsQuery) -> Result<OffsetsResponse, String> {
+        let at = Self::resolve_instant(query.at.as_deref(), &Tz::UTC)?;
 
-    #[test]
-    fn test_get_timezone_info_valid() {
-        let result = EpochZoneService::get_timezone_info("America/New_York");
-        assert!(result.is_ok());
-        
-        let info = result.unwrap();
-        assert_eq!(info.timezone, "America/New_York");
-        assert!(!info.current_time.is_empty());
+        // offset -> (zone names, abbreviations, whether every zone is in DST)
+        let mut groups: BTreeMap<i32, (Vec<&str>, BTreeSet<String>, bool)> = BTreeMap::new();
+        for tz in TZ_VARIANTS.iter() {
+            let local = at.with_timezone(tz);
+            let offset = local.offset().fix().local_minus_utc();
+            let entry = groups.entry(offset).or_insert_with(|| (Vec::new(), BTreeSet::new(), true));
+            entry.0.push(tz.name());
+            entry.1.insert(Self::format_abbreviation(&local));
+            entry.2 &= Self::is_daylight_saving_time(tz, &at);
+        }
+
+        let offsets = groups
+            .into_iter()
+            .map(|(offset, (mut zones, abbreviations, dst_only))| {
+                // Prefer Region/City names over aliases like "EST" or "Etc/GMT+5"
+                zones.sort_by_key(|name| (!name.contains('/') || name.starts_with("Etc/"), *name));
+                OffsetEntry {
+                    utc_offset: format!("UTC{}", Self::format_offset_seconds(offset)),
+                    offset_seconds: offset,
+                    zone_count: zones.len(),
+                    dst_only,
+                    abbreviations: abbreviations.into_iter().filter(|a| a != "N/A").collect(),
+                    representative_zones: zones
+                        .iter()
+                        .take(REPRESENTATIVE_ZONES)
+                        .map(|name| name.to_string())
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(OffsetsResponse {
+            at: at.to_rfc3339(),
+            offsets,
+        })
     }
 
-    #[test]
-    fn test_get_timezone_info_invalid() {
-        let result = EpochZoneService::get_timezone_info("Invalid/Timezone");
-        assert!(result.is_err());
+    // Next offset transition after `from` (within TRANSITION_SEARCH_DAYS), found by a daily
+    // scan and then a binary search down to the second
+    fn next_transition(tz: &Tz, from: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let state_at = |ts: i64| {
+            let utc = DateTime::from_timestamp(ts, 0).unwrap_or_default().naive_utc();
+            let offset = tz.offset_from_utc_datetime(&utc);
+            (offset.fix().local_minus_utc(), offset.dst_offset().num_seconds())
+        };
+
+        let start = from.timestamp();
+        let initial = state_at(start);
+        let day = Duration::days(1).num_seconds();
+        let mut lo = start;
+        for _ in 0..TRANSITION_SEARCH_DAYS {
+            let hi = lo + day;
+            if state_at(hi) != initial {
+                // Invariant: state at `lo` is still `initial`, at `hi` it has changed
+                let mut lo = lo;
+                let mut hi = hi;
+                while hi - lo > 1 {
+                    let mid = lo + (hi - lo) / 2;
+                    if state_at(mid) == initial {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                return DateTime::from_timestamp(hi, 0);
+            }
+            lo = hi;
+        }
+        None
     }
 
-    #[test]
-    fn test_get_timezone_info_utc() {
-        let result = EpochZoneService::get_timezone_info("UTC");
-        assert!(result.is_ok());
-        
-        let info = result.unwrap();
-        assert_eq!(info.utc_offset, "UTC+00:00");
-        assert_eq!(info.is_dst, false);
+    // Every offset transition in [from, to), stepping over quiet stretches longer than
+    // the transition search window
+    fn transitions_between(
+        tz: &Tz,
+        from: &DateTime<Utc>,
+        to: &DateTime<Utc>,
+    ) -> Vec<DateTime<Utc>> {
+        let mut transitions = Vec::new();
+        let mut at = *from;
+        while at < *to {
+            match Self::next_transition(tz, &at) {
+                Some(next) if next < *to => {
+                    transitions.push(next);
+                    at = next;
+                }
+                Some(_) => break,
+                None => at += Duration::days(TRANSITION_SEARCH_DAYS as i64),
+            }
+        }
+        transitions
     }
 
-    #[test]
-    fn test_get_all_timezones() {
-        let timezones = EpochZoneService::get_all_timezones();
-        assert!(!timezones.is_empty());
-        assert!(timezones.len() > 500); // Should have 600+ timezones
+    // Every offset transition in a zone between two years, inclusive, like `zdump -v`
+    pub fn dump_transitions(timezone_name: &str, query: &DumpQuery) -> Result<ZoneDump, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let from_year = query.from.unwrap_or(DEFAULT_DUMP_YEARS.0);
+        let to_year = query.to.unwrap_or(DEFAULT_DUMP_YEARS.1);
+        if !(1800..=9998).contains(&from_year) || !(1800..=9998).contains(&to_year) {
+            return Err("'from' and 'to' must be years between 1800 and 9998".to_string());
+        }
+        if to_year < from_year || to_year - from_year >= MAX_DUMP_YEARS {
+            return Err(format!(
+                "'to' must not be before 'from', and at most {} years can be dumped",
+                MAX_DUMP_YEARS
+            ));
+        }
+
+        let year_start = |year: i32| {
+            NaiveDate::from_ymd_opt(year, 1, 1)
+                .unwrap_or_default()
+                .and_time(NaiveTime::MIN)
+                .and_utc()
+        };
+        let transitions: Vec<ZoneTransition> =
+            Self::transitions_between(&tz, &year_start(from_year), &year_start(to_year + 1))
+                .into_iter()
+                .map(|at| Self::zone_transition(&tz, &at))
+                .collect();
+
+        Ok(ZoneDump {
+            timezone: tz.name().to_string(),
+            from: from_year,
+            to: to_year,
+            count: transitions.len(),
+            transitions,
+        })
     }
 
-    #[test]
-    fn test_is_valid_timezone() {
-        assert!(EpochZoneService::is_valid_timezone("America/New_York"));
-        assert!(EpochZoneService::is_valid_timezone("Europe/London"));
-        assert!(EpochZoneService::is_valid_timezone("UTC"));
-        assert!(!EpochZoneService::is_valid_timezone("Invalid/Timezone"));
+    // A transition with wall-clock readings on either side of it
+    fn zone_transition(tz: &Tz, at: &DateTime<Utc>) -> ZoneTransition {
+        let before = *at - Duration::seconds(1);
+        let wall_time = |t: &DateTime<Utc>| {
+            let local = t.with_timezone(tz);
+            WallTime {
+                local: local.to_rfc3339(),
+                utc_offset: Self::format_utc_offset(&local),
+                abbreviation: Self::format_abbreviation(&local),
+                is_dst: Self::is_daylight_saving_time(tz, t),
+            }
+        };
+        let offset_at = |t: &DateTime<Utc>| t.with_timezone(tz).offset().fix().local_minus_utc();
+        ZoneTransition {
+            at: at.to_rfc3339(),
+            timestamp: at.timestamp(),
+            before: wall_time(&before),
+            after: wall_time(at),
+            change_seconds: offset_at(at) - offset_at(&before),
+        }
     }
 
-    #[test]
-    fn test_timezone_list_format() {
-        let timezones = EpochZoneService::get_all_timezones();
-        let ny = timezones.iter().find(|tz| tz.name == "America/New_York");
+    // The next change into or out of DST after `query.at` (default now), with a countdown
+    pub fn get_next_dst_change(
+        timezone_name: &str,
+        query: &DstQuery,
+    ) -> Result<DstCountdown, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let from = Self::resolve_instant(query.at.as_deref(), &tz)?;
+        let next = Self::next_dst_change(&tz, &from, !Self::is_daylight_saving_time(&tz, &from));
+        let transition = next.map(|at| Self::zone_transition(&tz, &at));
 
-        assert!(ny.is_some());
-        let ny = ny.unwrap();
-        assert_eq!(ny.display_name, "America/New York");
+        Ok(DstCountdown {
+            timezone: tz.name().to_string(),
+            from: from.with_timezone(&tz).to_rfc3339(),
+            seconds_until: next.map(|at| (at - from).num_seconds()),
+            clocks: transition.as_ref().map(|t| {
+                if t.change_seconds > 0 {
+                    ClockChange::Forward
+                } else {
+                    ClockChange::Back
+                }
+            }),
+            transition,
+        })
+    }
+
+    // iCalendar feed of a zone's upcoming clock changes, one all-day event on each local
+    // date the clocks move
+    pub fn get_dst_calendar(
+        timezone_name: &str,
+        query: &DstCalendarQuery,
+    ) -> Result<String, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let years = query.years.unwrap_or(DEFAULT_DST_CALENDAR_YEARS);
+        if !(1..=MAX_DST_CALENDAR_YEARS).contains(&years) {
+            return Err(format!("'years' must be between 1 and {}", MAX_DST_CALENDAR_YEARS));
+        }
+        let now = Utc::now();
+        let until = now + Duration::days(366 * years as i64);
+
+        let events: Vec<ics::AllDayEvent> = Self::transitions_between(&tz, &now, &until)
+            .into_iter()
+            .map(|at| (at, Self::zone_transition(&tz, &at)))
+            .filter(|(_, transition)| transition.change_seconds != 0)
+            .map(|(at, transition)| {
+                let direction = if transition.change_seconds > 0 { "forward" } else { "back" };
+                let amount = humanize::humanize_duration(&DurationQuery {
+                    seconds: transition.change_seconds as i64,
+                    locale: None,
+                    max_unit: Some(TimeUnit::Hour),
+                    min_unit: Some(TimeUnit::Minute),
+                })
+                .map(|d| d.text)
+                .unwrap_or_default();
+                // Wall-clock readings at the moment of the change, on the old and new offsets
+                let after = at.with_timezone(&tz);
+                let before =
+                    after.naive_local() - Duration::seconds(transition.change_seconds as i64);
+                let (old, new) = (&transition.before, &transition.after);
+                ics::AllDayEvent {
+                    uid: format!("{}-{}@epochzone", at.timestamp(), tz.name()),
+                    date: after.date_naive(),
+                    summary: format!("Clocks go {} {} ({})", direction, amount, tz.name()),
+                    description: format!(
+                        "At {} local time clocks go {} to {}. {} ({}) becomes {} ({}).",
+                        before.format("%H:%M"),
+                        direction,
+                        after.format("%H:%M"),
+                        old.abbreviation,
+                        old.utc_offset,
+                        new.abbreviation,
+                        new.utc_offset
+                    ),
+                }
+            })
+            .collect();
+        Ok(ics::encode(&format!("Clock changes in {}", tz.name()), now, &events))
+    }
+
+    // Compile a zone's rules into a TZif file, with explicit transitions from 1800
+    // through TZIF_END_YEAR
+    pub fn get_tzif(timezone_name: &str) -> Result<Vec<u8>, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let from = DateTime::from_timestamp(MIN_SUPPORTED_TIMESTAMP, 0).unwrap_or_default();
+        let until = NaiveDate::from_ymd_opt(TZIF_END_YEAR + 1, 1, 1)
+            .unwrap_or_default()
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+
+        let type_at = |at: &DateTime<Utc>| {
+            let local = at.with_timezone(&tz);
+            LocalTimeType {
+                utc_offset: local.offset().fix().local_minus_utc(),
+                is_dst: local.offset().dst_offset().num_seconds() != 0,
+                abbreviation: local.format("%Z").to_string(),
+            }
+        };
+        let mut types = vec![type_at(&from)];
+        let mut transitions = Vec::new();
+        for at in Self::transitions_between(&tz, &from, &until) {
+            let local_type = type_at(&at);
+            let index = match types.iter().position(|t| *t == local_type) {
+                Some(index) => index,
+                None => {
+                    types.push(local_type);
+                    types.len() - 1
+                }
+            };
+            transitions.push((at.timestamp(), index));
+        }
+        Ok(tzif::encode(&types, &transitions))
+    }
+
+    // Next transition after `from` into (`to_dst` = true) or out of DST
+    fn next_dst_change(tz: &Tz, from: &DateTime<Utc>, to_dst: bool) -> Option<DateTime<Utc>> {
+        let mut at = *from;
+        // Offset-only changes (a zone moving its standard time) don't count
+        for _ in 0..4 {
+            at = Self::next_transition(tz, &at)?;
+            if Self::is_daylight_saving_time(tz, &at) == to_dst {
+                return Some(at);
+            }
+        }
+        None
+    }
+
+    // Export every canonical zone with its country, current offset, and transitions
+    // within EXPORT_YEARS of the current year, plus the alias table
+    pub fn export_timezones() -> TimezoneExport {
+        let now = Utc::now();
+        let (from_year, to_year) = (now.year() - EXPORT_YEARS, now.year() + EXPORT_YEARS);
+        let year_start = |year: i32| {
+            NaiveDate::from_ymd_opt(year, 1, 1)
+                .unwrap_or_default()
+                .and_time(NaiveTime::MIN)
+                .and_utc()
+        };
+        let (from, until) = (year_start(from_year), year_start(to_year + 1));
+
+        let zones = TZ_VARIANTS
+            .iter()
+            .filter(|tz| !zones::is_link(tz.name()))
+            .map(|tz| {
+                let record = zones::find_zone(tz.name());
+                let local = now.with_timezone(tz);
+                let transitions = Self::transitions_between(tz, &from, &until)
+                    .into_iter()
+                    .map(|at| {
+                        let local = at.with_timezone(tz);
+                        ExportTransition {
+                            timestamp: at.timestamp(),
+                            offset_seconds: local.offset().fix().local_minus_utc(),
+                            abbreviation: Self::format_abbreviation(&local),
+                            is_dst: Self::is_daylight_saving_time(tz, &at),
+                        }
+                    })
+                    .collect();
+                ExportZone {
+                    name: tz.name().to_string(),
+                    country_code: record.map(|r| r.country_code.to_string()),
+                    country_name: record
+                        .and_then(|r| zones::country_name(r.country_code))
+                        .map(str::to_string),
+                    utc_offset: Self::format_utc_offset(&local),
+                    offset_seconds: local.offset().fix().local_minus_utc(),
+                    abbreviation: Self::format_abbreviation(&local),
+                    is_dst: Self::is_daylight_saving_time(tz, &now),
+                    transitions,
+                }
+            })
+            .collect();
+
+        TimezoneExport {
+            tzdata_version: chrono_tz::IANA_TZDB_VERSION.to_string(),
+            generated_at: now.to_rfc3339(),
+            from_year,
+            to_year,
+            zones,
+            aliases: Self::get_aliases(),
+        }
+    }
+
+    // Report which tzdata release and boundary dataset this build was compiled with
+    pub fn get_tzdata_info(finder: &tzf_rs::DefaultFinder) -> TzdataInfo {
+        let link_count = zones::links().len();
+        TzdataInfo {
+            tzdata_version: chrono_tz::IANA_TZDB_VERSION.to_string(),
+            tzdata_source: "IANA tz database, compiled in by chrono-tz".to_string(),
+            boundaries_version: finder.data_version().to_string(),
+            boundaries_source: "timezone-boundary-builder, via tzf-rs".to_string(),
+            zone_count: TZ_VARIANTS.len() - link_count,
+            link_count,
+        }
+    }
+
+    // The leap second table with the TAI - UTC offset now and any announced leap second
+    pub fn get_leap_seconds() -> LeapSecondsResponse {
+        let now = Utc::now().timestamp();
+        let date = |timestamp: i64| {
+            DateTime::from_timestamp(timestamp, 0)
+                .unwrap_or_default()
+                .format("%Y-%m-%d")
+                .to_string()
+        };
+        let entry = |leap: &leap::LeapSecond| LeapSecondEntry {
+            date: date(leap.timestamp),
+            timestamp: leap.timestamp,
+            tai_minus_utc: leap.tai_minus_utc,
+        };
+        let list = &leap::LEAP_SECONDS;
+
+        LeapSecondsResponse {
+            tai_minus_utc: leap::tai_minus_utc(now).unwrap_or_default(),
+            leap_seconds: list.entries.iter().map(entry).collect(),
+            announced: list.entries.iter().find(|leap| leap.timestamp > now).map(entry),
+            updated: date(list.updated),
+            expires: date(list.expires),
+            is_expired: list.expires <= now,
+        }
+    }
+
+    // Look up timezone from geographic coordinates and return full timezone info
+    pub fn get_timezone_by_coordinates(
+        finder: &tzf_rs::DefaultFinder,
+        lat: f64,
+        lng: f64,
+    ) -> Result<TimezoneInfo, String> {
+        let (lat, lng) = Self::validate_coordinates(lat, lng, false).map_err(|e| e.to_string())?;
+        let tz_name = finder.get_tz_name(lng, lat);
+        let mut candidates = vec![tz_name.to_string()];
+        for name in finder.get_tz_names(lng, lat) {
+            if !candidates.iter().any(|c| c == name) {
+                candidates.push(name.to_string());
+            }
+        }
+        let mut info = Self::get_timezone_info(tz_name, &TimeQuery::default())?;
+        info.candidates = Some(candidates);
+        // The boundary data covers the oceans with nautical zones (15° bands of Etc/GMT±X)
+        if tz_name.starts_with("Etc/") {
+            let nearest = boundaries::nearest_land_zone(lng, lat);
+            info.ocean = Some(OceanInfo {
+                nearest_land_zone: nearest.map(|(zone, _)| zone.to_string()),
+                nearest_land_km: nearest.map(|(_, km)| (km * 10.0).round() / 10.0),
+            });
+        }
+        Ok(info)
+    }
+
+    // (lat, lng) of a GeoJSON Point or Point Feature. GeoJSON positions are
+    // [longitude, latitude], optionally followed by altitude.
+    pub fn geojson_coordinates(input: &GeoJsonInput) -> Result<(f64, f64), String> {
+        let coordinates = match input {
+            GeoJsonInput::Point { coordinates } => coordinates,
+            GeoJsonInput::Feature {
+                geometry: GeoJsonGeometry::Point { coordinates },
+            } => coordinates,
+        };
+        match coordinates.as_slice() {
+            [lng, lat] | [lng, lat, _] => Ok((*lat, *lng)),
+            _ => Err("A GeoJSON position needs [longitude, latitude]".to_string()),
+        }
+    }
+
+    // Check that a coordinate pair is on the globe. With `normalize`, longitudes outside
+    // [-180, 180] are wrapped around instead of rejected; latitudes never are.
+    pub fn validate_coordinates(
+        lat: f64,
+        lng: f64,
+        normalize: bool,
+    ) -> Result<(f64, f64), CoordinateError> {
+        if !lat.is_finite() {
+            return Err(CoordinateError::LatitudeNotFinite);
+        }
+        if !lng.is_finite() {
+            return Err(CoordinateError::LongitudeNotFinite);
+        }
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(CoordinateError::LatitudeOutOfRange);
+        }
+        if (-180.0..=180.0).contains(&lng) {
+            return Ok((lat, lng));
+        }
+        if !normalize {
+            return Err(CoordinateError::LongitudeOutOfRange);
+        }
+        Ok((lat, (lng + 180.0).rem_euclid(360.0) - 180.0))
+    }
+
+    // Resolve the zone at each of a batch of coordinates; failures are reported per point
+    pub fn geolocate_batch(
+        finder: &tzf_rs::DefaultFinder,
+        request: &BatchGeolocationRequest,
+    ) -> Result<BatchGeolocationResponse, String> {
+        if request.points.is_empty() {
+            return Err("At least one point is required".to_string());
+        }
+        if request.points.len() > MAX_BATCH_POINTS {
+            return Err(format!("At most {} points are supported", MAX_BATCH_POINTS));
+        }
+
+        let results: Vec<GeolocationResult> = request
+            .points
+            .iter()
+            .map(|point| {
+                let normalize = request.normalize || point.normalize;
+                let (lat, lng) = match Self::validate_coordinates(point.lat, point.lng, normalize) {
+                    Ok(coordinates) => coordinates,
+                    Err(e) => {
+                        return GeolocationResult {
+                            lat: point.lat,
+                            lng: point.lng,
+                            timezone: None,
+                            info: None,
+                            error: Some(e.to_string()),
+                            error_code: Some(e.code().to_string()),
+                        }
+                    }
+                };
+                match Self::get_timezone_by_coordinates(finder, lat, lng) {
+                    Ok(info) => GeolocationResult {
+                        lat,
+                        lng,
+                        timezone: Some(info.timezone.clone()),
+                        info: Some(info),
+                        error: None,
+                        error_code: None,
+                    },
+                    Err(e) => GeolocationResult {
+                        lat,
+                        lng,
+                        timezone: None,
+                        info: None,
+                        error: Some(e),
+                        error_code: None,
+                    },
+                }
+            })
+            .collect();
+
+        Ok(BatchGeolocationResponse {
+            errors: results.iter().filter(|r| r.error.is_some()).count(),
+            results,
+        })
+    }
+
+    // Zones used in a country with their current offsets, from zone.tab
+    pub fn get_country_zones(country_code: &str) -> Result<CountryZones, String> {
+        let country_name = zones::country_name(country_code)
+            .ok_or_else(|| format!("Unknown country code: {}", country_code))?;
+        let now = Utc::now();
+        let zones = zones::country_zones(country_code)
+            .into_iter()
+            .filter_map(|record| {
+                let tz: Tz = record.name.parse().ok()?;
+                let local = now.with_timezone(&tz);
+                Some(CountryZone {
+                    timezone: record.name.to_string(),
+                    comment: record.comment.map(str::to_string),
+                    abbreviation: Self::format_abbreviation(&local),
+                    utc_offset: Self::format_utc_offset(&local),
+                    offset_seconds: local.offset().fix().local_minus_utc(),
+                    is_dst: Self::is_daylight_saving_time(&tz, &now),
+                })
+            })
+            .collect();
+
+        Ok(CountryZones {
+            country_code: country_code.trim().to_ascii_uppercase(),
+            country_name: country_name.to_string(),
+            zones,
+        })
+    }
+
+    // A zone's boundary polygons as a GeoJSON Feature, simplified to keep payloads small
+    pub fn get_boundary(
+        timezone_name: &str,
+        query: &BoundaryQuery,
+    ) -> Result<BoundaryFeature, String> {
+        let tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let tolerance = query.tolerance.unwrap_or(DEFAULT_BOUNDARY_TOLERANCE);
+        if !(0.0..=MAX_BOUNDARY_TOLERANCE).contains(&tolerance) {
+            return Err(format!(
+                "'tolerance' must be between 0 and {} degrees",
+                MAX_BOUNDARY_TOLERANCE
+            ));
+        }
+
+        let canonical = zones::canonical_name(tz.name());
+        let coordinates = boundaries::zone_boundary(canonical, tolerance)
+            .ok_or_else(|| format!("No boundary data for timezone: {}", tz.name()))?;
+        let points = coordinates.iter().flatten().map(Vec::len).sum();
+
+        Ok(BoundaryFeature {
+            kind: "Feature".to_string(),
+            properties: BoundaryProperties {
+                timezone: tz.name().to_string(),
+                canonical: canonical.to_string(),
+                tolerance,
+                points,
+            },
+            geometry: BoundaryGeometry {
+                kind: "MultiPolygon".to_string(),
+                coordinates,
+            },
+        })
+    }
+
+    // Sunrise, sunset, day length, solar noon, and twilight on a local date, in the zone at
+    // the coordinates
+    pub fn get_sun_times(
+        finder: &tzf_rs::DefaultFinder,
+        query: &SunQuery,
+    ) -> Result<SunTimes, String> {
+        let (lat, lng) =
+            Self::validate_coordinates(query.lat, query.lng, false).map_err(|e| e.to_string())?;
+        let tz_name = finder.get_tz_name(lng, lat);
+        let tz: Tz = tz_name
+            .parse()
+            .map_err(|_| format!("Invalid timezone: {}", tz_name))?;
+        let date = match &query.date {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .filter(|day| (1800..=9999).contains(&day.year()))
+                .ok_or_else(|| {
+                    format!("Invalid date '{}' (expected YYYY-MM-DD, 1800 to 9999)", date)
+                })?,
+            None => Utc::now().with_timezone(&tz).date_naive(),
+        };
+
+        let day = solar::SolarDay::new(date, lng);
+        let out_of_range = || format!("Date {} is outside the supported range", date);
+        let noon = day.noon().ok_or_else(out_of_range)?;
+        let crossing = day.crossing(lat, solar::SUNRISE_ALTITUDE);
+        let (sunrise, sunset, polar) = match crossing.ok_or_else(out_of_range)? {
+            solar::Crossing::Times(rise, set) => (Some(rise), Some(set), None),
+            solar::Crossing::AlwaysAbove => (None, None, Some(PolarCondition::PolarDay)),
+            solar::Crossing::AlwaysBelow => (None, None, Some(PolarCondition::PolarNight)),
+        };
+        let day_length_seconds = match (sunrise, sunset, polar) {
+            (Some(rise), Some(set), _) => (set - rise).num_seconds(),
+            (_, _, Some(PolarCondition::PolarDay)) => 86_400,
+            _ => 0,
+        };
+        let local = |instant: Option<DateTime<Utc>>| {
+            instant.map(|instant| instant.with_timezone(&tz).to_rfc3339())
+        };
+
+        Ok(SunTimes {
+            timezone: tz_name.to_string(),
+            date: date.format("%Y-%m-%d").to_string(),
+            latitude: lat,
+            longitude: lng,
+            sunrise: local(sunrise),
+            sunrise_timestamp: sunrise.map(|instant| instant.timestamp()),
+            sunset: local(sunset),
+            sunset_timestamp: sunset.map(|instant| instant.timestamp()),
+            day_length_seconds,
+            day_length: format!(
+                "{:02}:{:02}:{:02}",
+                day_length_seconds / 3600,
+                day_length_seconds % 3600 / 60,
+                day_length_seconds % 60
+            ),
+            polar,
+            solar_noon: noon.with_timezone(&tz).to_rfc3339(),
+            solar_noon_timestamp: noon.timestamp(),
+            twilight: TwilightTimes {
+                civil: Self::twilight(&day, lat, solar::CIVIL_TWILIGHT_ALTITUDE, &tz)
+                    .ok_or_else(out_of_range)?,
+                nautical: Self::twilight(&day, lat, solar::NAUTICAL_TWILIGHT_ALTITUDE, &tz)
+                    .ok_or_else(out_of_range)?,
+                astronomical: Self::twilight(
+                    &day,
+                    lat,
+                    solar::ASTRONOMICAL_TWILIGHT_ALTITUDE,
+                    &tz,
+                )
+                .ok_or_else(out_of_range)?,
+            },
+        })
+    }
+
+    // Dawn and dusk when the sun crosses a twilight altitude, in local time
+    fn twilight(day: &solar::SolarDay, lat: f64, altitude: f64, tz: &Tz) -> Option<Twilight> {
+        let (dawn, dusk, condition) = match day.crossing(lat, altitude)? {
+            solar::Crossing::Times(dawn, dusk) => (Some(dawn), Some(dusk), None),
+            solar::Crossing::AlwaysAbove => (None, None, Some(TwilightCondition::NeverDark)),
+            solar::Crossing::AlwaysBelow => (None, None, Some(TwilightCondition::NeverLight)),
+        };
+        Some(Twilight {
+            dawn: dawn.map(|instant| instant.with_timezone(tz).to_rfc3339()),
+            dawn_timestamp: dawn.map(|instant| instant.timestamp()),
+            dusk: dusk.map(|instant| instant.with_timezone(tz).to_rfc3339()),
+            dusk_timestamp: dusk.map(|instant| instant.timestamp()),
+            condition,
+        })
+    }
+
+    // Countries using a zone, per zone1970.tab and zone.tab
+    pub fn get_zone_countries(timezone_name: &str) -> Result<ZoneCountries, String> {
+        let tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let countries = zones::zone_countries(tz.name())
+            .into_iter()
+            .map(|code| ZoneCountry {
+                code: code.to_string(),
+                name: zones::country_name(code).unwrap_or(code).to_string(),
+            })
+            .collect();
+
+        Ok(ZoneCountries {
+            timezone: tz.name().to_string(),
+            canonical: zones::canonical_name(tz.name()).to_string(),
+            countries,
+        })
+    }
+
+    // Look up an airport by IATA or ICAO code and return timezone info for its location
+    pub fn get_airport(finder: &tzf_rs::DefaultFinder, code: &str) -> Result<AirportInfo, String> {
+        let airport =
+            zones::find_airport(code).ok_or_else(|| format!("Unknown airport code: {}", code))?;
+        Ok(AirportInfo {
+            iata: airport.iata.to_string(),
+            icao: airport.icao.to_string(),
+            name: airport.name.to_string(),
+            city: airport.city.to_string(),
+            country_code: airport.country_code.to_string(),
+            latitude: airport.latitude,
+            longitude: airport.longitude,
+            timezone: Self::get_timezone_by_coordinates(
+                finder,
+                airport.latitude,
+                airport.longitude,
+            )?,
+        })
+    }
+
+    // Locate an IP address, or the caller's for "self", and give the time at its zone
+    pub fn get_ip_info(
+        geoip: &GeoIp,
+        addr: &str,
+        caller: Option<IpAddr>,
+    ) -> Result<IpInfo, String> {
+        let ip = if addr.eq_ignore_ascii_case("self") {
+            caller.ok_or_else(|| "Cannot determine the caller's address".to_string())?
+        } else {
+            addr.trim()
+                .parse()
+                .map_err(|_| format!("Invalid IP address: {}", addr))?
+        };
+        if !geoip.is_enabled() {
+            return Err("IP lookups are disabled (GEOIP_DATABASE is not configured)".to_string());
+        }
+        let location = geoip
+            .lookup(ip)
+            .ok_or_else(|| format!("No timezone known for {}", ip))?;
+
+        Ok(IpInfo {
+            ip: ip.to_string(),
+            timezone: Self::get_timezone_info(&location.timezone, &TimeQuery::default())?,
+            country_code: location.country_code,
+            latitude: location.latitude,
+            longitude: location.longitude,
+            accuracy_km: location.accuracy_km,
+        })
+    }
+
+    // Validate if a timezone name is valid
+    pub fn is_valid_timezone(timezone_name: &str) -> bool {
+        zones::parse_timezone(timezone_name).is_some()
+    }
+
+    // Convert a time between timezones
+    pub fn convert_timezone(request: &ConvertRequest) -> Result<ConvertResponse, String> {
+        // Parse target timezone
+        let to_zone = Self::parse_convert_zone(&request.to)
+            .ok_or_else(|| format!("Invalid target timezone: {}", request.to))?;
+
+        if let Some(pattern) = request.format.as_deref() {
+            Self::validate_format(pattern)?;
+        }
+        if let Some(hours) = &request.business_hours {
+            Self::working_hours(hours)?;
+        }
+
+        // Determine the UTC instant and the zone the source side is shown in, with how far
+        // a nonexistent local time was moved forward
+        let (utc_instant, from_zone, adjustment): (DateTime<Utc>, ConvertZone, i64) = match (
+            request.timestamp,
+            request.datetime.as_deref(),
+            request.from.as_deref(),
+        ) {
+            (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+                return Err(
+                    "Provide either 'timestamp' or 'datetime'+'from', not both".to_string(),
+                );
+            }
+            (Some(ts), None, None) => {
+                let utc = Self::datetime_from_timestamp(ts, request.unit)
+                    .ok_or_else(|| format!("Invalid timestamp: {}", ts))?;
+                (utc, ConvertZone::Named(chrono_tz::UTC), 0)
+            }
+            (None, Some(dt_str), from) => {
+                let (parsed, _) = Self::parse_flexible_datetime(dt_str).ok_or_else(|| {
+                    format!("Invalid datetime '{}': unrecognized format", dt_str)
+                })?;
+                match parsed {
+                    ParsedDateTime::Instant(with_offset) => {
+                        // The embedded offset pins the instant; 'from' only selects how it is shown
+                        let utc = with_offset.with_timezone(&Utc);
+                        let zone = match from {
+                            Some(from_str) => {
+                                Self::parse_convert_zone(from_str).ok_or_else(|| {
+                                    format!("Invalid source timezone: {}", from_str)
+                                })?
+                            }
+                            None => ConvertZone::Fixed(*with_offset.offset()),
+                        };
+                        (utc, zone, 0)
+                    }
+                    ParsedDateTime::Epoch(utc) => {
+                        let from_zone =
+                            Self::parse_convert_zone(from.unwrap_or("UTC")).ok_or_else(|| {
+                                format!("Invalid source timezone: {}", from.unwrap_or_default())
+                            })?;
+                        (utc, from_zone, 0)
+                    }
+                    ParsedDateTime::Local(naive) => {
+                        let from_str = from.ok_or_else(|| {
+                            "'from' timezone is required when 'datetime' has no UTC offset"
+                                .to_string()
+                        })?;
+                        let from_zone = Self::parse_convert_zone(from_str)
+                            .ok_or_else(|| format!("Invalid source timezone: {}", from_str))?;
+                        match from_zone {
+                            ConvertZone::Named(from_tz) => {
+                                let (local, adjustment) = Self::resolve_local_datetime(
+                                    &from_tz,
+                                    &naive,
+                                    request.gap_policy,
+                                )?;
+                                (local.with_timezone(&Utc), from_zone, adjustment)
+                            }
+                            ConvertZone::Fixed(offset) => {
+                                // A fixed offset has no gaps or overlaps
+                                let utc = naive
+                                    .checked_sub_offset(offset)
+                                    .ok_or_else(|| format!("Invalid datetime '{}'", dt_str))?
+                                    .and_utc();
+                                (utc, from_zone, 0)
+                            }
+                        }
+                    }
+                }
+            }
+            (None, None, _) => {
+                return Err("Either 'timestamp' or 'datetime'+'from' is required".to_string());
+            }
+        };
+
+        // Both sides must also have a four-digit local year to be shown
+        let from_local = Self::local_in_zone(&utc_instant, &from_zone);
+        let to_local = Self::local_in_zone(&utc_instant, &to_zone);
+        if !(MIN_SUPPORTED_TIMESTAMP..=MAX_SUPPORTED_TIMESTAMP).contains(&utc_instant.timestamp())
+            || [from_local, to_local].iter().any(|local| local.year() > 9999)
+        {
+            return Err(format!(
+                "Instant {} is outside the supported range (1800-01-01 to 9999-12-31 UTC)",
+                utc_instant.to_rfc3339()
+            ));
+        }
+
+        if Self::timestamp_in_unit(&utc_instant, request.unit).is_none() {
+            return Err("Converted instant is out of range for the requested unit".to_string());
+        }
+
+        let mut from_info = Self::build_zone_convert_info(&utc_instant, &from_zone, request);
+        from_info.was_adjusted = adjustment != 0;
+        from_info.adjustment_seconds = adjustment;
+        let to_info = Self::build_zone_convert_info(&utc_instant, &to_zone, request);
+
+        Ok(ConvertResponse {
+            offset_difference_seconds: to_local.offset().local_minus_utc()
+                - from_local.offset().local_minus_utc(),
+            day_shift: (to_local.date_naive() - from_local.date_naive()).num_days(),
+            from: from_info,
+            to: to_info,
+        })
+    }
+
+    // An instant on the wall clock of a conversion zone
+    fn local_in_zone(utc: &DateTime<Utc>, zone: &ConvertZone) -> DateTime<FixedOffset> {
+        match zone {
+            ConvertZone::Named(tz) => utc.with_timezone(tz).fixed_offset(),
+            ConvertZone::Fixed(offset) => utc.with_timezone(offset),
+        }
+    }
+
+    // Parse a conversion zone: any accepted zone name, else a numeric offset such as
+    // "+05:30", "UTC-7", or "GMT+2". Unlike the POSIX-style Etc/GMT+2 zone, "GMT+2" here
+    // means two hours ahead of UTC.
+    fn parse_convert_zone(value: &str) -> Option<ConvertZone> {
+        if let Some(tz) = zones::parse_timezone(value) {
+            return Some(ConvertZone::Named(tz));
+        }
+        let value = value.trim();
+        let offset = match value.get(..3) {
+            Some(prefix) if ["UTC", "GMT"].iter().any(|p| prefix.eq_ignore_ascii_case(p)) => {
+                &value[3..]
+            }
+            _ => value,
+        };
+        let (sign, rest) = match offset.split_at_checked(1)? {
+            ("+", rest) => (1, rest),
+            ("-", rest) => (-1, rest),
+            _ => return None,
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((hours, minutes)) => (hours, minutes),
+            None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+            None => (rest, "0"),
+        };
+        if hours.is_empty() || hours.len() > 2 || minutes.is_empty() || minutes.len() > 2 {
+            return None;
+        }
+        let hours: i32 = hours.parse().ok()?;
+        let minutes: i32 = minutes.parse().ok()?;
+        if hours > 18 || minutes > 59 || (hours == 18 && minutes > 0) {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).map(ConvertZone::Fixed)
+    }
+
+    // Parse a datetime string in any supported format, reporting the pattern that matched
+    fn parse_flexible_datetime(input: &str) -> Option<(ParsedDateTime, &'static str)> {
+        let input = input.trim();
+
+        if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+            return Some((ParsedDateTime::Instant(dt), "rfc3339"));
+        }
+        // ISO 8601 allows dropping the seconds, e.g. "2025-01-01T00:00Z"
+        if let Ok(dt) = DateTime::parse_from_str(input, "%Y-%m-%dT%H:%M%#z") {
+            return Some((ParsedDateTime::Instant(dt), "iso8601"));
+        }
+        if let Ok(dt) = DateTime::parse_from_rfc2822(input) {
+            return Some((ParsedDateTime::Instant(dt), "rfc2822"));
+        }
+        if let Ok(value) = input.parse::<i64>() {
+            return if value.abs() >= EPOCH_MILLIS_THRESHOLD {
+                let utc = DateTime::from_timestamp_millis(value)?;
+                Some((ParsedDateTime::Epoch(utc), "epoch_milliseconds"))
+            } else {
+                let utc = DateTime::from_timestamp(value, 0)?;
+                Some((ParsedDateTime::Epoch(utc), "epoch_seconds"))
+            };
+        }
+        for format in NAIVE_DATETIME_FORMATS {
+            if let Ok(naive) = NaiveDateTime::parse_from_str(input, format) {
+                return Some((ParsedDateTime::Local(naive), format));
+            }
+        }
+        for format in NAIVE_DATE_FORMATS {
+            if let Ok(date) = NaiveDate::parse_from_str(input, format) {
+                return Some((ParsedDateTime::Local(date.and_time(NaiveTime::MIN)), format));
+            }
+        }
+        None
+    }
+
+    // Parse a datetime in any supported format and normalize it to RFC3339.
+    // Inputs without an offset are interpreted in `tz` (UTC when omitted).
+    pub fn parse_datetime(query: &ParseQuery) -> Result<ParseResponse, String> {
+        let tz: Tz = match query.tz.as_deref() {
+            Some(name) => zones::parse_timezone(name)
+                .ok_or_else(|| zones::invalid_timezone(name))?,
+            None => chrono_tz::UTC,
+        };
+
+        let (parsed, pattern) = Self::parse_flexible_datetime(&query.value)
+            .ok_or_else(|| format!("Unrecognized datetime format: '{}'", query.value))?;
+
+        let (normalized, timestamp, timezone) = match parsed {
+            ParsedDateTime::Epoch(utc) => {
+                let local = utc.with_timezone(&tz);
+                (local.to_rfc3339(), local.timestamp(), tz.name().to_string())
+            }
+            ParsedDateTime::Instant(dt) => {
+                let timezone = Self::format_utc_offset(&dt);
+                (dt.to_rfc3339(), dt.timestamp(), timezone)
+            }
+            ParsedDateTime::Local(naive) => {
+                let (local, _) = Self::resolve_local_datetime(&tz, &naive, GapPolicy::Reject)?;
+                (local.to_rfc3339(), local.timestamp(), tz.name().to_string())
+            }
+        };
+
+        Ok(ParseResponse {
+            input: query.value.clone(),
+            normalized,
+            timestamp,
+            timezone,
+            pattern: pattern.to_string(),
+        })
+    }
+
+    // ISO 8601 week information for an instant (default now) in a timezone
+    pub fn get_week_info(timezone_name: &str, query: &WeekQuery) -> Result<WeekInfo, String> {
+        let tz: Tz = zones::parse_timezone(timezone_name)
+            .ok_or_else(|| zones::invalid_timezone(timezone_name))?;
+        let utc = Self::resolve_instant(query.at.as_deref(), &tz)?;
+        let local = utc.with_timezone(&tz);
+
+        let iso_week = local.iso_week();
+        let weekday = local.weekday();
+        let monday = local.date_naive() - Duration::days(weekday.num_days_from_monday() as i64);
+        let week_start = Self::start_of_local_day(&tz, monday);
+        let week_end = Self::start_of_local_day(&tz, monday + Duration::days(7));
+
+        Ok(WeekInfo {
+            timezone: tz.name().to_string(),
+            at: local.to_rfc3339(),
+            iso_year: iso_week.year(),
+            iso_week: iso_week.week(),
+            iso_week_date: format!(
+                "{}-W{:02}-{}",
+                iso_week.year(),
+                iso_week.week(),
+                weekday.number_from_monday()
+            ),
+            day_of_week: weekday.number_from_monday(),
+            day_name: local.format("%A").to_string(),
+            week_start: week_start.to_rfc3339(),
+            week_start_timestamp: week_start.timestamp(),
+            week_end: week_end.to_rfc3339(),
+            week_end_timestamp: week_end.timestamp(),
+        })
+    }
+
+    // Month edges as instants, plus every ISO week that overlaps the month
+    pub fn get_calendar_info(query: &CalendarQuery) -> Result<CalendarInfo, String> {
+        let tz: Tz = match query.tz.as_deref() {
+            Some(name) => zones::parse_timezone(name).ok_or_else(|| zones::invalid_timezone(name))?,
+            None => Tz::UTC,
+        };
+        if !(1800..=9999).contains(&query.year) {
+            return Err(format!(
+                "Year {} is outside the supported range (1800 to 9999)",
+                query.year
+            ));
+        }
+        let first_day = NaiveDate::from_ymd_opt(query.year, query.month, 1)
+            .ok_or_else(|| format!("Invalid year and month: {}-{}", query.year, query.month))?;
+        let next_month = first_day
+            .checked_add_months(Months::new(1))
+            .ok_or_else(|| "Month is out of range".to_string())?;
+        let is_leap_year = NaiveDate::from_ymd_opt(query.year, 2, 29).is_some();
+
+        let first = Self::start_of_local_day(&tz, first_day);
+        let last = Self::start_of_local_day(&tz, next_month) - Duration::seconds(1);
+
+        let out_of_range = || "Month is out of range".to_string();
+        let first_monday = first_day
+            .checked_sub_signed(Duration::days(first_day.weekday().num_days_from_monday() as i64))
+            .ok_or_else(out_of_range)?;
+        let weeks = first_monday
+            .iter_weeks()
+            .take_while(|monday| *monday < next_month)
+            .map(|monday| {
+                let next_monday = monday
+                    .checked_add_signed(Duration::days(7))
+                    .ok_or_else(out_of_range)?;
+                let start = Self::start_of_local_day(&tz, monday);
+                let end = Self::start_of_local_day(&tz, next_monday);
+                let iso_week = monday.iso_week();
+                Ok(CalendarWeek {
+                    iso_year: iso_week.year(),
+                    iso_week: iso_week.week(),
+                    start: start.to_rfc3339(),
+                    start_timestamp: start.timestamp(),
+                    end: end.to_rfc3339(),
+                    end_timestamp: end.timestamp(),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(CalendarInfo {
+            timezone: tz.name().to_string(),
+            year: query.year,
+            month: query.month,
+            month_name: first_day.format("%B").to_string(),
+            is_leap_year,
+            days_in_month: (next_month - first_day).num_days() as u32,
+            days_in_year: if is_leap_year { 366 } else { 365 },
+            first_instant: first.to_rfc3339(),
+            first_timestamp: first.timestamp(),
+            last_instant: last.to_rfc3339(),
+            last_timestamp: last.timestamp(),
+            weeks,
+        })
+    }
+
+    // Resolve an ISO 8601 interval or duration to start and end instants in a timezone
+    pub fn get_interval(query: &IntervalQuery) -> Result<IntervalResponse, String> {
+        let tz: Tz = match query.tz.as_deref() {
+            Some(name) => zones::parse_timezone(name).ok_or_else(|| zones::invalid_timezone(name))?,
+            None => Tz::UTC,
+        };
+        let instant = |value: &str| Self::resolve_instant(Some(value), &tz);
+        let parts = interval::parse_interval(&query.interval)?;
+        let (start, end, duration) = match parts.as_slice() {
+            [IntervalPart::Duration(duration)] => {
+                let start = Self::resolve_instant(query.at.as_deref(), &tz)?;
+                let end = Self::shift_by_iso_duration(start, duration, &tz, false)?;
+                (start, end, Some(duration))
+            }
+            [IntervalPart::DateTime(start), IntervalPart::Duration(duration)] => {
+                let start = instant(start)?;
+                let end = Self::shift_by_iso_duration(start, duration, &tz, false)?;
+                (start, end, Some(duration))
+            }
+            [IntervalPart::Duration(duration), IntervalPart::DateTime(end)] => {
+                let end = instant(end)?;
+                let start = Self::shift_by_iso_duration(end, duration, &tz, true)?;
+                (start, end, Some(duration))
+            }
+            [IntervalPart::DateTime(start), IntervalPart::DateTime(end)] => {
+                (instant(start)?, instant(end)?, None)
+            }
+            _ => return Err(format!("Invalid ISO 8601 interval '{}'", query.interval)),
+        };
+        if end < start {
+            return Err("Interval ends before it starts".to_string());
+        }
+
+        let (start, end) = (start.with_timezone(&tz), end.with_timezone(&tz));
+        Ok(IntervalResponse {
+            timezone: tz.name().to_string(),
+            interval: query.interval.trim().to_string(),
+            duration: duration.map(|d| d.to_string()),
+            start: start.to_rfc3339(),
+            start_timestamp: start.timestamp(),
+            end: end.to_rfc3339(),
+            end_timestamp: end.timestamp(),
+            total_seconds: (end - start).num_seconds(),
+        })
+    }
+
+    // Age at `query.at` (default now) in completed years, months, and days of the local
+    // calendar, plus the elapsed time since birth
+    pub fn get_age(query: &AgeQuery) -> Result<AgeResponse, String> {
+        let tz: Tz = match query.tz.as_deref() {
+            Some(name) => zones::parse_timezone(name).ok_or_else(|| zones::invalid_timezone(name))?,
+            None => Tz::UTC,
+        };
+        let birth = Self::resolve_instant(Some(&query.birth), &tz)?.with_timezone(&tz);
+        let at = Self::resolve_instant(query.at.as_deref(), &tz)?.with_timezone(&tz);
+        if at < birth {
+            return Err("'birth' is after 'at'".to_string());
+        }
+
+        // Whole months first, then the days left over; a month counts once its day of the
+        // month comes round (month ends clamp, so 31 January + 1 month is 28 February)
+        let (born, today) = (birth.date_naive(), at.date_naive());
+        let after_months = |months: u32| born.checked_add_months(Months::new(months));
+        let mut months = (today.year() - born.year()) * 12 + today.month() as i32
+            - born.month() as i32;
+        if after_months(months as u32).is_none_or(|date| date > today) {
+            months -= 1;
+        }
+        let anniversary = after_months(months as u32).unwrap_or(born);
+        let next_birthday = (0..)
+            .map_while(|years| after_months(12 * years))
+            .find(|date| *date >= today)
+            .ok_or_else(|| "Next birthday is out of range".to_string())?;
+
+        Ok(AgeResponse {
+            timezone: tz.name().to_string(),
+            birth: birth.to_rfc3339(),
+            at: at.to_rfc3339(),
+            years: months / 12,
+            months: (months % 12) as u32,
+            days: (today - anniversary).num_days() as u32,
+            total_days: (today - born).num_days(),
+            total_seconds: (at - birth).num_seconds(),
+            next_birthday: next_birthday.format("%Y-%m-%d").to_string(),
+            days_until_birthday: (next_birthday - today).num_days(),
+        })
+    }
+
+    // Add a duration to a zoned datetime, either on the local wall clock or in elapsed time.
+    // Wall-clock results in a DST overlap take the earlier instant.
+    pub fn add_duration(request: &AddRequest) -> Result<AddResponse, String> {
+        let tz: Tz = zones::parse_timezone(&request.timezone)
+            .ok_or_else(|| zones::invalid_timezone(&request.timezone))?;
+        let start = Self::resolve_instant(Some(&request.datetime), &tz)?;
+
+        let duration = [
+            Duration::try_days(request.days),
+            Duration::try_hours(request.hours),
+            Duration::try_minutes(request.minutes),
+            Duration::try_seconds(request.seconds),
+        ]
+        .into_iter()
+        .try_fold(Duration::zero(), |total, part| total.checked_add(&part?))
+        .ok_or_else(|| "Duration is out of range".to_string())?;
+
+        let (result, adjustment) = match request.mode {
+            AddMode::Absolute => {
+                let result = start
+                    .checked_add_signed(duration)
+                    .ok_or_else(|| "Result is out of range".to_string())?;
+                (result, 0)
+            }
+            AddMode::WallClock => {
+                let naive = start
+                    .with_timezone(&tz)
+                    .naive_local()
+                    .checked_add_signed(duration)
+                    .filter(|naive| (1799..=10000).contains(&naive.year()))
+                    .ok_or_else(|| "Result is out of range".to_string())?;
+                let (local, adjustment) = match tz.from_local_datetime(&naive) {
+                    LocalResult::Ambiguous(earliest, _) => (earliest, 0),
+                    _ => Self::resolve_local_datetime(&tz, &naive, request.gap_policy)?,
+                };
+                (local.with_timezone(&Utc), adjustment)
+            }
+        };
+        Self::ensure_supported(&result)?;
+
+        let defaults = ConvertRequest::default();
+        let mut result_info = Self::build_convert_info(&result, &tz, &defaults);
+        result_info.was_adjusted = adjustment != 0;
+        result_info.adjustment_seconds = adjustment;
+
+        Ok(AddResponse {
+            mode: request.mode,
+            start: Self::build_convert_info(&start, &tz, &defaults),
+            result: result_info,
+            elapsed_seconds: (result - start).num_seconds(),
+        })
+    }
+
+    // Public holidays of a country for a year, with each local date's bounds in `query.tz`
+    // (or the country's primary zone)
+    pub fn get_holidays(country: &str, query: &HolidayQuery) -> Result<HolidaysResponse, String> {
+        let calendar = holidays::find_country(country)?;
+        let tz_name = query.tz.as_deref().unwrap_or(calendar.timezone);
+        let tz: Tz = zones::parse_timezone(tz_name)
+            .ok_or_else(|| zones::invalid_timezone(tz_name))?;
+        let year = query
+            .year
+            .unwrap_or_else(|| Utc::now().with_timezone(&tz).year());
+
+        let holidays = calendar
+            .holidays(year)?
+            .into_iter()
+            .map(|h| {
+                let start = Self::start_of_local_day(&tz, h.date);
+                let end = Self::start_of_local_day(&tz, h.date + Duration::days(1));
+                Holiday {
+                    name: h.name.to_string(),
+                    local_name: h.local_name.to_string(),
+                    date: h.date.to_string(),
+                    day_name: h.date.format("%A").to_string(),
+                    observed: h.observed.map(|d| d.to_string()),
+                    start: start.to_rfc3339(),
+                    start_timestamp: start.timestamp(),
+                    end: end.to_rfc3339(),
+                    end_timestamp: end.timestamp(),
+                }
+            })
+            .collect();
+
+        Ok(HolidaysResponse {
+            country: calendar.code.to_string(),
+            country_name: calendar.name.to_string(),
+            year,
+            timezone: tz.name().to_string(),
+            holidays,
+        })
+    }
+
+    // N×N matrix of offset differences at `at` (default now): entry [i][j] is how far zone j
+    // is ahead of zone i. Also flags pairs whose difference changes within the next 30 days.
+    pub fn get_offset_matrix(request: &MatrixRequest) -> Result<MatrixResponse, String> {
+        if request.timezones.is_empty() {
+            return Err("At least one timezone is required".to_string());
+        }
+        if request.timezones.len() > MAX_MATRIX_ZONES {
+            return Err(format!("At most {} timezones are supported", MAX_MATRIX_ZONES));
+        }
+        let zones = request
+            .timezones
+            .iter()
+            .map(|name| {
+                zones::parse_timezone(name).ok_or_else(|| zones::invalid_timezone(name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let at = Self::resolve_instant(request.at.as_deref(), &Tz::UTC)?;
+
+        // Hourly offset samples over the look-ahead window, first sample at `at`
+        let instants = (0..=MATRIX_LOOKAHEAD_HOURS)
+            .map(|h| at.checked_add_signed(Duration::hours(h)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| "Look-ahead window is out of range".to_string())?;
+        let samples: Vec<Vec<i32>> = zones
+            .iter()
+            .map(|tz| {
+                instants
+                    .iter()
+                    .map(|instant| {
+                        tz.offset_from_utc_datetime(&instant.naive_utc())
+                            .fix()
+                            .local_minus_utc()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let n = zones.len();
+        let mut offset_seconds = vec![vec![0; n]; n];
+        let mut changes = vec![vec![false; n]; n];
+        for i in 0..n {
+            for j in 0..n {
+                let difference = |k: usize| samples[j][k] - samples[i][k];
+                offset_seconds[i][j] = difference(0);
+                changes[i][j] = (1..samples[i].len()).any(|k| difference(k) != difference(0));
+            }
+        }
+
+        Ok(MatrixResponse {
+            at: at.to_rfc3339(),
+            timezones: zones.iter().map(|tz| tz.name().to_string()).collect(),
+            utc_offsets: samples
+                .iter()
+                .map(|s| format!("UTC{}", Self::format_offset_seconds(s[0])))
+                .collect(),
+            differences: offset_seconds
+                .iter()
+                .map(|row| row.iter().map(|d| Self::format_offset_seconds(*d)).collect())
+                .collect(),
+            any_changes: changes.iter().flatten().any(|c| *c),
+            offset_seconds,
+            changes_within_30_days: changes,
+        })
+    }
+
+    // Local and UTC times at both ends of a trip, given the departure time and either the
+    // arrival time or the travel duration
+    pub fn plan_itinerary(
+        finder: &tzf_rs::DefaultFinder,
+        request: &ItineraryRequest,
+    ) -> Result<ItineraryResponse, String> {
+        let from = Self::resolve_place(finder, &request.departure.place)?;
+        let to = Self::resolve_place(finder, &request.arrival.place)?;
+        let departure_time = request
+            .departure
+            .local_time
+            .as_deref()
+            .ok_or_else(|| "'departure.local_time' is required".to_string())?;
+        let departure = Self::resolve_instant(Some(departure_time), &from)?;
+
+        let arrival = match (request.arrival.local_time.as_deref(), request.duration_minutes) {
+            (Some(arrival_time), None) => Self::resolve_instant(Some(arrival_time), &to)?,
+            (None, Some(minutes)) => Duration::try_minutes(minutes)
+                .and_then(|duration| departure.checked_add_signed(duration))
+                .ok_or_else(|| "'duration_minutes' is out of range".to_string())?,
+            _ => {
+                return Err(
+                    "Provide exactly one of 'arrival.local_time' and 'duration_minutes'"
+                        .to_string(),
+                )
+            }
+        };
+        if arrival < departure {
+            return Err("Arrival is before departure".to_string());
+        }
+
+        let minutes = (arrival - departure).num_minutes();
+        let local_departure = departure.with_timezone(&from);
+        let local_arrival = arrival.with_timezone(&to);
+        let defaults = ConvertRequest::default();
+        let end = |place: &str, instant: &DateTime<Utc>, tz: &Tz| ItineraryEnd {
+            place: place.to_string(),
+            local: Self::build_convert_info(instant, tz, &defaults),
+            utc: instant.to_rfc3339(),
+        };
+
+        Ok(ItineraryResponse {
+            departure: end(&request.departure.place, &departure, &from),
+            arrival: end(&request.arrival.place, &arrival, &to),
+            duration_minutes: minutes,
+            duration: format!("{}h {:02}m", minutes / 60, minutes % 60),
+            day_offset: (local_arrival.date_naive() - local_departure.date_naive()).num_days(),
+            offset_change_seconds: local_arrival.offset().fix().local_minus_utc()
+                - local_departure.offset().fix().local_minus_utc(),
+        })
+    }
+
+    // Move the usual sleep window from the origin's clock to the destination's a little each
+    // day, starting up to `prepare_days` before travel. Days before the travel date are in
+    // origin time, the rest in destination time.
+    pub fn plan_jet_lag(
+        finder: &tzf_rs::DefaultFinder,
+        request: &JetLagRequest,
+    ) -> Result<JetLagPlan, String> {
+        const MAX_PREPARE_DAYS: u32 = 7;
+        let origin = Self::resolve_place(finder, &request.origin)?;
+        let destination = Self::resolve_place(finder, &request.destination)?;
+        let travel_date = NaiveDate::parse_from_str(&request.travel_date, "%Y-%m-%d")
+            .ok()
+            .filter(|day| (1800..=9999).contains(&day.year()))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid travel_date '{}' (expected YYYY-MM-DD, 1800 to 9999)",
+                    request.travel_date
+                )
+            })?;
+        let bedtime = planning::parse_time(&request.bedtime, "bedtime")?;
+        let wake_time = planning::parse_time(&request.wake_time, "wake_time")?;
+        if request.prepare_days > MAX_PREPARE_DAYS {
+            return Err(format!("prepare_days must be at most {}", MAX_PREPARE_DAYS));
+        }
+
+        // Offsets at local noon on the travel date, with the difference taken the short way
+        let noon = travel_date.and_hms_opt(12, 0, 0).expect("valid time").and_utc();
+        let offset = |tz: &Tz| noon.with_timezone(tz).offset().fix().local_minus_utc();
+        let mut difference = offset(&destination) - offset(&origin);
+        if difference > 12 * 3600 {
+            difference -= 24 * 3600;
+        } else if difference < -12 * 3600 {
+            difference += 24 * 3600;
+        }
+
+        let direction = match difference.signum() {
+            1 => ClockShift::Advance,
+            -1 => ClockShift::Delay,
+            _ => ClockShift::None,
+        };
+        let rate = request
+            .shift_minutes_per_day
+            .unwrap_or(if direction == ClockShift::Delay { 90 } else { 60 });
+        if !(15..=180).contains(&rate) {
+            return Err("shift_minutes_per_day must be between 15 and 180".to_string());
+        }
+
+        let total = difference.unsigned_abs() / 60;
+        let days_needed = total.div_ceil(rate);
+        let start = travel_date
+            .checked_sub_signed(Duration::days(request.prepare_days.min(days_needed).into()))
+            .ok_or_else(|| "travel_date is out of range".to_string())?;
+        let window = |from: NaiveTime, minutes: i64| {
+            let to = from + Duration::minutes(minutes);
+            format!("{}-{}", from.format("%H:%M"), to.format("%H:%M"))
+        };
+
+        let mut days = Vec::new();
+        let mut shifted = 0;
+        for date in start.iter_days() {
+            if date > travel_date && shifted == total {
+                break;
+            }
+            let step = rate.min(total - shifted);
+            shifted += step;
+            // Sleep times on the origin's clock, moved earlier to advance and later to delay
+            let moved = Duration::minutes(shifted.into()) * -difference.signum();
+            let (tz, local) = if date < travel_date {
+                (origin, Duration::zero())
+            } else {
+                (destination, Duration::seconds(difference.into()))
+            };
+            let (bed, wake) = (bedtime + moved + local, wake_time + moved + local);
+            // Light after waking pulls the clock earlier, light before bed pushes it later
+            let (morning, evening) = (window(wake, 120), window(bed - Duration::hours(3), 180));
+            let (seek_light, avoid_light) = match direction {
+                _ if step == 0 => (None, None),
+                ClockShift::Advance => (Some(morning), Some(evening)),
+                _ => (Some(evening), Some(morning)),
+            };
+            days.push(JetLagDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                timezone: tz.name().to_string(),
+                bedtime: bed.format("%H:%M").to_string(),
+                wake_time: wake.format("%H:%M").to_string(),
+                shift_minutes: step,
+                remaining_minutes: total - shifted,
+                seek_light,
+                avoid_light,
+            });
+        }
+
+        Ok(JetLagPlan {
+            origin: origin.name().to_string(),
+            destination: destination.name().to_string(),
+            offset_difference_seconds: difference,
+            offset_difference: Self::format_offset_seconds(difference),
+            direction,
+            shift_minutes_per_day: rate,
+            days,
+        })
+    }
+
+    // Zone for a timezone name, Rails name, or IATA/ICAO airport code
+    fn resolve_place(finder: &tzf_rs::DefaultFinder, place: &str) -> Result<Tz, String> {
+        if let Some(tz) = zones::parse_timezone(place) {
+            return Ok(tz);
+        }
+        let airport = zones::find_airport(place).ok_or_else(|| {
+            format!("Unknown place '{}' (expected a timezone or airport code)", place)
+        })?;
+        let name = finder.get_tz_name(airport.longitude, airport.latitude);
+        name.parse()
+            .map_err(|_| format!("Invalid timezone: {}", name))
+    }
+
+    // Expand an RRULE from a wall-clock DTSTART in `timezone`, returning the occurrences that
+    // fall inside [start, end]. COUNT still counts occurrences before the window.
+    pub fn expand_recurrence(request: &RecurrenceRequest) -> Result<RecurrenceResponse, String> {
+        let tz: Tz = zones::parse_timezone(&request.timezone)
+            .ok_or_else(|| zones::invalid_timezone(&request.timezone))?;
+        let targets = request
+            .to
+            .iter()
+            .map(|name| {
+                zones::parse_timezone(name).ok_or_else(|| zones::invalid_timezone(name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let rule = RecurrenceRule::parse(&request.rrule)?;
+
+        let dtstart = Self::resolve_instant(Some(&request.dtstart), &tz)?;
+        let window_start = match &request.start {
+            Some(start) => Self::resolve_instant(Some(start), &tz)?,
+            None => dtstart,
+        };
+        let window_end = Self::resolve_instant(Some(&request.end), &tz)?;
+        if window_end < window_start {
+            return Err("'end' must not be before 'start'".to_string());
+        }
+        // A day past the window end in local time covers any offset difference
+        let horizon = window_end
+            .with_timezone(&tz)
+            .date_naive()
+            .succ_opt()
+            .unwrap_or(NaiveDate::MAX);
+        let defaults = ConvertRequest::default();
+        let mut occurrences = Vec::new();
+        let mut truncated = false;
+
+        let local_start = dtstart.with_timezone(&tz).naive_local();
+        let mut expansion = rule.occurrences(local_start, horizon);
+        for (index, naive) in expansion.by_ref().enumerate() {
+            if rule.count.is_some_and(|count| index >= count as usize) {
+                break;
+            }
+            let utc = Self::resolve_recurrence_time(&tz, &naive).with_timezone(&Utc);
+            let past_until = match rule.until {
+                Some(Until::Utc(until)) => utc > until,
+                Some(Until::Local(until)) => naive > until,
+                None => false,
+            };
+            if past_until || utc > window_end {
+                break;
+            }
+            if utc < window_start {
+                continue;
+            }
+            if occurrences.len() == MAX_OCCURRENCES {
+                truncated = true;
+                break;
+            }
+            occurrences.push(Occurrence {
+                timestamp: utc.timestamp(),
+                local: Self::build_convert_info(&utc, &tz, &defaults),
+                conversions: targets
+                    .iter()
+                    .map(|target| Self::build_convert_info(&utc, target, &defaults))
+                    .collect(),
+            });
+        }
+        if expansion.exhausted() {
+            return Err(
+                "RRULE takes too long to expand up to 'end'; move 'dtstart' closer to the window"
+                    .to_string(),
+            );
+        }
+
+        Ok(RecurrenceResponse {
+            rrule: request.rrule.clone(),
+            timezone: tz.name().to_string(),
+            count: occurrences.len(),
+            truncated,
+            occurrences,
+        })
+    }
+
+    // Resolve a recurrence instance the way RFC 5545 does: a repeated local time takes its
+    // first occurrence, and a time inside a DST gap keeps the offset from before the gap
+    // (02:30 on a spring-forward night becomes 03:30).
+    fn resolve_recurrence_time(tz: &Tz, naive: &NaiveDateTime) -> DateTime<Tz> {
+        match tz.from_local_datetime(naive) {
+            LocalResult::Single(local) => local,
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => {
+                let day_before = *naive - Duration::days(1);
+                let before = tz.offset_from_utc_datetime(&day_before).fix();
+                let utc = *naive - Duration::seconds(before.local_minus_utc() as i64);
+                tz.from_utc_datetime(&utc)
+            }
+        }
+    }
+
+    // Move an instant by an ISO duration: years, months, and days step the local calendar
+    // and keep the time of day, while hours, minutes, and seconds add elapsed time. Going
+    // backwards undoes the two steps in reverse order.
+    fn shift_by_iso_duration(
+        at: DateTime<Utc>,
+        duration: &IsoDuration,
+        tz: &Tz,
+        backwards: bool,
+    ) -> Result<DateTime<Utc>, String> {
+        let out_of_range = || "Interval is out of range".to_string();
+        let months = duration
+            .total_months()
+            .and_then(|months| u32::try_from(months).ok())
+            .map(Months::new)
+            .ok_or_else(out_of_range)?;
+        let days = Duration::try_days(duration.days).ok_or_else(out_of_range)?;
+        let clock = duration
+            .clock_seconds()
+            .and_then(Duration::try_seconds)
+            .ok_or_else(out_of_range)?;
+
+        let mut at = at;
+        if backwards {
+            at = at.checked_sub_signed(clock).ok_or_else(out_of_range)?;
+        }
+        if months != Months::new(0) || !days.is_zero() {
+            let local = at.with_timezone(tz).naive_local();
+            let naive = if backwards {
+                local
+                    .checked_sub_months(months)
+                    .and_then(|n| n.checked_sub_signed(days))
+            } else {
+                local
+                    .checked_add_months(months)
+                    .and_then(|n| n.checked_add_signed(days))
+            }
+            .ok_or_else(out_of_range)?;
+            let local = match tz.from_local_datetime(&naive) {
+                LocalResult::Ambiguous(earliest, _) => earliest,
+                _ => Self::resolve_local_datetime(tz, &naive, GapPolicy::ShiftForward)?.0,
+            };
+            at = local.with_timezone(&Utc);
+        }
+        if !backwards {
+            at = at.checked_add_signed(clock).ok_or_else(out_of_range)?;
+        }
+        Ok(at)
+    }
+
+    // Resolve an optional `at` parameter (any format parse_datetime accepts) to a UTC instant
+    // in the supported range. Wall-clock values are read in `tz`; a missing value means now.
+    fn resolve_instant(at: Option<&str>, tz: &Tz) -> Result<DateTime<Utc>, String> {
+        let Some(at) = at else {
+            return Ok(Utc::now());
+        };
+        let (parsed, _) = Self::parse_flexible_datetime(at)
+            .ok_or_else(|| format!("Unrecognized datetime format: '{}'", at))?;
+        let instant = match parsed {
+            ParsedDateTime::Instant(dt) => dt.with_timezone(&Utc),
+            ParsedDateTime::Epoch(utc) => utc,
+            ParsedDateTime::Local(naive) => {
+                // Zone rules can't be applied near the ends of chrono's range; a day of slack
+                // either side leaves the exact bounds to the check below
+                if !(1799..=10000).contains(&naive.year()) {
+                    return Err(format!("Datetime '{}' is outside the supported range", at));
+                }
+                let (local, _) = Self::resolve_local_datetime(tz, &naive, GapPolicy::Reject)?;
+                local.with_timezone(&Utc)
+            }
+        };
+        Self::ensure_supported(&instant)?;
+        Ok(instant)
+    }
+
+    fn ensure_supported(instant: &DateTime<Utc>) -> Result<(), String> {
+        if !(MIN_SUPPORTED_TIMESTAMP..=MAX_SUPPORTED_TIMESTAMP).contains(&instant.timestamp()) {
+            return Err(format!(
+                "Instant {} is outside the supported range (1800-01-01 to 9999-12-31 UTC)",
+                instant.to_rfc3339()
+            ));
+        }
+        Ok(())
+    }
+
+    // First instant of a local calendar day. Midnight can be skipped or repeated by a
+    // DST change in some zones, so take the earliest valid instant on that date.
+    fn start_of_local_day(tz: &Tz, date: NaiveDate) -> DateTime<Tz> {
+        let midnight = date.and_time(NaiveTime::MIN);
+        match tz.from_local_datetime(&midnight) {
+            LocalResult::Single(dt) => dt,
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => Self::next_valid_local_time(tz, &midnight),
+        }
+    }
+
+    // Map a naive local datetime onto `tz`, applying the gap policy for nonexistent times.
+    // Returns the resolved time and how many seconds the wall clock was moved forward.
+    fn resolve_local_datetime(
+        tz: &Tz,
+        naive: &NaiveDateTime,
+        gap_policy: GapPolicy,
+    ) -> Result<(DateTime<Tz>, i64), String> {
+        match tz.from_local_datetime(naive) {
+            LocalResult::Single(local) => Ok((local, 0)),
+            LocalResult::Ambiguous(_, _) => {
+                Err(format!("Ambiguous local time '{}' in {}", naive, tz.name()))
+            }
+            LocalResult::None => match gap_policy {
+                GapPolicy::Reject => Err(format!(
+                    "Nonexistent local time '{}' in {} (falls in a DST gap; use gap_policy 'shift_forward' to adjust)",
+                    naive,
+                    tz.name()
+                )),
+                GapPolicy::ShiftForward => {
+                    let shifted = Self::next_valid_local_time(tz, naive);
+                    let adjustment = (shifted.naive_local() - *naive).num_seconds();
+                    Ok((shifted, adjustment))
+                }
+            },
+        }
+    }
+
+    // Find the first valid instant after a local time that falls in a DST gap.
+    // The gap starts at a transition T where the offset jumps from `before` to `after`;
+    // T lies in (naive - after, naive - before], so binary search that window.
+    fn next_valid_local_time(tz: &Tz, naive: &NaiveDateTime) -> DateTime<Tz> {
+        let offset_at = |ts: i64| {
+            let utc = DateTime::from_timestamp(ts, 0).unwrap_or_default().naive_utc();
+            tz.offset_from_utc_datetime(&utc).fix().local_minus_utc() as i64
+        };
+
+        let local_ts = naive.and_utc().timestamp();
+        let before = offset_at(local_ts - Duration::days(1).num_seconds());
+        let after = offset_at(local_ts - before);
+
+        // Invariant: offset at `lo` is still `before`, offset at `hi` is already `after`
+        let mut lo = local_ts - after;
+        let mut hi = local_ts - before;
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if offset_at(mid) == after {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        let transition = DateTime::from_timestamp(hi, 0).unwrap_or_default();
+        transition.with_timezone(tz)
+    }
+
+    // Interpret an epoch value in the given unit as a UTC instant
+    fn datetime_from_timestamp(value: i64, unit: TimestampUnit) -> Option<DateTime<Utc>> {
+        let per_second = Self::units_per_second(unit);
+        let secs = value.div_euclid(per_second);
+        let nanos = value.rem_euclid(per_second) * (1_000_000_000 / per_second);
+        DateTime::from_timestamp(secs, nanos as u32)
+    }
+
+    // Express a UTC instant as an epoch value in the given unit
+    // (None only for nanoseconds outside the years 1677-2262)
+    fn timestamp_in_unit(dt: &DateTime<Utc>, unit: TimestampUnit) -> Option<i64> {
+        match unit {
+            TimestampUnit::Seconds => Some(dt.timestamp()),
+            TimestampUnit::Milliseconds => Some(dt.timestamp_millis()),
+            TimestampUnit::Microseconds => Some(dt.timestamp_micros()),
+            TimestampUnit::Nanoseconds => dt.timestamp_nanos_opt(),
+        }
+    }
+
+    fn units_per_second(unit: TimestampUnit) -> i64 {
+        match unit {
+            TimestampUnit::Seconds => 1,
+            TimestampUnit::Milliseconds => 1_000,
+            TimestampUnit::Microseconds => 1_000_000,
+            TimestampUnit::Nanoseconds => 1_000_000_000,
+        }
+    }
+
+    // Reject strftime patterns chrono cannot render (formatting them would panic). Some,
+    // such as "%#z", parse but only fail when written, so render a sample time too.
+    fn validate_format(pattern: &str) -> Result<(), String> {
+        use std::fmt::Write;
+
+        if pattern.len() > MAX_FORMAT_LENGTH {
+            return Err(format!(
+                "Format pattern is too long (max {} characters)",
+                MAX_FORMAT_LENGTH
+            ));
+        }
+        let invalid = || format!("Invalid format pattern: {}", pattern);
+        if StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+            return Err(invalid());
+        }
+        let sample = DateTime::<Utc>::UNIX_EPOCH.fixed_offset();
+        write!(String::new(), "{}", sample.format(pattern)).map_err(|_| invalid())
+    }
+
+    // Alternative calendar dates for the local date, when the request asks for any
+    fn calendar_dates<T: chrono::TimeZone>(
+        local: &DateTime<T>,
+        request: &ConvertRequest,
+    ) -> Option<CalendarDates> {
+        if request.calendars.is_empty() {
+            return None;
+        }
+        Some(calendar::to_calendar_dates(local.date_naive(), &request.calendars))
+    }
+
+    // Format a datetime as RFC 2822, which chrono can only do (and otherwise panics on)
+    // for four-digit years
+    fn format_rfc2822<T: chrono::TimeZone>(dt: &DateTime<T>) -> Option<String>
+    where
+        T::Offset: std::fmt::Display,
+    {
+        (0..=9999).contains(&dt.year()).then(|| dt.to_rfc2822())
+    }
+
+    // Format an instant as an HTTP-date (RFC 9110 IMF-fixdate), which has four-digit years
+    fn format_http_date(utc: &DateTime<Utc>) -> Option<String> {
+        (0..=9999)
+            .contains(&utc.year())
+            .then(|| utc.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+    }
+
+    // Format a datetime's UTC offset as "UTC+05:30", keeping seconds for historical
+    // LMT offsets such as New York's "UTC-04:56:02"
+    fn format_utc_offset<T: chrono::TimeZone>(dt: &DateTime<T>) -> String {
+        format!("UTC{}", Self::format_offset_seconds(dt.offset().fix().local_minus_utc()))
+    }
+
+    // Format a signed number of seconds as "+05:30" (or "-04:56:02" with seconds)
+    fn format_offset_seconds(total: i32) -> String {
+        let sign = if total < 0 { '-' } else { '+' };
+        let total = total.abs();
+        let (hours, minutes, seconds) = (total / 3600, total % 3600 / 60, total % 60);
+        if seconds == 0 {
+            format!("{}{:02}:{:02}", sign, hours, minutes)
+        } else {
+            format!("{}{:02}:{:02}:{:02}", sign, hours, minutes, seconds)
+        }
+    }
+
+    fn working_hours(hours: &BusinessHours) -> Result<WorkingHours, String> {
+        Ok(WorkingHours::new(
+            planning::parse_time(&hours.start, "business_hours.start")?,
+            planning::parse_time(&hours.end, "business_hours.end")?,
+            hours.include_weekends,
+        ))
+    }
+
+    // Whether a local time is within the requested business hours, and when they next open
+    // (both None when the request has no business hours)
+    fn business_hours_status<T: chrono::TimeZone>(
+        local: &DateTime<T>,
+        request: &ConvertRequest,
+    ) -> (Option<bool>, Option<String>)
+    where
+        T::Offset: std::fmt::Display,
+    {
+        let Some(hours) = request
+            .business_hours
+            .as_ref()
+            .and_then(|hours| Self::working_hours(hours).ok())
+        else {
+            return (None, None);
+        };
+        let naive = local.naive_local();
+        let next = hours.next_opening(local).map(|open| open.to_rfc3339());
+        (Some(hours.is_open_at(naive)), next)
+    }
+
+    // Build a ConvertTimezoneInfo for a given UTC instant in a given timezone
+    fn build_convert_info(
+        utc: &DateTime<Utc>,
+        tz: &Tz,
+        request: &ConvertRequest,
+    ) -> ConvertTimezoneInfo {
+        let local = utc.with_timezone(tz);
+        let utc_offset = Self::format_utc_offset(&local);
+
+        let abbreviation = Self::format_abbreviation(&local);
+        let is_dst = Self::is_daylight_saving_time(tz, utc);
+        let (is_within_business_hours, next_opening) =
+            Self::business_hours_status(&local, request);
+
+        ConvertTimezoneInfo {
+            timezone: tz.name().to_string(),
+            datetime: local.to_rfc3339(),
+            utc_offset,
+            abbreviation,
+            is_dst,
+            timestamp: Self::timestamp_in_unit(utc, request.unit).unwrap_or_default(),
+            unit: request.unit,
+            timestamp_ms: utc.timestamp_millis(),
+            formatted: request
+                .format
+                .as_deref()
+                .map(|pattern| local.format(pattern).to_string()),
+            rfc2822: Self::format_rfc2822(&local),
+            http_date: Self::format_http_date(utc),
+            calendars: Self::calendar_dates(&local, request),
+            was_adjusted: false,
+            adjustment_seconds: 0,
+            is_within_business_hours,
+            next_opening,
+        }
+    }
+
+    fn build_zone_convert_info(
+        utc: &DateTime<Utc>,
+        zone: &ConvertZone,
+        request: &ConvertRequest,
+    ) -> ConvertTimezoneInfo {
+        match zone {
+            ConvertZone::Named(tz) => Self::build_convert_info(utc, tz, request),
+            ConvertZone::Fixed(offset) => Self::build_fixed_convert_info(utc, offset, request),
+        }
+    }
+
+    // Build a ConvertTimezoneInfo for an instant expressed in a fixed UTC offset
+    // (no zone rules, so it never observes DST and has no abbreviation)
+    fn build_fixed_convert_info(
+        utc: &DateTime<Utc>,
+        offset: &FixedOffset,
+        request: &ConvertRequest,
+    ) -> ConvertTimezoneInfo {
+        let local = utc.with_timezone(offset);
+        let utc_offset = Self::format_utc_offset(&local);
+        let (is_within_business_hours, next_opening) =
+            Self::business_hours_status(&local, request);
+
+        ConvertTimezoneInfo {
+            timezone: utc_offset.clone(),
+            datetime: local.to_rfc3339(),
+            utc_offset,
+            abbreviation: "N/A".to_string(),
+            is_dst: false,
+            timestamp: Self::timestamp_in_unit(utc, request.unit).unwrap_or_default(),
+            unit: request.unit,
+            timestamp_ms: utc.timestamp_millis(),
+            formatted: request
+                .format
+                .as_deref()
+                .map(|pattern| local.format(pattern).to_string()),
+            rfc2822: Self::format_rfc2822(&local),
+            http_date: Self::format_http_date(utc),
+            calendars: Self::calendar_dates(&local, request),
+            was_adjusted: false,
+            adjustment_seconds: 0,
+            is_within_business_hours,
+            next_opening,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CalendarSystem;
+
+    #[test]
+    fn test_get_timezone_info_valid() {
+        let result = EpochZoneService::get_timezone_info("America/New_York", &TimeQuery::default());
+        assert!(result.is_ok());
+        
+        let info = result.unwrap();
+        assert_eq!(info.timezone, "America/New_York");
+        assert!(!info.current_time.is_empty());
+    }
+
+    #[test]
+    fn test_get_timezone_info_invalid() {
+        let result = EpochZoneService::get_timezone_info("Invalid/Timezone", &TimeQuery::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_get_timezone_info_utc() {
+        let result = EpochZoneService::get_timezone_info("UTC", &TimeQuery::default());
+        assert!(result.is_ok());
+        
+        let info = result.unwrap();
+        assert_eq!(info.utc_offset, "UTC+00:00");
+        assert!(!info.is_dst);
+    }
+
+    #[test]
+    fn test_get_time_output() {
+        let time = |output: Option<TimeOutput>| {
+            let query = TimeQuery {
+                output,
+                ..Default::default()
+            };
+            EpochZoneService::get_time("Europe/Belgrade", &query).unwrap()
+        };
+        let full = time(None);
+        assert!(matches!(full, TimeResponse::Full(info) if info.timezone == "Europe/Belgrade"));
+
+        let TimeResponse::Selected(unix) = time(Some(TimeOutput::Unix)) else {
+            panic!("expected the selected representation");
+        };
+        assert!(unix.unix.unwrap() > 1_700_000_000);
+        assert!(unix.unix_ms.is_none() && unix.iso.is_none() && unix.rfc2822.is_none());
+
+        let TimeResponse::Selected(all) = time(Some(TimeOutput::All)) else {
+            panic!("expected the selected representation");
+        };
+        assert_eq!(all.unix, all.unix_ms.map(|ms| ms / 1000));
+        assert!(all.iso.is_some() && all.rfc2822.is_some());
+    }
+
+    #[test]
+    fn test_get_auto_time() {
+        let geoip = GeoIp::disabled();
+        let auto = |zone: Option<&str>| {
+            let query = TimeQuery::default();
+            match EpochZoneService::get_auto_time(&geoip, None, zone, &query).unwrap() {
+                TimeResponse::Full(info) => (info.timezone, info.resolved_by),
+                TimeResponse::Selected(_) => panic!("expected the full response"),
+            }
+        };
+        let by_language = auto(Some("Europe/Belgrade"));
+        assert_eq!(by_language.0, "Europe/Belgrade");
+        assert_eq!(by_language.1, Some(TimezoneResolution::AcceptLanguage));
+        // Without a database the address is no help, and without a hint it is UTC
+        assert_eq!(auto(None), ("UTC".to_string(), Some(TimezoneResolution::Default)));
+    }
+
+    #[test]
+    fn test_get_ip_info_errors() {
+        let geoip = GeoIp::disabled();
+        let caller = Some(IpAddr::from([203, 0, 113, 7]));
+        let error = |addr: &str, caller| EpochZoneService::get_ip_info(&geoip, addr, caller);
+        assert!(error("not-an-ip", caller).unwrap_err().starts_with("Invalid IP address"));
+        assert!(error("self", None).unwrap_err().contains("caller's address"));
+        assert!(error("self", caller).unwrap_err().contains("GEOIP_DATABASE"));
+        assert!(error("2001:db8::1", None).unwrap_err().contains("GEOIP_DATABASE"));
+    }
+
+    #[test]
+    fn test_get_calendar_info() {
+        let query = CalendarQuery {
+            year: 2024,
+            month: 2,
+            tz: Some("Europe/Belgrade".to_string()),
+        };
+        let calendar = EpochZoneService::get_calendar_info(&query).unwrap();
+        assert!(calendar.is_leap_year);
+        assert_eq!(calendar.days_in_month, 29);
+        assert_eq!(calendar.month_name, "February");
+        assert_eq!(calendar.first_instant, "2024-02-01T00:00:00+01:00");
+        assert_eq!(calendar.first_timestamp, 1_706_742_000);
+        assert_eq!(calendar.last_instant, "2024-02-29T23:59:59+01:00");
+        // Thursday 1 February falls in ISO week 5; Thursday 29 February in week 9
+        assert_eq!(calendar.weeks.len(), 5);
+        assert_eq!(calendar.weeks[0].iso_week, 5);
+        assert_eq!(calendar.weeks[0].start, "2024-01-29T00:00:00+01:00");
+        assert_eq!(calendar.weeks[4].end, "2024-03-04T00:00:00+01:00");
+
+        // March 2025 in New York loses an hour to DST
+        let query = CalendarQuery {
+            year: 2025,
+            month: 3,
+            tz: Some("America/New_York".to_string()),
+        };
+        let calendar = EpochZoneService::get_calendar_info(&query).unwrap();
+        assert!(!calendar.is_leap_year);
+        assert_eq!(calendar.last_instant, "2025-03-31T23:59:59-04:00");
+        assert_eq!(
+            calendar.last_timestamp + 1 - calendar.first_timestamp,
+            31 * 86_400 - 3600
+        );
+
+        let query = CalendarQuery {
+            year: 2025,
+            month: 13,
+            tz: None,
+        };
+        assert!(EpochZoneService::get_calendar_info(&query).is_err());
+
+        for year in [-262143, 1799, 10000, 262142] {
+            let query = CalendarQuery {
+                year,
+                month: 1,
+                tz: None,
+            };
+            assert!(EpochZoneService::get_calendar_info(&query).is_err());
+        }
+        let query = CalendarQuery {
+            year: 9999,
+            month: 12,
+            tz: Some("Pacific/Kiritimati".to_string()),
+        };
+        let calendar = EpochZoneService::get_calendar_info(&query).unwrap();
+        assert_eq!(calendar.weeks.last().unwrap().iso_year, 9999);
+    }
+
+    #[test]
+    fn test_get_interval() {
+        let query = |interval: &str, tz: Option<&str>| IntervalQuery {
+            interval: interval.to_string(),
+            tz: tz.map(str::to_string),
+            at: Some("2025-01-01T00:00:00Z".to_string()),
+        };
+
+        let interval = EpochZoneService::get_interval(&query("2025-01-01T00:00Z/P3D", None));
+        let interval = interval.unwrap();
+        assert_eq!(interval.start, "2025-01-01T00:00:00+00:00");
+        assert_eq!(interval.end, "2025-01-04T00:00:00+00:00");
+        assert_eq!(interval.duration.as_deref(), Some("P3D"));
+        assert_eq!(interval.total_seconds, 3 * 86_400);
+
+        // A bare duration starts at `at`
+        let interval = EpochZoneService::get_interval(&query("P1Y2M10DT2H", None)).unwrap();
+        assert_eq!(interval.end, "2026-03-11T02:00:00+00:00");
+
+        // A calendar day across the spring-forward change is 23 hours; PT24H is not
+        let day = query("2025-03-08T12:00:00/P1D", Some("America/New_York"));
+        let day = EpochZoneService::get_interval(&day).unwrap();
+        assert_eq!(day.end, "2025-03-09T12:00:00-04:00");
+        assert_eq!(day.total_seconds, 23 * 3600);
+        let hours = query("2025-03-08T12:00:00/PT24H", Some("America/New_York"));
+        let hours = EpochZoneService::get_interval(&hours).unwrap();
+        assert_eq!(hours.end, "2025-03-09T13:00:00-04:00");
+
+        let before = query("P1MT2H/2025-03-31T00:00:00Z", None);
+        let before = EpochZoneService::get_interval(&before).unwrap();
+        assert_eq!(before.start, "2025-02-28T22:00:00+00:00");
+
+        let both = query("2025-01-01T00:00:00Z/2025-01-02T06:00:00Z", None);
+        let both = EpochZoneService::get_interval(&both).unwrap();
+        assert_eq!(both.duration, None);
+        assert_eq!(both.total_seconds, 30 * 3600);
+
+        let backwards = query("2025-01-02T00:00:00Z/2025-01-01T00:00:00Z", None);
+        assert!(EpochZoneService::get_interval(&backwards).is_err());
+        assert!(EpochZoneService::get_interval(&query("P1X", None)).is_err());
+    }
+
+    #[test]
+    fn test_get_age() {
+        let age = |birth: &str, tz: Option<&str>, at: &str| {
+            let query = AgeQuery {
+                birth: birth.to_string(),
+                tz: tz.map(str::to_string),
+                at: Some(at.to_string()),
+            };
+            EpochZoneService::get_age(&query).unwrap()
+        };
+        let a = age("1990-05-01", Some("Europe/Belgrade"), "2025-02-10T15:30");
+        assert_eq!((a.years, a.months, a.days), (34, 9, 9));
+        assert_eq!((a.total_days, a.next_birthday.as_str()), (12_704, "2025-05-01"));
+        assert_eq!(a.days_until_birthday, 80);
+        assert_eq!(a.birth, "1990-05-01T00:00:00+02:00");
+        // Born in summer time (UTC+2), measured in winter time (UTC+1)
+        assert_eq!(a.total_seconds, 12_704 * 86_400 + 16 * 3600 + 1800);
+
+        // The birthday itself, and the zone deciding which day it already is
+        let today = age("2000-02-10", None, "2025-02-10T00:00:00Z");
+        assert_eq!((today.years, today.months, today.days), (25, 0, 0));
+        assert_eq!(today.days_until_birthday, 0);
+        let tokyo = age("2000-02-11", Some("Asia/Tokyo"), "2025-02-10T16:00:00Z");
+        assert_eq!((tokyo.years, tokyo.days_until_birthday), (25, 0));
+
+        // Month ends and leap days
+        let leap = age("2024-02-29", None, "2025-02-28T12:00:00Z");
+        assert_eq!((leap.years, leap.months, leap.days), (1, 0, 0));
+        let month_end = age("2025-01-31", None, "2025-03-01T00:00:00Z");
+        assert_eq!((month_end.months, month_end.days), (1, 1));
+
+        let query = AgeQuery {
+            birth: "2030-01-01".to_string(),
+            at: Some("2025-01-01".to_string()),
+            ..Default::default()
+        };
+        assert!(EpochZoneService::get_age(&query).is_err());
+    }
+
+    #[test]
+    fn test_get_all_timezones() {
+        let timezones = EpochZoneService::get_all_timezones();
+        assert!(!timezones.is_empty());
+        assert!(timezones.len() > 500); // Should have 600+ timezones
+    }
+
+    #[test]
+    fn test_is_valid_timezone() {
+        assert!(EpochZoneService::is_valid_timezone("America/New_York"));
+        assert!(EpochZoneService::is_valid_timezone("Europe/London"));
+        assert!(EpochZoneService::is_valid_timezone("UTC"));
+        assert!(!EpochZoneService::is_valid_timezone("Invalid/Timezone"));
+    }
+
+    #[test]
+    fn test_timezone_list_format() {
+        let timezones = EpochZoneService::get_all_timezones();
+        let ny = timezones.iter().find(|tz| tz.name == "America/New_York");
+
+        assert!(ny.is_some());
+        let ny = ny.unwrap();
+        assert_eq!(ny.display_name, "America/New York");
+    }
+
+    #[test]
+    fn test_convert_timezone_with_timestamp() {
+        let request = ConvertRequest {
+            timestamp: Some(1707580800),
+            datetime: None,
+            from: None,
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_ok());
+
+        let resp = result.unwrap();
+        assert_eq!(resp.from.timezone, "UTC");
+        assert_eq!(resp.to.timezone, "America/New_York");
+        assert_eq!(resp.from.timestamp, resp.to.timestamp);
+    }
+
+    #[test]
+    fn test_convert_timezone_with_datetime() {
+        let request = ConvertRequest {
+            timestamp: None,
+            datetime: Some("2025-02-10T15:30:00".to_string()),
+            from: Some("Europe/Belgrade".to_string()),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_ok());
+
+        let resp = result.unwrap();
+        assert_eq!(resp.from.timezone, "Europe/Belgrade");
+        assert_eq!(resp.to.timezone, "America/New_York");
+        assert_eq!(resp.from.timestamp, resp.to.timestamp);
+        // Belgrade is CET (UTC+1) in February, NY is EST (UTC-5), difference is 6 hours
+        assert!(resp.to.datetime.contains("09:30:00"));
+        assert_eq!((resp.offset_difference_seconds, resp.day_shift), (-6 * 3600, 0));
+    }
+
+    #[test]
+    fn test_convert_timezone_day_shift() {
+        let convert = |datetime: &str, from: &str, to: &str| {
+            let request = ConvertRequest {
+                datetime: Some(datetime.to_string()),
+                from: Some(from.to_string()),
+                to: to.to_string(),
+                ..Default::default()
+            };
+            let resp = EpochZoneService::convert_timezone(&request).unwrap();
+            (resp.offset_difference_seconds, resp.day_shift)
+        };
+        assert_eq!(
+            convert("2025-02-10T08:00:00", "Asia/Tokyo", "America/Los_Angeles"),
+            (-17 * 3600, -1)
+        );
+        assert_eq!(
+            convert("2025-02-10T20:00:00", "America/New_York", "+05:30"),
+            (10 * 3600 + 1800, 1)
+        );
+        assert_eq!(
+            convert("2025-02-10T23:00:00", "Etc/GMT+12", "Pacific/Kiritimati"),
+            (26 * 3600, 2)
+        );
+    }
+
+    #[test]
+    fn test_convert_timezone_invalid_target() {
+        let request = ConvertRequest {
+            timestamp: Some(1707580800),
+            datetime: None,
+            from: None,
+            to: "Invalid/Zone".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid target timezone"));
+    }
+
+    #[test]
+    fn test_convert_timezone_missing_fields() {
+        let request = ConvertRequest {
+            timestamp: None,
+            datetime: None,
+            from: None,
+            to: "UTC".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("required"));
+    }
+
+    #[test]
+    fn test_convert_timezone_both_timestamp_and_datetime() {
+        let request = ConvertRequest {
+            timestamp: Some(1707580800),
+            datetime: Some("2025-02-10T15:30:00".to_string()),
+            from: Some("UTC".to_string()),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("not both"));
+    }
+
+    #[test]
+    fn test_get_timezone_by_coordinates_tokyo() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 35.6762, 139.6503);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().timezone, "Asia/Tokyo");
+    }
+
+    #[test]
+    fn test_get_timezone_by_coordinates_new_york() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 40.7128, -74.0060);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().timezone, "America/New_York");
+    }
+
+    #[test]
+    fn test_get_timezone_by_coordinates_london() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 51.5074, -0.1278);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().timezone, "Europe/London");
+    }
+
+    #[test]
+    fn test_geolocate_batch() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let point = |lat, lng| crate::models::GeolocationQuery {
+            lat,
+            lng,
+            normalize: false,
+        };
+        let request = BatchGeolocationRequest {
+            points: vec![point(35.6762, 139.6503), point(44.7866, 20.4489), point(91.0, 0.0)],
+            normalize: false,
+        };
+        let result = EpochZoneService::geolocate_batch(&finder, &request).unwrap();
+        assert_eq!(result.errors, 1);
+        assert_eq!(result.results[0].timezone.as_deref(), Some("Asia/Tokyo"));
+        assert_eq!(result.results[1].info.as_ref().unwrap().timezone, "Europe/Belgrade");
+        assert_eq!(result.results[2].error_code.as_deref(), Some("latitude_out_of_range"));
+
+        let empty = BatchGeolocationRequest {
+            points: vec![],
+            normalize: false,
+        };
+        assert!(EpochZoneService::geolocate_batch(&finder, &empty).is_err());
+        let too_many = BatchGeolocationRequest {
+            points: (0..=MAX_BATCH_POINTS).map(|_| point(0.0, 0.0)).collect(),
+            normalize: false,
+        };
+        assert!(EpochZoneService::geolocate_batch(&finder, &too_many).is_err());
+    }
+
+    #[test]
+    fn test_get_country_zones() {
+        let result = EpochZoneService::get_country_zones("au").unwrap();
+        assert_eq!(result.country_code, "AU");
+        assert_eq!(result.country_name, "Australia");
+        let perth = result.zones.iter().find(|z| z.timezone == "Australia/Perth").unwrap();
+        assert_eq!(perth.utc_offset, "UTC+08:00");
+        assert!(!perth.is_dst);
+        assert_eq!(perth.comment.as_deref(), Some("Western Australia (most areas)"));
+        assert!(EpochZoneService::get_country_zones("XX").is_err());
+    }
+
+    #[test]
+    fn test_get_boundary() {
+        let query = BoundaryQuery::default();
+        let feature = EpochZoneService::get_boundary("Europe/Belgrade", &query).unwrap();
+        assert_eq!(feature.kind, "Feature");
+        assert_eq!(feature.geometry.kind, "MultiPolygon");
+        assert_eq!(feature.properties.tolerance, DEFAULT_BOUNDARY_TOLERANCE);
+        assert!(feature.properties.points > 0);
+        // Links resolve to the canonical zone's outline
+        let sarajevo = EpochZoneService::get_boundary("Europe/Sarajevo", &query).unwrap();
+        assert_eq!(sarajevo.properties.canonical, "Europe/Belgrade");
+
+        let query = BoundaryQuery {
+            tolerance: Some(-1.0),
+        };
+        assert!(EpochZoneService::get_boundary("Europe/Belgrade", &query).is_err());
+        assert!(EpochZoneService::get_boundary("Mars/Base", &query).is_err());
+    }
+
+    #[test]
+    fn test_plan_itinerary_across_date_line() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let stop = |place: &str, local_time: Option<&str>| crate::models::ItineraryStop {
+            place: place.to_string(),
+            local_time: local_time.map(str::to_string),
+        };
+        // Tokyo to New York leaves in the evening and lands the same morning
+        let request = ItineraryRequest {
+            departure: stop("HND", Some("2025-06-10T18:00")),
+            arrival: stop("America/New_York", None),
+            duration_minutes: Some(12 * 60 + 50),
+        };
+        let trip = EpochZoneService::plan_itinerary(&finder, &request).unwrap();
+        assert_eq!(trip.departure.local.timezone, "Asia/Tokyo");
+        assert_eq!(trip.departure.utc, "2025-06-10T09:00:00+00:00");
+        assert_eq!(trip.arrival.local.datetime, "2025-06-10T17:50:00-04:00");
+        assert_eq!((trip.duration.as_str(), trip.day_offset), ("12h 50m", 0));
+        assert_eq!(trip.offset_change_seconds, -13 * 3600);
+
+        // Given the arrival time instead, the duration is derived
+        let request = ItineraryRequest {
+            departure: stop("America/New_York", Some("2025-06-10T22:00")),
+            arrival: stop("LYBE", Some("2025-06-11T14:30")),
+            duration_minutes: None,
+        };
+        let trip = EpochZoneService::plan_itinerary(&finder, &request).unwrap();
+        assert_eq!((trip.duration_minutes, trip.day_offset), (10 * 60 + 30, 1));
+
+        let both = ItineraryRequest {
+            duration_minutes: Some(60),
+            ..request
+        };
+        assert!(EpochZoneService::plan_itinerary(&finder, &both).is_err());
+        let backwards = ItineraryRequest {
+            departure: stop("UTC", Some("2025-06-10T12:00")),
+            arrival: stop("UTC", Some("2025-06-10T11:00")),
+            duration_minutes: None,
+        };
+        assert!(EpochZoneService::plan_itinerary(&finder, &backwards).is_err());
+    }
+
+    #[test]
+    fn test_plan_jet_lag() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let request = |origin: &str, destination: &str| JetLagRequest {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            travel_date: "2025-06-11".to_string(),
+            bedtime: "23:00".to_string(),
+            wake_time: "07:00".to_string(),
+            prepare_days: 3,
+            shift_minutes_per_day: None,
+        };
+        // Six hours east at an hour a day: three days at home, three after landing
+        let east = EpochZoneService::plan_jet_lag(&finder, &request("JFK", "BEG")).unwrap();
+        assert_eq!(east.direction, ClockShift::Advance);
+        assert_eq!(east.offset_difference, "+06:00");
+        let sleep: Vec<_> = east
+            .days
+            .iter()
+            .map(|d| (d.date.as_str(), d.timezone.as_str(), d.bedtime.as_str()))
+            .collect();
+        assert_eq!(
+            sleep,
+            [
+                ("2025-06-08", "America/New_York", "22:00"),
+                ("2025-06-09", "America/New_York", "21:00"),
+                ("2025-06-10", "America/New_York", "20:00"),
+                ("2025-06-11", "Europe/Belgrade", "01:00"),
+                ("2025-06-12", "Europe/Belgrade", "00:00"),
+                ("2025-06-13", "Europe/Belgrade", "23:00"),
+            ]
+        );
+        assert_eq!(east.days[0].seek_light.as_deref(), Some("06:00-08:00"));
+        assert_eq!(east.days[5].remaining_minutes, 0);
+
+        // Delaying goes faster, so the same trip west is done by the travel date
+        let west = request("Europe/Belgrade", "America/New_York");
+        let west = EpochZoneService::plan_jet_lag(&finder, &west).unwrap();
+        assert_eq!((west.direction, west.shift_minutes_per_day), (ClockShift::Delay, 90));
+        assert_eq!(west.days.len(), 4);
+        assert_eq!(west.days[0].bedtime, "00:30");
+        assert_eq!(west.days[3].bedtime, "23:00");
+        assert_eq!(west.days[0].avoid_light.as_deref(), Some("08:30-10:30"));
+
+        // Around the clock the short way: Honolulu to Auckland is +22 hours, a 2-hour delay
+        let far = request("Pacific/Honolulu", "Pacific/Auckland");
+        let far = EpochZoneService::plan_jet_lag(&finder, &far).unwrap();
+        assert_eq!(far.offset_difference, "-02:00");
+
+        for travel_date in ["-262143-01-01", "+262142-12-31", "1799-12-31", "10000-01-01"] {
+            let far_off = JetLagRequest {
+                travel_date: travel_date.to_string(),
+                ..request("UTC", "Pacific/Kiritimati")
+            };
+            assert!(EpochZoneService::plan_jet_lag(&finder, &far_off).is_err());
+        }
+
+        let same = request("Europe/Paris", "CET");
+        let same = EpochZoneService::plan_jet_lag(&finder, &same).unwrap();
+        assert_eq!(same.direction, ClockShift::None);
+        assert!(same.days.iter().all(|d| d.seek_light.is_none()));
+        assert!(EpochZoneService::plan_jet_lag(&finder, &request("Mars/Base", "UTC")).is_err());
+    }
+
+    #[test]
+    fn test_get_sun_times() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let query = SunQuery {
+            lat: 44.7866,
+            lng: 20.4489,
+            date: Some("2025-06-21".to_string()),
+        };
+        let sun = EpochZoneService::get_sun_times(&finder, &query).unwrap();
+        assert_eq!(sun.timezone, "Europe/Belgrade");
+        assert!(sun.sunrise.unwrap().starts_with("2025-06-21T04:5"));
+        assert!(sun.sunset.unwrap().starts_with("2025-06-21T20:"));
+        assert!((sun.day_length_seconds - (15 * 3600 + 38 * 60)).abs() < 300);
+        assert!(sun.polar.is_none());
+        assert!(sun.solar_noon.starts_with("2025-06-21T12:"), "{}", sun.solar_noon);
+        let civil_dawn = sun.twilight.civil.dawn_timestamp.unwrap();
+        assert!(civil_dawn < sun.sunrise_timestamp.unwrap());
+        assert!(sun.twilight.nautical.dawn_timestamp.unwrap() < civil_dawn);
+        assert!(sun.twilight.astronomical.condition.is_none());
+
+        // London at midsummer: the sun never sinks 18° below the horizon
+        let london = SunQuery {
+            lat: 51.5074,
+            lng: -0.1278,
+            date: Some("2025-06-21".to_string()),
+        };
+        let sun = EpochZoneService::get_sun_times(&finder, &london).unwrap();
+        let astronomical = &sun.twilight.astronomical;
+        assert_eq!(astronomical.condition, Some(TwilightCondition::NeverDark));
+        assert!(astronomical.dawn.is_none());
+        assert!(sun.twilight.nautical.dusk.is_some());
+
+        let query = SunQuery {
+            lat: 89.0,
+            date: Some("2025-12-21".to_string()),
+            ..query
+        };
+        let sun = EpochZoneService::get_sun_times(&finder, &query).unwrap();
+        assert_eq!(sun.polar, Some(PolarCondition::PolarNight));
+        assert_eq!((sun.day_length_seconds, sun.day_length.as_str()), (0, "00:00:00"));
+
+        let query = SunQuery {
+            date: Some("21.12.2025".to_string()),
+            ..query
+        };
+        assert!(EpochZoneService::get_sun_times(&finder, &query).is_err());
+        for date in ["+262142-12-31", "-262143-01-01", "1799-12-31", "10000-01-01"] {
+            let query = SunQuery {
+                lat: 0.0,
+                lng: 179.0,
+                date: Some(date.to_string()),
+            };
+            assert!(EpochZoneService::get_sun_times(&finder, &query).is_err());
+        }
+    }
+
+    #[test]
+    fn test_get_zone_countries() {
+        let result = EpochZoneService::get_zone_countries("Europe/Sarajevo").unwrap();
+        assert_eq!(result.canonical, "Europe/Belgrade");
+        let codes: Vec<&str> = result.countries.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, ["RS", "BA", "HR", "ME", "MK", "SI"]);
+        assert_eq!(result.countries[0].name, "Serbia");
+        assert!(EpochZoneService::get_zone_countries("UTC").unwrap().countries.is_empty());
+        assert!(EpochZoneService::get_zone_countries("Mars/Base").is_err());
+    }
+
+    #[test]
+    fn test_get_airport() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let airport = EpochZoneService::get_airport(&finder, "LYBE").unwrap();
+        assert_eq!(airport.iata, "BEG");
+        assert_eq!(airport.timezone.timezone, "Europe/Belgrade");
+        let airport = EpochZoneService::get_airport(&finder, "hnd").unwrap();
+        assert_eq!(airport.timezone.timezone, "Asia/Tokyo");
+        assert!(EpochZoneService::get_airport(&finder, "ZZZ").is_err());
+    }
+
+    #[test]
+    fn test_get_timezone_by_coordinates_candidates() {
+        let finder = tzf_rs::DefaultFinder::new();
+        let info = EpochZoneService::get_timezone_by_coordinates(&finder, 44.7866, 20.4489);
+        let candidates = info.unwrap().candidates.unwrap();
+        assert_eq!(candidates[0], "Europe/Belgrade");
+        assert!(candidates.windows(2).all(|w| w[0] != w[1]));
+        // Lookups by name carry no candidates
+        let info = EpochZoneService::get_timezone_info("Europe/Belgrade", &TimeQuery::default());
+        assert!(info.unwrap().candidates.is_none());
+    }
+
+    #[test]
+    fn test_geojson_coordinates() {
+        let parse = |body: &str| {
+            let input: GeoJsonInput = serde_json::from_str(body).unwrap();
+            EpochZoneService::geojson_coordinates(&input)
+        };
+        let point = r#"{"type": "Point", "coordinates": [20.4489, 44.7866]}"#;
+        assert_eq!(parse(point), Ok((44.7866, 20.4489)));
+        let feature = r#"{"type": "Feature", "properties": {"id": 7},
+            "geometry": {"type": "Point", "coordinates": [139.65, 35.67, 40.0]}}"#;
+        assert_eq!(parse(feature), Ok((35.67, 139.65)));
+        assert!(parse(r#"{"type": "Point", "coordinates": [20.4]}"#).is_err());
+
+        let polygon = r#"{"type": "Feature", "geometry": {"type": "Polygon", "coordinates": []}}"#;
+        assert!(serde_json::from_str::<GeoJsonInput>(polygon).is_err());
+    }
+
+    #[test]
+    fn test_validate_coordinates() {
+        let validate = EpochZoneService::validate_coordinates;
+        assert_eq!(validate(44.8, 20.5, false), Ok((44.8, 20.5)));
+        assert_eq!(validate(-90.0, 180.0, false), Ok((-90.0, 180.0)));
+        assert_eq!(validate(f64::NAN, 0.0, false), Err(CoordinateError::LatitudeNotFinite));
+        let infinite = validate(0.0, f64::INFINITY, true);
+        assert_eq!(infinite, Err(CoordinateError::LongitudeNotFinite));
+        assert_eq!(validate(90.5, 0.0, true), Err(CoordinateError::LatitudeOutOfRange));
+        assert_eq!(validate(0.0, 200.0, false), Err(CoordinateError::LongitudeOutOfRange));
+        assert_eq!(validate(0.0, 200.0, true), Ok((0.0, -160.0)));
+        assert_eq!(validate(0.0, -540.0, true), Ok((0.0, -180.0)));
+        assert_eq!(CoordinateError::LongitudeOutOfRange.code(), "longitude_out_of_range");
+
+        let finder = tzf_rs::DefaultFinder::new();
+        assert!(EpochZoneService::get_timezone_by_coordinates(&finder, 120.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_get_timezone_by_coordinates_ocean() {
+        let finder = tzf_rs::DefaultFinder::new();
+        // Middle of the Pacific Ocean
+        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 0.0, -160.0);
+        // Ocean points get the nautical zone, with the nearest land zone alongside
+        let info = result.unwrap();
+        assert_eq!(info.timezone, "Etc/GMT+11");
+        let ocean = info.ocean.unwrap();
+        assert!(ocean.nearest_land_zone.is_some());
+        assert!(ocean.nearest_land_km.unwrap() > 100.0);
+
+        let land = EpochZoneService::get_timezone_by_coordinates(&finder, 35.6762, 139.6503);
+        assert!(land.unwrap().ocean.is_none());
+    }
+
+    #[test]
+    fn test_convert_timezone_datetime_without_from() {
+        let request = ConvertRequest {
+            timestamp: None,
+            datetime: Some("2025-02-10T15:30:00".to_string()),
+            from: None,
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("'from' timezone is required"));
+    }
+
+    #[test]
+    fn test_convert_timezone_gap_rejected_by_default() {
+        // 02:30 does not exist in New York on 2025-03-09 (clocks jump 02:00 -> 03:00)
+        let request = ConvertRequest {
+            timestamp: None,
+            datetime: Some("2025-03-09T02:30:00".to_string()),
+            from: Some("America/New_York".to_string()),
+            to: "UTC".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Nonexistent local time"));
+    }
+
+    #[test]
+    fn test_convert_timezone_gap_shift_forward() {
+        let request = ConvertRequest {
+            timestamp: None,
+            datetime: Some("2025-03-09T02:30:00".to_string()),
+            from: Some("America/New_York".to_string()),
+            to: "UTC".to_string(),
+            gap_policy: GapPolicy::ShiftForward,
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        // Shifted to the first valid instant, 03:00 EDT == 07:00 UTC
+        assert_eq!(resp.from.datetime, "2025-03-09T03:00:00-04:00");
+        assert!(resp.from.was_adjusted);
+        assert_eq!(resp.from.adjustment_seconds, 1800);
+        assert_eq!(resp.to.datetime, "2025-03-09T07:00:00+00:00");
+        assert!(!resp.to.was_adjusted);
+    }
+
+    #[test]
+    fn test_convert_timezone_shift_forward_valid_time_not_adjusted() {
+        let request = ConvertRequest {
+            timestamp: None,
+            datetime: Some("2025-02-10T15:30:00".to_string()),
+            from: Some("Europe/Belgrade".to_string()),
+            to: "America/New_York".to_string(),
+            gap_policy: GapPolicy::ShiftForward,
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+        assert!(!resp.from.was_adjusted);
+        assert_eq!(resp.from.adjustment_seconds, 0);
+    }
+
+    #[test]
+    fn test_convert_timezone_rfc3339_with_offset() {
+        let request = ConvertRequest {
+            datetime: Some("2025-02-10T15:30:00+01:00".to_string()),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.timezone, "UTC+01:00");
+        assert_eq!(resp.from.datetime, "2025-02-10T15:30:00+01:00");
+        assert_eq!(resp.to.datetime, "2025-02-10T09:30:00-05:00");
+    }
+
+    #[test]
+    fn test_convert_timezone_rfc3339_zulu() {
+        let request = ConvertRequest {
+            datetime: Some("2025-02-10T14:30:00Z".to_string()),
+            to: "Europe/Belgrade".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.utc_offset, "UTC+00:00");
+        assert_eq!(resp.to.datetime, "2025-02-10T15:30:00+01:00");
+    }
+
+    #[test]
+    fn test_convert_timezone_rfc3339_with_from_for_display() {
+        let request = ConvertRequest {
+            datetime: Some("2025-02-10T14:30:00Z".to_string()),
+            from: Some("Asia/Tokyo".to_string()),
+            to: "UTC".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.timezone, "Asia/Tokyo");
+        assert_eq!(resp.from.datetime, "2025-02-10T23:30:00+09:00");
+    }
+
+    #[test]
+    fn test_convert_timezone_millisecond_timestamp() {
+        // Date.now() style value
+        let request = ConvertRequest {
+            timestamp: Some(1707580800123),
+            to: "America/New_York".to_string(),
+            unit: TimestampUnit::Milliseconds,
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.timestamp, 1707580800123);
+        assert_eq!(resp.to.unit, TimestampUnit::Milliseconds);
+        assert!(resp.from.datetime.starts_with("2024-02-10T16:00:00"));
+    }
+
+    #[test]
+    fn test_convert_timezone_nanosecond_timestamp() {
+        let request = ConvertRequest {
+            timestamp: Some(-1_500_000_000),
+            to: "UTC".to_string(),
+            unit: TimestampUnit::Nanoseconds,
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.to.timestamp, -1_500_000_000);
+        assert!(resp.to.datetime.starts_with("1969-12-31T23:59:58"));
+    }
+
+    #[test]
+    fn test_get_timezone_info_in_milliseconds() {
+        let options = TimeQuery {
+            unit: TimestampUnit::Milliseconds,
+            ..Default::default()
+        };
+        let info = EpochZoneService::get_timezone_info("UTC", &options).unwrap();
+
+        assert_eq!(info.unit, TimestampUnit::Milliseconds);
+        assert!(info.timestamp > 1_000_000_000_000);
+    }
+
+    #[test]
+    fn test_convert_timezone_preserves_fractional_seconds() {
+        let request = ConvertRequest {
+            datetime: Some("2025-02-10T15:30:00.123".to_string()),
+            from: Some("Europe/Belgrade".to_string()),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.datetime, "2025-02-10T15:30:00.123+01:00");
+        assert_eq!(resp.to.datetime, "2025-02-10T09:30:00.123-05:00");
+        assert_eq!(resp.to.timestamp, 1739197800);
+        assert_eq!(resp.to.timestamp_ms, 1739197800123);
+    }
+
+    #[test]
+    fn test_convert_timezone_sub_second_timestamp() {
+        let request = ConvertRequest {
+            timestamp: Some(1707580800123456),
+            to: "UTC".to_string(),
+            unit: TimestampUnit::Microseconds,
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.to.datetime, "2024-02-10T16:00:00.123456+00:00");
+        assert_eq!(resp.to.timestamp_ms, 1707580800123);
+    }
+
+    #[test]
+    fn test_convert_timezone_negative_timestamp() {
+        // 1950-06-15T12:00:00Z, during New York's historical DST
+        let request = ConvertRequest {
+            timestamp: Some(-616_852_800),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.to.datetime, "1950-06-15T08:00:00-04:00");
+        assert_eq!(resp.to.abbreviation, "EDT");
+        assert!(resp.to.is_dst);
+    }
+
+    #[test]
+    fn test_convert_timezone_1900_historical_offset() {
+        // 1900-01-01T00:00:00Z, when Kolkata used Madras Mean Time
+        let request = ConvertRequest {
+            timestamp: Some(-2_208_988_800),
+            to: "Asia/Kolkata".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.datetime, "1900-01-01T00:00:00+00:00");
+        assert_eq!(resp.to.utc_offset, "UTC+05:21:10");
+        assert_eq!(resp.to.abbreviation, "MMT");
+    }
+
+    #[test]
+    fn test_convert_timezone_lmt_offset() {
+        // 1874, before New York adopted standard time
+        let request = ConvertRequest {
+            timestamp: Some(-3_000_000_000),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.to.utc_offset, "UTC-04:56:02");
+        assert_eq!(resp.to.abbreviation, "LMT");
+    }
+
+    #[test]
+    fn test_convert_timezone_pre_1900_datetime() {
+        let request = ConvertRequest {
+            datetime: Some("1880-06-01T12:00:00".to_string()),
+            from: Some("Europe/Belgrade".to_string()),
+            to: "UTC".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.utc_offset, "UTC+01:22");
+        assert_eq!(resp.to.datetime, "1880-06-01T10:38:00+00:00");
+    }
+
+    #[test]
+    fn test_convert_timezone_out_of_range() {
+        let request = ConvertRequest {
+            timestamp: Some(-6_000_000_000),
+            to: "UTC".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("outside the supported range"));
+
+        // Past year 9999 in UTC, and in Kiritimati's local time only
+        for (timestamp, to) in [(300_000_000_000, "UTC"), (253_402_300_799, "Pacific/Kiritimati")] {
+            let request = ConvertRequest {
+                timestamp: Some(timestamp),
+                to: to.to_string(),
+                ..Default::default()
+            };
+            let result = EpochZoneService::convert_timezone(&request);
+            assert!(result.unwrap_err().contains("outside the supported range"));
+        }
+        let request = ConvertRequest {
+            datetime: Some("9999-12-31T23:00:00Z".to_string()),
+            from: Some("Asia/Tokyo".to_string()),
+            to: "UTC".to_string(),
+            ..Default::default()
+        };
+        assert!(EpochZoneService::convert_timezone(&request).is_err());
+    }
+
+    fn parse(value: &str, tz: Option<&str>) -> Result<ParseResponse, String> {
+        EpochZoneService::parse_datetime(&ParseQuery {
+            value: value.to_string(),
+            tz: tz.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn test_parse_datetime_slash_format() {
+        let resp = parse("2025/02/10 15:30", Some("Europe/Belgrade")).unwrap();
+        assert_eq!(resp.normalized, "2025-02-10T15:30:00+01:00");
+        assert_eq!(resp.pattern, "%Y/%m/%d %H:%M");
+        assert_eq!(resp.timezone, "Europe/Belgrade");
+    }
+
+    #[test]
+    fn test_parse_datetime_twelve_hour_clock() {
+        let resp = parse("10 Feb 2025 3:30 PM", None).unwrap();
+        assert_eq!(resp.normalized, "2025-02-10T15:30:00+00:00");
+        assert_eq!(resp.pattern, "%d %b %Y %I:%M %p");
+    }
+
+    #[test]
+    fn test_parse_datetime_rfc2822() {
+        let resp = parse("Mon, 10 Feb 2025 15:30:00 +0100", Some("Asia/Tokyo")).unwrap();
+        // Explicit offsets are kept as-is
+        assert_eq!(resp.normalized, "2025-02-10T15:30:00+01:00");
+        assert_eq!(resp.pattern, "rfc2822");
+        assert_eq!(resp.timestamp, 1739197800);
+    }
+
+    #[test]
+    fn test_parse_datetime_iso8601_without_seconds() {
+        let resp = parse("2025-02-10T15:30+01:00", Some("Asia/Tokyo")).unwrap();
+        assert_eq!(resp.normalized, "2025-02-10T15:30:00+01:00");
+        assert_eq!(resp.pattern, "iso8601");
+        assert_eq!(parse("2025-02-10T14:30Z", None).unwrap().timestamp, 1739197800);
+    }
+
+    #[test]
+    fn test_parse_datetime_epoch_strings() {
+        let resp = parse("1739197800", None).unwrap();
+        assert_eq!(resp.pattern, "epoch_seconds");
+        assert_eq!(resp.normalized, "2025-02-10T14:30:00+00:00");
+
+        let resp = parse("1739197800000", Some("Europe/Belgrade")).unwrap();
+        assert_eq!(resp.pattern, "epoch_milliseconds");
+        assert_eq!(resp.normalized, "2025-02-10T15:30:00+01:00");
+    }
+
+    #[test]
+    fn test_parse_datetime_date_only() {
+        let resp = parse("2025-02-10", None).unwrap();
+        assert_eq!(resp.normalized, "2025-02-10T00:00:00+00:00");
+        assert_eq!(resp.pattern, "%Y-%m-%d");
+    }
+
+    #[test]
+    fn test_parse_datetime_unrecognized() {
+        let result = parse("next tuesday", None);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Unrecognized datetime format"));
+    }
+
+    #[test]
+    fn test_convert_timezone_accepts_flexible_datetime() {
+        let request = ConvertRequest {
+            datetime: Some("10 Feb 2025 3:30 PM".to_string()),
+            from: Some("Europe/Belgrade".to_string()),
+            to: "America/New_York".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+        assert_eq!(resp.to.datetime, "2025-02-10T09:30:00-05:00");
+    }
+
+    #[test]
+    fn test_get_timezone_info_with_format() {
+        let options = TimeQuery {
+            format: Some("%Y|%Z".to_string()),
+            ..Default::default()
+        };
+        let info = EpochZoneService::get_timezone_info("UTC", &options).unwrap();
+        let formatted = info.formatted.unwrap();
+        assert!(formatted.ends_with("|UTC"));
+    }
+
+    #[test]
+    fn test_get_timezone_info_invalid_format() {
+        let options = TimeQuery {
+            format: Some("%Q".to_string()),
+            ..Default::default()
+        };
+        let result = EpochZoneService::get_timezone_info("UTC", &options);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Invalid format pattern"));
+
+        // Parses as a pattern but can't be rendered
+        let request = ConvertRequest {
+            timestamp: Some(1707580800),
+            to: "UTC".to_string(),
+            format: Some("%#z".to_string()),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request);
+        assert!(result.unwrap_err().contains("Invalid format pattern"));
+    }
+
+    #[test]
+    fn test_convert_timezone_with_format() {
+        let request = ConvertRequest {
+            datetime: Some("2025-02-10T15:30:00".to_string()),
+            from: Some("Europe/Belgrade".to_string()),
+            to: "America/New_York".to_string(),
+            format: Some("%A %d %B %Y %H:%M".to_string()),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.from.formatted.as_deref(), Some("Monday 10 February 2025 15:30"));
+        assert_eq!(resp.to.formatted.as_deref(), Some("Monday 10 February 2025 09:30"));
+
+        let request = ConvertRequest {
+            timestamp: Some(1707580800),
+            to: "Europe/Belgrade".to_string(),
+            format: Some("%H:%M %Z".to_string()),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+        assert_eq!(resp.to.formatted.as_deref(), Some("17:00 CET"));
+    }
+
+    #[test]
+    fn test_convert_timezone_rfc2822_and_http_date() {
+        let request = ConvertRequest {
+            timestamp: Some(1739197800),
+            to: "Europe/Belgrade".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(resp.to.rfc2822.as_deref(), Some("Mon, 10 Feb 2025 15:30:00 +0100"));
+        assert_eq!(resp.to.http_date.as_deref(), Some("Mon, 10 Feb 2025 14:30:00 GMT"));
+        assert_eq!(resp.from.http_date, resp.to.http_date);
+    }
+
+    #[test]
+    fn test_rfc2822_past_year_9999() {
+        // 9999-12-31T23:59:59Z is already 10000-01-01 in Kiritimati
+        let utc = DateTime::from_timestamp(MAX_SUPPORTED_TIMESTAMP, 0).unwrap();
+        let defaults = ConvertRequest::default();
+        let tz = Tz::Pacific__Kiritimati;
+        let kiritimati = EpochZoneService::build_convert_info(&utc, &tz, &defaults);
+        assert_eq!(kiritimati.rfc2822, None);
+        assert!(kiritimati.http_date.is_some());
+
+        let later = DateTime::from_timestamp(300_000_000_000, 0).unwrap();
+        let info = EpochZoneService::build_convert_info(&later, &Tz::UTC, &defaults);
+        assert_eq!((info.rfc2822, info.http_date), (None, None));
+    }
+
+    #[test]
+    fn test_convert_timezone_with_calendars() {
+        // 23:30 UTC on 2025-02-10 is already 2025-02-11 in Tokyo
+        let request = ConvertRequest {
+            timestamp: Some(1739230200),
+            to: "Asia/Tokyo".to_string(),
+            calendars: vec![CalendarSystem::Hebrew, CalendarSystem::Japanese],
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+
+        let from = resp.from.calendars.unwrap();
+        assert_eq!(from.hebrew.unwrap().day, 12);
+        let to = resp.to.calendars.unwrap();
+        assert_eq!(to.hebrew.unwrap().day, 13);
+        assert_eq!(to.japanese.unwrap().era, "Reiwa");
+        assert!(to.hijri.is_none());
+    }
+
+    #[test]
+    fn test_convert_timezone_without_calendars() {
+        let request = ConvertRequest {
+            timestamp: Some(1739230200),
+            to: "Asia/Tokyo".to_string(),
+            ..Default::default()
+        };
+        let resp = EpochZoneService::convert_timezone(&request).unwrap();
+        assert!(resp.to.calendars.is_none());
+    }
+
+    #[test]
+    fn test_get_week_info() {
+        // Wednesday 2025-01-01 belongs to ISO week 1 of 2025, which starts Monday 2024-12-30
+        let query = WeekQuery {
+            at: Some("2025-01-01T12:00:00".to_string()),
+        };
+        let week = EpochZoneService::get_week_info("Europe/Belgrade", &query).unwrap();
+
+        assert_eq!(week.iso_year, 2025);
+        assert_eq!(week.iso_week, 1);
+        assert_eq!(week.iso_week_date, "2025-W01-3");
+        assert_eq!(week.day_of_week, 3);
+        assert_eq!(week.day_name, "Wednesday");
+        assert_eq!(week.week_start, "2024-12-30T00:00:00+01:00");
+        assert_eq!(week.week_end, "2025-01-06T00:00:00+01:00");
+    }
+
+    #[test]
+    fn test_get_week_info_respects_timezone() {
+        // Sunday 23:30 UTC is already Monday (next ISO week) in Tokyo
+        let query = WeekQuery {
+            at: Some("2025-03-02T23:30:00Z".to_string()),
+        };
+        let utc = EpochZoneService::get_week_info("UTC", &query).unwrap();
+        let tokyo = EpochZoneService::get_week_info("Asia/Tokyo", &query).unwrap();
+
+        assert_eq!(utc.iso_week, 9);
+        assert_eq!(tokyo.iso_week, 10);
+        assert_eq!(tokyo.week_start, "2025-03-03T00:00:00+09:00");
+    }
+
+    #[test]
+    fn test_get_week_info_across_dst_change() {
+        // The week of 2025-03-09 in New York is only 167 hours long
+        let query = WeekQuery {
+            at: Some("2025-03-12T12:00:00".to_string()),
+        };
+        let week = EpochZoneService::get_week_info("America/New_York", &query).unwrap();
+        assert_eq!(week.week_start, "2025-03-10T00:00:00-04:00");
+
+        let query = WeekQuery {
+            at: Some("2025-03-05T12:00:00".to_string()),
+        };
+        let week = EpochZoneService::get_week_info("America/New_York", &query).unwrap();
+        assert_eq!(week.week_end_timestamp - week.week_start_timestamp, 167 * 3600);
+    }
+
+    #[test]
+    fn test_get_week_info_out_of_range() {
+        let last = NaiveDate::MAX.and_hms_opt(23, 0, 0).unwrap().and_utc();
+        for at in [
+            last.timestamp_millis().to_string(),
+            "+262142-12-31T23:00:00".to_string(),
+            "1700-01-01T00:00:00Z".to_string(),
+        ] {
+            let query = WeekQuery { at: Some(at) };
+            let result = EpochZoneService::get_week_info("Asia/Tokyo", &query);
+            assert!(result.unwrap_err().contains("outside the supported range"));
+        }
+    }
+
+    #[test]
+    fn test_get_epoch_now_units_agree() {
+        let anchor = ClockAnchor::now();
+        let now = EpochZoneService::get_epoch_now(&anchor);
+
+        assert_eq!(now.milliseconds / 1000, now.seconds);
+        assert_eq!(now.microseconds / 1000, now.milliseconds);
+        assert_eq!(now.nanoseconds / 1000, now.microseconds);
+        assert!(now.iso8601.ends_with('Z'));
+        // No clock step can have happened in the microseconds since the anchor
+        assert!(now.wall_clock_drift_ms.abs() < 1000);
+    }
+
+    fn add_one_day(mode: AddMode) -> AddResponse {
+        // New York springs forward on 2025-03-09
+        let request = AddRequest {
+            datetime: "2025-03-08T12:00:00".to_string(),
+            timezone: "America/New_York".to_string(),
+            days: 1,
+            mode,
+            ..Default::default()
+        };
+        EpochZoneService::add_duration(&request).unwrap()
+    }
+
+    #[test]
+    fn test_add_duration_wall_clock_across_dst() {
+        let resp = add_one_day(AddMode::WallClock);
+        assert_eq!(resp.result.datetime, "2025-03-09T12:00:00-04:00");
+        assert_eq!(resp.elapsed_seconds, 23 * 3600);
+    }
+
+    #[test]
+    fn test_add_duration_absolute_across_dst() {
+        let resp = add_one_day(AddMode::Absolute);
+        assert_eq!(resp.result.datetime, "2025-03-09T13:00:00-04:00");
+        assert_eq!(resp.elapsed_seconds, 24 * 3600);
+    }
+
+    #[test]
+    fn test_add_duration_wall_clock_into_gap() {
+        let mut request = AddRequest {
+            datetime: "2025-03-08T02:30:00".to_string(),
+            timezone: "America/New_York".to_string(),
+            days: 1,
+            ..Default::default()
+        };
+        assert!(EpochZoneService::add_duration(&request).is_err());
+
+        request.gap_policy = GapPolicy::ShiftForward;
+        let resp = EpochZoneService::add_duration(&request).unwrap();
+        assert_eq!(resp.result.datetime, "2025-03-09T03:00:00-04:00");
+        assert!(resp.result.was_adjusted);
     }
 
     #[test]
-    fn test_convert_timezone_with_timestamp() {
-        let request = ConvertRequest {
-            timestamp: Some(1707580800),
-            datetime: None,
-            from: None,
-            to: "America/New_York".to_string(),
+    fn test_add_duration_out_of_range() {
+        for (days, mode) in [
+            (3_000_000, AddMode::Absolute),
+            (3_000_000, AddMode::WallClock),
+            (-100_000, AddMode::Absolute),
+            (-100_000, AddMode::WallClock),
+        ] {
+            let request = AddRequest {
+                datetime: "2025-01-01T00:00:00Z".to_string(),
+                timezone: "UTC".to_string(),
+                days,
+                mode,
+                ..Default::default()
+            };
+            assert!(EpochZoneService::add_duration(&request).is_err());
+        }
+    }
+
+    #[test]
+    fn test_add_duration_negative_hours() {
+        let request = AddRequest {
+            datetime: "2025-02-10T15:30:00+01:00".to_string(),
+            timezone: "Europe/Belgrade".to_string(),
+            hours: -2,
+            minutes: -30,
+            mode: AddMode::Absolute,
+            ..Default::default()
         };
-        let result = EpochZoneService::convert_timezone(&request);
-        assert!(result.is_ok());
+        let resp = EpochZoneService::add_duration(&request).unwrap();
+        assert_eq!(resp.result.datetime, "2025-02-10T13:00:00+01:00");
+    }
 
-        let resp = result.unwrap();
-        assert_eq!(resp.from.timezone, "UTC");
-        assert_eq!(resp.to.timezone, "America/New_York");
-        assert_eq!(resp.from.timestamp, resp.to.timestamp);
+    fn recurrence_request(rrule: &str, dtstart: &str, end: &str) -> RecurrenceRequest {
+        RecurrenceRequest {
+            rrule: rrule.to_string(),
+            dtstart: dtstart.to_string(),
+            timezone: "America/New_York".to_string(),
+            end: end.to_string(),
+            ..Default::default()
+        }
     }
 
     #[test]
-    fn test_convert_timezone_with_datetime() {
-        let request = ConvertRequest {
-            timestamp: None,
-            datetime: Some("2025-02-10T15:30:00".to_string()),
-            from: Some("Europe/Belgrade".to_string()),
-            to: "America/New_York".to_string(),
+    fn test_expand_recurrence_keeps_wall_clock_across_dst() {
+        let mut request =
+            recurrence_request("FREQ=WEEKLY;BYDAY=MO", "2025-03-03T09:00:00", "2025-03-17");
+        request.to = vec!["Europe/London".to_string()];
+        let response = EpochZoneService::expand_recurrence(&request).unwrap();
+
+        assert_eq!(response.count, 2);
+        let first = &response.occurrences[0];
+        let second = &response.occurrences[1];
+        assert_eq!(first.local.datetime, "2025-03-03T09:00:00-05:00");
+        assert_eq!(second.local.datetime, "2025-03-10T09:00:00-04:00");
+        // London has not switched yet, so the gap shrinks to four hours
+        assert_eq!(first.conversions[0].datetime, "2025-03-03T14:00:00+00:00");
+        assert_eq!(second.conversions[0].datetime, "2025-03-10T13:00:00+00:00");
+    }
+
+    #[test]
+    fn test_expand_recurrence_gap_uses_offset_before_transition() {
+        let request =
+            recurrence_request("FREQ=DAILY", "2025-03-08T02:30:00", "2025-03-10T12:00:00");
+        let response = EpochZoneService::expand_recurrence(&request).unwrap();
+
+        let local: Vec<&str> = response
+            .occurrences
+            .iter()
+            .map(|o| o.local.datetime.as_str())
+            .collect();
+        assert_eq!(
+            local,
+            [
+                "2025-03-08T02:30:00-05:00",
+                "2025-03-09T03:30:00-04:00",
+                "2025-03-10T02:30:00-04:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_recurrence_window_and_count() {
+        let mut request =
+            recurrence_request("FREQ=DAILY;COUNT=5", "2025-01-01T08:00:00", "2025-12-31");
+        request.start = Some("2025-01-03".to_string());
+        let response = EpochZoneService::expand_recurrence(&request).unwrap();
+        // Two of the five occurrences fall before the window
+        assert_eq!(response.count, 3);
+        assert!(!response.truncated);
+
+        let request = recurrence_request("FREQ=DAILY", "2025-01-01T08:00:00", "2030-12-31");
+        let response = EpochZoneService::expand_recurrence(&request).unwrap();
+        assert_eq!(response.count, 1000);
+        assert!(response.truncated);
+    }
+
+    #[test]
+    fn test_expand_recurrence_until_utc() {
+        let request = recurrence_request(
+            "FREQ=DAILY;UNTIL=20250103T130000Z",
+            "2025-01-01T08:00:00",
+            "2025-12-31",
+        );
+        let response = EpochZoneService::expand_recurrence(&request).unwrap();
+        assert_eq!(response.count, 3);
+    }
+
+    #[test]
+    fn test_expand_recurrence_errors() {
+        let request = recurrence_request("FREQ=DAILY", "2025-01-01T08:00:00", "2024-12-31");
+        assert!(EpochZoneService::expand_recurrence(&request).is_err());
+
+        let mut request = recurrence_request("FREQ=DAILY", "2025-01-01T08:00:00", "2025-02-01");
+        request.to = vec!["Invalid/Zone".to_string()];
+        assert!(EpochZoneService::expand_recurrence(&request).is_err());
+
+        let request = recurrence_request("FREQ=SOMETIMES", "2025-01-01T08:00:00", "2025-02-01");
+        assert!(EpochZoneService::expand_recurrence(&request).is_err());
+    }
+
+    #[test]
+    fn test_get_holidays_day_bounds() {
+        let query = HolidayQuery {
+            year: Some(2025),
+            tz: None,
         };
-        let result = EpochZoneService::convert_timezone(&request);
-        assert!(result.is_ok());
+        let response = EpochZoneService::get_holidays("de", &query).unwrap();
+        assert_eq!(response.country, "DE");
+        assert_eq!(response.timezone, "Europe/Berlin");
 
-        let resp = result.unwrap();
-        assert_eq!(resp.from.timezone, "Europe/Belgrade");
-        assert_eq!(resp.to.timezone, "America/New_York");
-        assert_eq!(resp.from.timestamp, resp.to.timestamp);
-        // Belgrade is CET (UTC+1) in February, NY is EST (UTC-5), difference is 6 hours
-        assert!(resp.to.datetime.contains("09:30:00"));
+        let christmas = response.holidays.iter().find(|h| h.date == "2025-12-25").unwrap();
+        assert_eq!(christmas.start, "2025-12-25T00:00:00+01:00");
+        assert_eq!(christmas.start_timestamp, 1_766_617_200);
+        assert_eq!(christmas.end_timestamp - christmas.start_timestamp, 86_400);
     }
 
     #[test]
-    fn test_convert_timezone_invalid_target() {
-        let request = ConvertRequest {
-            timestamp: Some(1707580800),
-            datetime: None,
-            from: None,
-            to: "Invalid/Zone".to_string(),
+    fn test_get_holidays_custom_timezone() {
+        let query = HolidayQuery {
+            year: Some(2025),
+            tz: Some("America/Los_Angeles".to_string()),
         };
-        let result = EpochZoneService::convert_timezone(&request);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid target timezone"));
+        let response = EpochZoneService::get_holidays("US", &query).unwrap();
+        let july_4th = response.holidays.iter().find(|h| h.date == "2025-07-04").unwrap();
+        assert_eq!(july_4th.start, "2025-07-04T00:00:00-07:00");
+        assert_eq!(july_4th.day_name, "Friday");
+
+        let query = HolidayQuery {
+            year: Some(2025),
+            tz: Some("Invalid/Zone".to_string()),
+        };
+        assert!(EpochZoneService::get_holidays("US", &query).is_err());
     }
 
     #[test]
-    fn test_convert_timezone_missing_fields() {
-        let request = ConvertRequest {
-            timestamp: None,
-            datetime: None,
-            from: None,
-            to: "UTC".to_string(),
+    fn test_offset_matrix() {
+        let request = MatrixRequest {
+            timezones: vec![
+                "Asia/Kolkata".to_string(),
+                "America/New_York".to_string(),
+                "Europe/London".to_string(),
+            ],
+            // Two weeks before the US switches to DST, four before the UK does
+            at: Some("2025-02-24T12:00:00Z".to_string()),
         };
-        let result = EpochZoneService::convert_timezone(&request);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("required"));
+        let matrix = EpochZoneService::get_offset_matrix(&request).unwrap();
+
+        assert_eq!(matrix.utc_offsets, ["UTC+05:30", "UTC-05:00", "UTC+00:00"]);
+        assert_eq!(matrix.offset_seconds[0][1], -37_800);
+        assert_eq!(matrix.differences[1][0], "+10:30");
+        assert_eq!(matrix.differences[2][2], "+00:00");
+        assert!(matrix.changes_within_30_days[0][1]);
+        assert!(matrix.changes_within_30_days[1][2]);
+        // London moves on March 30th, more than 30 days out
+        assert!(!matrix.changes_within_30_days[0][2]);
+        assert!(matrix.any_changes);
     }
 
     #[test]
-    fn test_convert_timezone_both_timestamp_and_datetime() {
+    fn test_offset_matrix_same_transition_is_not_a_change() {
+        let request = MatrixRequest {
+            timezones: vec!["Europe/Berlin".to_string(), "Europe/Belgrade".to_string()],
+            at: Some("2025-03-15T00:00:00Z".to_string()),
+        };
+        let matrix = EpochZoneService::get_offset_matrix(&request).unwrap();
+        assert_eq!(matrix.offset_seconds, [[0, 0], [0, 0]]);
+        assert!(!matrix.any_changes);
+
+        let request = MatrixRequest {
+            timezones: vec!["Europe/Berlin".to_string(), "Invalid/Zone".to_string()],
+            at: None,
+        };
+        assert!(EpochZoneService::get_offset_matrix(&request).is_err());
+
+        // The last supported instant still has its look-ahead window
+        let last = NaiveDate::MAX.and_hms_opt(23, 0, 0).unwrap().and_utc();
+        let cases = [
+            ("9999-12-31T23:59:59Z".to_string(), true),
+            (last.timestamp_millis().to_string(), false),
+        ];
+        for (at, ok) in cases {
+            let request = MatrixRequest {
+                timezones: vec!["Asia/Tokyo".to_string()],
+                at: Some(at),
+            };
+            assert_eq!(EpochZoneService::get_offset_matrix(&request).is_ok(), ok);
+        }
+    }
+
+    #[test]
+    fn test_convert_timezone_business_hours() {
         let request = ConvertRequest {
-            timestamp: Some(1707580800),
-            datetime: Some("2025-02-10T15:30:00".to_string()),
-            from: Some("UTC".to_string()),
-            to: "America/New_York".to_string(),
+            // Wednesday 15:00 UTC
+            timestamp: Some(1_749_654_000),
+            to: "Asia/Tokyo".to_string(),
+            business_hours: Some(BusinessHours {
+                start: "09:00".to_string(),
+                end: "17:00".to_string(),
+                include_weekends: false,
+            }),
+            ..Default::default()
         };
-        let result = EpochZoneService::convert_timezone(&request);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not both"));
+        let response = EpochZoneService::convert_timezone(&request).unwrap();
+
+        assert_eq!(response.from.is_within_business_hours, Some(true));
+        assert_eq!(response.from.next_opening.as_deref(), Some("2025-06-12T09:00:00+00:00"));
+        // Midnight in Tokyo
+        assert_eq!(response.to.is_within_business_hours, Some(false));
+        assert_eq!(response.to.next_opening.as_deref(), Some("2025-06-12T09:00:00+09:00"));
+
+        // Closing time itself is outside business hours
+        let closing = ConvertRequest {
+            // Wednesday 17:00 UTC
+            timestamp: Some(1_749_661_200),
+            ..request
+        };
+        let response = EpochZoneService::convert_timezone(&closing).unwrap();
+        assert_eq!(response.from.is_within_business_hours, Some(false));
     }
 
     #[test]
-    fn test_get_timezone_by_coordinates_tokyo() {
-        let finder = tzf_rs::DefaultFinder::new();
-        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 35.6762, 139.6503);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().timezone, "Asia/Tokyo");
+    fn test_convert_timezone_business_hours_optional_and_validated() {
+        let mut request = ConvertRequest {
+            timestamp: Some(1_749_654_000),
+            to: "Asia/Tokyo".to_string(),
+            ..Default::default()
+        };
+        let response = EpochZoneService::convert_timezone(&request).unwrap();
+        assert!(response.to.is_within_business_hours.is_none());
+        assert!(response.to.next_opening.is_none());
+
+        request.business_hours = Some(BusinessHours {
+            start: "9".to_string(),
+            end: "17:00".to_string(),
+            include_weekends: false,
+        });
+        assert!(EpochZoneService::convert_timezone(&request).is_err());
     }
 
     #[test]
-    fn test_get_timezone_by_coordinates_new_york() {
-        let finder = tzf_rs::DefaultFinder::new();
-        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 40.7128, -74.0060);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().timezone, "America/New_York");
+    fn test_get_current_times_reports_errors_per_zone() {
+        let zones = vec![
+            "Europe/Belgrade".to_string(),
+            "Invalid/Zone".to_string(),
+            "Asia/Tokyo".to_string(),
+        ];
+        let response = EpochZoneService::get_current_times(&zones).unwrap();
+
+        assert_eq!(response.results.len(), 3);
+        assert_eq!(response.errors, 1);
+        assert_eq!(response.results[0].info.as_ref().unwrap().timezone, "Europe/Belgrade");
+        assert!(response.results[1].info.is_none());
+        assert!(response.results[1].error.as_ref().unwrap().contains("Invalid/Zone"));
+        assert!(response.results[2].error.is_none());
+
+        assert!(EpochZoneService::get_current_times(&[]).is_err());
     }
 
     #[test]
-    fn test_get_timezone_by_coordinates_london() {
-        let finder = tzf_rs::DefaultFinder::new();
-        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 51.5074, -0.1278);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().timezone, "Europe/London");
+    fn test_get_leap_seconds() {
+        let table = EpochZoneService::get_leap_seconds();
+        assert!(table.tai_minus_utc >= 37);
+        let first = &table.leap_seconds[0];
+        assert_eq!((first.date.as_str(), first.tai_minus_utc), ("1972-01-01", 10));
+        let last_known = table.leap_seconds.iter().find(|l| l.tai_minus_utc == 37).unwrap();
+        assert_eq!(last_known.date, "2017-01-01");
+        assert!(table.updated < table.expires);
     }
 
     #[test]
-    fn test_get_timezone_by_coordinates_ocean() {
-        let finder = tzf_rs::DefaultFinder::new();
-        // Middle of the Pacific Ocean
-        let result = EpochZoneService::get_timezone_by_coordinates(&finder, 0.0, -160.0);
-        // tzf-rs returns a timezone even for ocean points (nearest land timezone)
-        // so we just verify it doesn't error
-        assert!(result.is_ok());
+    fn test_get_dst_zones() {
+        let query = DstQuery {
+            at: Some("2025-07-01T12:00:00Z".to_string()),
+        };
+        let response = EpochZoneService::get_dst_zones(&query).unwrap();
+        assert_eq!(response.count, response.zones.len());
+
+        let belgrade = response
+            .zones
+            .iter()
+            .find(|z| z.timezone == "Europe/Belgrade")
+            .unwrap();
+        assert_eq!(belgrade.abbreviation, "CEST");
+        assert_eq!(belgrade.dst_offset, "+01:00");
+        assert_eq!(belgrade.ends_at.as_deref(), Some("2025-10-26T02:00:00+01:00"));
+        assert_eq!(belgrade.ends_at_timestamp, Some(1_761_440_400));
+        assert_eq!(belgrade.ends_in.as_deref(), Some("3 months, 3 weeks, 5 days, 13 hours"));
+
+        // Southern hemisphere and no-DST zones are absent in July
+        assert!(response.zones.iter().all(|z| z.timezone != "Australia/Sydney"));
+        assert!(response.zones.iter().all(|z| z.timezone != "Asia/Tokyo"));
     }
 
     #[test]
-    fn test_convert_timezone_datetime_without_from() {
+    fn test_get_dst_status() {
+        let status = |zone: &str, timestamp: &str| {
+            let query = DstAtQuery {
+                timestamp: Some(timestamp.to_string()),
+            };
+            EpochZoneService::get_dst_status(zone, &query).unwrap()
+        };
+        let summer = status("Europe/Belgrade", "1751371200");
+        assert!(summer.is_dst);
+        assert_eq!(summer.at, "2025-07-01T14:00:00+02:00");
+        assert_eq!(summer.abbreviation, "CEST");
+        assert_eq!(summer.utc_offset, "UTC+02:00");
+        assert_eq!(summer.standard_offset, "+01:00");
+        assert_eq!(summer.dst_offset, "+01:00");
+
+        // Wall-clock input is read in the zone: Belgrade skipped DST in the 1970s
+        let winter = status("Europe/Belgrade", "1975-07-01T12:00");
+        assert!(!winter.is_dst);
+        assert_eq!(winter.dst_offset, "+00:00");
+        let sydney = status("Australia/Sydney", "2025-01-15T12:00:00Z");
+        assert_eq!((sydney.is_dst, sydney.abbreviation.as_str()), (true, "AEDT"));
+        assert!(EpochZoneService::get_dst_status("Mars/Base", &DstAtQuery::default()).is_err());
+    }
+
+    #[test]
+    fn test_get_next_dst_change() {
+        let next = |zone: &str, at: &str| {
+            let query = DstQuery {
+                at: Some(at.to_string()),
+            };
+            EpochZoneService::get_next_dst_change(zone, &query).unwrap()
+        };
+        let spring = next("America/New_York", "2025-01-01T00:00:00Z");
+        assert_eq!(spring.clocks, Some(ClockChange::Forward));
+        assert_eq!(spring.seconds_until, Some(5_814_000));
+        let transition = spring.transition.unwrap();
+        assert_eq!(transition.at, "2025-03-09T07:00:00+00:00");
+        assert_eq!(transition.after.abbreviation, "EDT");
+
+        let autumn = next("Europe/Belgrade", "2025-07-01T12:00");
+        assert_eq!(autumn.clocks, Some(ClockChange::Back));
+        assert_eq!(autumn.transition.unwrap().change_seconds, -3600);
+
+        let tokyo = next("Asia/Tokyo", "2025-01-01T00:00:00Z");
+        assert!(tokyo.transition.is_none() && tokyo.clocks.is_none());
+    }
+
+    #[test]
+    fn test_next_transition() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let from = DateTime::from_timestamp(1_735_689_600, 0).unwrap(); // 2025-01-01
+        let next = EpochZoneService::next_transition(&tz, &from).unwrap();
+        assert_eq!(next.to_rfc3339(), "2025-03-09T07:00:00+00:00");
+
+        let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+        assert!(EpochZoneService::next_transition(&tokyo, &from).is_none());
+    }
+
+    #[test]
+    fn test_get_offsets() {
+        let query = OffsetsQuery {
+            at: Some("2025-07-01T12:00:00Z".to_string()),
+        };
+        let response = EpochZoneService::get_offsets(&query).unwrap();
+        assert!(response
+            .offsets
+            .windows(2)
+            .all(|w| w[0].offset_seconds < w[1].offset_seconds));
+
+        let india = response.offsets.iter().find(|o| o.utc_offset == "UTC+05:30").unwrap();
+        assert!(!india.dst_only);
+        assert!(india.representative_zones.contains(&"Asia/Kolkata".to_string()));
+        assert!(india.abbreviations.contains(&"IST".to_string()));
+
+        // In January only South Australia is on +10:30, and only because of DST
+        let query = OffsetsQuery {
+            at: Some("2025-01-15T00:00:00Z".to_string()),
+        };
+        let response = EpochZoneService::get_offsets(&query).unwrap();
+        let adelaide = response.offsets.iter().find(|o| o.utc_offset == "UTC+10:30").unwrap();
+        assert!(adelaide.dst_only);
+        assert_eq!(adelaide.representative_zones[0], "Australia/Adelaide");
+    }
+
+    #[test]
+    fn test_search_timezones_ranking() {
+        let query = TimezoneSearchQuery {
+            q: Some("bel".to_string()),
+            ..Default::default()
+        };
+        let results = EpochZoneService::search_timezones(&query).unwrap();
+        assert_eq!(results[0].name, "America/Belem");
+        assert_eq!(results[0].matched, Some(MatchKind::Prefix));
+        let belgrade = results.iter().find(|r| r.name == "Europe/Belgrade").unwrap();
+        assert_eq!(belgrade.country_name.as_deref(), Some("Serbia"));
+        assert!(results.windows(2).all(|w| w[0].matched <= w[1].matched));
+
+        let query = TimezoneSearchQuery {
+            q: Some("new york".to_string()),
+            ..Default::default()
+        };
+        let results = EpochZoneService::search_timezones(&query).unwrap();
+        assert_eq!(results[0].name, "America/New_York");
+        assert_eq!(results[0].matched, Some(MatchKind::Exact));
+    }
+
+    #[test]
+    fn test_search_timezones_filters() {
+        let query = TimezoneSearchQuery {
+            q: Some("bel".to_string()),
+            country: Some("rs".to_string()),
+            region: Some("Europe".to_string()),
+            limit: None,
+        };
+        let results = EpochZoneService::search_timezones(&query).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Europe/Belgrade");
+
+        // Country-name matches rank after name matches
+        let query = TimezoneSearchQuery {
+            q: Some("serbia".to_string()),
+            ..Default::default()
+        };
+        let results = EpochZoneService::search_timezones(&query).unwrap();
+        assert_eq!(results[0].name, "Europe/Belgrade");
+        assert_eq!(results[0].matched, Some(MatchKind::Country));
+
+        let query = TimezoneSearchQuery {
+            region: Some("Antarctica".to_string()),
+            limit: Some(500),
+            ..Default::default()
+        };
+        assert!(EpochZoneService::search_timezones(&query).is_err());
+    }
+
+    fn autocomplete(q: &str) -> Vec<AutocompleteResult> {
+        let index = EpochZoneService::build_search_index();
+        let query = AutocompleteQuery {
+            q: q.to_string(),
+            limit: Some(5),
+        };
+        EpochZoneService::autocomplete(&index, &query).unwrap()
+    }
+
+    #[test]
+    fn test_autocomplete_typos_and_local_names() {
+        let results = autocomplete("New Yrok");
+        assert_eq!(results[0].name, "America/New_York");
+        assert!(results[0].score < 1.0);
+
+        let results = autocomplete("Beograd");
+        assert_eq!(results[0].name, "Europe/Belgrade");
+        assert_eq!(results[0].matched, "beograd");
+
+        let results = autocomplete("zurich");
+        assert_eq!(results[0].name, "Europe/Zurich");
+        assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_autocomplete_prefix_and_validation() {
+        let results = autocomplete("tok");
+        assert_eq!(results[0].name, "Asia/Tokyo");
+        assert!(results[0].score >= 0.9);
+
+        let index = EpochZoneService::build_search_index();
+        let query = AutocompleteQuery {
+            q: "  ".to_string(),
+            limit: None,
+        };
+        assert!(EpochZoneService::autocomplete(&index, &query).is_err());
+    }
+
+    #[test]
+    fn test_list_timezones_grouped_by_region() {
+        let query = TimezonesQuery {
+            group_by: Some(TimezoneGrouping::Region),
+            include: None,
+            lang: None,
+        };
+        let Ok(TimezonesResponse::Grouped(groups)) = EpochZoneService::list_timezones(&query)
+        else {
+            panic!("expected grouped list");
+        };
+        assert!(groups["Europe"].iter().any(|z| z.name == "Europe/Belgrade"));
+        assert!(groups["America"].iter().any(|z| z.name == "America/Argentina/Salta"));
+        assert!(groups["Other"].iter().any(|z| z.name == "UTC"));
+        let total: usize = groups.values().map(Vec::len).sum();
+        assert_eq!(total, TZ_VARIANTS.len());
+    }
+
+    #[test]
+    fn test_list_timezones_include_fields() {
+        let query = TimezonesQuery {
+            group_by: None,
+            include: Some("offset, dst,country".to_string()),
+            lang: None,
+        };
+        let Ok(TimezonesResponse::List(list)) = EpochZoneService::list_timezones(&query) else {
+            panic!("expected flat list");
+        };
+        let kolkata = list.iter().find(|z| z.name == "Asia/Kolkata").unwrap();
+        assert_eq!(kolkata.utc_offset.as_deref(), Some("UTC+05:30"));
+        assert_eq!(kolkata.offset_seconds, Some(19800));
+        assert_eq!(kolkata.is_dst, Some(false));
+        assert_eq!(kolkata.country_codes.as_deref(), Some(&["IN".to_string()][..]));
+        let utc = list.iter().find(|z| z.name == "UTC").unwrap();
+        assert_eq!(utc.country_codes.as_deref(), Some(&[][..]));
+
+        let query = TimezonesQuery {
+            group_by: None,
+            include: Some("offset,population".to_string()),
+            lang: None,
+        };
+        assert!(EpochZoneService::list_timezones(&query).is_err());
+    }
+
+    #[test]
+    fn test_list_timezones_localized() {
+        let query = TimezonesQuery {
+            group_by: None,
+            include: None,
+            lang: Some("fr".to_string()),
+        };
+        let Ok(TimezonesResponse::List(list)) = EpochZoneService::list_timezones(&query) else {
+            panic!("expected flat list");
+        };
+        let paris = list.iter().find(|z| z.name == "Europe/Paris").unwrap();
+        assert_eq!(paris.display_name, "Heure d’Europe centrale – Paris");
+        // Zones without CLDR names keep the default display name
+        let etc = list.iter().find(|z| z.name == "Etc/GMT+2").unwrap();
+        assert_eq!(etc.display_name, "Etc/GMT+2");
+
+        let query = TimezonesQuery {
+            lang: Some("xx".to_string()),
+            ..query
+        };
+        assert!(EpochZoneService::list_timezones(&query).is_err());
+    }
+
+    #[test]
+    fn test_timezone_info_reports_canonical_name() {
+        let info =
+            EpochZoneService::get_timezone_info("Asia/Calcutta", &TimeQuery::default()).unwrap();
+        assert_eq!(info.timezone, "Asia/Calcutta");
+        assert_eq!(info.canonical, "Asia/Kolkata");
+        assert!(!info.is_canonical);
+
+        let info =
+            EpochZoneService::get_timezone_info("Asia/Kolkata", &TimeQuery::default()).unwrap();
+        assert_eq!(info.canonical, "Asia/Kolkata");
+        assert!(info.is_canonical);
+    }
+
+    #[test]
+    fn test_aliases_are_accepted_everywhere() {
+        let aliases = EpochZoneService::get_aliases();
+        assert!(aliases
+            .iter()
+            .any(|a| a.alias == "Asia/Saigon" && a.canonical == "Asia/Ho_Chi_Minh"));
+        for alias in &aliases {
+            assert!(
+                EpochZoneService::is_valid_timezone(&alias.alias),
+                "{} is not accepted",
+                alias.alias
+            );
+            assert!(EpochZoneService::is_valid_timezone(&alias.canonical));
+        }
+    }
+
+    #[test]
+    fn test_lookup_abbreviation() {
+        let ist = EpochZoneService::lookup_abbreviation("ist").unwrap();
+        assert!(ist.ambiguous);
+        assert!(ist.candidates.iter().any(|c| c.timezone == "Asia/Kolkata"));
+        assert!(ist.candidates.iter().any(|c| c.timezone == "Asia/Jerusalem"));
+        assert!(ist.offsets.contains(&"UTC+05:30".to_string()));
+
+        let cest = EpochZoneService::lookup_abbreviation("CEST").unwrap();
+        assert!(!cest.ambiguous);
+        assert_eq!(cest.offsets, ["UTC+02:00"]);
+        assert!(cest.candidates.iter().all(|c| c.is_dst));
+        assert!(cest.candidates.iter().any(|c| c.timezone == "Europe/Belgrade"));
+        // Links such as Europe/Ljubljana are not listed separately
+        assert!(cest.candidates.iter().all(|c| !zones::is_link(&c.timezone)));
+
+        assert!(EpochZoneService::lookup_abbreviation("XYZT").is_err());
+    }
+
+    #[test]
+    fn test_windows_mapping_both_directions() {
+        let mapping = EpochZoneService::windows_to_iana("India Standard Time").unwrap();
+        assert_eq!(mapping.timezone, "Asia/Kolkata");
+
+        let mapping = EpochZoneService::iana_to_windows("US/Pacific").unwrap();
+        assert_eq!(mapping.windows_id, "Pacific Standard Time");
+        assert_eq!(mapping.timezone, "America/Los_Angeles");
+        assert!(mapping.zones.contains(&"America/Vancouver".to_string()));
+
+        assert!(EpochZoneService::windows_to_iana("Mars Standard Time").is_err());
+        assert!(EpochZoneService::iana_to_windows("Mars/Base").is_err());
+    }
+
+    #[test]
+    fn test_windows_zones_are_valid_timezones() {
+        let mapping = EpochZoneService::windows_to_iana("UTC").unwrap();
+        assert_eq!(mapping.timezone, "Etc/UTC");
+        for tz in TZ_VARIANTS.iter() {
+            if let Some(id) = zones::windows_id(tz.name()) {
+                let mapping = EpochZoneService::windows_to_iana(id).unwrap();
+                for zone in &mapping.zones {
+                    assert!(EpochZoneService::is_valid_timezone(zone), "{}", zone);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rails_names_accepted_as_input() {
+        let info = EpochZoneService::get_timezone_info(
+            "Eastern Time (US & Canada)",
+            &TimeQuery::default(),
+        )
+        .unwrap();
+        assert_eq!(info.canonical, "America/New_York");
+        assert!(!info.is_canonical);
+
         let request = ConvertRequest {
-            timestamp: None,
-            datetime: Some("2025-02-10T15:30:00".to_string()),
-            from: None,
-            to: "America/New_York".to_string(),
+            timestamp: Some(1_700_000_000),
+            to: "Tokyo".to_string(),
+            ..Default::default()
         };
-        let result = EpochZoneService::convert_timezone(&request);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("'from' timezone is required"));
+        let result = EpochZoneService::convert_timezone(&request).unwrap();
+        assert_eq!(result.to.timezone, "Asia/Tokyo");
+
+        let names = EpochZoneService::get_rails_names();
+        assert!(names
+            .iter()
+            .any(|n| n.alias == "Kyiv" && n.canonical == "Europe/Kyiv"));
+    }
+
+    #[test]
+    fn test_convert_with_fixed_offsets() {
+        let request = ConvertRequest {
+            datetime: Some("2025-02-10T12:00:00".to_string()),
+            from: Some("UTC-7".to_string()),
+            to: "+05:30".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request).unwrap();
+        assert_eq!(result.from.datetime, "2025-02-10T12:00:00-07:00");
+        assert_eq!(result.to.datetime, "2025-02-11T00:30:00+05:30");
+        assert_eq!(result.to.timezone, "UTC+05:30");
+        assert!(!result.to.is_dst);
+
+        // "GMT+2" is two hours ahead of UTC, unlike the Etc/GMT+2 zone
+        let request = ConvertRequest {
+            timestamp: Some(1_700_000_000),
+            to: "GMT+2".to_string(),
+            ..Default::default()
+        };
+        let result = EpochZoneService::convert_timezone(&request).unwrap();
+        assert_eq!(result.to.utc_offset, "UTC+02:00");
+
+        for invalid in ["+25:00", "UTC+5:60", "GMT+", "+123456"] {
+            let request = ConvertRequest {
+                timestamp: Some(0),
+                to: invalid.to_string(),
+                ..Default::default()
+            };
+            assert!(EpochZoneService::convert_timezone(&request).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_get_dst_calendar() {
+        let query = DstCalendarQuery { years: Some(2) };
+        let ics = EpochZoneService::get_dst_calendar("Europe/Belgrade", &query).unwrap();
+        let unfolded = ics.replace("\r\n ", "");
+        assert_eq!(unfolded.matches("BEGIN:VEVENT").count(), 4);
+        assert!(unfolded.contains("SUMMARY:Clocks go forward 1 hour (Europe/Belgrade)"));
+        assert!(unfolded.contains(
+            "DESCRIPTION:At 02:00 local time clocks go forward to 03:00. \
+             CET (UTC+01:00) becomes CEST (UTC+02:00)."
+        ));
+        assert!(unfolded.contains("At 03:00 local time clocks go back to 02:00."));
+
+        let tokyo = EpochZoneService::get_dst_calendar("Asia/Tokyo", &query).unwrap();
+        assert!(!tokyo.contains("BEGIN:VEVENT"));
+        let query = DstCalendarQuery { years: Some(0) };
+        assert!(EpochZoneService::get_dst_calendar("Europe/Belgrade", &query).is_err());
+    }
+
+    #[test]
+    fn test_get_tzif() {
+        let data = EpochZoneService::get_tzif("Europe/Belgrade").unwrap();
+        assert_eq!(&data[..5], b"TZif2");
+        // Version 1 block: 32-bit transitions only
+        let count = |at: usize| u32::from_be_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+        let (timecnt, typecnt, charcnt) = (count(32), count(36), count(40));
+        let v2 = 44 + timecnt * 5 + typecnt * 6 + charcnt;
+        let v2_timecnt = count(v2 + 32);
+        assert!(v2_timecnt > 100);
+
+        // The 2025 spring-forward transition is listed
+        let spring = 1_743_296_400_i64.to_be_bytes();
+        let times = &data[v2 + 44..v2 + 44 + v2_timecnt * 8];
+        assert!(times.chunks(8).any(|t| t == spring));
+
+        assert!(EpochZoneService::get_tzif("Mars/Base").is_err());
+    }
+
+    #[test]
+    fn test_dump_transitions() {
+        let query = DumpQuery {
+            from: Some(2025),
+            to: Some(2025),
+        };
+        let dump = EpochZoneService::dump_transitions("America/New_York", &query).unwrap();
+        assert_eq!(dump.count, 2);
+        let spring = &dump.transitions[0];
+        assert_eq!(spring.at, "2025-03-09T07:00:00+00:00");
+        assert_eq!(spring.before.local, "2025-03-09T01:59:59-05:00");
+        assert_eq!(spring.after.local, "2025-03-09T03:00:00-04:00");
+        assert_eq!(spring.after.abbreviation, "EDT");
+        assert!(spring.after.is_dst);
+        assert_eq!(spring.change_seconds, 3600);
+        assert_eq!(dump.transitions[1].change_seconds, -3600);
+
+        // Kolkata has not changed offset since 1945
+        let dump =
+            EpochZoneService::dump_transitions("Asia/Kolkata", &DumpQuery::default()).unwrap();
+        assert_eq!(dump.count, 0);
+
+        let query = DumpQuery {
+            from: Some(2040),
+            to: Some(1970),
+        };
+        assert!(EpochZoneService::dump_transitions("UTC", &query).is_err());
+    }
+
+    #[test]
+    fn test_tzdata_info() {
+        let info = EpochZoneService::get_tzdata_info(&tzf_rs::DefaultFinder::new());
+        // Release names are a year and a letter, e.g. "2025b"
+        assert_eq!(info.tzdata_version.len(), 5);
+        assert!(info.tzdata_version.starts_with("20"));
+        assert!(info.zone_count > 300);
+        assert!(info.link_count > 200);
+    }
+
+    #[test]
+    fn test_export_timezones() {
+        let export = EpochZoneService::export_timezones();
+        assert_eq!(export.to_year - export.from_year, 20);
+        let belgrade = export.zones.iter().find(|z| z.name == "Europe/Belgrade").unwrap();
+        assert_eq!(belgrade.country_code.as_deref(), Some("RS"));
+        // Two transitions a year
+        assert_eq!(belgrade.transitions.len(), 42);
+        assert!(belgrade.transitions.windows(2).all(|w| w[0].is_dst != w[1].is_dst));
+        assert!(export.zones.iter().all(|z| !zones::is_link(&z.name)));
+        assert!(export.aliases.iter().any(|a| a.alias == "Europe/Kiev"));
     }
 }
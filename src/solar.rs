@@ -0,0 +1,173 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Sun position from the sunrise equation (accurate to about a minute away from the poles)
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+// Sun altitude at sunrise and sunset: refraction plus the sun's apparent radius
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+
+// Sun altitudes bounding civil, nautical, and astronomical twilight
+pub const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+pub const NAUTICAL_TWILIGHT_ALTITUDE: f64 = -12.0;
+pub const ASTRONOMICAL_TWILIGHT_ALTITUDE: f64 = -18.0;
+
+const J2000: f64 = 2_451_545.0;
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+const OBLIQUITY: f64 = 23.4397;
+
+// When the sun crosses an altitude on a given day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crossing {
+    // Rises through the altitude, then sets through it
+    Times(DateTime<Utc>, DateTime<Utc>),
+    // Stays above all day (polar day for sunrise)
+    AlwaysAbove,
+    // Stays below all day (polar night for sunrise)
+    AlwaysBelow,
+}
+
+// The sun's path for one date at one longitude
+pub struct SolarDay {
+    // Julian date of solar noon
+    transit: f64,
+    // Declination in degrees
+    declination: f64,
+}
+
+impl SolarDay {
+    // `lng` is east-positive degrees
+    pub fn new(date: NaiveDate, lng: f64) -> Self {
+        let j2000 = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+        let days = (date - j2000).num_days() as f64;
+        let mean_noon = days - lng / 360.0;
+        let anomaly = (357.5291 + 0.985_600_28 * mean_noon).rem_euclid(360.0);
+        let m = anomaly.to_radians();
+        let center = 1.9148 * m.sin() + 0.0200 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+        let ecliptic = (anomaly + center + 180.0 + 102.9372)
+            .rem_euclid(360.0)
+            .to_radians();
+        let transit = J2000 + mean_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic).sin();
+        let declination = (ecliptic.sin() * OBLIQUITY.to_radians().sin())
+            .asin()
+            .to_degrees();
+        SolarDay {
+            transit,
+            declination,
+        }
+    }
+
+    // When the sun is highest, or None if that falls outside chrono's range
+    pub fn noon(&self) -> Option<DateTime<Utc>> {
+        from_julian(self.transit)
+    }
+
+    // When the sun's center crosses `altitude` degrees at latitude `lat`, or None if a
+    // crossing falls outside chrono's range
+    pub fn crossing(&self, lat: f64, altitude: f64) -> Option<Crossing> {
+        let (phi, delta) = (lat.to_radians(), self.declination.to_radians());
+        let cos_hour_angle =
+            (altitude.to_radians().sin() - phi.sin() * delta.sin()) / (phi.cos() * delta.cos());
+        if cos_hour_angle > 1.0 {
+            return Some(Crossing::AlwaysBelow);
+        }
+        if cos_hour_angle < -1.0 {
+            return Some(Crossing::AlwaysAbove);
+        }
+        let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+        Some(Crossing::Times(
+            from_julian(self.transit - half_day)?,
+            from_julian(self.transit + half_day)?,
+        ))
+    }
+}
+
+fn from_julian(jd: f64) -> Option<DateTime<Utc>> {
+    let seconds = ((jd - UNIX_EPOCH_JD) * 86_400.0).round() as i64;
+    DateTime::from_timestamp(seconds, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn assert_close(actual: DateTime<Utc>, expected: DateTime<Utc>) {
+        let minutes = (actual - expected).num_minutes().abs();
+        assert!(
+            minutes <= 2,
+            "{} is not within 2 minutes of {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_belgrade_summer_solstice() {
+        let day = SolarDay::new(date(2025, 6, 21), 20.4489);
+        let Crossing::Times(rise, set) = day.crossing(44.7866, SUNRISE_ALTITUDE).unwrap() else {
+            panic!("expected sunrise and sunset");
+        };
+        // 04:51 and 20:29 CEST
+        assert_close(rise, Utc.with_ymd_and_hms(2025, 6, 21, 2, 51, 0).unwrap());
+        assert_close(set, Utc.with_ymd_and_hms(2025, 6, 21, 18, 29, 0).unwrap());
+        assert_close(
+            day.noon().unwrap(),
+            Utc.with_ymd_and_hms(2025, 6, 21, 10, 40, 0).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_polar_day_and_night() {
+        // Tromsø
+        let summer = SolarDay::new(date(2025, 6, 21), 18.96);
+        assert_eq!(
+            summer.crossing(69.65, SUNRISE_ALTITUDE),
+            Some(Crossing::AlwaysAbove)
+        );
+        let winter = SolarDay::new(date(2025, 12, 21), 18.96);
+        assert_eq!(
+            winter.crossing(69.65, SUNRISE_ALTITUDE),
+            Some(Crossing::AlwaysBelow)
+        );
+    }
+
+    #[test]
+    fn test_twilight_brackets_sunrise() {
+        let day = SolarDay::new(date(2025, 3, 20), 20.4489);
+        let times = |altitude| match day.crossing(44.7866, altitude).unwrap() {
+            Crossing::Times(dawn, dusk) => (dawn, dusk),
+            other => panic!("unexpected {:?}", other),
+        };
+        let (sunrise, sunset) = times(SUNRISE_ALTITUDE);
+        let (civil_dawn, civil_dusk) = times(CIVIL_TWILIGHT_ALTITUDE);
+        let (nautical_dawn, _) = times(NAUTICAL_TWILIGHT_ALTITUDE);
+        let (astronomical_dawn, _) = times(ASTRONOMICAL_TWILIGHT_ALTITUDE);
+        assert!(astronomical_dawn < nautical_dawn && nautical_dawn < civil_dawn);
+        assert!(civil_dawn < sunrise && sunset < civil_dusk);
+        // Civil twilight lasts about half an hour at the equinox in mid-latitudes
+        assert!((25..35).contains(&(sunrise - civil_dawn).num_minutes()));
+        // At 60°N in midsummer the sun never sinks below -12°: nautical dusk never comes
+        let summer = SolarDay::new(date(2025, 6, 21), 18.96);
+        let white_night = summer.crossing(60.0, NAUTICAL_TWILIGHT_ALTITUDE);
+        assert_eq!(white_night, Some(Crossing::AlwaysAbove));
+    }
+}
@@ -0,0 +1,395 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Saved teams: members with home timezones and working hours, so the meeting planner and
+// /api/now can be given a team name instead of a list of zones. A team belongs to the
+// calling key's organization, shared by all of its keys, or to the key alone.
+
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::auth::models::AuthenticatedKey;
+use crate::models::{Team, TeamMember, TeamMemberRequest};
+use crate::{planning, zones};
+
+// Most teams one owner may keep, and most members in a team (the meeting planner's limit)
+pub const MAX_TEAMS: u64 = 100;
+pub const MAX_MEMBERS: usize = 50;
+
+const MAX_TEAM_NAME_LENGTH: usize = 64;
+const MAX_MEMBER_NAME_LENGTH: usize = 100;
+
+// Columns read by member_from_row, in order
+const MEMBER_COLUMNS: &str = "id, name, timezone, work_start, work_end, include_weekends";
+
+// Who a key's teams belong to: "org:<id>" for keys in an organization, else "key:<id>"
+pub fn owner(key: &AuthenticatedKey) -> String {
+    match &key.org_id {
+        Some(org_id) => format!("org:{}", org_id),
+        None => format!("key:{}", key.id),
+    }
+}
+
+// Team names end up in paths and query strings, so they are kept to URL-safe characters
+pub fn validate_team_name(name: &str) -> Result<(), String> {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    if name.is_empty() || name.len() > MAX_TEAM_NAME_LENGTH || !name.chars().all(valid_char) {
+        return Err(format!(
+            "Invalid team name '{}' (1 to {} letters, digits, '-', '_', or '.')",
+            name, MAX_TEAM_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+// Check a member's fields, storing the timezone under its canonical name
+pub fn normalize_member(mut member: TeamMemberRequest) -> Result<TeamMemberRequest, String> {
+    member.name = member.name.trim().to_string();
+    if member.name.is_empty() || member.name.chars().count() > MAX_MEMBER_NAME_LENGTH {
+        return Err(format!(
+            "Member name must be 1 to {} characters",
+            MAX_MEMBER_NAME_LENGTH
+        ));
+    }
+    let tz = zones::parse_timezone(&member.timezone)
+        .ok_or_else(|| zones::invalid_timezone(&member.timezone))?;
+    member.timezone = zones::canonical_name(tz.name()).to_string();
+    planning::parse_time(&member.work_start, "work_start")?;
+    planning::parse_time(&member.work_end, "work_end")?;
+    Ok(member)
+}
+
+pub async fn team_count(db: &Connection, owner: String) -> Result<u64, String> {
+    db.call(move |conn| {
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM teams WHERE owner = ?1",
+            [owner],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    })
+    .await
+    .map_err(|e| format!("Failed to count teams: {}", e))
+}
+
+// Create a team with its first members, which should already have passed
+// normalize_member. Returns None when the owner already has a team of that name.
+pub async fn create_team(
+    db: &Connection,
+    owner: String,
+    name: String,
+    members: Vec<TeamMemberRequest>,
+) -> Result<Option<Team>, String> {
+    let id = Uuid::new_v4().to_string();
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        let created_at: Option<String> = tx
+            .query_row(
+                "INSERT INTO teams (id, owner, name) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (owner, name) DO NOTHING
+                 RETURNING created_at",
+                rusqlite::params![id, owner, name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(created_at) = created_at else {
+            return Ok(None);
+        };
+        let members = members
+            .into_iter()
+            .map(|member| insert_member(&tx, &id, member))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(Some(Team {
+            id,
+            name,
+            created_at,
+            members,
+        }))
+    })
+    .await
+    .map_err(|e| format!("Failed to create team: {}", e))
+}
+
+pub async fn list_teams(db: &Connection, owner: String) -> Result<Vec<Team>, String> {
+    db.call(move |conn| {
+        let teams = conn
+            .prepare("SELECT id, name, created_at FROM teams WHERE owner = ?1 ORDER BY name")?
+            .query_map([owner], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<(String, String, String)>, _>>()?;
+        let teams = teams
+            .into_iter()
+            .map(|(id, name, created_at)| {
+                Ok(Team {
+                    members: team_members(conn, &id)?,
+                    id,
+                    name,
+                    created_at,
+                })
+            })
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(teams)
+    })
+    .await
+    .map_err(|e| format!("Failed to list teams: {}", e))
+}
+
+// One of the owner's teams by name, ignoring case
+pub async fn get_team(
+    db: &Connection,
+    owner: String,
+    name: String,
+) -> Result<Option<Team>, String> {
+    db.call(move |conn| {
+        let team: Option<(String, String, String)> = conn
+            .query_row(
+                "SELECT id, name, created_at FROM teams WHERE owner = ?1 AND name = ?2",
+                rusqlite::params![owner, name],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((id, name, created_at)) = team else {
+            return Ok(None);
+        };
+        Ok(Some(Team {
+            members: team_members(conn, &id)?,
+            id,
+            name,
+            created_at,
+        }))
+    })
+    .await
+    .map_err(|e| format!("Failed to read team: {}", e))
+}
+
+pub async fn delete_team(db: &Connection, owner: String, name: String) -> Result<bool, String> {
+    db.call(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM team_members
+             WHERE team_id IN (SELECT id FROM teams WHERE owner = ?1 AND name = ?2)",
+            rusqlite::params![owner, name],
+        )?;
+        let deleted = tx.execute(
+            "DELETE FROM teams WHERE owner = ?1 AND name = ?2",
+            rusqlite::params![owner, name],
+        )?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to delete team: {}", e))
+}
+
+// Delete every team of an owner, for keys and organizations that are going away
+pub(crate) fn delete_owner_teams(conn: &rusqlite::Connection, owner: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM team_members WHERE team_id IN (SELECT id FROM teams WHERE owner = ?1)",
+        [owner],
+    )?;
+    conn.execute("DELETE FROM teams WHERE owner = ?1", [owner])?;
+    Ok(())
+}
+
+pub async fn add_member(
+    db: &Connection,
+    team_id: String,
+    member: TeamMemberRequest,
+) -> Result<TeamMember, String> {
+    db.call(move |conn| Ok(insert_member(conn, &team_id, member)?))
+        .await
+        .map_err(|e| format!("Failed to add team member: {}", e))
+}
+
+// Replace a member's details. Returns None for members not in the team.
+pub async fn update_member(
+    db: &Connection,
+    team_id: String,
+    member_id: String,
+    member: TeamMemberRequest,
+) -> Result<Option<TeamMember>, String> {
+    db.call(move |conn| {
+        let updated = conn
+            .query_row(
+                &format!(
+                    "UPDATE team_members
+                     SET name = ?3, timezone = ?4, work_start = ?5, work_end = ?6,
+                         include_weekends = ?7
+                     WHERE id = ?1 AND team_id = ?2
+                     RETURNING {}",
+                    MEMBER_COLUMNS
+                ),
+                rusqlite::params![
+                    member_id,
+                    team_id,
+                    member.name,
+                    member.timezone,
+                    member.work_start,
+                    member.work_end,
+                    member.include_weekends
+                ],
+                member_from_row,
+            )
+            .optional()?;
+        Ok(updated)
+    })
+    .await
+    .map_err(|e| format!("Failed to update team member: {}", e))
+}
+
+pub async fn delete_member(
+    db: &Connection,
+    team_id: String,
+    member_id: String,
+) -> Result<bool, String> {
+    db.call(move |conn| {
+        let deleted = conn.execute(
+            "DELETE FROM team_members WHERE id = ?1 AND team_id = ?2",
+            rusqlite::params![member_id, team_id],
+        )?;
+        Ok(deleted > 0)
+    })
+    .await
+    .map_err(|e| format!("Failed to remove team member: {}", e))
+}
+
+fn insert_member(
+    conn: &rusqlite::Connection,
+    team_id: &str,
+    member: TeamMemberRequest,
+) -> rusqlite::Result<TeamMember> {
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO team_members
+            (id, team_id, name, timezone, work_start, work_end, include_weekends)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            id,
+            team_id,
+            member.name,
+            member.timezone,
+            member.work_start,
+            member.work_end,
+            member.include_weekends
+        ],
+    )?;
+    Ok(TeamMember {
+        id,
+        name: member.name,
+        timezone: member.timezone,
+        work_start: member.work_start,
+        work_end: member.work_end,
+        include_weekends: member.include_weekends,
+    })
+}
+
+// A team's members in the order they were added
+fn team_members(conn: &rusqlite::Connection, team_id: &str) -> rusqlite::Result<Vec<TeamMember>> {
+    conn.prepare(&format!(
+        "SELECT {} FROM team_members WHERE team_id = ?1 ORDER BY created_at, rowid",
+        MEMBER_COLUMNS
+    ))?
+    .query_map([team_id], member_from_row)?
+    .collect()
+}
+
+fn member_from_row(row: &rusqlite::Row) -> rusqlite::Result<TeamMember> {
+    Ok(TeamMember {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        timezone: row.get(2)?,
+        work_start: row.get(3)?,
+        work_end: row.get(4)?,
+        include_weekends: row.get(5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_db;
+
+    fn member(name: &str, timezone: &str) -> TeamMemberRequest {
+        TeamMemberRequest {
+            name: name.to_string(),
+            timezone: timezone.to_string(),
+            work_start: "09:00".to_string(),
+            work_end: "17:00".to_string(),
+            include_weekends: false,
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(validate_team_name("engineering").is_ok());
+        assert!(validate_team_name("eng-ops_2.0").is_ok());
+        assert!(validate_team_name("").is_err());
+        assert!(validate_team_name("has space").is_err());
+        assert!(validate_team_name("a/b").is_err());
+
+        let normalized = normalize_member(member(" Ana ", "asia/calcutta")).unwrap();
+        assert_eq!(normalized.name, "Ana");
+        assert_eq!(normalized.timezone, "Asia/Kolkata");
+        assert!(normalize_member(member("Ana", "Mars/Olympus")).is_err());
+        assert!(normalize_member(member("", "UTC")).is_err());
+        let mut late = member("Ana", "UTC");
+        late.work_end = "25:00".to_string();
+        assert!(normalize_member(late).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_team_lifecycle() {
+        let db = init_db(":memory:").await;
+        let owner = "key:a".to_string();
+        let members = vec![member("Ana", "Europe/Belgrade"), member("Ben", "America/New_York")];
+        let team = create_team(&db, owner.clone(), "engineering".to_string(), members)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(team.members.len(), 2);
+        // Names are unique per owner, ignoring case
+        let again = create_team(&db, owner.clone(), "Engineering".to_string(), Vec::new());
+        assert!(again.await.unwrap().is_none());
+        let name = "engineering".to_string();
+        let elsewhere = create_team(&db, "key:b".to_string(), name, Vec::new());
+        assert!(elsewhere.await.unwrap().is_some());
+
+        let added = add_member(&db, team.id.clone(), member("Chi", "Asia/Tokyo")).await.unwrap();
+        let mut changed = member("Ben", "America/Chicago");
+        changed.work_start = "08:00".to_string();
+        let updated = update_member(&db, team.id.clone(), team.members[1].id.clone(), changed)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.timezone, "America/Chicago");
+        assert!(delete_member(&db, team.id.clone(), team.members[0].id.clone()).await.unwrap());
+
+        let fetched = get_team(&db, owner.clone(), "ENGINEERING".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        let names: Vec<&str> = fetched.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Ben", "Chi"]);
+        assert_eq!(fetched.members[1].id, added.id);
+        assert_eq!(fetched.members[0].work_start, "08:00");
+        assert_eq!(team_count(&db, owner.clone()).await.unwrap(), 1);
+
+        assert!(delete_team(&db, owner.clone(), "engineering".to_string()).await.unwrap());
+        assert!(list_teams(&db, owner).await.unwrap().is_empty());
+        assert_eq!(list_teams(&db, "key:b".to_string()).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,126 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Encoder for the TZif binary format (RFC 9636), as read by zoneinfo consumers
+
+// One local time type: an offset, whether it is daylight time, and its abbreviation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalTimeType {
+    pub utc_offset: i32,
+    pub is_dst: bool,
+    pub abbreviation: String,
+}
+
+// Encode a version 2 TZif file. `types[0]` applies before the first transition; each
+// transition is (UTC seconds, index into `types`), in ascending order. The footer is
+// left empty, so readers keep the last type after the final transition.
+pub fn encode(types: &[LocalTimeType], transitions: &[(i64, usize)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    // Version 1 block, limited to transitions representable in 32 bits
+    let legacy: Vec<(i64, usize)> = transitions
+        .iter()
+        .copied()
+        .filter(|(at, _)| i32::try_from(*at).is_ok())
+        .collect();
+    write_block(&mut out, types, &legacy, 4);
+    write_block(&mut out, types, transitions, 8);
+    out.extend_from_slice(b"\n\n");
+    out
+}
+
+fn write_block(
+    out: &mut Vec<u8>,
+    types: &[LocalTimeType],
+    transitions: &[(i64, usize)],
+    size: usize,
+) {
+    // Abbreviations are stored once each, NUL-terminated, and referenced by byte index
+    let mut chars: Vec<u8> = Vec::new();
+    let mut indexes = Vec::with_capacity(types.len());
+    for t in types {
+        let mut needle = t.abbreviation.as_bytes().to_vec();
+        needle.push(0);
+        let index = match chars.windows(needle.len()).position(|w| w == needle.as_slice()) {
+            Some(index) => index,
+            None => {
+                chars.extend_from_slice(&needle);
+                chars.len() - needle.len()
+            }
+        };
+        indexes.push(index as u8);
+    }
+
+    out.extend_from_slice(b"TZif2");
+    out.extend_from_slice(&[0; 15]);
+    // isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt
+    for count in [0, 0, 0, transitions.len(), types.len(), chars.len()] {
+        out.extend_from_slice(&(count as u32).to_be_bytes());
+    }
+    for (at, _) in transitions {
+        if size == 4 {
+            out.extend_from_slice(&(*at as i32).to_be_bytes());
+        } else {
+            out.extend_from_slice(&at.to_be_bytes());
+        }
+    }
+    out.extend(transitions.iter().map(|(_, index)| *index as u8));
+    for (t, index) in types.iter().zip(indexes) {
+        out.extend_from_slice(&t.utc_offset.to_be_bytes());
+        out.push(t.is_dst as u8);
+        out.push(index);
+    }
+    out.extend_from_slice(&chars);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_type(utc_offset: i32, is_dst: bool, abbreviation: &str) -> LocalTimeType {
+        LocalTimeType {
+            utc_offset,
+            is_dst,
+            abbreviation: abbreviation.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_encode_layout() {
+        let types = [local_type(3600, false, "CET"), local_type(7200, true, "CEST")];
+        // One transition before 1901 (64-bit block only) and two after
+        let transitions = [(-3_000_000_000, 1), (1_711_846_800, 1), (1_729_990_800, 0)];
+        let data = encode(&types, &transitions);
+
+        assert_eq!(&data[..5], b"TZif2");
+        let count = |at: usize| u32::from_be_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+        // Version 1 header: two transitions, two types, "CET\0CEST\0"
+        assert_eq!((count(32), count(36), count(40)), (2, 2, 9));
+        let v1_len = 44 + 2 * 4 + 2 + 2 * 6 + 9;
+        assert_eq!(&data[v1_len..v1_len + 5], b"TZif2");
+        assert_eq!(count(v1_len + 32), 3);
+        let v2_len = 44 + 3 * 8 + 3 + 2 * 6 + 9;
+        assert_eq!(data.len(), v1_len + v2_len + 2);
+        assert!(data.ends_with(b"\n\n"));
+    }
+
+    #[test]
+    fn test_abbreviations_shared() {
+        let types = [local_type(0, false, "GMT"), local_type(0, false, "GMT")];
+        let data = encode(&types, &[]);
+        let charcnt = u32::from_be_bytes(data[40..44].try_into().unwrap());
+        assert_eq!(charcnt, 4);
+    }
+}
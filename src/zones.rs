@@ -0,0 +1,432 @@
+// Epoch Zone
+// Copyright (C) 2026 Nemanja Hiršl
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono_tz::{Tz, TZ_VARIANTS};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// Zone metadata from the IANA tz database (public domain), embedded at build time
+const ZONE_TAB: &str = include_str!("../data/zone.tab");
+const ZONE1970_TAB: &str = include_str!("../data/zone1970.tab");
+const ISO3166_TAB: &str = include_str!("../data/iso3166.tab");
+const BACKWARD: &str = include_str!("../data/backward");
+const WINDOWS_ZONES_TAB: &str = include_str!("../data/windowsZones.tab");
+const RAILS_TAB: &str = include_str!("../data/rails.tab");
+const AIRPORTS_TAB: &str = include_str!("../data/airports.tab");
+
+// One row of zone.tab: a zone with the country it serves and its principal location
+#[derive(Debug, Clone)]
+pub struct ZoneRecord {
+    pub name: &'static str,
+    pub country_code: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+    // Disambiguates zones within a country, e.g. "Eastern (most areas)"
+    pub comment: Option<&'static str>,
+}
+
+static ZONES: LazyLock<Vec<ZoneRecord>> = LazyLock::new(|| {
+    data_lines(ZONE_TAB)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let country_code = fields.next()?;
+            let (latitude, longitude) = parse_iso6709(fields.next()?)?;
+            let name = fields.next()?;
+            Some(ZoneRecord {
+                name,
+                country_code,
+                latitude,
+                longitude,
+                comment: fields.next().filter(|c| !c.is_empty()),
+            })
+        })
+        .collect()
+});
+
+static ZONES_BY_NAME: LazyLock<HashMap<&'static str, &'static ZoneRecord>> =
+    LazyLock::new(|| ZONES.iter().map(|z| (z.name, z)).collect());
+
+// Countries whose clocks have agreed with each zone since 1970, from zone1970.tab. The
+// country of the zone's principal location comes first.
+static ZONE_COUNTRIES: LazyLock<HashMap<&'static str, Vec<&'static str>>> = LazyLock::new(|| {
+    data_lines(ZONE1970_TAB)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let codes = fields.next()?.split(',').collect();
+            Some((fields.nth(1)?, codes))
+        })
+        .collect()
+});
+
+static COUNTRIES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    data_lines(ISO3166_TAB)
+        .filter_map(|line| line.split_once('\t'))
+        .collect()
+});
+
+// Backward-compatibility links, alias -> canonical zone. "GMT" is linked from the
+// etcetera file rather than backward.
+static LINKS: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    data_lines(BACKWARD)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != "Link" {
+                return None;
+            }
+            let target = fields.next()?;
+            Some((fields.next()?, target))
+        })
+        .chain([("GMT", "Etc/GMT")])
+        .collect()
+});
+
+// Windows timezone IDs with their IANA zones, CLDR's default zone first
+static WINDOWS_ZONES: LazyLock<Vec<(&'static str, Vec<&'static str>)>> = LazyLock::new(|| {
+    data_lines(WINDOWS_ZONES_TAB)
+        .filter_map(|line| {
+            let (id, zones) = line.split_once('\t')?;
+            Some((id, zones.split_whitespace().collect()))
+        })
+        .collect()
+});
+
+// Rails friendly names ("Eastern Time (US & Canada)") with their IANA zones, in Rails' order
+static RAILS_ZONES: LazyLock<Vec<(&'static str, &'static str)>> = LazyLock::new(|| {
+    data_lines(RAILS_TAB)
+        .filter_map(|line| line.split_once('\t'))
+        .collect()
+});
+
+// An airport with its codes and reference point; the zone comes from the coordinates
+#[derive(Debug, Clone)]
+pub struct Airport {
+    pub iata: &'static str,
+    pub icao: &'static str,
+    pub name: &'static str,
+    pub city: &'static str,
+    pub country_code: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+static AIRPORTS: LazyLock<Vec<Airport>> = LazyLock::new(|| {
+    data_lines(AIRPORTS_TAB)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some(Airport {
+                iata: fields.next()?,
+                icao: fields.next()?,
+                name: fields.next()?,
+                city: fields.next()?,
+                country_code: fields.next()?,
+                latitude: fields.next()?.parse().ok()?,
+                longitude: fields.next()?.parse().ok()?,
+            })
+        })
+        .collect()
+});
+
+fn data_lines(table: &'static str) -> impl Iterator<Item = &'static str> {
+    table
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+}
+
+// All zone.tab rows, sorted by country code
+pub fn zone_records() -> &'static [ZoneRecord] {
+    &ZONES
+}
+
+// zone.tab row for a zone or for the zone a link points to
+pub fn find_zone(name: &str) -> Option<&'static ZoneRecord> {
+    ZONES_BY_NAME.get(canonical_name(name)).copied()
+}
+
+// zone.tab rows for a country (case-insensitive), in the file's order
+pub fn country_zones(code: &str) -> Vec<&'static ZoneRecord> {
+    ZONES
+        .iter()
+        .filter(|z| z.country_code.eq_ignore_ascii_case(code.trim()))
+        .collect()
+}
+
+// ISO codes of the countries using a zone or the zone a link points to, e.g. RS, BA,
+// HR, ME, MK, SI for Europe/Belgrade. Empty for zones not tied to a country ("UTC").
+pub fn zone_countries(name: &str) -> Vec<&'static str> {
+    let canonical = canonical_name(name);
+    let mut codes = ZONE_COUNTRIES.get(canonical).cloned().unwrap_or_default();
+    // zone.tab still lists zones that zone1970.tab folds into a neighbour's
+    for record in ZONES.iter().filter(|z| canonical_name(z.name) == canonical) {
+        if !codes.contains(&record.country_code) {
+            codes.push(record.country_code);
+        }
+    }
+    codes
+}
+
+// Country or territory name for an ISO 3166-1 alpha-2 code, e.g. "RS" -> "Serbia"
+pub fn country_name(code: &str) -> Option<&'static str> {
+    COUNTRIES.get(code.to_ascii_uppercase().as_str()).copied()
+}
+
+// Canonical zone for a link name; other names are returned unchanged
+pub fn canonical_name(name: &str) -> &str {
+    LINKS.get(name).copied().unwrap_or(name)
+}
+
+pub fn is_link(name: &str) -> bool {
+    LINKS.contains_key(name)
+}
+
+// All (alias, canonical) links, sorted by alias
+pub fn links() -> Vec<(&'static str, &'static str)> {
+    let mut links: Vec<_> = LINKS.iter().map(|(alias, target)| (*alias, *target)).collect();
+    links.sort_unstable();
+    links
+}
+
+// IANA zones for a Windows timezone ID (case-insensitive), the default zone first
+pub fn windows_zones(windows_id: &str) -> Option<(&'static str, &'static [&'static str])> {
+    WINDOWS_ZONES
+        .iter()
+        .find(|(id, _)| id.eq_ignore_ascii_case(windows_id.trim()))
+        .map(|(id, zones)| (*id, zones.as_slice()))
+}
+
+// Windows timezone ID covering a zone or the zone a link points to
+pub fn windows_id(name: &str) -> Option<&'static str> {
+    let canonical = canonical_name(name);
+    WINDOWS_ZONES
+        .iter()
+        .find(|(_, zones)| zones.iter().any(|zone| canonical_name(zone) == canonical))
+        .map(|(id, _)| *id)
+}
+
+// Every zone and link keyed by its forgiving form (see `lookup_key`)
+static ZONES_BY_KEY: LazyLock<HashMap<String, Tz>> =
+    LazyLock::new(|| TZ_VARIANTS.iter().map(|tz| (lookup_key(tz.name()), *tz)).collect());
+
+// Lowercased and trimmed, with spaces for underscores: "america/new york" for
+// "America/New_York"
+fn lookup_key(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "_")
+}
+
+// Parse a zone name as accepted by every endpoint: an IANA name or link, or a Rails
+// friendly name. Case, surrounding whitespace, and spaces for underscores are forgiven.
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.parse()
+        .ok()
+        .or_else(|| ZONES_BY_KEY.get(&lookup_key(name)).copied())
+        .or_else(|| rails_zone(name)?.parse().ok())
+}
+
+// Up to three zone names within a few edits of an unrecognized name, closest first.
+// The city part alone is compared too, so "Belgrad" finds "Europe/Belgrade".
+pub fn suggest_timezones(name: &str) -> Vec<&'static str> {
+    let key = lookup_key(name);
+    let max_distance = (key.chars().count() / 4).clamp(1, 3);
+    let mut matches: Vec<(usize, &'static str)> = TZ_VARIANTS
+        .iter()
+        .filter_map(|tz| {
+            let candidate = lookup_key(tz.name());
+            let city = candidate.rsplit('/').next().unwrap_or_default();
+            let distance = strsim::levenshtein(&key, &candidate)
+                .min(strsim::levenshtein(&key, city));
+            (distance <= max_distance).then_some((distance, tz.name()))
+        })
+        .collect();
+    // Prefer canonical names over links at the same distance
+    matches.sort_by_key(|(distance, name)| (*distance, is_link(name), *name));
+    matches.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+// Error message for an unrecognized zone name, with suggestions when there are any
+pub fn invalid_timezone(name: &str) -> String {
+    let suggestions = suggest_timezones(name);
+    if suggestions.is_empty() {
+        return format!("Invalid timezone: {}", name);
+    }
+    format!(
+        "Invalid timezone: {} (did you mean {}?)",
+        name,
+        suggestions.join(", ")
+    )
+}
+
+// IANA zone for a Rails friendly name, case-insensitive
+pub fn rails_zone(name: &str) -> Option<&'static str> {
+    RAILS_ZONES
+        .iter()
+        .find(|(rails, _)| rails.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, zone)| *zone)
+}
+
+// All (Rails name, IANA zone) pairs
+pub fn rails_zones() -> &'static [(&'static str, &'static str)] {
+    &RAILS_ZONES
+}
+
+// Airport by three-letter IATA or four-letter ICAO code, case-insensitive
+pub fn find_airport(code: &str) -> Option<&'static Airport> {
+    let code = code.trim();
+    AIRPORTS.iter().find(|airport| match code.len() {
+        3 => airport.iata.eq_ignore_ascii_case(code),
+        4 => airport.icao.eq_ignore_ascii_case(code),
+        _ => false,
+    })
+}
+
+// Leading area of a zone name ("Europe" for "Europe/Belgrade"); None for names like "UTC"
+pub fn region(name: &str) -> Option<&str> {
+    name.split_once('/').map(|(region, _)| region)
+}
+
+// Parse ISO 6709 coordinates as used in zone.tab: ±DDMM±DDDMM or ±DDMMSS±DDDMMSS
+fn parse_iso6709(value: &str) -> Option<(f64, f64)> {
+    let split = value[1..].find(['+', '-'])? + 1;
+    let (lat, lng) = value.split_at(split);
+    Some((parse_degrees(lat, 2)?, parse_degrees(lng, 3)?))
+}
+
+fn parse_degrees(value: &str, degree_digits: usize) -> Option<f64> {
+    let (sign, digits) = value.split_at(1);
+    let sign = match sign {
+        "+" => 1.0,
+        "-" => -1.0,
+        _ => return None,
+    };
+    let part = |range: std::ops::Range<usize>| -> Option<f64> { digits.get(range)?.parse().ok() };
+    let degrees = part(0..degree_digits)?;
+    let minutes = part(degree_digits..degree_digits + 2)?;
+    let seconds = match digits.len() - degree_digits {
+        2 => 0.0,
+        4 => part(degree_digits + 2..degree_digits + 4)?,
+        _ => return None,
+    };
+    Some(sign * (degrees + minutes / 60.0 + seconds / 3600.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_table_loaded() {
+        let belgrade = find_zone("Europe/Belgrade").unwrap();
+        assert_eq!(belgrade.country_code, "RS");
+        assert!((belgrade.latitude - 44.8333).abs() < 0.001);
+        assert!((belgrade.longitude - 20.5).abs() < 0.001);
+        assert!(zone_records().len() > 400);
+        assert!(find_zone("UTC").is_none());
+    }
+
+    #[test]
+    fn test_parse_iso6709_with_seconds() {
+        let (lat, lng) = parse_iso6709("+404251-0740023").unwrap();
+        assert!((lat - 40.714_167).abs() < 1e-5);
+        assert!((lng + 74.006_389).abs() < 1e-5);
+        assert!(parse_iso6709("4042-07400").is_none());
+    }
+
+    #[test]
+    fn test_country_name_and_region() {
+        assert_eq!(country_name("rs"), Some("Serbia"));
+        assert_eq!(country_name("XX"), None);
+        assert_eq!(region("America/Argentina/Salta"), Some("America"));
+        assert_eq!(region("UTC"), None);
+    }
+
+    #[test]
+    fn test_country_zones() {
+        let us: Vec<&str> = country_zones("us").iter().map(|z| z.name).collect();
+        assert_eq!(us[0], "America/New_York");
+        assert!(us.contains(&"Pacific/Honolulu"));
+        assert_eq!(country_zones("RS")[0].name, "Europe/Belgrade");
+        assert!(country_zones("XX").is_empty());
+    }
+
+    #[test]
+    fn test_zone_countries() {
+        assert_eq!(zone_countries("Europe/Belgrade"), ["RS", "BA", "HR", "ME", "MK", "SI"]);
+        assert_eq!(zone_countries("Europe/Sarajevo"), zone_countries("Europe/Belgrade"));
+        assert_eq!(zone_countries("Asia/Kolkata"), ["IN"]);
+        assert!(zone_countries("UTC").is_empty());
+    }
+
+    #[test]
+    fn test_links() {
+        assert_eq!(canonical_name("Asia/Calcutta"), "Asia/Kolkata");
+        assert_eq!(canonical_name("US/Eastern"), "America/New_York");
+        assert_eq!(canonical_name("GMT"), "Etc/GMT");
+        assert_eq!(canonical_name("Europe/Belgrade"), "Europe/Belgrade");
+        assert!(is_link("Asia/Saigon"));
+        assert!(!is_link("Asia/Ho_Chi_Minh"));
+        assert_eq!(find_zone("US/Eastern").unwrap().name, "America/New_York");
+        assert!(links().windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_windows_zones() {
+        let (id, zones) = windows_zones("pacific standard time").unwrap();
+        assert_eq!(id, "Pacific Standard Time");
+        assert_eq!(zones[0], "America/Los_Angeles");
+        assert_eq!(windows_id("Europe/Belgrade"), Some("Central Europe Standard Time"));
+        // CLDR spells it Asia/Calcutta; both names resolve
+        assert_eq!(windows_id("Asia/Kolkata"), Some("India Standard Time"));
+        assert_eq!(windows_id("Asia/Calcutta"), Some("India Standard Time"));
+        assert!(windows_zones("Mars Standard Time").is_none());
+    }
+
+    #[test]
+    fn test_rails_names() {
+        assert_eq!(rails_zone("Eastern Time (US & Canada)"), Some("America/New_York"));
+        assert_eq!(rails_zone("central time (us & canada)"), Some("America/Chicago"));
+        assert_eq!(parse_timezone("Belgrade").unwrap().name(), "Europe/Belgrade");
+        assert_eq!(parse_timezone("US/Eastern").unwrap().name(), "US/Eastern");
+        assert!(parse_timezone("Atlantis").is_none());
+        assert!(rails_zones().iter().all(|(_, zone)| zone.parse::<Tz>().is_ok()));
+    }
+
+    #[test]
+    fn test_forgiving_names() {
+        let new_york = Some(Tz::America__New_York);
+        assert_eq!(parse_timezone("America/New_York"), new_york);
+        assert_eq!(parse_timezone("  america/new york "), new_york);
+        assert_eq!(parse_timezone("AMERICA/NEW_YORK"), new_york);
+        assert_eq!(parse_timezone("america/new-york"), None);
+
+        assert_eq!(suggest_timezones("Europe/Belgrad")[0], "Europe/Belgrade");
+        assert_eq!(suggest_timezones("Amerika/New_York")[0], "America/New_York");
+        assert_eq!(suggest_timezones("Belgrade")[0], "Europe/Belgrade");
+        assert!(suggest_timezones("Mars/Olympus_Mons").is_empty());
+        assert_eq!(
+            invalid_timezone("Europe/Belgrad"),
+            "Invalid timezone: Europe/Belgrad (did you mean Europe/Belgrade?)"
+        );
+    }
+
+    #[test]
+    fn test_find_airport() {
+        let beg = find_airport("beg").unwrap();
+        assert_eq!((beg.icao, beg.city, beg.country_code), ("LYBE", "Belgrade", "RS"));
+        assert!((beg.latitude - 44.8184).abs() < 1e-6);
+        assert_eq!(find_airport("KJFK").unwrap().iata, "JFK");
+        assert!(find_airport("XXX").is_none());
+        assert!(find_airport("JF").is_none());
+        assert!(AIRPORTS.len() > 80);
+    }
+}